@@ -0,0 +1,98 @@
+//! A minimal monotonic clock abstraction
+//!
+//! # Timestamps
+//! All timestamps of this crate, i.e. every `now` parameter and every returned clock time, are in microseconds of a
+//! monotonic [`Clock`].
+//!
+//! # Time Sources
+//! All time-based subsystems of this crate (e.g. the TX queue, the RX watchdog, the airtime ledger or the beacon) take
+//! timestamps from the same monotonic time source instead of managing their own. The
+//! `embassy-time`-feature provides [`EmbassyClock`], the `fugit`-feature provides [`FugitClock`] for HAL timers, and
+//! the `rtic-monotonics`-feature provides [`MonotonicClock`] for RTIC 2. For network time,
+//! [`crate::lora::device_time::GpsClock`] yields the GPS-epoch time on top of any monotonic clock.
+
+/// A monotonic clock
+pub trait Clock {
+    /// The current monotonic time in microseconds
+    ///
+    /// # Monotonicity
+    /// The returned value must never decrease. The epoch is arbitrary (e.g. the boot time of the device), as the value
+    /// is only used to compute time differences.
+    fn now(&self) -> u64;
+}
+impl<T> Clock for &T
+where
+    T: Clock + ?Sized,
+{
+    fn now(&self) -> u64 {
+        (*self).now()
+    }
+}
+
+/// A [`Clock`] backed by the `embassy-time` time driver
+#[cfg(feature = "embassy-time")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EmbassyClock;
+#[cfg(feature = "embassy-time")]
+impl Clock for EmbassyClock {
+    fn now(&self) -> u64 {
+        embassy_time::Instant::now().as_micros()
+    }
+}
+
+/// A [`Clock`] backed by a function that returns a 64-bit `fugit` instant, e.g. the `now`-function of a HAL timer
+///
+/// # Monotonicity
+/// The instant must not wrap, so 32-bit timers must be extended to 64 bit (e.g. by counting overflows).
+#[cfg(feature = "fugit")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FugitClock<F> {
+    /// The function that returns the current instant
+    now: F,
+}
+#[cfg(feature = "fugit")]
+impl<F> FugitClock<F> {
+    /// Creates a new clock from the given function that returns the current instant
+    pub const fn new(now: F) -> Self {
+        Self { now }
+    }
+}
+#[cfg(feature = "fugit")]
+impl<F, const NOM: u32, const DENOM: u32> Clock for FugitClock<F>
+where
+    F: Fn() -> fugit::Instant<u64, NOM, DENOM>,
+{
+    fn now(&self) -> u64 {
+        (self.now)().duration_since_epoch().to_micros()
+    }
+}
+
+/// A [`Clock`] backed by an `rtic-monotonics` monotonic with a 64-bit `fugit` instant
+#[cfg(feature = "rtic-monotonics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonotonicClock<M> {
+    /// The monotonic type
+    _monotonic: core::marker::PhantomData<M>,
+}
+#[cfg(feature = "rtic-monotonics")]
+impl<M> MonotonicClock<M> {
+    /// Creates a new clock for the monotonic `M`
+    pub const fn new() -> Self {
+        Self { _monotonic: core::marker::PhantomData }
+    }
+}
+#[cfg(feature = "rtic-monotonics")]
+impl<M> Default for MonotonicClock<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(feature = "rtic-monotonics")]
+impl<M, const NOM: u32, const DENOM: u32> Clock for MonotonicClock<M>
+where
+    M: rtic_time::Monotonic<Instant = fugit::Instant<u64, NOM, DENOM>>,
+{
+    fn now(&self) -> u64 {
+        M::now().duration_since_epoch().to_micros()
+    }
+}
@@ -1,5 +1,7 @@
 //! The crate's error types
 
+use core::time::Duration;
+
 /// Creates an error
 #[macro_export]
 macro_rules! err {
@@ -29,9 +31,9 @@ pub struct IoError {
     pub description: &'static str,
 }
 
-/// A timeout error
+/// A CRC-validation or format error
 #[derive(Debug, Clone, Copy)]
-pub struct TimeoutError {
+pub struct InvalidMessageError {
     /// The file where the error was created
     #[cfg(feature = "backtrace")]
     pub file: &'static str,
@@ -43,9 +45,9 @@ pub struct TimeoutError {
     pub description: &'static str,
 }
 
-/// A CRC-validation or format error
+/// A buffer-too-small error
 #[derive(Debug, Clone, Copy)]
-pub struct InvalidMessageError {
+pub struct BufferTooSmallError {
     /// The file where the error was created
     #[cfg(feature = "backtrace")]
     pub file: &'static str,
@@ -71,6 +73,64 @@ pub struct InvalidArgumentError {
     pub description: &'static str,
 }
 
+/// A busy error, i.e. the modem is in an active TX, RX or CAD operation
+#[derive(Debug, Clone, Copy)]
+pub struct BusyError {
+    /// The file where the error was created
+    #[cfg(feature = "backtrace")]
+    pub file: &'static str,
+    /// The line at which the error was created
+    #[cfg(feature = "backtrace")]
+    pub line: u32,
+    /// A human readable error description
+    #[cfg(feature = "backtrace")]
+    pub description: &'static str,
+}
+
+/// A dwell-time error, i.e. a transmission would exceed the maximum dwell time
+#[derive(Debug, Clone, Copy)]
+pub struct DwellTimeError {
+    /// The computed airtime of the transmission
+    pub airtime: Duration,
+    /// The maximum dwell time
+    pub max_dwell: Duration,
+    /// The file where the error was created
+    #[cfg(feature = "backtrace")]
+    pub file: &'static str,
+    /// The line at which the error was created
+    #[cfg(feature = "backtrace")]
+    pub line: u32,
+    /// A human readable error description
+    #[cfg(feature = "backtrace")]
+    pub description: &'static str,
+}
+
+/// A configuration error
+#[derive(Debug, Clone, Copy)]
+pub enum ConfigError {
+    /// An I/O error
+    IoError(IoError),
+    /// An invalid-argument error
+    InvalidArgumentError(InvalidArgumentError),
+    /// A busy error
+    BusyError(BusyError),
+}
+impl From<BusyError> for ConfigError {
+    fn from(error: BusyError) -> Self {
+        Self::BusyError(error)
+    }
+}
+impl From<IoError> for ConfigError {
+    fn from(error: IoError) -> Self {
+        Self::IoError(error)
+    }
+}
+impl From<InvalidArgumentError> for ConfigError {
+    fn from(error: InvalidArgumentError) -> Self {
+        Self::InvalidArgumentError(error)
+    }
+}
+
 /// An TX-start error
 #[derive(Debug, Clone, Copy)]
 pub enum TxStartError {
@@ -78,6 +138,15 @@ pub enum TxStartError {
     IoError(IoError),
     /// An invalid-argument error
     InvalidArgumentError(InvalidArgumentError),
+    /// A dwell-time error
+    DwellTimeError(DwellTimeError),
+    /// A busy error
+    BusyError(BusyError),
+}
+impl From<BusyError> for TxStartError {
+    fn from(error: BusyError) -> Self {
+        Self::BusyError(error)
+    }
 }
 impl From<IoError> for TxStartError {
     fn from(error: IoError) -> Self {
@@ -89,6 +158,20 @@ impl From<InvalidArgumentError> for TxStartError {
         Self::InvalidArgumentError(error)
     }
 }
+impl From<DwellTimeError> for TxStartError {
+    fn from(error: DwellTimeError) -> Self {
+        Self::DwellTimeError(error)
+    }
+}
+impl From<ConfigError> for TxStartError {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::IoError(error) => Self::IoError(error),
+            ConfigError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            ConfigError::BusyError(error) => Self::BusyError(error),
+        }
+    }
+}
 
 /// An RX-start error
 #[derive(Debug, Clone, Copy)]
@@ -97,6 +180,13 @@ pub enum RxStartError {
     IoError(IoError),
     /// An invalid-argument error
     InvalidArgumentError(InvalidArgumentError),
+    /// A busy error
+    BusyError(BusyError),
+}
+impl From<BusyError> for RxStartError {
+    fn from(error: BusyError) -> Self {
+        Self::BusyError(error)
+    }
 }
 impl From<IoError> for RxStartError {
     fn from(error: IoError) -> Self {
@@ -108,29 +198,158 @@ impl From<InvalidArgumentError> for RxStartError {
         Self::InvalidArgumentError(error)
     }
 }
+impl From<ConfigError> for RxStartError {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::IoError(error) => Self::IoError(error),
+            ConfigError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            ConfigError::BusyError(error) => Self::BusyError(error),
+        }
+    }
+}
 
 /// An RX-completion specific error
 #[derive(Debug, Clone, Copy)]
 pub enum RxCompleteError {
     /// An I/O error
     IoError(IoError),
-    /// A timeout error
-    TimeoutError(TimeoutError),
-    /// A CRC-validation or format error
-    InvalidMessageError(InvalidMessageError),
+    /// A buffer-too-small error
+    BufferTooSmallError(BufferTooSmallError),
 }
 impl From<IoError> for RxCompleteError {
     fn from(error: IoError) -> Self {
         Self::IoError(error)
     }
 }
-impl From<TimeoutError> for RxCompleteError {
-    fn from(error: TimeoutError) -> Self {
-        Self::TimeoutError(error)
+impl From<BufferTooSmallError> for RxCompleteError {
+    fn from(error: BufferTooSmallError) -> Self {
+        Self::BufferTooSmallError(error)
+    }
+}
+
+/// A loopback-test error
+#[derive(Debug, Clone, Copy)]
+pub enum LoopbackError {
+    /// An I/O error
+    IoError(IoError),
+    /// An invalid-argument error
+    InvalidArgumentError(InvalidArgumentError),
+    /// A busy error
+    BusyError(BusyError),
+}
+impl From<BusyError> for LoopbackError {
+    fn from(error: BusyError) -> Self {
+        Self::BusyError(error)
+    }
+}
+impl From<IoError> for LoopbackError {
+    fn from(error: IoError) -> Self {
+        Self::IoError(error)
+    }
+}
+impl From<InvalidArgumentError> for LoopbackError {
+    fn from(error: InvalidArgumentError) -> Self {
+        Self::InvalidArgumentError(error)
+    }
+}
+impl From<ConfigError> for LoopbackError {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::IoError(error) => Self::IoError(error),
+            ConfigError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            ConfigError::BusyError(error) => Self::BusyError(error),
+        }
+    }
+}
+impl From<TxStartError> for LoopbackError {
+    fn from(error: TxStartError) -> Self {
+        match error {
+            TxStartError::IoError(error) => Self::IoError(error),
+            TxStartError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            TxStartError::BusyError(error) => Self::BusyError(error),
+            TxStartError::DwellTimeError(_) => {
+                Self::InvalidArgumentError(err!(InvalidArgumentError, "Transmission exceeds the maximum dwell time"))
+            }
+        }
+    }
+}
+impl From<RxStartError> for LoopbackError {
+    fn from(error: RxStartError) -> Self {
+        match error {
+            RxStartError::IoError(error) => Self::IoError(error),
+            RxStartError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            RxStartError::BusyError(error) => Self::BusyError(error),
+        }
+    }
+}
+
+/// A radio protocol error
+#[derive(Debug, Clone, Copy)]
+pub enum ProtocolError {
+    /// An I/O error
+    IoError(IoError),
+    /// An invalid-argument error
+    InvalidArgumentError(InvalidArgumentError),
+    /// A buffer-too-small error
+    BufferTooSmallError(BufferTooSmallError),
+    /// A busy error
+    BusyError(BusyError),
+}
+impl From<BusyError> for ProtocolError {
+    fn from(error: BusyError) -> Self {
+        Self::BusyError(error)
+    }
+}
+impl From<IoError> for ProtocolError {
+    fn from(error: IoError) -> Self {
+        Self::IoError(error)
+    }
+}
+impl From<InvalidArgumentError> for ProtocolError {
+    fn from(error: InvalidArgumentError) -> Self {
+        Self::InvalidArgumentError(error)
+    }
+}
+impl From<BufferTooSmallError> for ProtocolError {
+    fn from(error: BufferTooSmallError) -> Self {
+        Self::BufferTooSmallError(error)
+    }
+}
+impl From<ConfigError> for ProtocolError {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::IoError(error) => Self::IoError(error),
+            ConfigError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            ConfigError::BusyError(error) => Self::BusyError(error),
+        }
     }
 }
-impl From<InvalidMessageError> for RxCompleteError {
-    fn from(error: InvalidMessageError) -> Self {
-        Self::InvalidMessageError(error)
+impl From<TxStartError> for ProtocolError {
+    fn from(error: TxStartError) -> Self {
+        match error {
+            TxStartError::IoError(error) => Self::IoError(error),
+            TxStartError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            TxStartError::BusyError(error) => Self::BusyError(error),
+            TxStartError::DwellTimeError(_) => {
+                Self::InvalidArgumentError(err!(InvalidArgumentError, "Transmission exceeds the maximum dwell time"))
+            }
+        }
+    }
+}
+impl From<RxStartError> for ProtocolError {
+    fn from(error: RxStartError) -> Self {
+        match error {
+            RxStartError::IoError(error) => Self::IoError(error),
+            RxStartError::InvalidArgumentError(error) => Self::InvalidArgumentError(error),
+            RxStartError::BusyError(error) => Self::BusyError(error),
+        }
+    }
+}
+impl From<RxCompleteError> for ProtocolError {
+    fn from(error: RxCompleteError) -> Self {
+        match error {
+            RxCompleteError::IoError(error) => Self::IoError(error),
+            RxCompleteError::BufferTooSmallError(error) => Self::BufferTooSmallError(error),
+        }
     }
 }
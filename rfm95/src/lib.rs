@@ -5,9 +5,11 @@
 // Clippy lints
 #![warn(clippy::large_stack_arrays)]
 #![warn(clippy::arithmetic_side_effects)]
-#![warn(clippy::unwrap_used)]
-#![warn(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
 #![warn(clippy::indexing_slicing)]
+#![warn(clippy::cast_possible_truncation)]
 #![warn(clippy::todo)]
 #![warn(clippy::unimplemented)]
 #![warn(clippy::unreachable)]
@@ -15,6 +17,9 @@
 #![warn(clippy::allow_attributes_without_reason)]
 #![warn(clippy::cognitive_complexity)]
 
+pub mod clock;
 pub mod error;
 pub mod lora;
+pub mod persistence;
+pub mod prelude;
 pub mod rfm95;
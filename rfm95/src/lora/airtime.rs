@@ -18,80 +18,139 @@ pub const fn ceildiv(num: i32, divided_by: i32) -> i32 {
     // Perform operation as u64 to avoid overflows
     #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow")]
     let result = (num as i64 + divided_by as i64 - 1) / (divided_by as i64);
-    result as i32
+
+    // Saturate the result as `i32::try_from` is not const
+    #[allow(clippy::cast_possible_truncation, reason = "The value is clamped to the `i32` range")]
+    match result {
+        ..=-0x8000_0000 => i32::MIN,
+        0x7FFF_FFFF.. => i32::MAX,
+        result => result as i32,
+    }
 }
 
-/// Computes the duration of a single chip (not chirp!) for the given bandwidth
+/// Computes the duration of a single chip (not chirp!) in nanoseconds for the given bandwidth
+///
+/// # Precision
+/// All bandwidths of the modem are derived from the 32 MHz crystal as `125 kHz * 2^n` or `125 kHz / n`, so the chip
+/// durations are whole microseconds and thus exact.
 #[inline]
 #[must_use]
-const fn chip_duration(bandwidth: Bandwidth) -> Duration {
+const fn chip_nanos(bandwidth: Bandwidth) -> u64 {
     // Duration is `1/frequency`
     match bandwidth {
-        Bandwidth::B500 => Duration::from_micros(2),
-        Bandwidth::B250 => Duration::from_micros(4),
-        Bandwidth::B125 => Duration::from_micros(8),
-        Bandwidth::B62_5 => Duration::from_micros(16),
-        Bandwidth::B41_7 => Duration::from_micros(24),
-        Bandwidth::B31_25 => Duration::from_micros(32),
-        Bandwidth::B20_8 => Duration::from_micros(48),
-        Bandwidth::B15_6 => Duration::from_micros(64),
-        Bandwidth::B10_4 => Duration::from_micros(96),
-        Bandwidth::B7_8 => Duration::from_micros(128),
+        Bandwidth::B500 => 2_000,
+        Bandwidth::B250 => 4_000,
+        Bandwidth::B125 => 8_000,
+        Bandwidth::B62_5 => 16_000,
+        Bandwidth::B41_7 => 24_000,
+        Bandwidth::B31_25 => 32_000,
+        Bandwidth::B20_8 => 48_000,
+        Bandwidth::B15_6 => 64_000,
+        Bandwidth::B10_4 => 96_000,
+        Bandwidth::B7_8 => 128_000,
     }
 }
 
-/// The amount of chips per symbol for the given spreading factor
+/// Computes the duration of a single chip (not chirp!) for the given bandwidth
+///
+/// # Chirps
+/// A LoRa symbol is a chirp that sweeps the entire bandwidth; it is divided into `2^SF` chips, each of which lasts
+/// `1/BW`. See also [`chip_count`].
+#[must_use]
+pub const fn chip_duration(bandwidth: Bandwidth) -> Duration {
+    Duration::from_nanos(chip_nanos(bandwidth))
+}
+
+/// The amount of chips per symbol (i.e. `2^SF`) for the given spreading factor
+///
+/// # Chirps
+/// Each symbol encodes `SF` bits as one of the `2^SF` cyclic shifts of the chirp. See also [`chip_duration`].
 #[inline]
 #[must_use]
-const fn chip_count(spreading_factor: SpreadingFactor) -> u32 {
+pub const fn chip_count(spreading_factor: SpreadingFactor) -> u64 {
     // Chip count is `2^spreading_factor`
     let spreading_factor = spreading_factor as u8 as u32;
-    2u32.pow(spreading_factor)
+    2u64.pow(spreading_factor)
+}
+
+/// Computes the airtime of a single symbol in nanoseconds for the given bandwidth and spreading factor
+#[inline]
+#[must_use]
+const fn symbol_nanos(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> u64 {
+    // The airtime of a single symbol is the duration of one chip times the number of chips per symbol
+    #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow")]
+    (chip_nanos(bandwidth) * chip_count(spreading_factor))
 }
 
 /// Computes the airtime of a single symbol for the given bandwidth and spreading factor
 #[must_use]
 pub const fn symbol_airtime(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> Duration {
-    // Get chip duration and count
-    let chip_duration = chip_duration(bandwidth).as_micros() as u64;
-    let chip_count = chip_count(spreading_factor) as u64;
+    Duration::from_nanos(symbol_nanos(spreading_factor, bandwidth))
+}
 
-    // The airtime of a single symbol is the duration of one chip times the number of chips per symbol
-    #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow")]
-    Duration::from_micros(chip_duration * chip_count)
+/// Computes the symbol rate in milli-symbols per second (i.e. `1000 * BW / 2^SF`) for the given bandwidth and spreading
+/// factor
+///
+/// # Fixed-Point
+/// The symbol rate is fractional for most configurations (e.g. `30.517` symbols per second for SF12 at 125 kHz), so it
+/// is returned in milli-symbols per second and rounded down. Use [`symbol_airtime`] for exact computations.
+#[must_use]
+pub const fn symbol_rate(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> u32 {
+    /// The amount of milli-symbols per second if a symbol lasts one nanosecond
+    const MILLI_SYMBOLS_NANOS: u64 = 1_000_000_000_000;
+
+    // The symbol rate is the reciprocal of the symbol airtime; the result is at most `3_906_250` for SF7 at 500 kHz
+    #[allow(clippy::arithmetic_side_effects, reason = "The symbol airtime is never zero")]
+    let symbol_rate = MILLI_SYMBOLS_NANOS / symbol_nanos(spreading_factor, bandwidth);
+    #[allow(clippy::cast_possible_truncation, reason = "The symbol rate always fits into an `u32`")]
+    (symbol_rate as u32)
 }
 
 /// Computes if a configuration needs low-datarate-optimization
 ///
 /// # Note
 /// Low-datarate-optimization is a special mode that needs to be enabled on the modem if a single symbol needs more
-/// than 16ms airtime.
+/// than 16ms airtime (e.g. SF11 and SF12 at 125 kHz). It must be enabled on both ends, and it changes the payload
+/// airtime as each symbol carries two bits less (see [`airtime`]).
 #[inline]
-pub fn needs_ldo(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> bool {
+#[must_use]
+pub const fn needs_ldo(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> bool {
     /// The threshold for low-datarate optimization is 16ms per symbol
-    pub const THRESHOLD: Duration = Duration::from_millis(16);
-    symbol_airtime(spreading_factor, bandwidth) > THRESHOLD
+    const THRESHOLD_NANOS: u64 = 16_000_000;
+    symbol_nanos(spreading_factor, bandwidth) > THRESHOLD_NANOS
 }
 
-/// Gets the airtime of the preamble
+/// Gets the airtime of the preamble in nanoseconds
 ///
-/// # Implementation note
-/// This function assumes `5` symbols as preamble-overhead; not the theoretically correct `4.25` symbols.
-/// Subsequently, the computed airtime is always a little bit too long, which should not matter in practice and
-/// gives us a bit of "safety margin".
+/// # Fixed-Point
+/// The preamble consists of the programmed preamble symbols plus `4.25` symbols for the sync word and start frame
+/// delimiter; this is computed in quarter-symbols, which is exact as the symbol airtime is always divisible by `4`.
 #[must_use]
-fn preamble_airtime(config: Config) -> Duration {
-    // Get preamble length and symbol airtime
+fn preamble_nanos(config: Config) -> u64 {
+    /// The fixed preamble overhead in quarter-symbols
+    const OVERHEAD_QUARTERS: u64 = 17;
+
+    // Get preamble length in quarter-symbols and symbol airtime
     #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow")]
-    let preamble_len = u16::from(config.preamble_len()) as u64 + 5;
-    let symbol_airtime = symbol_airtime(config.spreading_factor(), config.bandwidth()).as_micros() as u64;
+    let preamble_quarters = u16::from(config.preamble_len()) as u64 * 4 + OVERHEAD_QUARTERS;
+    let symbol_nanos = symbol_nanos(config.spreading_factor(), config.bandwidth());
 
     // The airtime of the preamble is the amount of preamble symbols times the airtime of one symbol
     #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow")]
-    Duration::from_micros(preamble_len * symbol_airtime)
+    (preamble_quarters * (symbol_nanos / 4))
 }
 
-/// Computes the airtime of a payload
+/// Computes the airtime of the preamble, including the sync word and start frame delimiter
+///
+/// # Preamble
+/// The preamble consists of the programmed preamble symbols (see [`Config::preamble_len`]) plus `4.25` symbols for the
+/// sync word and start frame delimiter. The result is exact; see [`airtime`].
+#[must_use]
+pub fn preamble_airtime(config: Config) -> Duration {
+    Duration::from_nanos(preamble_nanos(config))
+}
+
+/// Computes the airtime of a payload in nanoseconds
 ///
 /// # Formula
 /// Formula from `SX1276, SX1277, SX1278, SX1279` datasheet, where
@@ -104,9 +163,9 @@ fn preamble_airtime(config: Config) -> Duration {
 ///
 /// `8 + max(ceil((8PL - 4SF + 28 + 16CRC - 20IH) / 4(SF - 2DE)) * (CR + 4), 0)`
 #[must_use]
-fn payload_airtime(payload_len: usize, config: Config) -> Duration {
-    // Prepare vars
-    let pl = payload_len as i32;
+fn payload_nanos(payload_len: usize, config: Config) -> u64 {
+    // Prepare vars; the payload length is clamped to avoid overflows
+    let pl = i32::from(u16::try_from(payload_len).unwrap_or(u16::MAX));
     let sf = config.spreading_factor() as u8 as i32;
     let crc = config.crc_mode() as u8 as i32;
     let ih = config.header_mode() as u8 as i32;
@@ -120,21 +179,74 @@ fn payload_airtime(payload_len: usize, config: Config) -> Duration {
         let payload_symbol_count =
             ceildiv((8 * pl) - (4 * sf) + 28 + (16 * crc) - (20 * ih), 4 * (sf - (2 * de))) * (cr + 4);
         let symbol_count = cmp::max(payload_symbol_count, 0) as u64 + 8;
-        let symbol_airtime = symbol_airtime(config.spreading_factor(), config.bandwidth()).as_micros() as u64;
+        let symbol_nanos = symbol_nanos(config.spreading_factor(), config.bandwidth());
 
-        // The airtime of the preamble is the amount of preamble symbols times the airtime of one symbol
-        Duration::from_micros(symbol_count * symbol_airtime)
+        // The airtime of the payload is the amount of payload symbols times the airtime of one symbol
+        symbol_count * symbol_nanos
     }
 }
 
 /// Computes the total airtime of a message
+///
+/// # Precision
+/// The airtime is computed in 64-bit fixed-point nanoseconds, including the fractional `4.25` preamble overhead, the
+/// low-datarate-optimization and the coding rate terms. As all symbol airtimes are whole microseconds, the result is
+/// exact; the worst-case error is thus only the crystal tolerance of the modems (e.g. ±20 ppm, i.e. ±20 µs per second
+/// of airtime).
 #[must_use]
 pub fn airtime(payload_len: usize, config: Config) -> Duration {
-    // Get airtimes of the preamble and payload
-    let preamble_airtime = preamble_airtime(config).as_micros() as u64;
-    let payload_airtime = payload_airtime(payload_len, config).as_micros() as u64;
-
     // The airtime of the message is the preamble plus the payload
-    #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow")]
-    Duration::from_micros(preamble_airtime + payload_airtime)
+    let airtime_nanos = preamble_nanos(config).saturating_add(payload_nanos(payload_len, config));
+    Duration::from_nanos(airtime_nanos)
+}
+
+/// Computes the total airtime of a message as `fugit` duration; see [`airtime`]
+#[cfg(feature = "fugit")]
+#[must_use]
+pub fn airtime_fugit(payload_len: usize, config: Config) -> fugit::MicrosDurationU64 {
+    to_fugit(airtime(payload_len, config))
+}
+/// Computes the airtime of the preamble as `fugit` duration; see [`preamble_airtime`]
+#[cfg(feature = "fugit")]
+#[must_use]
+pub fn preamble_airtime_fugit(config: Config) -> fugit::MicrosDurationU64 {
+    to_fugit(preamble_airtime(config))
+}
+/// Computes the airtime of a single symbol as `fugit` duration; see [`symbol_airtime`]
+#[cfg(feature = "fugit")]
+#[must_use]
+pub fn symbol_airtime_fugit(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> fugit::MicrosDurationU64 {
+    to_fugit(symbol_airtime(spreading_factor, bandwidth))
+}
+
+/// Converts a duration into a `fugit` duration, rounding down to whole microseconds
+#[cfg(feature = "fugit")]
+pub(crate) fn to_fugit(duration: Duration) -> fugit::MicrosDurationU64 {
+    let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+    fugit::MicrosDurationU64::micros(micros)
+}
+/// Converts a `fugit` duration into a duration
+#[cfg(feature = "fugit")]
+pub(crate) fn from_fugit(duration: fugit::MicrosDurationU64) -> Duration {
+    Duration::from_micros(duration.to_micros())
+}
+
+/// Computes the effective data rate in bits per second for the given config
+///
+/// # Effective Data Rate
+/// The effective data rate is the amount of payload bits per second for a message with the maximum payload length of
+/// 255 bytes, and accounts for the preamble, header, CRC and coding overhead. Shorter messages have a lower effective
+/// data rate, as the fixed overhead is amortized over fewer payload bytes.
+#[must_use]
+pub fn data_rate(config: Config) -> u32 {
+    /// The maximum payload length
+    const PAYLOAD_LEN: u8 = 255;
+
+    // Get the airtime of a message with the maximum payload length
+    let airtime = preamble_nanos(config).saturating_add(payload_nanos(PAYLOAD_LEN as usize, config));
+
+    // The data rate is the amount of payload bits divided by the airtime
+    #[allow(clippy::arithmetic_side_effects, reason = "This will never overflow, and the airtime is never zero")]
+    let data_rate = (PAYLOAD_LEN as u64 * 8 * 1_000_000_000) / airtime;
+    u32::try_from(data_rate).unwrap_or(u32::MAX)
 }
@@ -0,0 +1,351 @@
+//! LoRa-APRS position and status frames
+//!
+//! # Frame Format
+//! A LoRa-APRS frame is the 3-byte prefix `<\xFF\x01` followed by an APRS packet in TNC2 text format, e.g.
+//! `N0CALL-7>APLRT1,WIDE1-1:!4903.50N/07201.75W>comment`. This module encodes and parses the address header and the
+//! uncompressed position (`!`, `=`, `/` and `@`) and status (`>`) reports; other reports (e.g. compressed positions,
+//! messages or telemetry) are passed through verbatim as [`Payload::Other`].
+//!
+//! # Licensing
+//! LoRa-APRS is operated on amateur radio frequencies; transmitting requires an amateur radio license, and the frames
+//! must carry the callsign of the licensee as source address.
+
+use crate::err;
+use crate::error::{BufferTooSmallError, InvalidArgumentError, InvalidMessageError};
+use crate::lora::config::Config;
+use crate::lora::types::{
+    Bandwidth, CodingRate, CrcMode, Frequency, HeaderMode, Polarity, PreambleLength, SpreadingFactor, SyncWord,
+};
+use core::fmt::Write;
+
+/// The prefix of every LoRa-APRS frame
+pub const PREFIX: [u8; 3] = [b'<', 0xFF, 0x01];
+/// The conventional LoRa-APRS frequency in the 70 cm band (433.775 MHz)
+pub const FREQUENCY_433: Frequency = Frequency::hz(433_775_000);
+/// A LoRa-APRS frequency in the 868 MHz band (869.525 MHz)
+///
+/// # Important
+/// There is no single convention in the 868 MHz band; 869.525 MHz lies in the 869.4-869.65 MHz sub-band, which allows
+/// 500 mW ERP at a 10% duty cycle in the EU. Check which frequency the local network uses.
+pub const FREQUENCY_868: Frequency = Frequency::hz(869_525_000);
+
+/// The conventional LoRa-APRS radio config (SF12, 125 kHz, CR 4/5, private sync word) on the given frequency
+pub const fn config(frequency: Frequency) -> Config {
+    Config::builder()
+        .set_spreading_factor(SpreadingFactor::S12)
+        .set_bandwidth(Bandwidth::B125)
+        .set_coding_rate(CodingRate::C4_5)
+        .set_polarity(Polarity::Normal)
+        .set_header_mode(HeaderMode::Explicit)
+        .set_crc_mode(CrcMode::Enabled)
+        .set_sync_word(SyncWord::PRIVATE)
+        .set_preamble_length(PreambleLength::L8)
+        .set_frequency(frequency)
+}
+
+/// An uncompressed APRS position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// The latitude in microdegrees (north is positive)
+    latitude: i32,
+    /// The longitude in microdegrees (east is positive)
+    longitude: i32,
+    /// The symbol table identifier (e.g. `/` for the primary table)
+    symbol_table: u8,
+    /// The symbol code (e.g. `>` for a car)
+    symbol: u8,
+    /// Whether the station supports APRS messaging
+    messaging: bool,
+}
+impl Position {
+    /// The microdegrees per degree
+    const MICRODEGREES: u32 = 1_000_000;
+
+    /// Creates a new position from the latitude and longitude in microdegrees and the symbol table and code
+    ///
+    /// # Resolution
+    /// Uncompressed positions have a resolution of `1/100` minutes (approximately 18 m), so the coordinates are rounded
+    /// when encoded.
+    pub const fn new(
+        latitude: i32,
+        longitude: i32,
+        symbol_table: u8,
+        symbol: u8,
+    ) -> Result<Self, InvalidArgumentError> {
+        if latitude.unsigned_abs() > 90 * Self::MICRODEGREES || longitude.unsigned_abs() > 180 * Self::MICRODEGREES {
+            return Err(err!(InvalidArgumentError, "Coordinates are out of range"));
+        }
+        if !symbol_table.is_ascii_graphic() || !symbol.is_ascii_graphic() {
+            return Err(err!(InvalidArgumentError, "Symbol is not a printable character"));
+        }
+        Ok(Self { latitude, longitude, symbol_table, symbol, messaging: false })
+    }
+    /// Sets whether the station supports APRS messaging
+    pub const fn with_messaging(mut self, messaging: bool) -> Self {
+        self.messaging = messaging;
+        self
+    }
+
+    /// The latitude in microdegrees (north is positive)
+    pub const fn latitude(&self) -> i32 {
+        self.latitude
+    }
+    /// The longitude in microdegrees (east is positive)
+    pub const fn longitude(&self) -> i32 {
+        self.longitude
+    }
+    /// The symbol table identifier
+    pub const fn symbol_table(&self) -> u8 {
+        self.symbol_table
+    }
+    /// The symbol code
+    pub const fn symbol(&self) -> u8 {
+        self.symbol
+    }
+    /// Whether the station supports APRS messaging
+    pub const fn messaging(&self) -> bool {
+        self.messaging
+    }
+
+    /// Writes the position as `DDMM.hhN/DDDMM.hhW>`
+    fn write(&self, writer: &mut FrameWriter) -> core::fmt::Result {
+        let (lat_degrees, lat_minutes, lat_hundredths) = Self::to_minutes(self.latitude);
+        let (lon_degrees, lon_minutes, lon_hundredths) = Self::to_minutes(self.longitude);
+        let north_south = if self.latitude < 0 { 'S' } else { 'N' };
+        let east_west = if self.longitude < 0 { 'W' } else { 'E' };
+        write!(writer, "{lat_degrees:02}{lat_minutes:02}.{lat_hundredths:02}{north_south}")?;
+        writer.write_char(char::from(self.symbol_table))?;
+        write!(writer, "{lon_degrees:03}{lon_minutes:02}.{lon_hundredths:02}{east_west}")?;
+        writer.write_char(char::from(self.symbol))
+    }
+    /// Parses a position from `DDMM.hhN/DDDMM.hhW>` and returns the remaining comment
+    fn parse(info: &str, messaging: bool) -> Option<(Self, &str)> {
+        let (latitude, rest) = info.as_bytes().split_at_checked(8)?;
+        let (&symbol_table, rest) = rest.split_first()?;
+        let (longitude, rest) = rest.split_at_checked(9)?;
+        let (&symbol, comment) = rest.split_first()?;
+
+        // Parse the coordinates
+        let latitude = Self::parse_coordinate(latitude, 2, b'N', b'S')?;
+        let longitude = Self::parse_coordinate(longitude, 3, b'E', b'W')?;
+        let position = Self::new(latitude, longitude, symbol_table, symbol).ok()?;
+        let comment = core::str::from_utf8(comment).ok()?;
+        Some((position.with_messaging(messaging), comment))
+    }
+
+    /// Splits microdegrees into degrees, minutes and hundredths of a minute
+    const fn to_minutes(microdegrees: i32) -> (u32, u32, u32) {
+        // Round to the nearest hundredth of a minute (there are 6000 per degree)
+        let hundredths = microdegrees.unsigned_abs().saturating_mul(6).saturating_add(500) / 1000;
+        (hundredths / 6000, (hundredths % 6000) / 100, hundredths % 100)
+    }
+    /// Parses a coordinate in the format `(D)DDMM.hhX` with the given amount of degree digits
+    fn parse_coordinate(coordinate: &[u8], degree_digits: usize, positive: u8, negative: u8) -> Option<i32> {
+        let (hemisphere, digits) = coordinate.split_last()?;
+        let (degrees, minutes) = digits.split_at_checked(degree_digits)?;
+        let [m0, m1, b'.', h0, h1] = *minutes else {
+            // The minutes are malformed
+            return None;
+        };
+
+        // Parse the fields and convert them to microdegrees
+        let degrees = degrees.iter().try_fold(0u32, |value, &digit| Self::push_digit(value, digit))?;
+        let minutes = [m0, m1].iter().try_fold(0u32, |value, &digit| Self::push_digit(value, digit))?;
+        let hundredths = [h0, h1].iter().try_fold(0u32, |value, &digit| Self::push_digit(value, digit))?;
+        if minutes >= 60 {
+            return None;
+        }
+        let hundredths = minutes.saturating_mul(100).saturating_add(hundredths);
+        let microdegrees =
+            degrees.saturating_mul(Self::MICRODEGREES).saturating_add(hundredths.saturating_mul(1000) / 6);
+        let microdegrees = i32::try_from(microdegrees).ok()?;
+        match *hemisphere {
+            hemisphere if hemisphere == positive => Some(microdegrees),
+            hemisphere if hemisphere == negative => Some(microdegrees.saturating_neg()),
+            _ => None,
+        }
+    }
+    /// Appends a decimal digit to the value
+    fn push_digit(value: u32, digit: u8) -> Option<u32> {
+        let digit = char::from(digit).to_digit(10)?;
+        value.checked_mul(10)?.checked_add(digit)
+    }
+}
+
+/// The payload of an APRS packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payload<'a> {
+    /// An uncompressed position report; the timestamp of timestamped reports is dropped
+    Position {
+        /// The position
+        position: Position,
+        /// The comment
+        comment: &'a str,
+    },
+    /// A status report
+    Status(&'a str),
+    /// Any other report, as raw information field
+    Other(&'a str),
+}
+impl<'a> Payload<'a> {
+    /// Parses the information field of an APRS packet
+    pub fn parse(info: &'a str) -> Self {
+        let Some((&data_type, rest)) = info.as_bytes().split_first() else {
+            // The information field is empty
+            return Self::Other(info);
+        };
+        let Ok(rest) = core::str::from_utf8(rest) else {
+            // Unreachable since `rest` starts after an ASCII character
+            return Self::Other(info);
+        };
+
+        // Skip the timestamp of timestamped positions
+        let position = match data_type {
+            b'!' => Position::parse(rest, false),
+            b'=' => Position::parse(rest, true),
+            b'/' => rest.get(7..).and_then(|rest| Position::parse(rest, false)),
+            b'@' => rest.get(7..).and_then(|rest| Position::parse(rest, true)),
+            b'>' => return Self::Status(rest),
+            _ => None,
+        };
+        match position {
+            Some((position, comment)) => Self::Position { position, comment },
+            None => Self::Other(info),
+        }
+    }
+
+    /// Writes the information field
+    fn write(&self, writer: &mut FrameWriter) -> core::fmt::Result {
+        match self {
+            Self::Position { position, comment } => {
+                writer.write_char(if position.messaging { '=' } else { '!' })?;
+                position.write(writer)?;
+                writer.write_str(comment)
+            }
+            Self::Status(status) => {
+                writer.write_char('>')?;
+                writer.write_str(status)
+            }
+            Self::Other(info) => writer.write_str(info),
+        }
+    }
+}
+
+/// A LoRa-APRS frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// The source callsign including the SSID (e.g. `N0CALL-7`)
+    source: &'a str,
+    /// The destination (e.g. `APLRT1`)
+    destination: &'a str,
+    /// The comma-separated digipeater path (e.g. `WIDE1-1`), or empty
+    path: &'a str,
+    /// The payload
+    payload: Payload<'a>,
+}
+impl<'a> Frame<'a> {
+    /// Creates a new frame with the given source callsign, destination and payload, and without digipeater path
+    pub fn new(source: &'a str, destination: &'a str, payload: Payload<'a>) -> Result<Self, InvalidArgumentError> {
+        let is_valid = |address: &str| !address.is_empty() && address.bytes().all(Self::is_address_char);
+        if !is_valid(source) || !is_valid(destination) {
+            return Err(err!(InvalidArgumentError, "Invalid address"));
+        }
+        Ok(Self { source, destination, path: "", payload })
+    }
+    /// Sets the comma-separated digipeater path (e.g. `WIDE1-1` or `WIDE1-1,WIDE2-1`)
+    pub fn with_path(mut self, path: &'a str) -> Result<Self, InvalidArgumentError> {
+        if !path.bytes().all(|byte| byte == b',' || Self::is_address_char(byte)) {
+            return Err(err!(InvalidArgumentError, "Invalid digipeater path"));
+        }
+        self.path = path;
+        Ok(self)
+    }
+
+    /// The source callsign including the SSID
+    pub const fn source(&self) -> &'a str {
+        self.source
+    }
+    /// The destination
+    pub const fn destination(&self) -> &'a str {
+        self.destination
+    }
+    /// The comma-separated digipeater path, or empty
+    pub const fn path(&self) -> &'a str {
+        self.path
+    }
+    /// The payload
+    pub const fn payload(&self) -> &Payload<'a> {
+        &self.payload
+    }
+
+    /// Decodes a LoRa-APRS frame
+    pub fn parse(frame: &'a [u8]) -> Result<Self, InvalidMessageError> {
+        let Some((&PREFIX, packet)) = frame.split_first_chunk::<3>() else {
+            // The frame is not a LoRa-APRS frame
+            return Err(err!(InvalidMessageError, "Missing LoRa-APRS prefix"));
+        };
+        let Ok(packet) = core::str::from_utf8(packet) else {
+            // The packet is not valid text
+            return Err(err!(InvalidMessageError, "Packet is not valid UTF-8"));
+        };
+
+        // Split the packet into `SOURCE>DESTINATION[,PATH]:INFO`
+        let Some((header, info)) = packet.split_once(':') else {
+            // The packet has no information field
+            return Err(err!(InvalidMessageError, "Missing information field"));
+        };
+        let Some((source, addresses)) = header.split_once('>') else {
+            // The header has no destination
+            return Err(err!(InvalidMessageError, "Missing destination address"));
+        };
+        let (destination, path) = addresses.split_once(',').unwrap_or((addresses, ""));
+        if source.is_empty() || destination.is_empty() {
+            return Err(err!(InvalidMessageError, "Empty address"));
+        }
+        Ok(Self { source, destination, path, payload: Payload::parse(info) })
+    }
+    /// Encodes the frame into `buf` and returns the frame length
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+        let mut writer = FrameWriter { buf, len: 0 };
+        self.write(&mut writer).map_err(|_| err!(BufferTooSmallError, "Buffer is too small for the frame"))?;
+        Ok(writer.len)
+    }
+
+    /// Writes the frame
+    fn write(&self, writer: &mut FrameWriter) -> core::fmt::Result {
+        writer.write_bytes(&PREFIX)?;
+        write!(writer, "{}>{}", self.source, self.destination)?;
+        if !self.path.is_empty() {
+            write!(writer, ",{}", self.path)?;
+        }
+        writer.write_char(':')?;
+        self.payload.write(writer)
+    }
+    /// Whether the byte may be part of an address
+    const fn is_address_char(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'*'
+    }
+}
+
+/// A formatter that writes a frame into a fixed buffer
+struct FrameWriter<'a> {
+    /// The frame buffer
+    buf: &'a mut [u8],
+    /// The amount of bytes written
+    len: usize,
+}
+impl FrameWriter<'_> {
+    /// Writes the given raw bytes
+    fn write_bytes(&mut self, bytes: &[u8]) -> core::fmt::Result {
+        let end = self.len.checked_add(bytes.len()).ok_or(core::fmt::Error)?;
+        let slot = self.buf.get_mut(self.len..end).ok_or(core::fmt::Error)?;
+        slot.copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+impl Write for FrameWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.write_bytes(s.as_bytes())
+    }
+}
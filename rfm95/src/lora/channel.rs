@@ -0,0 +1,190 @@
+//! Channel plans for multi-channel operation
+
+use crate::err;
+use crate::error::InvalidArgumentError;
+use crate::lora::types::Frequency;
+use crate::persistence::{self, Persistence};
+
+/// A channel within a channel plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Channel {
+    /// The channel index within the channel plan
+    index: u8,
+    /// The channel frequency
+    frequency: Frequency,
+}
+impl Channel {
+    /// The channel index within the channel plan
+    pub const fn index(&self) -> u8 {
+        self.index
+    }
+    /// The channel frequency
+    pub const fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+}
+impl From<Channel> for Frequency {
+    fn from(value: Channel) -> Self {
+        value.frequency
+    }
+}
+
+/// A channel plan of equally spaced channels
+///
+/// # Channel Selection
+/// Channels can be selected in a round-robin fashion via [`Self::next_channel`], or randomly via
+/// [`Self::random_channel`]. Only enabled channels are selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelPlan {
+    /// The frequency of the first channel
+    base: Frequency,
+    /// The spacing between two channels in Hz
+    spacing: u32,
+    /// The amount of channels
+    count: u8,
+    /// The enabled channels as bitmask, where bit `n` corresponds to channel `n`
+    enabled: u64,
+    /// The index to start the next round-robin search at
+    next: u8,
+}
+impl ChannelPlan {
+    /// The maximum amount of channels within a channel plan
+    pub const MAX_CHANNELS: u8 = 64;
+
+    /// The three default LoRaWAN channels for EU868 (868.1, 868.3 and 868.5 MHz)
+    pub const EU868_DEFAULT: Self =
+        Self { base: Frequency::F868_1, spacing: 200_000, count: 3, enabled: 0b111, next: 0 };
+    /// The eight EU868 uplink channels used by The Things Network (867.1 to 868.5 MHz)
+    pub const EU868_TTN: Self =
+        Self { base: Frequency::hz(867_100_000), spacing: 200_000, count: 8, enabled: 0xFF, next: 0 };
+    /// The eight 125 kHz US915 uplink channels of sub-band 2 (channels 8 to 15, 903.9 to 905.3 MHz)
+    pub const US915_SUB_BAND_2: Self =
+        Self { base: Frequency::hz(903_900_000), spacing: 200_000, count: 8, enabled: 0xFF, next: 0 };
+
+    /// Creates a new channel plan with `count` channels starting at `base`, spaced by `spacing` Hz
+    ///
+    /// # Enabled Channels
+    /// All channels are enabled initially.
+    pub const fn new(base: Frequency, spacing: u32, count: u8) -> Result<Self, InvalidArgumentError> {
+        // Validate the channel count
+        let 1..=Self::MAX_CHANNELS = count else {
+            // The channel count is invalid
+            return Err(err!(InvalidArgumentError, "Invalid channel count"));
+        };
+
+        // Validate the frequency of the last channel
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as count is at least 1")]
+        let last = (count - 1) as u64;
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as all operands are at most 32 bit")]
+        let 0..=0xFFFF_FFFF = base.as_u32() as u64 + (spacing as u64 * last) else {
+            // The channel frequencies exceed the representable range
+            return Err(err!(InvalidArgumentError, "Channel frequency is too large"));
+        };
+
+        // Enable all channels
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as count is at most `MAX_CHANNELS`")]
+        let enabled = u64::MAX >> (Self::MAX_CHANNELS - count);
+        Ok(Self { base, spacing, count, enabled, next: 0 })
+    }
+
+    /// The amount of channels
+    pub const fn count(&self) -> u8 {
+        self.count
+    }
+    /// The frequency of the channel with the given index, or `None` if the index is out of range
+    pub const fn frequency(&self, index: u8) -> Option<Frequency> {
+        // Validate the index
+        if index >= self.count {
+            return None;
+        }
+
+        // Compute the frequency
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as this is validated during creation")]
+        let frequency = self.base.as_u32() + (self.spacing * index as u32);
+        Some(Frequency::hz(frequency))
+    }
+    /// The channel with the given index, or `None` if the index is out of range
+    pub const fn channel(&self, index: u8) -> Option<Channel> {
+        match self.frequency(index) {
+            Some(frequency) => Some(Channel { index, frequency }),
+            None => None,
+        }
+    }
+
+    /// The enabled channels as bitmask, where bit `n` corresponds to channel `n`
+    pub const fn enabled_mask(&self) -> u64 {
+        self.enabled
+    }
+    /// Sets the enabled channels as bitmask, where bit `n` corresponds to channel `n`; bits for channels beyond the
+    /// channel count are ignored
+    pub fn set_enabled_mask(&mut self, mask: u64) {
+        let valid = u64::MAX >> (Self::MAX_CHANNELS.saturating_sub(self.count));
+        self.enabled = mask & valid;
+    }
+    /// Whether the channel with the given index is enabled
+    pub const fn is_enabled(&self, index: u8) -> bool {
+        index < self.count && (self.enabled >> index) & 1 == 1
+    }
+    /// Enables or disables the channel with the given index; out-of-range indices are ignored
+    pub fn set_enabled(&mut self, index: u8, enabled: bool) {
+        // Validate the index
+        if index >= self.count {
+            return;
+        }
+
+        // Update the mask
+        match enabled {
+            true => self.enabled |= 1 << index,
+            false => self.enabled &= !(1 << index),
+        }
+    }
+    /// The amount of enabled channels
+    pub const fn enabled_count(&self) -> u8 {
+        #[allow(clippy::cast_possible_truncation, reason = "A `u64` has at most 64 bits set")]
+        (self.enabled.count_ones() as u8)
+    }
+
+    /// Selects the next enabled channel in a round-robin fashion, or returns `None` if no channel is enabled
+    pub fn next_channel(&mut self) -> Option<Channel> {
+        // Search for the next enabled channel, starting at the current position
+        let index = (0..self.count)
+            .filter_map(|offset| self.next.saturating_add(offset).checked_rem(self.count))
+            .find(|index| self.is_enabled(*index))?;
+
+        // Advance the position
+        self.next = index.saturating_add(1).checked_rem(self.count).unwrap_or_default();
+        self.channel(index)
+    }
+    /// Selects an enabled channel using the given random value, or returns `None` if no channel is enabled
+    ///
+    /// # Randomness
+    /// The random value should be uniformly distributed (e.g. from a hardware RNG); it is reduced modulo the amount of
+    /// enabled channels.
+    pub fn random_channel(&self, random: u32) -> Option<Channel> {
+        // Select the n-th enabled channel
+        let nth = random.checked_rem(self.enabled_count() as u32)?;
+        let index = (0..self.count).filter(|index| self.is_enabled(*index)).nth(nth as usize)?;
+        self.channel(index)
+    }
+
+    /// Persists the enabled channels (see [`crate::persistence::KEY_CHANNEL_PLAN`])
+    pub fn save<P>(&self, persistence: &mut P) -> Result<(), P::Error>
+    where
+        P: Persistence,
+    {
+        persistence.write(persistence::KEY_CHANNEL_PLAN, &self.enabled.to_le_bytes())
+    }
+    /// Restores the enabled channels if they have been persisted, and returns whether they have been restored
+    pub fn restore<P>(&mut self, persistence: &mut P) -> Result<bool, P::Error>
+    where
+        P: Persistence,
+    {
+        let mut mask = [0; 8];
+        let Some(8) = persistence.read(persistence::KEY_CHANNEL_PLAN, &mut mask)? else {
+            // The record does not exist or is invalid
+            return Ok(false);
+        };
+        self.set_enabled_mask(u64::from_le_bytes(mask));
+        Ok(true)
+    }
+}
@@ -0,0 +1,103 @@
+//! End-to-end payload checksums, independent of the radio CRC
+//!
+//! # Usage
+//! The radio CRC is only 16 bits wide and is not available on implicit-header links without CRC. An application-layer
+//! checksum is appended to the payload as 4 little-endian bytes by [`seal`] (or
+//! [`crate::rfm95::Rfm95Driver::start_tx_checked`]), and verified and stripped by [`open`] (or
+//! [`crate::rfm95::Rfm95Driver::complete_rx_checked`]). Both sides must use the same checksum.
+
+use crate::err;
+use crate::error::BufferTooSmallError;
+
+/// A 32-bit checksum algorithm
+pub trait Checksum {
+    /// Computes the checksum of the given data
+    fn checksum(&self, data: &[u8]) -> u32;
+}
+
+/// The CRC-32 as used by Ethernet, zlib and PNG (reflected polynomial `0xEDB88320`)
+///
+/// # Implementation
+/// The CRC is computed bitwise without a lookup table, to save flash; this is fast enough for LoRa frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Crc32;
+impl Crc32 {
+    /// The reflected CRC-32 polynomial
+    const POLYNOMIAL: u32 = 0xEDB8_8320;
+}
+impl Checksum for Crc32 {
+    fn checksum(&self, data: &[u8]) -> u32 {
+        let mut crc = u32::MAX;
+        for byte in data {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                // Shift out the LSB and apply the polynomial if it was set
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (Self::POLYNOMIAL & mask);
+            }
+        }
+        !crc
+    }
+}
+
+/// The length of the checksum trailer
+pub const CHECKSUM_LEN: usize = 4;
+
+/// Writes the payload followed by its checksum into `frame` and returns the frame length
+pub fn seal<C>(payload: &[u8], frame: &mut [u8], checksum: &C) -> Result<usize, BufferTooSmallError>
+where
+    C: Checksum,
+{
+    // Split the frame into payload and trailer
+    let len = payload.len().saturating_add(CHECKSUM_LEN);
+    let Some((body, trailer)) = frame.get_mut(..len).map(|frame| frame.split_at_mut(payload.len())) else {
+        // The payload and the checksum do not fit into the frame
+        return Err(err!(BufferTooSmallError, "Frame buffer is too small"));
+    };
+
+    // Copy the payload and append the checksum
+    body.copy_from_slice(payload);
+    trailer.copy_from_slice(&checksum.checksum(payload).to_le_bytes());
+    Ok(len)
+}
+/// Verifies the checksum of a frame written by [`seal`] and returns the payload, or `None` if the checksum is invalid
+pub fn open<'a, C>(frame: &'a [u8], checksum: &C) -> Option<&'a [u8]>
+where
+    C: Checksum,
+{
+    let (payload, trailer) = frame.split_last_chunk::<CHECKSUM_LEN>()?;
+    (checksum.checksum(payload) == u32::from_le_bytes(*trailer)).then_some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        // The standard check value of the CRC-32
+        assert_eq!(Crc32.checksum(b"123456789"), 0xCBF4_3926);
+        assert_eq!(Crc32.checksum(b""), 0);
+        assert_eq!(Crc32.checksum(b"The quick brown fox jumps over the lazy dog"), 0x414F_A339);
+    }
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let mut frame = [0; 13];
+        assert_eq!(seal(b"123456789", &mut frame, &Crc32).ok(), Some(13));
+        assert_eq!(frame.get(9..), Some(&[0x26, 0x39, 0xF4, 0xCB][..]));
+        assert_eq!(open(&frame, &Crc32), Some(&b"123456789"[..]));
+
+        // A flipped bit or a truncated frame is rejected
+        let mut corrupt = frame;
+        corrupt[0] ^= 0x01;
+        assert_eq!(open(&corrupt, &Crc32), None);
+        assert_eq!(open(&frame[..3], &Crc32), None);
+    }
+
+    #[test]
+    fn seal_buffer_too_small() {
+        let mut frame = [0; 12];
+        assert!(seal(b"123456789", &mut frame, &Crc32).is_err());
+    }
+}
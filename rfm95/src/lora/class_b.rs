@@ -0,0 +1,482 @@
+//! LoRaWAN Class B beacon tracking and ping slots
+//!
+//! # Beacons
+//! Class B gateways broadcast a beacon at the start of each 128-second beacon period, i.e. whenever the GPS time is a
+//! multiple of 128 seconds. A device that tracks the beacons knows the network time, and can open short RX windows
+//! (ping slots) at times the network server can predict, which gives downlinks a deterministic latency without keeping
+//! the receiver on.
+//!
+//! # Usage
+//! 1. Acquire the beacon timing, either via [`BeaconTracker::acquire`] if the GPS time is known (e.g. via
+//!    [`crate::lora::device_time::GpsClock`]), or by receiving a beacon in continuous RX mode with
+//!    [`beacon_config`] (see [`crate::rfm95::Rfm95Driver::set_implicit_rx_len`]) and passing it to
+//!    [`BeaconTracker::beacon_received`].
+//! 2. Open an RX window for each beacon via [`BeaconTracker::next_window`], and report the outcome via
+//!    [`BeaconTracker::beacon_received`] or [`BeaconTracker::beacon_missed`].
+//! 3. Compute the ping slots of each beacon period via [`PingSlots::new`], and open an RX window at each ping slot via
+//!    [`BeaconTracker::ping_slot_at`].
+//!
+//! # Drift Compensation
+//! The tracker measures the drift of the local clock between consecutive beacons, and corrects all predicted times
+//! accordingly. The remaining uncertainty (see [`BeaconTracker::with_clock_accuracy`]) widens the beacon windows with
+//! every missed beacon; after the maximum amount of missed beacons (56 by default, i.e. 2 hours of beacon-less
+//! operation), the beacon is considered lost and must be reacquired.
+
+use crate::err;
+use crate::error::InvalidMessageError;
+use crate::lora::airtime;
+use crate::lora::config::Config;
+use crate::lora::lorawan_crypto::{BlockCipher, BLOCK_LEN};
+use crate::lora::region::Region;
+use crate::lora::types::{
+    Bandwidth, CodingRate, CrcMode, Frequency, HeaderMode, Polarity, PreambleLength, SpreadingFactor, SyncWord,
+};
+use core::time::Duration;
+
+/// The beacon period in microseconds
+pub const BEACON_PERIOD: u64 = 128_000_000;
+/// The time reserved for the beacon at the start of each beacon period in microseconds
+pub const BEACON_RESERVED: u64 = 2_120_000;
+/// The length of a ping slot in microseconds
+pub const PING_SLOT_LEN: u64 = 30_000;
+/// The amount of ping slots per beacon period
+const PING_SLOT_COUNT: u16 = 4096;
+/// The beacon period in seconds
+const BEACON_PERIOD_SECONDS: u32 = 128;
+
+/// The length of the beacon frame in the given region
+pub const fn beacon_len(region: Region) -> usize {
+    let (rfu1, rfu2) = beacon_rfu(region);
+    rfu1.saturating_add(rfu2).saturating_add(15)
+}
+/// The frequency of the beacon with the given beacon time in the given region
+///
+/// # Frequency Hopping
+/// In US915 and AU915, the beacon hops over 8 channels, so the frequency depends on the beacon time.
+pub const fn beacon_frequency(region: Region, beacon_time: u32) -> Frequency {
+    match region {
+        Region::Eu868 => Frequency::hz(869_525_000),
+        Region::Eu433 => Frequency::hz(434_665_000),
+        Region::Us915 | Region::Au915 => hopping_frequency((beacon_time / BEACON_PERIOD_SECONDS) % 8),
+        Region::As923 => Frequency::hz(923_400_000),
+        Region::In865 => Frequency::hz(866_550_000),
+        Region::Kr920 => Frequency::hz(923_100_000),
+    }
+}
+/// The default ping slot frequency for the given device address during the beacon period with the given beacon time
+///
+/// # Frequency Hopping
+/// In US915 and AU915, the ping slots hop over the 8 beacon channels, depending on the device address and the beacon
+/// time; in all other regions, the ping slots use the beacon frequency.
+pub const fn ping_slot_frequency(region: Region, beacon_time: u32, dev_addr: u32) -> Frequency {
+    match region {
+        Region::Us915 | Region::Au915 => {
+            let channel = dev_addr.wrapping_add(beacon_time / BEACON_PERIOD_SECONDS) % 8;
+            hopping_frequency(channel)
+        }
+        region => beacon_frequency(region, beacon_time),
+    }
+}
+/// The radio config to receive the beacon with the given beacon time in the given region
+///
+/// # Implicit Header
+/// Beacons are sent in implicit header mode without CRC, so the RX payload length must be set to [`beacon_len`] (see
+/// [`crate::rfm95::Rfm95Driver::set_implicit_rx_len`]).
+pub const fn beacon_config(region: Region, beacon_time: u32) -> Config {
+    let (spreading_factor, bandwidth) = match region {
+        Region::Us915 | Region::Au915 => (SpreadingFactor::S12, Bandwidth::B500),
+        Region::In865 => (SpreadingFactor::S8, Bandwidth::B125),
+        _ => (SpreadingFactor::S9, Bandwidth::B125),
+    };
+    Config::builder()
+        .set_spreading_factor(spreading_factor)
+        .set_bandwidth(bandwidth)
+        .set_coding_rate(CodingRate::C4_5)
+        .set_polarity(Polarity::Normal)
+        .set_header_mode(HeaderMode::Implicit)
+        .set_crc_mode(CrcMode::Disabled)
+        .set_sync_word(SyncWord::LORAWAN_PUBLIC)
+        .set_preamble_length(PreambleLength::new(10))
+        .set_frequency(beacon_frequency(region, beacon_time))
+}
+
+/// The lengths of the two RFU fields of the beacon frame in the given region
+const fn beacon_rfu(region: Region) -> (usize, usize) {
+    match region {
+        Region::Us915 | Region::Au915 => (5, 3),
+        Region::In865 => (1, 3),
+        _ => (2, 0),
+    }
+}
+/// The frequency of the given beacon channel in US915 and AU915
+const fn hopping_frequency(channel: u32) -> Frequency {
+    Frequency::hz(923_300_000u32.saturating_add(channel.saturating_mul(600_000)))
+}
+/// Computes the CRC-16/CCITT (polynomial `0x1021`, initial value `0`) of a beacon field
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            // Shift out the MSB and apply the polynomial if it was set
+            let mask = (crc >> 15).wrapping_neg();
+            crc = (crc << 1) ^ (0x1021 & mask);
+        }
+    }
+    crc
+}
+
+/// A decoded Class B beacon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconFrame {
+    /// The GPS time of the beacon in seconds, modulo 2^32
+    time: u32,
+    /// The gateway info descriptor
+    info_desc: u8,
+    /// The gateway info
+    info: [u8; 6],
+}
+impl BeaconFrame {
+    /// Decodes a beacon received in the given region, and verifies both CRCs
+    pub fn parse(frame: &[u8], region: Region) -> Result<Self, InvalidMessageError> {
+        let (rfu1, rfu2) = beacon_rfu(region);
+        let Some(frame) = frame.get(..beacon_len(region)) else {
+            // The frame is too short
+            return Err(err!(InvalidMessageError, "Beacon is too short"));
+        };
+
+        // Split and verify the network common part and the gateway specific part
+        let Some((common, gateway)) = frame.split_at_checked(rfu1.saturating_add(6)) else {
+            // The frame layout is inconsistent
+            return Err(err!(InvalidMessageError, "Beacon is too short"));
+        };
+        let (Some((common, common_crc)), Some((gateway, gateway_crc))) =
+            (common.split_last_chunk::<2>(), gateway.split_last_chunk::<2>())
+        else {
+            // The frame layout is inconsistent
+            return Err(err!(InvalidMessageError, "Beacon is too short"));
+        };
+        if crc16(common) != u16::from_le_bytes(*common_crc) || crc16(gateway) != u16::from_le_bytes(*gateway_crc) {
+            return Err(err!(InvalidMessageError, "Invalid beacon CRC"));
+        }
+
+        // Decode the fields
+        let (Some((_, time)), Some((info_desc, info))) = (
+            common.split_last_chunk::<4>(),
+            gateway.get(..gateway.len().saturating_sub(rfu2)).and_then(<[u8]>::split_first),
+        ) else {
+            // The frame layout is inconsistent
+            return Err(err!(InvalidMessageError, "Beacon is too short"));
+        };
+        let Ok(info) = <[u8; 6]>::try_from(info) else {
+            // The gateway info has an invalid length
+            return Err(err!(InvalidMessageError, "Invalid beacon gateway info"));
+        };
+        Ok(Self { time: u32::from_le_bytes(*time), info_desc: *info_desc, info })
+    }
+
+    /// The GPS time of the beacon in seconds, modulo 2^32
+    pub const fn time(&self) -> u32 {
+        self.time
+    }
+    /// The gateway info descriptor (e.g. `0..=2` for the GPS coordinates of the gateway antenna)
+    pub const fn info_desc(&self) -> u8 {
+        self.info_desc
+    }
+    /// The gateway info
+    pub const fn info(&self) -> [u8; 6] {
+        self.info
+    }
+}
+
+/// The ping slots of a device within a beacon period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingSlots {
+    /// The randomized offset of the first ping slot in slots
+    offset: u16,
+    /// The ping period in slots
+    period: u16,
+}
+impl PingSlots {
+    /// Computes the ping slots of the given device address within the beacon period of the given beacon time, with the
+    /// given ping slot periodicity (`0..=7`, i.e. one ping slot every `2^periodicity` seconds)
+    ///
+    /// # Keys
+    /// `cipher` must use the all-zero key, as required by the ping offset randomization.
+    pub fn new<C>(cipher: &C, beacon_time: u32, dev_addr: u32, periodicity: u8) -> Self
+    where
+        C: BlockCipher,
+    {
+        // Compute the pseudo-random offset
+        let [t0, t1, t2, t3] = beacon_time.to_le_bytes();
+        let [a0, a1, a2, a3] = dev_addr.to_le_bytes();
+        let mut block: [u8; BLOCK_LEN] = [t0, t1, t2, t3, a0, a1, a2, a3, 0, 0, 0, 0, 0, 0, 0, 0];
+        cipher.encrypt_block(&mut block);
+        let [r0, r1, ..] = block;
+
+        // The ping period is `2^12 / 2^(7 - periodicity)` slots
+        let period = 1u16 << periodicity.min(7).saturating_add(5);
+        let offset = u16::from_le_bytes([r0, r1]).checked_rem(period).unwrap_or_default();
+        Self { offset, period }
+    }
+
+    /// The offset of the first ping slot in slots
+    pub const fn offset(&self) -> u16 {
+        self.offset
+    }
+    /// The ping period in slots
+    pub const fn period(&self) -> u16 {
+        self.period
+    }
+    /// The amount of ping slots per beacon period
+    pub const fn count(&self) -> u16 {
+        match PING_SLOT_COUNT.checked_div(self.period) {
+            Some(count) => count,
+            None => 0,
+        }
+    }
+    /// The offset of the given ping slot relative to the start of the beacon period in microseconds, or `None` if the
+    /// index is not less than [`Self::count`]
+    pub fn slot_offset(&self, index: u16) -> Option<u64> {
+        if index >= self.count() {
+            return None;
+        }
+        let slot = u64::from(self.offset).saturating_add(u64::from(index).saturating_mul(u64::from(self.period)));
+        Some(BEACON_RESERVED.saturating_add(slot.saturating_mul(PING_SLOT_LEN)))
+    }
+}
+
+/// An RX window for an expected beacon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconWindow {
+    /// The expected beacon time
+    beacon_time: u32,
+    /// The local clock time in microseconds at which the window opens
+    open_at: u64,
+    /// The window length
+    timeout: Duration,
+    /// The radio config for the beacon
+    config: Config,
+}
+impl BeaconWindow {
+    /// The expected beacon time
+    pub const fn beacon_time(&self) -> u32 {
+        self.beacon_time
+    }
+    /// The local clock time in microseconds at which the RX window should be opened
+    pub const fn open_at(&self) -> u64 {
+        self.open_at
+    }
+    /// The RX timeout, i.e. the window length
+    pub const fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    /// The radio config to receive the beacon
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+/// The tracking state of a [`BeaconTracker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackingState {
+    /// The beacon timing is unknown
+    Searching,
+    /// The beacon timing is known; the given amount of beacons has been missed since the last beacon
+    Tracking {
+        /// The amount of beacons missed since the last beacon
+        missed: u16,
+    },
+    /// Too many beacons have been missed; the beacon must be reacquired
+    Lost,
+}
+
+/// The reference beacon of a [`BeaconTracker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reference {
+    /// The local clock time in microseconds at which the beacon period started
+    local: u64,
+    /// The beacon time of the beacon period
+    beacon_time: u32,
+    /// The uncertainty of the local clock time in microseconds
+    uncertainty: u64,
+    /// Whether the reference is a received beacon (or an estimate)
+    received: bool,
+}
+
+/// A Class B beacon tracker with drift compensation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconTracker {
+    /// The region
+    region: Region,
+    /// The accuracy of the local clock in ppm
+    clock_accuracy: u32,
+    /// The maximum amount of missed beacons
+    max_missed: u16,
+    /// The reference beacon
+    reference: Option<Reference>,
+    /// The amount of beacons missed since the reference
+    missed: u16,
+    /// The measured drift of the local clock in ppm
+    drift: i32,
+}
+impl BeaconTracker {
+    /// The default accuracy of the local clock in ppm
+    pub const DEFAULT_CLOCK_ACCURACY: u32 = 30;
+    /// The default maximum amount of missed beacons (i.e. 2 hours of beacon-less operation)
+    pub const DEFAULT_MAX_MISSED: u16 = 56;
+    /// The minimum margin of a beacon window in microseconds
+    const WINDOW_MARGIN: u64 = 5_000;
+
+    /// Creates a new tracker for the given region without known beacon timing
+    pub const fn new(region: Region) -> Self {
+        Self {
+            region,
+            clock_accuracy: Self::DEFAULT_CLOCK_ACCURACY,
+            max_missed: Self::DEFAULT_MAX_MISSED,
+            reference: None,
+            missed: 0,
+            drift: 0,
+        }
+    }
+    /// Sets the accuracy of the local clock in ppm, which determines how fast the beacon windows widen
+    pub const fn with_clock_accuracy(mut self, clock_accuracy: u32) -> Self {
+        self.clock_accuracy = clock_accuracy;
+        self
+    }
+    /// Sets the maximum amount of consecutive missed beacons until the beacon is considered lost
+    pub const fn with_max_missed(mut self, max_missed: u16) -> Self {
+        self.max_missed = max_missed;
+        self
+    }
+
+    /// The region
+    pub const fn region(&self) -> Region {
+        self.region
+    }
+    /// The tracking state
+    pub const fn state(&self) -> TrackingState {
+        match self.reference {
+            None => TrackingState::Searching,
+            Some(_) if self.missed > self.max_missed => TrackingState::Lost,
+            Some(_) => TrackingState::Tracking { missed: self.missed },
+        }
+    }
+    /// The measured drift of the local clock in ppm, where positive values mean the local clock runs fast
+    pub const fn drift(&self) -> i32 {
+        self.drift
+    }
+
+    /// Acquires the beacon timing from the given GPS-epoch time in microseconds that corresponds to the given local
+    /// clock time, with the given uncertainty of the GPS time
+    pub fn acquire(&mut self, gps_time: u64, now: u64, uncertainty: Duration) {
+        // Compute the start of the current beacon period
+        let into_period = gps_time.checked_rem(BEACON_PERIOD).unwrap_or_default();
+        let period_start = gps_time.saturating_sub(into_period) / 1_000_000;
+        self.reference = Some(Reference {
+            local: now.saturating_sub(into_period),
+            beacon_time: u32::try_from(period_start & u64::from(u32::MAX)).unwrap_or_default(),
+            uncertainty: u64::try_from(uncertainty.as_micros()).unwrap_or(u64::MAX),
+            received: false,
+        });
+        self.missed = 0;
+    }
+    /// Resets the tracker to the searching state
+    pub fn reset(&mut self) {
+        self.reference = None;
+        self.missed = 0;
+    }
+
+    /// The RX window for the next expected beacon, or `None` if the beacon timing is unknown or lost
+    pub fn next_window(&self) -> Option<BeaconWindow> {
+        let reference = self.reference.filter(|_| self.missed <= self.max_missed)?;
+
+        // Predict the start of the next beacon period and the uncertainty
+        let periods = self.missed.saturating_add(1);
+        let beacon_time = reference.beacon_time.wrapping_add(u32::from(periods).wrapping_mul(BEACON_PERIOD_SECONDS));
+        let elapsed = BEACON_PERIOD.saturating_mul(u64::from(periods));
+        let expected = reference.local.saturating_add(self.local_duration(elapsed));
+        let drift = elapsed.saturating_mul(u64::from(self.clock_accuracy)) / 1_000_000;
+        let margin = reference.uncertainty.saturating_add(drift).saturating_add(Self::WINDOW_MARGIN);
+
+        // Open the window early enough, and keep it open until the preamble has been detected
+        let config = beacon_config(self.region, beacon_time);
+        let preamble = airtime::preamble_airtime(config);
+        let timeout = Duration::from_micros(margin.saturating_mul(2)).saturating_add(preamble);
+        Some(BeaconWindow { beacon_time, open_at: expected.saturating_sub(margin), timeout, config })
+    }
+    /// Records a received beacon, where `rx_done` is the local clock time in microseconds at which the reception
+    /// completed (see [`crate::rfm95::Rfm95Driver::complete_rx_timestamped`])
+    pub fn beacon_received(&mut self, beacon: &BeaconFrame, rx_done: u64) {
+        // The beacon is sent at the start of the beacon period
+        let airtime = airtime::airtime(beacon_len(self.region), beacon_config(self.region, beacon.time));
+        let start = rx_done.saturating_sub(u64::try_from(airtime.as_micros()).unwrap_or(u64::MAX));
+
+        // Measure the drift against the last received beacon
+        if let Some(reference) = self.reference.filter(|reference| reference.received) {
+            let seconds = beacon.time.wrapping_sub(reference.beacon_time);
+            let expected = i64::from(seconds).saturating_mul(1_000_000);
+            let measured = i64::try_from(start.saturating_sub(reference.local)).unwrap_or(i64::MAX);
+            let drift = measured.saturating_sub(expected).saturating_mul(1_000_000).checked_div(expected);
+            if let Some(drift) = drift.and_then(|drift| i32::try_from(drift).ok()) {
+                self.drift = drift;
+            }
+        }
+
+        self.reference = Some(Reference { local: start, beacon_time: beacon.time, uncertainty: 0, received: true });
+        self.missed = 0;
+    }
+    /// Records that the beacon was not received in the window returned by [`Self::next_window`]
+    pub fn beacon_missed(&mut self) {
+        if self.reference.is_some() {
+            self.missed = self.missed.saturating_add(1);
+        }
+    }
+
+    /// The local clock time in microseconds at which the given ping slot of the beacon period with the given beacon
+    /// time starts, or `None` if the beacon timing is unknown or lost, or the ping slot does not exist
+    pub fn ping_slot_at(&self, ping_slots: &PingSlots, beacon_time: u32, index: u16) -> Option<u64> {
+        let reference = self.reference.filter(|_| self.missed <= self.max_missed)?;
+        let offset = ping_slots.slot_offset(index)?;
+
+        // Compute the start of the beacon period relative to the reference
+        let seconds = beacon_time.wrapping_sub(reference.beacon_time);
+        let elapsed = u64::from(seconds).saturating_mul(1_000_000).saturating_add(offset);
+        Some(reference.local.saturating_add(self.local_duration(elapsed)))
+    }
+
+    /// Converts a nominal duration in microseconds into a local clock duration, compensating the measured drift
+    fn local_duration(&self, nominal: u64) -> u64 {
+        let nominal_signed = i64::try_from(nominal).unwrap_or(i64::MAX);
+        let correction = nominal_signed.saturating_mul(i64::from(self.drift)) / 1_000_000;
+        nominal.saturating_add_signed(correction)
+    }
+}
+
+#[cfg(all(test, feature = "aes"))]
+mod tests {
+    use super::*;
+    use crate::lora::lorawan_crypto::Aes128;
+
+    #[test]
+    fn ping_slot_offset() {
+        // `Rand = aes128_encrypt(0^16, beacon_time | dev_addr | pad16)`, `offset = (Rand[0] + Rand[1] * 256) % period`
+        let cipher = Aes128::new(&[0; 16]);
+        let slots = PingSlots::new(&cipher, 0x4BB0_0000, 0x2601_1BDA, 0);
+        assert_eq!((slots.offset(), slots.period(), slots.count()), (28, 32, 128));
+        let slots = PingSlots::new(&cipher, 0x4D7C_6D00, 0x2601_1BDA, 7);
+        assert_eq!((slots.offset(), slots.period(), slots.count()), (308, 4096, 1));
+        let slots = PingSlots::new(&cipher, 0, 0, 5);
+        assert_eq!((slots.offset(), slots.period(), slots.count()), (358, 1024, 4));
+    }
+
+    #[test]
+    fn ping_slot_times() {
+        let cipher = Aes128::new(&[0; 16]);
+        let slots = PingSlots::new(&cipher, 0x4BB0_0000, 0x2601_1BDA, 0);
+        assert_eq!(slots.slot_offset(0), Some(BEACON_RESERVED + 28 * PING_SLOT_LEN));
+        assert_eq!(slots.slot_offset(1), Some(BEACON_RESERVED + 60 * PING_SLOT_LEN));
+        assert_eq!(slots.slot_offset(127), Some(BEACON_RESERVED + (28 + 127 * 32) * PING_SLOT_LEN));
+        assert_eq!(slots.slot_offset(128), None);
+    }
+}
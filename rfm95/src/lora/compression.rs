@@ -0,0 +1,285 @@
+//! LZSS payload compression to save airtime
+//!
+//! # Format
+//! The compressed stream consists of groups of a control byte followed by up to eight items. If the corresponding bit
+//! of the control byte (LSB first) is set, the item is a back-reference of two bytes: the distance to the source (`1`
+//! to `255`) and the length minus [`MIN_MATCH`]; otherwise, it is a literal byte. As LoRa frames are at most 255 bytes
+//! long, the window covers the entire frame.
+//!
+//! # Frames
+//! [`pack`] prepends a header byte that marks whether the payload is compressed, and falls back to the raw payload if
+//! compression does not save space (e.g. for short or already compressed/encrypted data). [`unpack`] reverses this.
+//! Also see [`crate::rfm95::Rfm95Driver::start_tx_compressed`] and
+//! [`crate::rfm95::Rfm95Driver::complete_rx_decompressed`].
+
+use crate::err;
+use crate::error::{BufferTooSmallError, InvalidArgumentError, ProtocolError};
+
+/// The minimum length of a back-reference
+pub const MIN_MATCH: usize = 3;
+/// The maximum length of a back-reference
+pub const MAX_MATCH: usize = MIN_MATCH + u8::MAX as usize;
+/// The maximum distance of a back-reference
+const MAX_DISTANCE: usize = u8::MAX as usize;
+
+/// The frame header of a raw payload
+pub const HEADER_RAW: u8 = 0x00;
+/// The frame header of a compressed payload
+pub const HEADER_COMPRESSED: u8 = 0x01;
+
+/// A bounded output buffer
+struct Output<'a> {
+    /// The underlying buffer
+    buf: &'a mut [u8],
+    /// The amount of bytes written
+    len: usize,
+}
+impl Output<'_> {
+    /// Appends a byte
+    fn push(&mut self, byte: u8) -> Result<(), BufferTooSmallError> {
+        let Some(slot) = self.buf.get_mut(self.len) else {
+            // The output buffer is full
+            return Err(err!(BufferTooSmallError, "Output buffer is too small"));
+        };
+        *slot = byte;
+        self.len = self.len.saturating_add(1);
+        Ok(())
+    }
+    /// Appends a copy of `len` bytes that start `distance` bytes before the end; the ranges may overlap
+    fn copy_back(&mut self, distance: usize, len: usize) -> Result<(), ProtocolError> {
+        let Some(start) = self.len.checked_sub(distance).filter(|_| distance > 0) else {
+            // The back-reference points before the start of the output
+            return Err(err!(InvalidArgumentError, "Invalid back-reference in compressed stream"))?;
+        };
+        for index in start..start.saturating_add(len) {
+            let byte = self.buf.get(index).copied().unwrap_or_default();
+            self.push(byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compresses `input` into `output` and returns the compressed length
+///
+/// # Buffer too small
+/// If the compressed stream does not fit into `output`, an error is returned. The compressed stream of incompressible
+/// data is up to one eighth larger than the input.
+pub fn compress(input: &[u8], output: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+    let mut output = Output { buf: output, len: 0 };
+    let (mut position, mut control_position, mut bit) = (0, 0, 8u32);
+    while position < input.len() {
+        // Start a new group
+        if bit == 8 {
+            control_position = output.len;
+            output.push(0)?;
+            bit = 0;
+        }
+
+        // Emit a back-reference or a literal
+        match longest_match(input, position) {
+            (distance, len) if len >= MIN_MATCH => {
+                if let Some(control) = output.buf.get_mut(control_position) {
+                    *control |= 1 << bit;
+                }
+                #[allow(clippy::cast_possible_truncation, reason = "Distance and length are bounded to fit into u8")]
+                let (distance, len_code) = (distance as u8, len.saturating_sub(MIN_MATCH) as u8);
+                output.push(distance)?;
+                output.push(len_code)?;
+                position = position.saturating_add(len);
+            }
+            _ => {
+                output.push(input.get(position).copied().unwrap_or_default())?;
+                position = position.saturating_add(1);
+            }
+        }
+        bit = bit.saturating_add(1);
+    }
+    Ok(output.len)
+}
+/// Decompresses `input` into `output` and returns the decompressed length
+///
+/// # Errors
+/// If the stream is corrupt, an invalid-argument error is returned; if the decompressed data does not fit into
+/// `output`, a buffer-too-small error is returned.
+pub fn decompress(input: &[u8], output: &mut [u8]) -> Result<usize, ProtocolError> {
+    let mut output = Output { buf: output, len: 0 };
+    let mut input = input.iter().copied();
+    while let Some(control) = input.next() {
+        for bit in 0..8 {
+            // Decode the next item
+            let is_reference = control & (1 << bit) != 0;
+            match (is_reference, input.next()) {
+                (false, Some(literal)) => output.push(literal)?,
+                (true, Some(distance)) => {
+                    let Some(len_code) = input.next() else {
+                        // The back-reference is truncated
+                        return Err(err!(InvalidArgumentError, "Truncated compressed stream"))?;
+                    };
+                    let len = (len_code as usize).saturating_add(MIN_MATCH);
+                    output.copy_back(distance as usize, len)?;
+                }
+                (false, None) => return Ok(output.len),
+                (true, None) => return Err(err!(InvalidArgumentError, "Truncated compressed stream"))?,
+            }
+        }
+    }
+    Ok(output.len)
+}
+
+/// Writes a frame with the header and the compressed payload, or the raw payload if compression does not save space,
+/// into `frame` and returns the frame length
+pub fn pack(payload: &[u8], frame: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+    let Some((header, body)) = frame.split_first_mut() else {
+        // The frame buffer is empty
+        return Err(err!(BufferTooSmallError, "Frame buffer is too small"));
+    };
+
+    // Try to compress the payload
+    if let Ok(len) = compress(payload, body) {
+        if len < payload.len() {
+            *header = HEADER_COMPRESSED;
+            return Ok(len.saturating_add(1));
+        }
+    }
+
+    // Fall back to the raw payload
+    let Some(body) = body.get_mut(..payload.len()) else {
+        // The payload does not fit into the frame
+        return Err(err!(BufferTooSmallError, "Frame buffer is too small"));
+    };
+    body.copy_from_slice(payload);
+    *header = HEADER_RAW;
+    Ok(payload.len().saturating_add(1))
+}
+/// Reads a frame written by [`pack`], writes the payload into `payload` and returns the payload length
+pub fn unpack(frame: &[u8], payload: &mut [u8]) -> Result<usize, ProtocolError> {
+    match frame.split_first() {
+        Some((&HEADER_COMPRESSED, body)) => decompress(body, payload),
+        Some((&HEADER_RAW, body)) => {
+            let Some(payload) = payload.get_mut(..body.len()) else {
+                // The payload does not fit into the buffer
+                return Err(err!(BufferTooSmallError, "Output buffer is too small"))?;
+            };
+            payload.copy_from_slice(body);
+            Ok(body.len())
+        }
+        _ => Err(err!(InvalidArgumentError, "Invalid compression frame header"))?,
+    }
+}
+
+/// Finds the longest match for the data at `position` within the window, and returns its distance and length
+fn longest_match(input: &[u8], position: usize) -> (usize, usize) {
+    let ahead = input.get(position..).unwrap_or_default();
+    let ahead = ahead.get(..MAX_MATCH).unwrap_or(ahead);
+    let mut best = (0, 0);
+    for distance in 1..=position.min(MAX_DISTANCE) {
+        // Compare the source with the lookahead; the source may overlap the lookahead
+        let source = input.get(position.saturating_sub(distance)..).unwrap_or_default();
+        let len = ahead.iter().zip(source).take_while(|(a, b)| a == b).count();
+        if len > best.1 {
+            best = (distance, len);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+#[allow(clippy::panic, clippy::indexing_slicing, reason = "Test failures are reported via panics")]
+mod tests {
+    use super::*;
+
+    /// Compresses and decompresses the given input, and returns the compressed length
+    fn roundtrip(input: &[u8]) -> usize {
+        let mut compressed = [0; 512];
+        let Ok(compressed_len) = compress(input, &mut compressed) else {
+            // Incompressible data grows by at most one eighth
+            panic!("Failed to compress {} bytes", input.len());
+        };
+
+        let mut decompressed = [0; 256];
+        let decompressed_len = decompress(&compressed[..compressed_len], &mut decompressed).ok();
+        assert_eq!(decompressed_len, Some(input.len()));
+        assert_eq!(&decompressed[..input.len()], input);
+        compressed_len
+    }
+
+    /// Fills the buffer with xorshift noise
+    fn noise(buf: &mut [u8]) {
+        let mut state = 0x2545_F491_u32;
+        for byte in buf {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            *byte = state.to_le_bytes()[0];
+        }
+    }
+
+    #[test]
+    fn compress_known_stream() {
+        // A literal followed by an overlapping back-reference to it
+        let mut compressed = [0; 8];
+        assert_eq!(compress(b"aaaaaa", &mut compressed).ok(), Some(4));
+        assert_eq!(compressed[..4], [0b0000_0010, b'a', 1, 2]);
+    }
+
+    #[test]
+    fn roundtrip_inputs() {
+        assert_eq!(roundtrip(b""), 0);
+        roundtrip(b"a");
+        roundtrip(b"abcabcabcabcabcabc");
+        roundtrip(b"The quick brown fox jumps over the lazy dog. The quick brown fox jumps over the lazy dog.");
+        assert!(roundtrip(&[0x42; 255]) < 8);
+
+        // Runs longer than the longest back-reference
+        let mut runs = [0; 255];
+        runs[128..].fill(0xFF);
+        roundtrip(&runs);
+
+        // Incompressible data grows by at most one eighth
+        let mut random = [0; 255];
+        noise(&mut random);
+        assert!(roundtrip(&random) <= 255 + 32);
+    }
+
+    #[test]
+    fn pack_falls_back_to_raw() {
+        let mut random = [0; 64];
+        noise(&mut random);
+        let mut frame = [0; 65];
+        assert_eq!(pack(&random, &mut frame).ok(), Some(65));
+        assert_eq!(frame[0], HEADER_RAW);
+
+        let mut payload = [0; 64];
+        assert_eq!(unpack(&frame, &mut payload).ok(), Some(64));
+        assert_eq!(payload, random);
+    }
+
+    #[test]
+    fn pack_compresses() {
+        let mut frame = [0; 256];
+        let Ok(len) = pack(&[0x42; 200], &mut frame) else {
+            panic!("Failed to pack the payload");
+        };
+        assert!(len < 10);
+        assert_eq!(frame[0], HEADER_COMPRESSED);
+
+        let mut payload = [0; 200];
+        assert_eq!(unpack(&frame[..len], &mut payload).ok(), Some(200));
+        assert_eq!(payload, [0x42; 200]);
+    }
+
+    #[test]
+    fn decompress_rejects_corrupt_streams() {
+        let mut output = [0; 16];
+        // A back-reference before the start of the output
+        assert!(decompress(&[0b0000_0001, 1, 0], &mut output).is_err());
+        // A back-reference with distance zero
+        assert!(decompress(&[0b0000_0010, b'a', 0, 0], &mut output).is_err());
+        // A truncated back-reference
+        assert!(decompress(&[0b0000_0010, b'a', 1], &mut output).is_err());
+        // An output that is too small
+        assert!(decompress(&[0b0000_0010, b'a', 1, 20], &mut output).is_err());
+        // An invalid frame header
+        assert!(unpack(&[0x02, b'a'], &mut output).is_err());
+    }
+}
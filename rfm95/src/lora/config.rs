@@ -1,5 +1,9 @@
 //! A LoRa radio config object with builder pattern for initial initialization
 
+use crate::err;
+use crate::error::InvalidArgumentError;
+use crate::lora::datarate::DataRate;
+use crate::lora::region::Region;
 use crate::lora::types::{
     Bandwidth, CodingRate, CrcMode, Frequency, HeaderMode, Polarity, PreambleLength, SpreadingFactor, SyncWord,
 };
@@ -13,7 +17,7 @@ use crate::lora::types::{
 /// Or, to be more precised: The final `Config` exactly the same as the `Builder` type with all fields set.
 // Note: We use 1-letter abbreviations for the config fields to keep the code readable and to not bloat the file with
 // dozens of repetitions
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Builder<S = (), B = (), R = (), P = (), H = (), C = (), W = (), L = (), F = ()> {
     /// Spreading factor
     s: S,
@@ -34,71 +38,198 @@ pub struct Builder<S = (), B = (), R = (), P = (), H = (), C = (), W = (), L = (
     /// Frequency
     f: F,
 }
-impl<B, R, P, H, C, W, L, F> Builder<(), B, R, P, H, C, W, L, F> {
+impl<B: Copy, R: Copy, P: Copy, H: Copy, C: Copy, W: Copy, L: Copy, F: Copy> Builder<(), B, R, P, H, C, W, L, F> {
     /// Sets the spreading factor
-    pub fn set_spreading_factor(self, s: SpreadingFactor) -> Builder<SpreadingFactor, B, R, P, H, C, W, L, F> {
+    pub const fn set_spreading_factor(self, s: SpreadingFactor) -> Builder<SpreadingFactor, B, R, P, H, C, W, L, F> {
         Builder { s, b: self.b, r: self.r, p: self.p, h: self.h, c: self.c, w: self.w, l: self.l, f: self.f }
     }
 }
-impl<S, R, P, H, C, W, L, F> Builder<S, (), R, P, H, C, W, L, F> {
+impl<S: Copy, R: Copy, P: Copy, H: Copy, C: Copy, W: Copy, L: Copy, F: Copy> Builder<S, (), R, P, H, C, W, L, F> {
     /// Sets the bandwidth
-    pub fn set_bandwidth(self, b: Bandwidth) -> Builder<S, Bandwidth, R, P, H, C, W, L, F> {
+    pub const fn set_bandwidth(self, b: Bandwidth) -> Builder<S, Bandwidth, R, P, H, C, W, L, F> {
         Builder { s: self.s, b, r: self.r, p: self.p, h: self.h, c: self.c, w: self.w, l: self.l, f: self.f }
     }
 }
-impl<S, B, P, H, C, W, L, F> Builder<S, B, (), P, H, C, W, L, F> {
+impl<R: Copy, P: Copy, H: Copy, C: Copy, W: Copy, L: Copy, F: Copy> Builder<(), (), R, P, H, C, W, L, F> {
+    /// Sets the spreading factor and bandwidth from the given LoRaWAN data rate in the given region, or returns `None`
+    /// if the data rate is not a LoRa data rate in the given region
+    #[allow(clippy::type_complexity, reason = "The builder type is inherently complex")]
+    pub const fn set_data_rate(
+        self,
+        data_rate: DataRate,
+        region: Region,
+    ) -> Option<Builder<SpreadingFactor, Bandwidth, R, P, H, C, W, L, F>> {
+        let Some((s, b)) = data_rate.parameters(region) else {
+            // The data rate is not a LoRa data rate in the given region
+            return None;
+        };
+        Some(Builder { s, b, r: self.r, p: self.p, h: self.h, c: self.c, w: self.w, l: self.l, f: self.f })
+    }
+}
+impl<S: Copy, B: Copy, P: Copy, H: Copy, C: Copy, W: Copy, L: Copy, F: Copy> Builder<S, B, (), P, H, C, W, L, F> {
     /// Sets the coding rate
-    pub fn set_coding_rate(self, r: CodingRate) -> Builder<S, B, CodingRate, P, H, C, W, L, F> {
+    pub const fn set_coding_rate(self, r: CodingRate) -> Builder<S, B, CodingRate, P, H, C, W, L, F> {
         Builder { s: self.s, b: self.b, r, p: self.p, h: self.h, c: self.c, w: self.w, l: self.l, f: self.f }
     }
 }
-impl<S, B, R, H, C, W, L, F> Builder<S, B, R, (), H, C, W, L, F> {
+impl<S: Copy, B: Copy, R: Copy, H: Copy, C: Copy, W: Copy, L: Copy, F: Copy> Builder<S, B, R, (), H, C, W, L, F> {
     /// Sets the P polarity
-    pub fn set_polarity(self, p: Polarity) -> Builder<S, B, R, Polarity, H, C, W, L, F> {
+    pub const fn set_polarity(self, p: Polarity) -> Builder<S, B, R, Polarity, H, C, W, L, F> {
         Builder { s: self.s, b: self.b, r: self.r, p, h: self.h, c: self.c, w: self.w, l: self.l, f: self.f }
     }
 }
-impl<S, B, R, P, C, W, L, F> Builder<S, B, R, P, (), C, W, L, F> {
+impl<S: Copy, B: Copy, R: Copy, P: Copy, C: Copy, W: Copy, L: Copy, F: Copy> Builder<S, B, R, P, (), C, W, L, F> {
     /// Sets the header mode
-    pub fn set_header_mode(self, h: HeaderMode) -> Builder<S, B, R, P, HeaderMode, C, W, L, F> {
+    pub const fn set_header_mode(self, h: HeaderMode) -> Builder<S, B, R, P, HeaderMode, C, W, L, F> {
         Builder { s: self.s, b: self.b, r: self.r, p: self.p, h, c: self.c, w: self.w, l: self.l, f: self.f }
     }
 }
-impl<S, B, R, P, H, W, L, F> Builder<S, B, R, P, H, (), W, L, F> {
+impl<S: Copy, B: Copy, R: Copy, P: Copy, H: Copy, W: Copy, L: Copy, F: Copy> Builder<S, B, R, P, H, (), W, L, F> {
     /// Sets the CC mode
-    pub fn set_crc_mode(self, c: CrcMode) -> Builder<S, B, R, P, H, CrcMode, W, L, F> {
+    pub const fn set_crc_mode(self, c: CrcMode) -> Builder<S, B, R, P, H, CrcMode, W, L, F> {
         Builder { s: self.s, b: self.b, r: self.r, p: self.p, h: self.h, c, w: self.w, l: self.l, f: self.f }
     }
 }
-impl<S, B, R, P, H, C, L, F> Builder<S, B, R, P, H, C, (), L, F> {
+impl<S: Copy, B: Copy, R: Copy, P: Copy, H: Copy, C: Copy, L: Copy, F: Copy> Builder<S, B, R, P, H, C, (), L, F> {
     /// Sets the sync word
-    pub fn set_sync_word(self, w: SyncWord) -> Builder<S, B, R, P, H, C, SyncWord, L, F> {
+    pub const fn set_sync_word(self, w: SyncWord) -> Builder<S, B, R, P, H, C, SyncWord, L, F> {
         Builder { s: self.s, b: self.b, r: self.r, p: self.p, h: self.h, c: self.c, w, l: self.l, f: self.f }
     }
 }
-impl<S, B, R, P, H, C, W, F> Builder<S, B, R, P, H, C, W, (), F> {
+impl<S: Copy, B: Copy, R: Copy, P: Copy, H: Copy, C: Copy, W: Copy, F: Copy> Builder<S, B, R, P, H, C, W, (), F> {
     /// Sets the preamble length
-    pub fn set_preamble_length(self, l: PreambleLength) -> Builder<S, B, R, P, H, C, W, PreambleLength, F> {
+    pub const fn set_preamble_length(self, l: PreambleLength) -> Builder<S, B, R, P, H, C, W, PreambleLength, F> {
         Builder { s: self.s, b: self.b, r: self.r, p: self.p, h: self.h, c: self.c, w: self.w, l, f: self.f }
     }
 }
-impl<S, B, R, P, H, C, W, L> Builder<S, B, R, P, H, C, W, L, ()> {
+impl<S: Copy, B: Copy, R: Copy, P: Copy, H: Copy, C: Copy, W: Copy, L: Copy> Builder<S, B, R, P, H, C, W, L, ()> {
     /// Sets the frequency
-    pub fn set_frequency(self, f: Frequency) -> Builder<S, B, R, P, H, C, W, L, Frequency> {
+    pub const fn set_frequency(self, f: Frequency) -> Builder<S, B, R, P, H, C, W, L, Frequency> {
         Builder { s: self.s, b: self.b, r: self.r, p: self.p, h: self.h, c: self.c, w: self.w, l: self.l, f }
     }
 }
 
 /// A LoRa radio config
+///
+/// # Const Construction
+/// The builder is fully usable in `const` contexts, so configs can live in `static` configuration tables:
+/// ```rust
+/// use embedded_lora_rfm95::lora::config::Config;
+/// use embedded_lora_rfm95::lora::types::*;
+///
+/// static CONFIG: Config = Config::builder()
+///     .set_spreading_factor(SpreadingFactor::S7)
+///     .set_bandwidth(Bandwidth::B125)
+///     .set_coding_rate(CodingRate::C4_5)
+///     .set_polarity(Polarity::Normal)
+///     .set_header_mode(HeaderMode::Explicit)
+///     .set_crc_mode(CrcMode::Enabled)
+///     .set_sync_word(SyncWord::PRIVATE_DEFAULT)
+///     .set_preamble_length(PreambleLength::L8)
+///     .set_frequency(Frequency::F868_1);
+/// ```
 pub type Config =
     Builder<SpreadingFactor, Bandwidth, CodingRate, Polarity, HeaderMode, CrcMode, SyncWord, PreambleLength, Frequency>;
 impl Config {
     /// Creates a new config builder
     #[allow(clippy::self_named_constructors, reason = "Mislint due to type alias")]
-    pub fn builder() -> Builder {
+    pub const fn builder() -> Builder {
         Builder { s: (), b: (), r: (), p: (), h: (), c: (), w: (), l: (), f: () }
     }
 
+    /// The lowest frequency supported by the modem
+    pub const FREQUENCY_MIN: Frequency = Frequency::hz(137_000_000);
+    /// The highest frequency supported by the modem
+    pub const FREQUENCY_MAX: Frequency = Frequency::hz(1_020_000_000);
+    /// The shortest preamble length supported by the modem
+    pub const PREAMBLE_LENGTH_MIN: PreambleLength = PreambleLength::new(6);
+
+    /// A long range preset for EU868 (SF12, 125 kHz, CR 4/8) with ~180 bps
+    ///
+    /// # Note
+    /// This preset has a very long airtime of up to ~14 seconds per packet; mind the duty cycle limits of your region.
+    pub const LONG_RANGE: Self =
+        Self::preset(SpreadingFactor::S12, Bandwidth::B125, CodingRate::C4_8, Frequency::F868_1);
+    /// A balanced preset for EU868 (SF9, 125 kHz, CR 4/5) with ~1.7 kbps
+    pub const BALANCED: Self = Self::preset(SpreadingFactor::S9, Bandwidth::B125, CodingRate::C4_5, Frequency::F868_1);
+    /// A fast short range preset for EU868 (SF7, 250 kHz, CR 4/5) with ~11 kbps
+    pub const FAST: Self = Self::preset(SpreadingFactor::S7, Bandwidth::B250, CodingRate::C4_5, Frequency::F868_1);
+
+    /// A long range preset for US915 (SF10, 125 kHz, CR 4/5)
+    ///
+    /// # Note
+    /// Spreading factors above SF10 exceed the 400 ms dwell time limit of the US915 band even for tiny packets. Even
+    /// with SF10, the dwell time limits the payload to ~11 bytes.
+    pub const LONG_RANGE_US915: Self =
+        Self::preset(SpreadingFactor::S10, Bandwidth::B125, CodingRate::C4_5, Frequency::hz(902_300_000));
+    /// A balanced preset for US915 (SF9, 125 kHz, CR 4/5)
+    pub const BALANCED_US915: Self =
+        Self::preset(SpreadingFactor::S9, Bandwidth::B125, CodingRate::C4_5, Frequency::hz(902_300_000));
+    /// A fast short range preset for US915 (SF8, 500 kHz, CR 4/5)
+    pub const FAST_US915: Self =
+        Self::preset(SpreadingFactor::S8, Bandwidth::B500, CodingRate::C4_5, Frequency::hz(903_000_000));
+
+    /// The raw frequency limits in Hz for pattern matching
+    const FREQUENCY_MIN_HZ: u32 = Self::FREQUENCY_MIN.as_u32();
+    /// The raw frequency limits in Hz for pattern matching
+    const FREQUENCY_MAX_HZ: u32 = Self::FREQUENCY_MAX.as_u32();
+    /// The raw preamble length limit for pattern matching
+    const PREAMBLE_LENGTH_MIN_RAW: u16 = Self::PREAMBLE_LENGTH_MIN.as_u16();
+
+    /// Creates a preset with the given modulation parameters and frequency, and sensible defaults for the other fields
+    const fn preset(s: SpreadingFactor, b: Bandwidth, r: CodingRate, f: Frequency) -> Self {
+        Builder {
+            s,
+            b,
+            r,
+            p: Polarity::Normal,
+            h: HeaderMode::Explicit,
+            c: CrcMode::Enabled,
+            w: SyncWord::PRIVATE_DEFAULT,
+            l: PreambleLength::L8,
+            f,
+        }
+    }
+    /// Replaces the frequency, e.g. to move a preset to another channel
+    pub const fn with_frequency(mut self, frequency: Frequency) -> Self {
+        self.f = frequency;
+        self
+    }
+    /// Replaces the spreading factor, e.g. to adjust the data rate of a preset
+    pub const fn with_spreading_factor(mut self, spreading_factor: SpreadingFactor) -> Self {
+        self.s = spreading_factor;
+        self
+    }
+    /// Replaces the bandwidth, e.g. to adjust the data rate of a preset
+    pub const fn with_bandwidth(mut self, bandwidth: Bandwidth) -> Self {
+        self.b = bandwidth;
+        self
+    }
+
+    /// Validates the config against the modem limits, and returns the config if it is valid
+    ///
+    /// # Const Validation
+    /// This function is usable in `const` contexts, so static configuration tables can be validated at compile time.
+    pub const fn validate(self) -> Result<Self, InvalidArgumentError> {
+        // Validate the frequency
+        let Self::FREQUENCY_MIN_HZ..=Self::FREQUENCY_MAX_HZ = self.f.as_u32() else {
+            // The frequency is outside of the supported range
+            return Err(err!(InvalidArgumentError, "Frequency is outside of the supported range"));
+        };
+
+        // Validate the preamble length
+        let Self::PREAMBLE_LENGTH_MIN_RAW.. = self.l.as_u16() else {
+            // The preamble is too short
+            return Err(err!(InvalidArgumentError, "Preamble length is too short"));
+        };
+
+        // Validate the sync word
+        if self.w.is_ambiguous() {
+            return Err(err!(InvalidArgumentError, "Sync word cross-receives with a well-known sync word"));
+        }
+        Ok(self)
+    }
+
     /// The spreading factor
     pub const fn spreading_factor(&self) -> SpreadingFactor {
         self.s
@@ -0,0 +1,227 @@
+//! Retransmission of confirmed LoRaWAN uplinks
+//!
+//! # Retransmission Rules
+//! A confirmed uplink is retransmitted until a downlink with the `ACK` bit is received, or until the maximum amount of
+//! transmissions (`NbTrans`, 8 by default) is reached:
+//! - each retransmission is delayed by `ACK_TIMEOUT` (a random delay of 1 to 3 seconds) after the RX windows of the
+//!   previous transmission have closed
+//! - the data rate is lowered by one step every two transmissions (e.g. `DR5, DR5, DR4, DR4, DR3, ...`), down to the
+//!   minimum data rate (see [`ConfirmedUplink::with_data_rate_decay`])
+//!
+//! All retransmissions must reuse the frame counter of the first transmission. Regional duty-cycle limits still apply
+//! (see [`crate::lora::ledger::AirtimeLedger`]).
+//!
+//! # Usage
+//! Start a confirmed uplink via [`ConfirmedUplink::start`], then call [`ConfirmedUplink::poll_transmit`] regularly; if
+//! it returns a data rate, transmit the frame with that data rate and open the RX windows. Pass each authenticated
+//! downlink to [`ConfirmedUplink::acknowledge`], and call [`ConfirmedUplink::rx_windows_closed`] once the RX windows
+//! have closed without an ACK. The delivery status is reported via [`ConfirmedUplink::status`].
+
+use crate::lora::csma::Csma;
+use crate::lora::datarate::DataRate;
+use crate::lora::phypayload::DataPayload;
+use core::time::Duration;
+
+/// The delivery status of a confirmed uplink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// No confirmed uplink has been started
+    Idle,
+    /// The uplink has been transmitted the given amount of times, and is waiting for an ACK or a retransmission
+    Pending {
+        /// The amount of transmissions so far
+        transmissions: u8,
+    },
+    /// The uplink has been acknowledged
+    Delivered {
+        /// The amount of transmissions until the uplink was acknowledged
+        transmissions: u8,
+    },
+    /// The uplink has not been acknowledged after the maximum amount of transmissions, or has been cancelled
+    Failed {
+        /// The amount of transmissions
+        transmissions: u8,
+    },
+}
+
+/// The state of a confirmed uplink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UplinkState {
+    /// No confirmed uplink has been started
+    Idle,
+    /// The next transmission is due at the given timestamp
+    Ready(u64),
+    /// The uplink has been transmitted and the RX windows are open
+    AwaitingAck,
+    /// The uplink has been acknowledged
+    Delivered,
+    /// The uplink has failed
+    Failed,
+}
+
+/// A non-blocking retransmission state machine for confirmed uplinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmedUplink {
+    /// The backoff generator for the ACK timeout
+    backoff: Csma,
+    /// The minimum ACK timeout in microseconds
+    ack_timeout: u64,
+    /// The maximum amount of transmissions
+    max_transmissions: u8,
+    /// Whether the data rate is lowered every two transmissions
+    data_rate_decay: bool,
+    /// The minimum data rate for the data rate decay
+    min_data_rate: DataRate,
+    /// The data rate of the first transmission
+    data_rate: DataRate,
+    /// The amount of transmissions so far
+    transmissions: u8,
+    /// The state of the uplink
+    state: UplinkState,
+}
+impl ConfirmedUplink {
+    /// The default maximum amount of transmissions
+    pub const DEFAULT_MAX_TRANSMISSIONS: u8 = 8;
+    /// The maximum amount of transmissions that can be configured via `NbTrans`
+    const MAX_TRANSMISSIONS: u8 = 15;
+
+    /// Creates a new state machine with the given seed for the ACK timeout jitter, and an ACK timeout of 1 to 3 seconds
+    ///
+    /// # Seed
+    /// Seed the generator via [`crate::rfm95::Rfm95Driver::random_u32`], so devices that lost the same ACK do not
+    /// retransmit in lockstep.
+    pub const fn new(seed: u32) -> Self {
+        Self {
+            backoff: Csma::new(seed, Duration::from_millis(1), 2_001),
+            ack_timeout: 1_000_000,
+            max_transmissions: Self::DEFAULT_MAX_TRANSMISSIONS,
+            data_rate_decay: true,
+            min_data_rate: DataRate::DR0,
+            data_rate: DataRate::DR0,
+            transmissions: 0,
+            state: UplinkState::Idle,
+        }
+    }
+    /// Sets the maximum amount of transmissions, including the first transmission
+    ///
+    /// # Range
+    /// The amount of transmissions is clamped to `1..=15`.
+    pub const fn with_max_transmissions(mut self, max_transmissions: u8) -> Self {
+        self.max_transmissions = match max_transmissions {
+            0 => 1,
+            16.. => Self::MAX_TRANSMISSIONS,
+            max_transmissions => max_transmissions,
+        };
+        self
+    }
+    /// Sets whether the data rate is lowered every two transmissions, and the minimum data rate
+    ///
+    /// # LoRaWAN 1.0.4
+    /// Since LoRaWAN 1.0.4, retransmissions use the data rate of the first transmission; to comply, disable the data
+    /// rate decay.
+    pub const fn with_data_rate_decay(mut self, data_rate_decay: bool, min_data_rate: DataRate) -> Self {
+        self.data_rate_decay = data_rate_decay;
+        self.min_data_rate = min_data_rate;
+        self
+    }
+    /// Sets the range of the random ACK timeout
+    pub fn with_ack_timeout(mut self, min: Duration, max: Duration) -> Self {
+        let jitter = u16::try_from(max.saturating_sub(min).as_millis()).unwrap_or(u16::MAX);
+        self.ack_timeout = u64::try_from(min.as_micros()).unwrap_or(u64::MAX);
+        self.backoff = Csma::new(self.backoff.next_random(), Duration::from_millis(1), jitter.saturating_add(1));
+        self
+    }
+
+    /// The maximum amount of transmissions
+    pub const fn max_transmissions(&self) -> u8 {
+        self.max_transmissions
+    }
+    /// The delivery status
+    pub const fn status(&self) -> DeliveryStatus {
+        let transmissions = self.transmissions;
+        match self.state {
+            UplinkState::Idle => DeliveryStatus::Idle,
+            UplinkState::Ready(_) | UplinkState::AwaitingAck => DeliveryStatus::Pending { transmissions },
+            UplinkState::Delivered => DeliveryStatus::Delivered { transmissions },
+            UplinkState::Failed => DeliveryStatus::Failed { transmissions },
+        }
+    }
+    /// The clock time in microseconds at which the next transmission is due, if any
+    pub const fn next_transmission(&self) -> Option<u64> {
+        match self.state {
+            UplinkState::Ready(at) => Some(at),
+            _ => None,
+        }
+    }
+
+    /// Starts a new confirmed uplink with the given initial data rate, replacing any previous uplink
+    pub fn start(&mut self, data_rate: DataRate, now: u64) {
+        self.data_rate = data_rate;
+        self.transmissions = 0;
+        self.state = UplinkState::Ready(now);
+    }
+    /// Cancels a pending uplink, which is then reported as failed
+    pub fn cancel(&mut self) {
+        if let UplinkState::Ready(_) | UplinkState::AwaitingAck = self.state {
+            self.state = UplinkState::Failed;
+        }
+    }
+
+    /// Returns the data rate for the next transmission if it is due
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If it returns a data rate, the frame must be transmitted (with the frame counter
+    /// of the first transmission), followed by the RX windows.
+    pub fn poll_transmit(&mut self, now: u64) -> Option<DataRate> {
+        let UplinkState::Ready(at) = self.state else {
+            // No transmission is pending
+            return None;
+        };
+        if now < at {
+            return None;
+        }
+
+        // Compute the data rate of this transmission
+        let data_rate = match self.data_rate_decay {
+            true => {
+                let steps = self.transmissions / 2;
+                let index = self.data_rate.index().saturating_sub(steps).max(self.min_data_rate.index());
+                DataRate::from_index(index).unwrap_or(self.min_data_rate)
+            }
+            false => self.data_rate,
+        };
+        self.transmissions = self.transmissions.saturating_add(1);
+        self.state = UplinkState::AwaitingAck;
+        Some(data_rate)
+    }
+    /// Checks whether the given downlink acknowledges the uplink, and if so, marks the uplink as delivered
+    ///
+    /// # Important
+    /// This should only be called after the MIC of the downlink has been verified, so forged frames cannot acknowledge
+    /// the uplink.
+    pub fn acknowledge(&mut self, downlink: &DataPayload) -> bool {
+        // Only downlinks during a pending uplink can acknowledge it
+        let pending = matches!(self.state, UplinkState::Ready(_) | UplinkState::AwaitingAck);
+        let ack = !downlink.mhdr().mtype().is_uplink() && downlink.fhdr().f_ctrl().ack();
+        if pending && ack && self.transmissions > 0 {
+            self.state = UplinkState::Delivered;
+            return true;
+        }
+        false
+    }
+    /// Schedules a retransmission after the RX windows have closed without an ACK, or marks the uplink as failed if the
+    /// maximum amount of transmissions has been reached
+    pub fn rx_windows_closed(&mut self, now: u64) {
+        if self.state != UplinkState::AwaitingAck {
+            return;
+        }
+
+        // Retransmit after the random ACK timeout
+        if self.transmissions >= self.max_transmissions {
+            self.state = UplinkState::Failed;
+            return;
+        }
+        let jitter = u64::try_from(self.backoff.backoff().as_micros()).unwrap_or(u64::MAX);
+        self.state = UplinkState::Ready(now.saturating_add(self.ack_timeout).saturating_add(jitter));
+    }
+}
@@ -0,0 +1,68 @@
+//! Carrier-sense multiple access (CSMA) with randomized backoff
+//!
+//! # Usage
+//! Before transmitting, sense the channel via [`crate::rfm95::Rfm95Driver::start_cad`] and
+//! [`crate::rfm95::Rfm95Driver::complete_cad`] (or via [`crate::rfm95::Rfm95Driver::rssi`] during RX). If the channel
+//! is busy, wait for [`Csma::backoff`] and sense again. Seed the backoff generator via
+//! [`crate::rfm95::Rfm95Driver::random_u32`], so nodes that react to the same event do not back off in lockstep.
+//!
+//! For a complete non-blocking CSMA/CA state machine with binary exponential backoff, see
+//! [`crate::rfm95::CsmaTransmitter`].
+
+use core::time::Duration;
+
+/// A randomized backoff generator for CSMA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Csma {
+    /// The xorshift PRNG state; never zero
+    state: u32,
+    /// The duration of a single backoff slot
+    slot: Duration,
+    /// The amount of slots in the backoff window
+    window: u16,
+}
+impl Csma {
+    /// A fallback PRNG state if the seed is zero
+    const FALLBACK_STATE: u32 = 0x9E37_79B9;
+
+    /// Creates a new backoff generator with the given seed, slot duration and amount of slots in the backoff window
+    ///
+    /// # Slot Duration
+    /// A reasonable slot duration is the duration of a CAD operation plus the TX/RX turnaround time, i.e. roughly two
+    /// to three symbols (see [`crate::lora::airtime::symbol_airtime`]).
+    pub const fn new(seed: u32, slot: Duration, window: u16) -> Self {
+        let state = match seed {
+            0 => Self::FALLBACK_STATE,
+            seed => seed,
+        };
+        Self { state, slot, window }
+    }
+
+    /// The duration of a single backoff slot
+    pub const fn slot(&self) -> Duration {
+        self.slot
+    }
+    /// The amount of slots in the backoff window
+    pub const fn window(&self) -> u16 {
+        self.window
+    }
+
+    /// Generates the next pseudo-random value
+    pub fn next_random(&mut self) -> u32 {
+        // Xorshift32
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+    /// Generates a random backoff duration within the backoff window
+    pub fn backoff(&mut self) -> Duration {
+        self.backoff_within(self.window)
+    }
+    /// Generates a random backoff duration within the given amount of slots
+    pub(crate) fn backoff_within(&mut self, window: u16) -> Duration {
+        let random = self.next_random();
+        let slots = random.checked_rem(window as u32).unwrap_or_default();
+        self.slot.saturating_mul(slots)
+    }
+}
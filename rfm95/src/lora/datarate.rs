@@ -0,0 +1,128 @@
+//! LoRaWAN data rate presets
+
+use crate::lora::region::Region;
+use crate::lora::types::{Bandwidth, SpreadingFactor};
+
+/// A LoRaWAN data rate index
+///
+/// # Region Dependency
+/// The meaning of a data rate index depends on the region, see [`Self::parameters`]. Data rates that map to non-LoRa
+/// modulations (FSK, LR-FHSS) or that are reserved for future use in a region have no LoRa parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum DataRate {
+    /// Data rate 0
+    DR0 = 0,
+    /// Data rate 1
+    DR1 = 1,
+    /// Data rate 2
+    DR2 = 2,
+    /// Data rate 3
+    DR3 = 3,
+    /// Data rate 4
+    DR4 = 4,
+    /// Data rate 5
+    DR5 = 5,
+    /// Data rate 6
+    DR6 = 6,
+    /// Data rate 7
+    DR7 = 7,
+    /// Data rate 8
+    DR8 = 8,
+    /// Data rate 9
+    DR9 = 9,
+    /// Data rate 10
+    DR10 = 10,
+    /// Data rate 11
+    DR11 = 11,
+    /// Data rate 12
+    DR12 = 12,
+    /// Data rate 13
+    DR13 = 13,
+    /// Data rate 14
+    DR14 = 14,
+    /// Data rate 15
+    DR15 = 15,
+}
+impl DataRate {
+    /// The data rate with the given index, or `None` if the index is greater than `15`
+    pub const fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Self::DR0),
+            1 => Some(Self::DR1),
+            2 => Some(Self::DR2),
+            3 => Some(Self::DR3),
+            4 => Some(Self::DR4),
+            5 => Some(Self::DR5),
+            6 => Some(Self::DR6),
+            7 => Some(Self::DR7),
+            8 => Some(Self::DR8),
+            9 => Some(Self::DR9),
+            10 => Some(Self::DR10),
+            11 => Some(Self::DR11),
+            12 => Some(Self::DR12),
+            13 => Some(Self::DR13),
+            14 => Some(Self::DR14),
+            15 => Some(Self::DR15),
+            _ => None,
+        }
+    }
+    /// The data rate index
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// The LoRa spreading factor and bandwidth for `self` in the given region, or `None` if the data rate is not a LoRa
+    /// data rate in the given region
+    pub const fn parameters(self, region: Region) -> Option<(SpreadingFactor, Bandwidth)> {
+        use Bandwidth::*;
+        use SpreadingFactor::*;
+
+        match (region, self) {
+            // Data rates shared by all regions except US915
+            (Region::Eu868 | Region::Eu433 | Region::Au915 | Region::As923 | Region::In865 | Region::Kr920, dr) => {
+                match dr {
+                    Self::DR0 => Some((S12, B125)),
+                    Self::DR1 => Some((S11, B125)),
+                    Self::DR2 => Some((S10, B125)),
+                    Self::DR3 => Some((S9, B125)),
+                    Self::DR4 => Some((S8, B125)),
+                    Self::DR5 => Some((S7, B125)),
+                    // Region specific data rates
+                    Self::DR6 => match region {
+                        Region::Eu868 | Region::Eu433 | Region::As923 => Some((S7, B250)),
+                        Region::Au915 => Some((S8, B500)),
+                        _ => None,
+                    },
+                    dr => match region {
+                        Region::Au915 => Self::us915_downlink(dr),
+                        _ => None,
+                    },
+                }
+            }
+            // US915 uplink and downlink data rates
+            (Region::Us915, Self::DR0) => Some((S10, B125)),
+            (Region::Us915, Self::DR1) => Some((S9, B125)),
+            (Region::Us915, Self::DR2) => Some((S8, B125)),
+            (Region::Us915, Self::DR3) => Some((S7, B125)),
+            (Region::Us915, Self::DR4) => Some((S8, B500)),
+            (Region::Us915, dr) => Self::us915_downlink(dr),
+        }
+    }
+
+    /// The 500 kHz downlink data rates `DR8..=DR13` shared by US915 and AU915
+    const fn us915_downlink(self) -> Option<(SpreadingFactor, Bandwidth)> {
+        use Bandwidth::*;
+        use SpreadingFactor::*;
+
+        match self {
+            Self::DR8 => Some((S12, B500)),
+            Self::DR9 => Some((S11, B500)),
+            Self::DR10 => Some((S10, B500)),
+            Self::DR11 => Some((S9, B500)),
+            Self::DR12 => Some((S8, B500)),
+            Self::DR13 => Some((S7, B500)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Network time synchronization via the LoRaWAN `DeviceTimeReq`/`DeviceTimeAns` MAC commands
+//!
+//! # Usage
+//! Piggyback [`DEVICE_TIME_REQ`] on an uplink (e.g. in the frame options), and record the monotonic time at which the
+//! transmission ended (e.g. after [`crate::rfm95::Rfm95Driver::complete_tx`]). Once the network answers, parse the
+//! answer via [`DeviceTimeAns::parse`] and pass it to [`GpsClock::sync`] together with the recorded time. The
+//! [`GpsClock`] then yields the GPS-epoch time via the [`Clock`] trait.
+//!
+//! # Accuracy
+//! The answer refers to the end of the uplink transmission with a resolution of `1/256` seconds; the accuracy is
+//! additionally limited by the drift of the local clock since the last sync, so the time should be resynced regularly.
+
+use crate::clock::Clock;
+use crate::err;
+use crate::error::InvalidMessageError;
+
+/// The command identifier of `DeviceTimeReq` and `DeviceTimeAns`
+pub const DEVICE_TIME_CID: u8 = 0x0D;
+/// An encoded `DeviceTimeReq` MAC command
+pub const DEVICE_TIME_REQ: [u8; 1] = [DEVICE_TIME_CID];
+/// The UNIX time in seconds of the GPS epoch (1980-01-06T00:00:00Z)
+///
+/// # Leap Seconds
+/// GPS time does not include leap seconds; to convert GPS time to UTC, the current leap second offset (18 seconds since
+/// 2017) must be subtracted as well.
+pub const GPS_EPOCH_UNIX_SECONDS: u64 = 315_964_800;
+
+/// A decoded `DeviceTimeAns` MAC command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceTimeAns {
+    /// The seconds since the GPS epoch
+    seconds: u32,
+    /// The fractional second in `1/256` seconds
+    fraction: u8,
+}
+impl DeviceTimeAns {
+    /// The encoded length including the command identifier
+    pub const LEN: usize = 6;
+
+    /// Creates a new answer from the seconds since the GPS epoch and the fractional second in `1/256` seconds
+    pub const fn new(seconds: u32, fraction: u8) -> Self {
+        Self { seconds, fraction }
+    }
+    /// Decodes an answer from the MAC command including the command identifier
+    pub fn parse(command: &[u8]) -> Result<Self, InvalidMessageError> {
+        let Some(([DEVICE_TIME_CID, s0, s1, s2, s3, fraction], _)) = command.split_first_chunk::<{ Self::LEN }>()
+        else {
+            // The command is too short or not a `DeviceTimeAns`
+            return Err(err!(InvalidMessageError, "Invalid DeviceTimeAns command"));
+        };
+        Ok(Self { seconds: u32::from_le_bytes([*s0, *s1, *s2, *s3]), fraction: *fraction })
+    }
+
+    /// The seconds since the GPS epoch
+    pub const fn seconds(&self) -> u32 {
+        self.seconds
+    }
+    /// The fractional second in `1/256` seconds
+    pub const fn fraction(&self) -> u8 {
+        self.fraction
+    }
+    /// The time since the GPS epoch in microseconds
+    pub const fn gps_micros(&self) -> u64 {
+        let fraction = (self.fraction as u64).saturating_mul(1_000_000) >> 8;
+        (self.seconds as u64).saturating_mul(1_000_000).saturating_add(fraction)
+    }
+}
+
+/// A [`Clock`] that yields the GPS-epoch time in microseconds, based on a monotonic clock and `DeviceTimeAns`
+/// synchronizations
+///
+/// # Monotonicity
+/// Forward corrections are applied immediately. Backward corrections (i.e. if the local clock runs fast) are slewed at
+/// 1 ms per second instead of stepping the clock back, so the time never decreases. Until the first sync, the clock
+/// yields the time of the underlying clock (see [`Self::is_synced`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpsClock<C> {
+    /// The underlying monotonic clock
+    clock: C,
+    /// The offset between the monotonic time and the GPS time
+    offset: u64,
+    /// The backward correction that is slewed since the last sync
+    slew: u64,
+    /// The monotonic time of the last sync
+    synced_at: Option<u64>,
+}
+impl<C> GpsClock<C>
+where
+    C: Clock,
+{
+    /// The slew rate, i.e. the amount of microseconds after which a backward correction of 1 µs is applied
+    const SLEW_RATE: u64 = 1000;
+
+    /// Creates a new, unsynchronized clock on top of the given monotonic clock
+    pub const fn new(clock: C) -> Self {
+        Self { clock, offset: 0, slew: 0, synced_at: None }
+    }
+
+    /// Whether the clock has been synchronized
+    pub const fn is_synced(&self) -> bool {
+        self.synced_at.is_some()
+    }
+    /// The monotonic time in microseconds of the last sync, if any
+    pub const fn synced_at(&self) -> Option<u64> {
+        self.synced_at
+    }
+    /// The current GPS-epoch time in microseconds, or `None` if the clock has not been synchronized yet
+    pub fn gps_time(&self) -> Option<u64> {
+        self.synced_at.map(|_| self.now())
+    }
+    /// The underlying monotonic clock
+    pub const fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Synchronizes the clock with the given answer, where `tx_end` is the monotonic time in microseconds at which the
+    /// uplink with the `DeviceTimeReq` ended
+    pub fn sync(&mut self, answer: &DeviceTimeAns, tx_end: u64) {
+        // Compute the target time relative to the current monotonic time
+        let monotonic = self.clock.now();
+        let elapsed = monotonic.saturating_sub(tx_end);
+        let target = answer.gps_micros().saturating_add(elapsed);
+
+        // Step forward, or slew backward from the current time
+        let current = self.now();
+        match self.synced_at {
+            Some(_) if target < current => {
+                self.offset = current.saturating_sub(monotonic);
+                self.slew = current.saturating_sub(target);
+            }
+            _ => {
+                self.offset = target.saturating_sub(monotonic);
+                self.slew = 0;
+            }
+        }
+        self.synced_at = Some(monotonic);
+    }
+}
+impl<C> Clock for GpsClock<C>
+where
+    C: Clock,
+{
+    fn now(&self) -> u64 {
+        let monotonic = self.clock.now();
+        let elapsed = monotonic.saturating_sub(self.synced_at.unwrap_or(monotonic));
+        let slewed = (elapsed / Self::SLEW_RATE).min(self.slew);
+        monotonic.saturating_add(self.offset).saturating_sub(slewed)
+    }
+}
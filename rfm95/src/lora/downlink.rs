@@ -0,0 +1,259 @@
+//! Queueing and per-port dispatch of LoRaWAN application downlinks
+//!
+//! # Usage
+//! Once a downlink has been received in an RX window, authenticated and decrypted, queue its application payload via
+//! [`DownlinkQueue::push`]. The application then consumes the queued payloads at its own pace, either by polling
+//! ([`DownlinkQueue::pop`] or [`DownlinkQueue::pop_port`] for a single port), or via a callback
+//! ([`DownlinkQueue::dispatch`]). Payloads are yielded in the order they have been received.
+//!
+//! # Ports
+//! Only application ports (`1..=223`) are queued; port `0` carries MAC commands, and ports `224..=255` are reserved for
+//! the LoRaWAN test protocol and future use.
+
+use crate::err;
+use crate::error::{BufferTooSmallError, InvalidArgumentError};
+
+/// The metadata of a queued downlink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownlinkMetadata {
+    /// The port
+    f_port: u8,
+    /// The full downlink frame counter
+    f_cnt: u32,
+    /// The RSSI in dBm
+    rssi: i16,
+    /// The SNR in dB
+    snr: i8,
+    /// Whether the network has more downlinks pending
+    f_pending: bool,
+    /// The reception time in clock microseconds
+    received_at: u64,
+}
+impl DownlinkMetadata {
+    /// Creates new downlink metadata for the given port, full frame counter and reception time in clock microseconds
+    pub const fn new(f_port: u8, f_cnt: u32, received_at: u64) -> Self {
+        Self { f_port, f_cnt, rssi: 0, snr: 0, f_pending: false, received_at }
+    }
+    /// Sets the RSSI in dBm and the SNR in dB (see [`crate::rfm95::RxMetadata`])
+    pub const fn with_signal(mut self, rssi: i16, snr: i8) -> Self {
+        self.rssi = rssi;
+        self.snr = snr;
+        self
+    }
+    /// Sets whether the network has more downlinks pending (i.e. the `FPending` bit of the frame header)
+    pub const fn with_f_pending(mut self, f_pending: bool) -> Self {
+        self.f_pending = f_pending;
+        self
+    }
+
+    /// The port
+    pub const fn f_port(&self) -> u8 {
+        self.f_port
+    }
+    /// The full downlink frame counter
+    pub const fn f_cnt(&self) -> u32 {
+        self.f_cnt
+    }
+    /// The RSSI in dBm
+    pub const fn rssi(&self) -> i16 {
+        self.rssi
+    }
+    /// The SNR in dB
+    pub const fn snr(&self) -> i8 {
+        self.snr
+    }
+    /// Whether the network has more downlinks pending
+    pub const fn f_pending(&self) -> bool {
+        self.f_pending
+    }
+    /// The reception time in clock microseconds
+    pub const fn received_at(&self) -> u64 {
+        self.received_at
+    }
+}
+
+/// A queued downlink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuedDownlink<const MTU: usize> {
+    /// The payload
+    payload: [u8; MTU],
+    /// The payload length
+    len: usize,
+    /// The metadata
+    metadata: DownlinkMetadata,
+    /// The enqueue sequence number to yield downlinks in order
+    sequence: u32,
+}
+impl<const MTU: usize> QueuedDownlink<MTU> {
+    /// The payload
+    fn payload(&self) -> &[u8] {
+        self.payload.get(..self.len).unwrap_or_default()
+    }
+}
+
+/// A fixed-capacity queue for application downlinks
+///
+/// # Overflow
+/// If the queue is full, the oldest downlink is dropped in favor of the new one, as downlinks cannot be rejected once
+/// they have been received; see [`Self::dropped`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownlinkQueue<const CAPACITY: usize, const MTU: usize> {
+    /// The queued downlinks
+    slots: [Option<QueuedDownlink<MTU>>; CAPACITY],
+    /// The next enqueue sequence number
+    sequence: u32,
+    /// The amount of downlinks that have been dropped because the queue was full
+    dropped: u32,
+}
+impl<const CAPACITY: usize, const MTU: usize> DownlinkQueue<CAPACITY, MTU> {
+    /// The application ports
+    const APPLICATION_PORTS: core::ops::RangeInclusive<u8> = 1..=223;
+
+    /// Creates a new empty queue
+    pub const fn new() -> Self {
+        Self { slots: [const { None }; CAPACITY], sequence: 0, dropped: 0 }
+    }
+
+    /// The maximum amount of queued downlinks
+    pub const fn capacity(&self) -> usize {
+        CAPACITY
+    }
+    /// The amount of queued downlinks
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+    /// Whether the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+    /// The amount of queued downlinks for the given port
+    pub fn pending(&self, f_port: u8) -> usize {
+        self.slots.iter().flatten().filter(|downlink| downlink.metadata.f_port == f_port).count()
+    }
+    /// The amount of downlinks that have been dropped because the queue was full
+    pub const fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Queues the decrypted application payload of a downlink
+    pub fn push(&mut self, payload: &[u8], metadata: DownlinkMetadata) -> Result<(), InvalidArgumentError> {
+        // Validate the port and copy the payload
+        if !Self::APPLICATION_PORTS.contains(&metadata.f_port) {
+            return Err(err!(InvalidArgumentError, "Port is not an application port"));
+        }
+        let mut queued = [0; MTU];
+        let Some(queued_slice) = queued.get_mut(..payload.len()) else {
+            // The payload is too long
+            return Err(err!(InvalidArgumentError, "Payload exceeds the queue MTU"));
+        };
+        queued_slice.copy_from_slice(payload);
+
+        // Store the downlink in a free slot, or replace the oldest downlink
+        let slot = match self.slots.iter().position(Option::is_none) {
+            Some(index) => index,
+            None => {
+                let Some(index) = self.next_index(|_| true) else {
+                    // The queue has no capacity
+                    return Err(err!(InvalidArgumentError, "Downlink queue has no capacity"));
+                };
+                self.dropped = self.dropped.saturating_add(1);
+                index
+            }
+        };
+        let downlink = QueuedDownlink { payload: queued, len: payload.len(), metadata, sequence: self.sequence };
+        if let Some(slot) = self.slots.get_mut(slot) {
+            *slot = Some(downlink);
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(())
+    }
+    /// Removes all queued downlinks
+    pub fn clear(&mut self) {
+        self.slots = [const { None }; CAPACITY];
+    }
+
+    /// Copies the oldest downlink into `buf` and removes it from the queue, and returns the payload length and metadata
+    ///
+    /// # Buffer too small
+    /// If the payload is longer than `buf`, an error is returned and the downlink remains queued.
+    pub fn pop(&mut self, buf: &mut [u8]) -> Result<Option<(usize, DownlinkMetadata)>, BufferTooSmallError> {
+        let index = self.next_index(|_| true);
+        self.take(index, buf)
+    }
+    /// Copies the oldest downlink of the given port into `buf` and removes it from the queue, and returns the payload
+    /// length and metadata
+    ///
+    /// # Buffer too small
+    /// If the payload is longer than `buf`, an error is returned and the downlink remains queued.
+    pub fn pop_port(
+        &mut self,
+        f_port: u8,
+        buf: &mut [u8],
+    ) -> Result<Option<(usize, DownlinkMetadata)>, BufferTooSmallError> {
+        let index = self.next_index(|metadata| metadata.f_port == f_port);
+        self.take(index, buf)
+    }
+    /// Passes all queued downlinks in order to `handler` and removes them from the queue, and returns the amount of
+    /// dispatched downlinks
+    ///
+    /// # Dispatch
+    /// The handler receives the payload and the metadata, and can dispatch by [`DownlinkMetadata::f_port`].
+    pub fn dispatch<F>(&mut self, mut handler: F) -> usize
+    where
+        F: FnMut(&[u8], &DownlinkMetadata),
+    {
+        let mut dispatched = 0usize;
+        while let Some(index) = self.next_index(|_| true) {
+            let Some(downlink) = self.slots.get_mut(index).and_then(Option::take) else {
+                // The slot is empty
+                break;
+            };
+            handler(downlink.payload(), &downlink.metadata);
+            dispatched = dispatched.saturating_add(1);
+        }
+        dispatched
+    }
+
+    /// Finds the index of the oldest downlink that matches the filter
+    fn next_index<F>(&self, filter: F) -> Option<usize>
+    where
+        F: Fn(&DownlinkMetadata) -> bool,
+    {
+        // Compare sequence numbers relative to the next sequence number, so wrap-arounds are handled
+        let age = |downlink: &QueuedDownlink<MTU>| self.sequence.wrapping_sub(downlink.sequence);
+        let slots = self.slots.iter().enumerate();
+        let matching = slots.filter_map(|(index, slot)| Some((index, slot.as_ref()?)));
+        let matching = matching.filter(|(_, downlink)| filter(&downlink.metadata));
+        matching.max_by_key(|(_, downlink)| age(downlink)).map(|(index, _)| index)
+    }
+    /// Copies the downlink at the given index into `buf` and removes it from the queue
+    fn take(
+        &mut self,
+        index: Option<usize>,
+        buf: &mut [u8],
+    ) -> Result<Option<(usize, DownlinkMetadata)>, BufferTooSmallError> {
+        let Some(slot) = index.and_then(|index| self.slots.get_mut(index)) else {
+            // No downlink is queued
+            return Ok(None);
+        };
+        let Some(downlink) = slot.as_ref() else {
+            // The slot is empty
+            return Ok(None);
+        };
+
+        // Copy the payload and free the slot
+        let Some(buf) = buf.get_mut(..downlink.len) else {
+            // The payload does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "Buffer is too small for the downlink payload"));
+        };
+        buf.copy_from_slice(downlink.payload());
+        let metadata = downlink.metadata;
+        *slot = None;
+        Ok(Some((buf.len(), metadata)))
+    }
+}
+impl<const CAPACITY: usize, const MTU: usize> Default for DownlinkQueue<CAPACITY, MTU> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,212 @@
+//! Persistent LoRaWAN frame counters
+//!
+//! # Rollover
+//! LoRaWAN frames only carry the lower 16 bits of the 32-bit frame counter, but the MIC and the payload encryption use
+//! the full counter (see [`crate::lora::lorawan_crypto`]). The uplink counter is kept as a full 32-bit counter and
+//! truncated when the frame header is built; the full downlink counter is reconstructed from the last accepted counter
+//! via [`FrameCounters::down_candidate`]. If a counter is lost, the network server silently rejects uplinks (or the
+//! device silently rejects downlinks), so the counters must survive reboots.
+//!
+//! # Wear
+//! To limit the storage writes, the counters are only persisted every `N` frames (see
+//! [`FrameCounters::with_save_interval`]):
+//! - the uplink counter reserves the next `N` counters before the first of them is used, and resumes after the reserved
+//!   counters after a reboot; so an uplink counter is never reused, but up to `N` counters may be skipped
+//! - the downlink counter is persisted once it has advanced by `N`, and resumes at the persisted counter after a
+//!   reboot; so no valid downlink is rejected, but up to `N` recent downlinks may be accepted again
+//!
+//! # Usage
+//! Load the counters via [`FrameCounters::load`] at boot, and reset them via [`FrameCounters::reset`] after each join.
+//! Number each uplink via [`FrameCounters::next_up`]; for each downlink, reconstruct the full counter via
+//! [`FrameCounters::down_candidate`], verify the MIC with it, and only then record it via
+//! [`FrameCounters::accept_down`].
+
+use crate::persistence::{self, Persistence};
+
+/// The uplink and downlink frame counters of a LoRaWAN session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameCounters<P> {
+    /// The underlying persistence
+    persistence: P,
+    /// The save interval
+    interval: u32,
+    /// The maximum downlink counter gap
+    max_gap: u32,
+    /// The next uplink counter, or `None` if the counter space is exhausted
+    up: Option<u32>,
+    /// The persisted uplink counter, i.e. the first counter that is not reserved yet
+    up_reserved: u32,
+    /// The last accepted downlink counter
+    down: Option<u32>,
+    /// The persisted downlink counter
+    down_persisted: Option<u32>,
+}
+impl<P> FrameCounters<P>
+where
+    P: Persistence,
+{
+    /// The default save interval
+    pub const DEFAULT_SAVE_INTERVAL: u32 = 16;
+    /// The default maximum downlink counter gap (LoRaWAN's `MAX_FCNT_GAP`)
+    pub const DEFAULT_MAX_GAP: u32 = 16_384;
+    /// The width of the counter value that is transmitted in the frame header
+    const FCNT_SPAN: u32 = 0x1_0000;
+
+    /// Loads the counters from the persistence (see [`crate::persistence::KEY_FCNT_UP`] and
+    /// [`crate::persistence::KEY_FCNT_DOWN`]), or starts at zero if no counters have been stored yet
+    pub fn load(mut persistence: P) -> Result<Self, P::Error> {
+        // Resume after the reserved uplink counters
+        let up_reserved = Self::read(&mut persistence, persistence::KEY_FCNT_UP)?.unwrap_or(0);
+        let up = match up_reserved {
+            // The last counter may have been used already
+            u32::MAX => None,
+            up_reserved => Some(up_reserved),
+        };
+
+        // Resume at the persisted downlink counter
+        let down = Self::read(&mut persistence, persistence::KEY_FCNT_DOWN)?;
+        Ok(Self {
+            persistence,
+            interval: Self::DEFAULT_SAVE_INTERVAL,
+            max_gap: Self::DEFAULT_MAX_GAP,
+            up,
+            up_reserved,
+            down,
+            down_persisted: down,
+        })
+    }
+    /// Sets the number of frames after which the counters are persisted
+    ///
+    /// # Range
+    /// The interval is clamped to `1..`; an interval of `1` persists every counter.
+    pub const fn with_save_interval(mut self, interval: u32) -> Self {
+        self.interval = match interval {
+            0 => 1,
+            interval => interval,
+        };
+        self
+    }
+    /// Sets the maximum amount by which a downlink counter may jump ahead of the last accepted counter
+    ///
+    /// # LoRaWAN 1.0.4
+    /// LoRaWAN 1.0.4 dropped the gap check; to disable it, set the maximum gap to `u32::MAX`.
+    pub const fn with_max_gap(mut self, max_gap: u32) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// The save interval
+    pub const fn save_interval(&self) -> u32 {
+        self.interval
+    }
+    /// The maximum downlink counter gap
+    pub const fn max_gap(&self) -> u32 {
+        self.max_gap
+    }
+    /// The next uplink counter without consuming it, or `None` if the counter space is exhausted
+    pub const fn peek_up(&self) -> Option<u32> {
+        self.up
+    }
+    /// The last accepted downlink counter
+    pub const fn down(&self) -> Option<u32> {
+        self.down
+    }
+    /// Returns the underlying persistence
+    pub fn into_inner(self) -> P {
+        self.persistence
+    }
+
+    /// Resets and persists both counters, e.g. after a join
+    pub fn reset(&mut self) -> Result<(), P::Error> {
+        // An empty record is not a valid counter
+        self.persistence.write(persistence::KEY_FCNT_UP, &0u32.to_le_bytes())?;
+        self.persistence.write(persistence::KEY_FCNT_DOWN, &[])?;
+        (self.up, self.up_reserved) = (Some(0), 0);
+        (self.down, self.down_persisted) = (None, None);
+        Ok(())
+    }
+    /// Persists the last accepted downlink counter immediately, e.g. before a planned shutdown
+    pub fn flush(&mut self) -> Result<(), P::Error> {
+        let Some(down) = self.down.filter(|down| self.down_persisted != Some(*down)) else {
+            // The counter is persisted already
+            return Ok(());
+        };
+        self.persistence.write(persistence::KEY_FCNT_DOWN, &down.to_le_bytes())?;
+        self.down_persisted = Some(down);
+        Ok(())
+    }
+
+    /// Gets the next full uplink counter, or `None` if the counter space is exhausted
+    ///
+    /// # Frame Header
+    /// The frame header carries the lower 16 bits of the counter; the MIC and the payload encryption use the full
+    /// counter.
+    ///
+    /// # Exhaustion
+    /// Once the counter space is exhausted, the device must rejoin, as counters must never be reused.
+    pub fn next_up(&mut self) -> Result<Option<u32>, P::Error> {
+        let Some(counter) = self.up else {
+            // The counter space is exhausted
+            return Ok(None);
+        };
+
+        // Reserve the next counters before the counter is used
+        if counter >= self.up_reserved {
+            let up_reserved = counter.saturating_add(self.interval);
+            self.persistence.write(persistence::KEY_FCNT_UP, &up_reserved.to_le_bytes())?;
+            self.up_reserved = up_reserved;
+        }
+        self.up = counter.checked_add(1);
+        Ok(Some(counter))
+    }
+
+    /// Reconstructs the full downlink counter from the 16-bit counter of the frame header, or `None` if the counter
+    /// is too far ahead of the last accepted counter or the counter space is exhausted
+    ///
+    /// # Rollover
+    /// The full counter is the smallest counter above the last accepted counter whose lower 16 bits match `f_cnt`.
+    pub fn down_candidate(&self, f_cnt: u16) -> Option<u32> {
+        let Some(last) = self.down else {
+            // This is the first downlink of the session
+            return Some(u32::from(f_cnt));
+        };
+
+        // Extend the counter and roll over into the next 16-bit span if necessary
+        let candidate = (last & !Self::FCNT_SPAN.wrapping_sub(1)) | u32::from(f_cnt);
+        let candidate = match candidate {
+            candidate if candidate > last => candidate,
+            candidate => candidate.checked_add(Self::FCNT_SPAN)?,
+        };
+        let gap = candidate.saturating_sub(last);
+        (gap <= self.max_gap).then_some(candidate)
+    }
+    /// Records the full downlink counter if it is greater than the last accepted counter, and returns whether the
+    /// downlink should be processed
+    ///
+    /// # Important
+    /// This should only be called after the MIC of the downlink has been verified with the full counter, so forged
+    /// frames cannot advance the counter.
+    pub fn accept_down(&mut self, f_cnt: u32) -> Result<bool, P::Error> {
+        if self.down.is_some_and(|last| f_cnt <= last) {
+            return Ok(false);
+        }
+
+        // Persist the counter once it has advanced by the save interval
+        self.down = Some(f_cnt);
+        let persisted = self.down_persisted.map(|persisted| f_cnt.saturating_sub(persisted));
+        if persisted.is_none_or(|advance| advance >= self.interval) {
+            self.flush()?;
+        }
+        Ok(true)
+    }
+
+    /// Reads a counter record
+    fn read(persistence: &mut P, key: u64) -> Result<Option<u32>, P::Error> {
+        let mut counter = [0; 4];
+        match persistence.read(key, &mut counter)? {
+            Some(4) => Ok(Some(u32::from_le_bytes(counter))),
+            // The record does not exist or is invalid
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Persistent OTAA join state
+//!
+//! # Nonce Reuse
+//! Since LoRaWAN 1.0.4, the DevNonce is a counter that must never be reused for the lifetime of the device; join
+//! servers reject join requests with a DevNonce that is not greater than the last one. The counter must thus survive
+//! reboots, and must be persisted _before_ it is sent (see [`JoinState::next_dev_nonce`]). Likewise, a device should
+//! only accept a join accept with a JoinNonce greater than the last one (see [`JoinState::accept_join_nonce`]).
+//!
+//! # Usage
+//! Load the state via [`JoinState::load`] at boot, and pass the same store to all mutating functions, which persist the
+//! state before returning. The store is pluggable via [`JoinStore`]; any [`crate::persistence::Persistence`] can be
+//! used via [`PersistentJoinState`].
+
+use crate::persistence::{self, Persistence};
+
+/// The length of a session key
+pub const SESSION_KEY_LEN: usize = 16;
+
+/// The length of a persisted join state
+const RECORD_LEN: usize = 3 + 4 + 1 + 4 + 2 * SESSION_KEY_LEN;
+
+/// The session of a joined device
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    /// The device address
+    dev_addr: u32,
+    /// The network session key
+    nwk_s_key: [u8; SESSION_KEY_LEN],
+    /// The application session key
+    app_s_key: [u8; SESSION_KEY_LEN],
+}
+impl Session {
+    /// Creates a new session from the device address and the derived session keys
+    pub const fn new(dev_addr: u32, nwk_s_key: [u8; SESSION_KEY_LEN], app_s_key: [u8; SESSION_KEY_LEN]) -> Self {
+        Self { dev_addr, nwk_s_key, app_s_key }
+    }
+
+    /// The device address
+    pub const fn dev_addr(&self) -> u32 {
+        self.dev_addr
+    }
+    /// The network session key
+    pub const fn nwk_s_key(&self) -> &[u8; SESSION_KEY_LEN] {
+        &self.nwk_s_key
+    }
+    /// The application session key
+    pub const fn app_s_key(&self) -> &[u8; SESSION_KEY_LEN] {
+        &self.app_s_key
+    }
+}
+impl core::fmt::Debug for Session {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Do not leak the session keys
+        f.debug_struct("Session").field("dev_addr", &self.dev_addr).finish_non_exhaustive()
+    }
+}
+
+/// The OTAA join state of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JoinState {
+    /// The next DevNonce, or `None` if all DevNonces have been used
+    next_dev_nonce: Option<u16>,
+    /// The last accepted JoinNonce
+    join_nonce: Option<u32>,
+    /// The current session
+    session: Option<Session>,
+}
+impl JoinState {
+    /// The maximum JoinNonce (24 bit)
+    const JOIN_NONCE_MAX: u32 = 0x00FF_FFFF;
+
+    /// Creates a new state for a device that has never joined
+    pub const fn new() -> Self {
+        Self { next_dev_nonce: Some(0), join_nonce: None, session: None }
+    }
+    /// Loads the state from the store, or creates a new state if none has been stored yet
+    pub fn load<S>(store: &mut S) -> Result<Self, S::Error>
+    where
+        S: JoinStore,
+    {
+        Ok(store.load()?.unwrap_or_else(Self::new))
+    }
+
+    /// The next DevNonce without consuming it, or `None` if all DevNonces have been used
+    pub const fn peek_dev_nonce(&self) -> Option<u16> {
+        self.next_dev_nonce
+    }
+    /// The last accepted JoinNonce
+    pub const fn join_nonce(&self) -> Option<u32> {
+        self.join_nonce
+    }
+    /// The current session, if the device has joined
+    pub const fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Consumes the next DevNonce for a join request, and persists the state before returning it
+    ///
+    /// # Exhaustion
+    /// Returns `None` if all 65536 DevNonces have been used; the device cannot join with this `AppKey` anymore.
+    pub fn next_dev_nonce<S>(&mut self, store: &mut S) -> Result<Option<u16>, S::Error>
+    where
+        S: JoinStore,
+    {
+        let Some(dev_nonce) = self.next_dev_nonce else {
+            // All DevNonces have been used
+            return Ok(None);
+        };
+
+        // Persist the consumed nonce before it is used
+        self.next_dev_nonce = dev_nonce.checked_add(1);
+        store.store(self)?;
+        Ok(Some(dev_nonce))
+    }
+    /// Accepts the JoinNonce of a join accept if it is greater than the last accepted one, and persists it
+    ///
+    /// # Important
+    /// This should only be called after the MIC of the join accept has been verified, and returns whether the join
+    /// accept should be processed.
+    pub fn accept_join_nonce<S>(&mut self, join_nonce: u32, store: &mut S) -> Result<bool, S::Error>
+    where
+        S: JoinStore,
+    {
+        // Validate the nonce
+        let join_nonce = join_nonce & Self::JOIN_NONCE_MAX;
+        if self.join_nonce.is_some_and(|last| join_nonce <= last) {
+            return Ok(false);
+        }
+
+        // Persist the nonce
+        self.join_nonce = Some(join_nonce);
+        store.store(self)?;
+        Ok(true)
+    }
+    /// Sets and persists the session derived from the join accept
+    pub fn set_session<S>(&mut self, session: Session, store: &mut S) -> Result<(), S::Error>
+    where
+        S: JoinStore,
+    {
+        self.session = Some(session);
+        store.store(self)
+    }
+    /// Clears and persists the session, e.g. to force a rejoin; the nonces are retained
+    pub fn clear_session<S>(&mut self, store: &mut S) -> Result<(), S::Error>
+    where
+        S: JoinStore,
+    {
+        self.session = None;
+        store.store(self)
+    }
+}
+
+/// A persistent store for the OTAA join state
+pub trait JoinStore {
+    /// The storage error
+    type Error;
+
+    /// Loads the join state, or `None` if no state has been stored yet
+    fn load(&mut self) -> Result<Option<JoinState>, Self::Error>;
+    /// Stores the join state
+    fn store(&mut self, state: &JoinState) -> Result<(), Self::Error>;
+}
+
+/// A [`JoinStore`] that persists the join state as a record (see [`crate::persistence::KEY_JOIN_STATE`])
+///
+/// # Security
+/// The session keys are stored in plain; the persistence should be protected accordingly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PersistentJoinState<P> {
+    /// The underlying persistence
+    persistence: P,
+}
+impl<P> PersistentJoinState<P>
+where
+    P: Persistence,
+{
+    /// Creates a new join state store on top of the given persistence
+    pub const fn new(persistence: P) -> Self {
+        Self { persistence }
+    }
+    /// Returns the underlying persistence
+    pub fn into_inner(self) -> P {
+        self.persistence
+    }
+}
+impl<P> JoinStore for PersistentJoinState<P>
+where
+    P: Persistence,
+{
+    type Error = P::Error;
+
+    fn load(&mut self) -> Result<Option<JoinState>, Self::Error> {
+        let mut record = [0; RECORD_LEN];
+        let Some(RECORD_LEN) = self.persistence.read(persistence::KEY_JOIN_STATE, &mut record)? else {
+            // The record does not exist or is invalid
+            return Ok(None);
+        };
+
+        // Decode the record
+        let mut bytes = record.into_iter();
+        let mut next = || bytes.next().unwrap_or_default();
+        let [has_dev_nonce, d0, d1] = [(); 3].map(|_| next());
+        let [has_join_nonce, j0, j1, j2] = [(); 4].map(|_| next());
+        let has_session = next();
+        let dev_addr = u32::from_le_bytes([(); 4].map(|_| next()));
+        let nwk_s_key = [(); SESSION_KEY_LEN].map(|_| next());
+        let app_s_key = [(); SESSION_KEY_LEN].map(|_| next());
+        Ok(Some(JoinState {
+            next_dev_nonce: (has_dev_nonce != 0).then_some(u16::from_le_bytes([d0, d1])),
+            join_nonce: (has_join_nonce != 0).then_some(u32::from_le_bytes([j0, j1, j2, 0])),
+            session: (has_session != 0).then_some(Session { dev_addr, nwk_s_key, app_s_key }),
+        }))
+    }
+    fn store(&mut self, state: &JoinState) -> Result<(), Self::Error> {
+        // Encode the optional fields with a presence flag
+        let [d0, d1] = state.next_dev_nonce.unwrap_or_default().to_le_bytes();
+        let [j0, j1, j2, _] = state.join_nonce.unwrap_or_default().to_le_bytes();
+        let session = state.session.unwrap_or(Session::new(0, [0; SESSION_KEY_LEN], [0; SESSION_KEY_LEN]));
+        let header = [
+            state.next_dev_nonce.is_some() as u8,
+            d0,
+            d1,
+            state.join_nonce.is_some() as u8,
+            j0,
+            j1,
+            j2,
+            state.session.is_some() as u8,
+        ];
+
+        // Assemble and write the record
+        let mut record = [0; RECORD_LEN];
+        let fields = (header.into_iter().chain(session.dev_addr.to_le_bytes()))
+            .chain(session.nwk_s_key)
+            .chain(session.app_s_key);
+        for (byte, field) in record.iter_mut().zip(fields) {
+            *byte = field;
+        }
+        self.persistence.write(persistence::KEY_JOIN_STATE, &record)
+    }
+}
@@ -0,0 +1,318 @@
+//! Airtime accounting for compliance logging and diagnostics
+//!
+//! # Usage
+//! Record every transmission via [`AirtimeLedger::record_tx`] (or [`AirtimeLedger::record`] if the airtime is already
+//! known), and query the transmitted airtime over the last hour or day per channel or sub-band. The ledger only
+//! accounts the airtime, and does not enforce any limits by itself; schedulers enforce a [`DutyCycle`] limit by waiting
+//! for the period of each transmission (see [`DutyCycle::period`]).
+
+use crate::err;
+use crate::error::InvalidArgumentError;
+use crate::lora::airtime;
+use crate::lora::config::Config;
+use crate::lora::types::Frequency;
+use crate::persistence::{self, Persistence};
+use core::time::Duration;
+
+/// The amount of buckets of the hourly window
+const HOUR_BUCKETS: usize = 12;
+/// The width of a bucket of the hourly window in microseconds (five minutes)
+const HOUR_BUCKET_MICROS: u64 = 5 * 60 * 1_000_000;
+/// The amount of buckets of the daily window
+const DAY_BUCKETS: usize = 24;
+/// The width of a bucket of the daily window in microseconds (one hour)
+const DAY_BUCKET_MICROS: u64 = 60 * 60 * 1_000_000;
+
+/// Rolling airtime buckets of a fixed width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Buckets<const N: usize> {
+    /// The accumulated airtime in microseconds per bucket, indexed by slot modulo `N`
+    airtime: [u32; N],
+    /// The slot of the most recently updated bucket
+    last_slot: u64,
+}
+impl<const N: usize> Buckets<N> {
+    /// Creates new empty buckets
+    const fn new() -> Self {
+        Self { airtime: [0; N], last_slot: 0 }
+    }
+
+    /// Adds the given airtime in microseconds to the bucket of the given slot
+    fn add(&mut self, slot: u64, micros: u32) {
+        // Clear the buckets that have expired since the last update
+        let expired = slot.saturating_sub(self.last_slot);
+        for offset in 1..=expired.min(N as u64) {
+            let expired_slot = self.last_slot.saturating_add(offset);
+            if let Some(bucket) = self.airtime.get_mut(Self::index(expired_slot)) {
+                *bucket = 0;
+            }
+        }
+        self.last_slot = self.last_slot.max(slot);
+
+        // Add the airtime to the bucket of the slot, unless it has already expired
+        if slot.saturating_add(N as u64) > self.last_slot {
+            if let Some(bucket) = self.airtime.get_mut(Self::index(slot)) {
+                *bucket = bucket.saturating_add(micros);
+            }
+        }
+    }
+    /// Sums the airtime in microseconds of the `N` buckets up to and including the given slot
+    fn sum(&self, slot: u64) -> u64 {
+        (0..N as u64)
+            .filter_map(|age| self.last_slot.checked_sub(age))
+            .filter(|bucket_slot| *bucket_slot <= slot && bucket_slot.saturating_add(N as u64) > slot)
+            .filter_map(|bucket_slot| self.airtime.get(Self::index(bucket_slot)))
+            .map(|micros| *micros as u64)
+            .sum()
+    }
+
+    /// The bucket index of the given slot
+    #[allow(clippy::cast_possible_truncation, reason = "The remainder is always smaller than `N`")]
+    fn index(slot: u64) -> usize {
+        slot.checked_rem(N as u64).unwrap_or_default() as usize
+    }
+}
+
+/// The airtime record of a single channel or sub-band
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LedgerEntry {
+    /// The channel or sub-band frequency
+    frequency: Frequency,
+    /// The airtime in five-minute buckets for the last hour
+    hour: Buckets<HOUR_BUCKETS>,
+    /// The airtime in one-hour buckets for the last day
+    day: Buckets<DAY_BUCKETS>,
+}
+
+impl LedgerEntry {
+    /// The length of a persisted entry
+    const RECORD_LEN: usize = 4 + (HOUR_BUCKETS + DAY_BUCKETS) * 4 + 2 * 8;
+
+    /// Encodes the entry as little-endian frequency, hourly buckets, hourly slot, daily buckets and daily slot
+    fn encode<'a>(&self, record: &'a mut [u8; Self::RECORD_LEN]) -> &'a [u8] {
+        let hour = self.hour.airtime.iter().map(|micros| micros.to_le_bytes());
+        let day = self.day.airtime.iter().map(|micros| micros.to_le_bytes());
+        let fields = (core::iter::once(self.frequency.as_u32().to_le_bytes()).chain(hour))
+            .flatten()
+            .chain(self.hour.last_slot.to_le_bytes())
+            .chain(day.flatten())
+            .chain(self.day.last_slot.to_le_bytes());
+        for (byte, field) in record.iter_mut().zip(fields) {
+            *byte = field;
+        }
+        record
+    }
+    /// Decodes an entry encoded via [`Self::encode`]
+    fn decode(record: &[u8; Self::RECORD_LEN]) -> Self {
+        let mut bytes = record.iter().copied();
+        let mut next = || bytes.next().unwrap_or_default();
+        let frequency = Frequency::hz(u32::from_le_bytes([(); 4].map(|_| next())));
+        let hour_airtime = [(); HOUR_BUCKETS].map(|_| u32::from_le_bytes([(); 4].map(|_| next())));
+        let hour_slot = u64::from_le_bytes([(); 8].map(|_| next()));
+        let day_airtime = [(); DAY_BUCKETS].map(|_| u32::from_le_bytes([(); 4].map(|_| next())));
+        let day_slot = u64::from_le_bytes([(); 8].map(|_| next()));
+        Self {
+            frequency,
+            hour: Buckets { airtime: hour_airtime, last_slot: hour_slot },
+            day: Buckets { airtime: day_airtime, last_slot: day_slot },
+        }
+    }
+}
+
+/// A ledger of the transmitted airtime per channel or sub-band over rolling windows of one hour and one day
+///
+/// # Rolling Windows
+/// The windows are approximated by buckets: The hourly window consists of twelve five-minute buckets, and the daily
+/// window consists of twenty-four one-hour buckets. Each transmission is attributed to the bucket it started in, so a
+/// window covers the current bucket and the preceding ones, i.e. the effective window length is up to one bucket
+/// shorter than nominal.
+///
+/// # Capacity
+/// The ledger tracks up to `CHANNELS` distinct frequencies; recording a transmission on an additional frequency is
+/// rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AirtimeLedger<const CHANNELS: usize> {
+    /// The airtime records per channel or sub-band
+    entries: [Option<LedgerEntry>; CHANNELS],
+}
+impl<const CHANNELS: usize> AirtimeLedger<CHANNELS> {
+    /// Creates a new, empty ledger
+    pub const fn new() -> Self {
+        Self { entries: [None; CHANNELS] }
+    }
+
+    /// Records a transmission of `payload_len` bytes with the given config, started at `now`, using the time-on-air
+    /// calculator (see [`crate::lora::airtime::airtime`])
+    pub fn record_tx(
+        &mut self,
+        frequency: Frequency,
+        payload_len: usize,
+        config: Config,
+        now: u64,
+    ) -> Result<(), InvalidArgumentError> {
+        let airtime = airtime::airtime(payload_len, config);
+        self.record(frequency, airtime, now)
+    }
+    /// Records a transmission with the given airtime on the given frequency, started at `now`
+    pub fn record(&mut self, frequency: Frequency, airtime: Duration, now: u64) -> Result<(), InvalidArgumentError> {
+        // Get or create the entry for the frequency
+        let entry = match self.entries.iter().position(|entry| Self::matches(entry, frequency)) {
+            Some(position) => self.entries.get_mut(position),
+            None => self.entries.iter_mut().find(|entry| entry.is_none()),
+        };
+        let Some(entry) = entry else {
+            // All entries are occupied by other frequencies
+            return Err(err!(InvalidArgumentError, "Airtime ledger is full"));
+        };
+        let entry = entry.get_or_insert(LedgerEntry { frequency, hour: Buckets::new(), day: Buckets::new() });
+
+        // Record the airtime
+        let micros = u32::try_from(airtime.as_micros()).unwrap_or(u32::MAX);
+        entry.hour.add(Self::hour_slot(now), micros);
+        entry.day.add(Self::day_slot(now), micros);
+        Ok(())
+    }
+
+    /// The airtime transmitted on the given frequency within the last hour
+    pub fn hour(&self, frequency: Frequency, now: u64) -> Duration {
+        let micros = (self.entries.iter().flatten())
+            .filter(|entry| entry.frequency == frequency)
+            .map(|entry| entry.hour.sum(Self::hour_slot(now)))
+            .sum();
+        Duration::from_micros(micros)
+    }
+    /// The airtime transmitted on the given frequency within the last day
+    pub fn day(&self, frequency: Frequency, now: u64) -> Duration {
+        let micros = (self.entries.iter().flatten())
+            .filter(|entry| entry.frequency == frequency)
+            .map(|entry| entry.day.sum(Self::day_slot(now)))
+            .sum();
+        Duration::from_micros(micros)
+    }
+    /// The airtime transmitted on all frequencies within the last hour
+    pub fn total_hour(&self, now: u64) -> Duration {
+        let micros = (self.entries.iter().flatten()).map(|entry| entry.hour.sum(Self::hour_slot(now))).sum();
+        Duration::from_micros(micros)
+    }
+    /// The airtime transmitted on all frequencies within the last day
+    pub fn total_day(&self, now: u64) -> Duration {
+        let micros = (self.entries.iter().flatten()).map(|entry| entry.day.sum(Self::day_slot(now))).sum();
+        Duration::from_micros(micros)
+    }
+    /// The duty cycle on the given frequency within the last hour in per-mille
+    pub fn hour_duty_cycle(&self, frequency: Frequency, now: u64) -> u16 {
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as the airtime is at most 32 bit")]
+        let per_mille = self.hour(frequency, now).as_micros() * 1000 / Self::hour_window_micros();
+        u16::try_from(per_mille).unwrap_or(u16::MAX)
+    }
+
+    /// The frequencies tracked by the ledger
+    pub fn frequencies(&self) -> impl Iterator<Item = Frequency> + '_ {
+        self.entries.iter().flatten().map(|entry| entry.frequency)
+    }
+    /// Removes all records
+    pub fn clear(&mut self) {
+        self.entries = [None; CHANNELS];
+    }
+
+    /// Persists the ledger as one record per entry (see [`crate::persistence::ledger_key`])
+    ///
+    /// # Important
+    /// The slots of the rolling windows are derived from the timestamps, so the restored ledger is only meaningful if
+    /// the clock keeps counting across reboots (e.g. an RTC-backed [`crate::clock::Clock`]).
+    pub fn save<P>(&self, persistence: &mut P) -> Result<(), P::Error>
+    where
+        P: Persistence,
+    {
+        for (index, entry) in (0..).zip(&self.entries) {
+            let mut record = [0; LedgerEntry::RECORD_LEN];
+            let record = match entry {
+                Some(entry) => entry.encode(&mut record),
+                None => &[],
+            };
+            persistence.write(persistence::ledger_key(index), record)?;
+        }
+        Ok(())
+    }
+    /// Restores the ledger from the persisted records; entries without a valid record are cleared
+    pub fn restore<P>(&mut self, persistence: &mut P) -> Result<(), P::Error>
+    where
+        P: Persistence,
+    {
+        for (index, entry) in (0..).zip(&mut self.entries) {
+            let mut record = [0; LedgerEntry::RECORD_LEN];
+            let len = persistence.read(persistence::ledger_key(index), &mut record)?;
+            *entry = match len {
+                Some(LedgerEntry::RECORD_LEN) => Some(LedgerEntry::decode(&record)),
+                // The record does not exist, is empty or is invalid
+                _ => None,
+            };
+        }
+        Ok(())
+    }
+
+    /// Whether the given entry tracks the given frequency
+    fn matches(entry: &Option<LedgerEntry>, frequency: Frequency) -> bool {
+        entry.is_some_and(|entry| entry.frequency == frequency)
+    }
+    /// The slot of the hourly window for the given timestamp
+    fn hour_slot(now: u64) -> u64 {
+        now.checked_div(HOUR_BUCKET_MICROS).unwrap_or_default()
+    }
+    /// The slot of the daily window for the given timestamp
+    fn day_slot(now: u64) -> u64 {
+        now.checked_div(DAY_BUCKET_MICROS).unwrap_or_default()
+    }
+    /// The nominal length of the hourly window in microseconds
+    const fn hour_window_micros() -> u128 {
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
+        let micros = HOUR_BUCKET_MICROS as u128 * HOUR_BUCKETS as u128;
+        micros
+    }
+}
+impl<const CHANNELS: usize> Default for AirtimeLedger<CHANNELS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A duty cycle limit in per-mille (e.g. `10` for the 1% limit of most EU868 sub-bands)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DutyCycle(u16);
+impl DutyCycle {
+    /// No duty cycle limit
+    pub const UNLIMITED: Self = Self(1000);
+
+    /// Creates a new duty cycle limit from the given per-mille value
+    ///
+    /// # Range
+    /// The duty cycle is clamped to `1..=1000`.
+    pub const fn per_mille(per_mille: u16) -> Self {
+        match per_mille {
+            0 => Self(1),
+            1001.. => Self::UNLIMITED,
+            per_mille => Self(per_mille),
+        }
+    }
+    /// The duty cycle limit in per-mille
+    pub const fn as_per_mille(self) -> u16 {
+        self.0
+    }
+
+    /// The minimum period in microseconds between the start of a transmission with the given airtime in microseconds
+    /// and the start of the next transmission, so the transmission makes up at most the duty cycle of the period
+    ///
+    /// # Off-Time
+    /// The off-time after the transmission is the period minus the airtime.
+    pub const fn period(self, airtime: u64) -> u64 {
+        match airtime.saturating_mul(1000).checked_div(self.0 as u64) {
+            Some(period) => period,
+            None => u64::MAX,
+        }
+    }
+}
+impl Default for DutyCycle {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
@@ -0,0 +1,98 @@
+//! Link budget estimation
+//!
+//! # Accuracy
+//! The link budget is a planning aid: real-world links are affected by fading, interference and the installation, so a
+//! reasonable margin (usually `10..20 dB`) should be kept on top of the computed values.
+
+use crate::lora::math;
+use crate::lora::sensitivity;
+use crate::lora::types::{Bandwidth, Frequency, SpreadingFactor};
+
+/// Computes the free-space path loss in dB for the given distance in meters and frequency
+///
+/// # Formula
+/// `20 * log10(d) + 20 * log10(f) - 147.55`
+pub fn free_space_path_loss(distance: f32, frequency: Frequency) -> f32 {
+    /// `20 * log10(4 * pi / c)`
+    const FSPL_CONSTANT: f32 = -147.55;
+    let frequency = frequency.as_u32() as f32;
+    (20.0 * math::log10(distance)) + (20.0 * math::log10(frequency)) + FSPL_CONSTANT
+}
+
+/// A link budget for a given transmitter and receiver setup
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkBudget {
+    /// The TX power in dBm
+    tx_power: i8,
+    /// The TX antenna gain in dBi
+    tx_antenna_gain: i8,
+    /// The RX antenna gain in dBi
+    rx_antenna_gain: i8,
+    /// Additional losses (e.g. cables and connectors) in dB
+    losses: u8,
+}
+impl LinkBudget {
+    /// Creates a new link budget for the given TX power in dBm
+    pub const fn new(tx_power: i8) -> Self {
+        Self { tx_power, tx_antenna_gain: 0, rx_antenna_gain: 0, losses: 0 }
+    }
+    /// Sets the TX and RX antenna gains in dBi
+    pub const fn with_antenna_gains(mut self, tx_antenna_gain: i8, rx_antenna_gain: i8) -> Self {
+        self.tx_antenna_gain = tx_antenna_gain;
+        self.rx_antenna_gain = rx_antenna_gain;
+        self
+    }
+    /// Sets additional losses (e.g. cables and connectors) in dB
+    pub const fn with_losses(mut self, losses: u8) -> Self {
+        self.losses = losses;
+        self
+    }
+
+    /// The TX power in dBm
+    pub const fn tx_power(&self) -> i8 {
+        self.tx_power
+    }
+    /// The TX antenna gain in dBi
+    pub const fn tx_antenna_gain(&self) -> i8 {
+        self.tx_antenna_gain
+    }
+    /// The RX antenna gain in dBi
+    pub const fn rx_antenna_gain(&self) -> i8 {
+        self.rx_antenna_gain
+    }
+    /// Additional losses (e.g. cables and connectors) in dB
+    pub const fn losses(&self) -> u8 {
+        self.losses
+    }
+
+    /// The expected received signal strength in dBm for the given path loss in dB
+    pub fn received_power(&self, path_loss: f32) -> f32 {
+        let gains = self.tx_power as f32 + self.tx_antenna_gain as f32 + self.rx_antenna_gain as f32;
+        gains - self.losses as f32 - path_loss
+    }
+    /// The expected link margin in dB for the given path loss in dB, spreading factor and bandwidth
+    ///
+    /// # Note
+    /// A positive margin means the link is expected to work; the larger the margin, the more robust the link is.
+    pub fn margin(&self, path_loss: f32, spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> f32 {
+        let sensitivity = sensitivity::sensitivity(spreading_factor, bandwidth);
+        self.received_power(path_loss) - sensitivity as f32
+    }
+    /// The smallest (i.e. fastest) spreading factor for the given bandwidth that achieves at least the required margin
+    /// in dB over the given path loss in dB, or `None` if no spreading factor achieves the required margin
+    pub fn min_spreading_factor(&self, path_loss: f32, bandwidth: Bandwidth, margin: f32) -> Option<SpreadingFactor> {
+        /// All spreading factors from the fastest to the most robust
+        const SPREADING_FACTORS: [SpreadingFactor; 6] = [
+            SpreadingFactor::S7,
+            SpreadingFactor::S8,
+            SpreadingFactor::S9,
+            SpreadingFactor::S10,
+            SpreadingFactor::S11,
+            SpreadingFactor::S12,
+        ];
+
+        // Find the first spreading factor with the required margin
+        (SPREADING_FACTORS.into_iter())
+            .find(|spreading_factor| self.margin(path_loss, *spreading_factor, bandwidth) >= margin)
+    }
+}
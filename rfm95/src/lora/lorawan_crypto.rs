@@ -0,0 +1,287 @@
+//! AES-CMAC based MIC computation and payload encryption as used by LoRaWAN 1.0.x
+//!
+//! # Block Construction
+//! The MIC of a data message is the truncated AES-CMAC (RFC 4493) over the `B0` block followed by the message without
+//! the MIC; the payload is encrypted with the keystream of the `A_i` blocks. Both blocks are built from the direction,
+//! the device address and the full 32-bit frame counter (not only the 16 bits transmitted in the frame header).
+//!
+//! # AES Backend
+//! The AES block cipher is pluggable via [`BlockCipher`], so a hardware AES peripheral or a secure element can be used.
+//! The `aes`-feature provides [`Aes128`], a software implementation based on the [`aes`](https://crates.io/crates/aes)
+//! crate. Only the encryption direction is required, including for join accepts (see [`decrypt_join_accept`]).
+
+use crate::err;
+use crate::error::InvalidArgumentError;
+use crate::lora::phypayload::MIC_LEN;
+
+/// The AES block length
+pub const BLOCK_LEN: usize = 16;
+
+/// An AES-128 block cipher
+pub trait BlockCipher {
+    /// Encrypts the given block in place
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_LEN]);
+}
+impl<T> BlockCipher for &T
+where
+    T: BlockCipher + ?Sized,
+{
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_LEN]) {
+        (**self).encrypt_block(block);
+    }
+}
+
+/// A software AES-128 block cipher
+#[cfg(feature = "aes")]
+#[derive(Clone)]
+pub struct Aes128 {
+    /// The cipher instance
+    cipher: aes::Aes128,
+}
+#[cfg(feature = "aes")]
+impl Aes128 {
+    /// Creates a new cipher with the given key
+    pub fn new(key: &[u8; 16]) -> Self {
+        use aes::cipher::KeyInit;
+        Self { cipher: aes::Aes128::new(key.into()) }
+    }
+}
+#[cfg(feature = "aes")]
+impl BlockCipher for Aes128 {
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_LEN]) {
+        use aes::cipher::BlockEncrypt;
+        self.cipher.encrypt_block(block.into());
+    }
+}
+#[cfg(feature = "aes")]
+impl core::fmt::Debug for Aes128 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Do not leak the key
+        f.debug_struct("Aes128").finish_non_exhaustive()
+    }
+}
+
+/// The direction of a data message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Direction {
+    /// An uplink, i.e. from the device to the network
+    Up = 0,
+    /// A downlink, i.e. from the network to the device
+    Down = 1,
+}
+
+/// Computes the AES-CMAC (RFC 4493) over the concatenation of the given parts
+pub fn cmac<C>(cipher: &C, parts: &[&[u8]]) -> [u8; BLOCK_LEN]
+where
+    C: BlockCipher,
+{
+    // Derive the subkeys
+    let mut l = [0; BLOCK_LEN];
+    cipher.encrypt_block(&mut l);
+    let k1 = double(&l);
+    let k2 = double(&k1);
+
+    // Process all complete blocks but the last one
+    let mut state = [0; BLOCK_LEN];
+    let mut block = [0; BLOCK_LEN];
+    let mut block_len = 0;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        // Flush the block once it is full and more data follows
+        if block_len == BLOCK_LEN {
+            xor(&mut state, &block);
+            cipher.encrypt_block(&mut state);
+            block_len = 0;
+        }
+        if let Some(slot) = block.get_mut(block_len) {
+            *slot = *byte;
+        }
+        block_len = block_len.saturating_add(1);
+    }
+
+    // Pad and mask the last block
+    let subkey = match block_len {
+        BLOCK_LEN => &k1,
+        _ => {
+            let padding = block.get_mut(block_len..).unwrap_or_default();
+            padding.fill(0);
+            if let Some(marker) = padding.first_mut() {
+                *marker = 0x80;
+            }
+            &k2
+        }
+    };
+    xor(&mut block, subkey);
+    xor(&mut state, &block);
+    cipher.encrypt_block(&mut state);
+    state
+}
+
+/// Computes the MIC of a data message, where `message` is the encoded frame without the MIC (see
+/// [`crate::lora::phypayload::mic_input`])
+///
+/// # Keys
+/// For LoRaWAN 1.0.x, `cipher` uses the `NwkSKey`.
+pub fn data_mic<C>(
+    cipher: &C,
+    direction: Direction,
+    dev_addr: u32,
+    f_cnt: u32,
+    message: &[u8],
+) -> Result<[u8; MIC_LEN], InvalidArgumentError>
+where
+    C: BlockCipher,
+{
+    let Ok(len) = u8::try_from(message.len()) else {
+        // The message is longer than a LoRa frame
+        return Err(err!(InvalidArgumentError, "Message is too long"));
+    };
+    let b0 = block(0x49, direction, dev_addr, f_cnt, len);
+    Ok(truncate(cmac(cipher, &[&b0, message])))
+}
+/// Computes the MIC of a join request or a decrypted join accept, where `message` is the frame without the MIC
+///
+/// # Keys
+/// For LoRaWAN 1.0.x, `cipher` uses the `AppKey`.
+pub fn join_mic<C>(cipher: &C, message: &[u8]) -> [u8; MIC_LEN]
+where
+    C: BlockCipher,
+{
+    truncate(cmac(cipher, &[message]))
+}
+
+/// Encrypts or decrypts the `FRMPayload` of a data message in place
+///
+/// # Keys
+/// For LoRaWAN 1.0.x, `cipher` uses the `AppSKey` for application ports, and the `NwkSKey` for port `0`.
+pub fn crypt_payload<C>(cipher: &C, direction: Direction, dev_addr: u32, f_cnt: u32, payload: &mut [u8])
+where
+    C: BlockCipher,
+{
+    for (index, chunk) in (1..=u8::MAX).zip(payload.chunks_mut(BLOCK_LEN)) {
+        // Compute the keystream block `S_i = aes128_encrypt(K, A_i)`
+        let mut keystream = block(0x01, direction, dev_addr, f_cnt, index);
+        cipher.encrypt_block(&mut keystream);
+        for (byte, key) in chunk.iter_mut().zip(keystream) {
+            *byte ^= key;
+        }
+    }
+}
+/// Decrypts a join accept in place, where `payload` is the frame without the MAC header (including the MIC)
+///
+/// # Join Accept Encryption
+/// The network server "encrypts" join accepts via AES decryption, so the device decrypts them via AES encryption of
+/// each block. The payload must be 16 or 32 bytes long.
+pub fn decrypt_join_accept<C>(cipher: &C, payload: &mut [u8]) -> Result<(), InvalidArgumentError>
+where
+    C: BlockCipher,
+{
+    if payload.len().checked_rem(BLOCK_LEN) != Some(0) {
+        // The payload is not a multiple of the block length
+        return Err(err!(InvalidArgumentError, "Join accept is not a multiple of the block length"));
+    }
+    for block in payload.chunks_exact_mut(BLOCK_LEN) {
+        let Ok(block) = <&mut [u8; BLOCK_LEN]>::try_from(block) else {
+            // Exact chunks always have the block length
+            continue;
+        };
+        cipher.encrypt_block(block);
+    }
+    Ok(())
+}
+
+/// Builds a `B0` or `A_i` block
+fn block(tag: u8, direction: Direction, dev_addr: u32, f_cnt: u32, last: u8) -> [u8; BLOCK_LEN] {
+    let [a0, a1, a2, a3] = dev_addr.to_le_bytes();
+    let [c0, c1, c2, c3] = f_cnt.to_le_bytes();
+    [tag, 0, 0, 0, 0, direction as u8, a0, a1, a2, a3, c0, c1, c2, c3, 0, last]
+}
+/// Doubles a block in GF(2^128) as required for the CMAC subkey derivation
+fn double(block: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+    let value = u128::from_be_bytes(*block);
+    let reduction = match value >> 127 {
+        0 => 0,
+        _ => 0x87,
+    };
+    ((value << 1) ^ reduction).to_be_bytes()
+}
+/// XORs `other` into `block`
+fn xor(block: &mut [u8; BLOCK_LEN], other: &[u8; BLOCK_LEN]) {
+    for (byte, other) in block.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+/// Truncates a CMAC to the MIC length
+fn truncate(cmac: [u8; BLOCK_LEN]) -> [u8; MIC_LEN] {
+    let [m0, m1, m2, m3, ..] = cmac;
+    [m0, m1, m2, m3]
+}
+
+#[cfg(all(test, feature = "aes"))]
+mod tests {
+    use super::*;
+
+    /// The RFC 4493 key
+    const RFC4493_KEY: [u8; 16] =
+        [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+    /// The RFC 4493 message
+    const RFC4493_MESSAGE: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, //
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51, //
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, //
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10, //
+    ];
+
+    #[test]
+    fn cmac_rfc4493() {
+        let cipher = Aes128::new(&RFC4493_KEY);
+        let vectors: [(usize, [u8; BLOCK_LEN]); 4] = [
+            (0, [0xbb, 0x1d, 0x69, 0x29, 0xe9, 0x59, 0x37, 0x28, 0x7f, 0xa3, 0x7d, 0x12, 0x9b, 0x75, 0x67, 0x46]),
+            (16, [0x07, 0x0a, 0x16, 0xb4, 0x6b, 0x4d, 0x41, 0x44, 0xf7, 0x9b, 0xdd, 0x9d, 0xd0, 0x4a, 0x28, 0x7c]),
+            (40, [0xdf, 0xa6, 0x67, 0x47, 0xde, 0x9a, 0xe6, 0x30, 0x30, 0xca, 0x32, 0x61, 0x14, 0x97, 0xc8, 0x27]),
+            (64, [0x51, 0xf0, 0xbe, 0xbf, 0x7e, 0x3b, 0x9d, 0x92, 0xfc, 0x49, 0x74, 0x17, 0x79, 0x36, 0x3c, 0xfe]),
+        ];
+        for (len, expected) in vectors {
+            let message = RFC4493_MESSAGE.get(..len).unwrap_or_default();
+            assert_eq!(cmac(&cipher, &[message]), expected, "message length {len}");
+        }
+    }
+
+    #[test]
+    fn cmac_split_parts() {
+        // The parts must be processed as one contiguous message, regardless of the block boundaries
+        let cipher = Aes128::new(&RFC4493_KEY);
+        let (head, tail) = RFC4493_MESSAGE.split_at(7);
+        assert_eq!(cmac(&cipher, &[head, &[], tail]), cmac(&cipher, &[&RFC4493_MESSAGE]));
+    }
+
+    /// An uplink with `DevAddr = 0x49BE7DF1`, `FCnt = 2`, `FPort = 1` and the encrypted payload `test`
+    const UPLINK: [u8; 17] =
+        [0x40, 0xf1, 0x7d, 0xbe, 0x49, 0x00, 0x02, 0x00, 0x01, 0x95, 0x43, 0x78, 0x76, 0x2b, 0x11, 0xff, 0x0d];
+    /// The network session key of the uplink
+    const NWK_S_KEY: [u8; 16] =
+        [0x44, 0x02, 0x42, 0x41, 0xed, 0x4c, 0xe9, 0xa6, 0x8c, 0x6a, 0x8b, 0xc0, 0x55, 0x23, 0x3f, 0xd3];
+    /// The application session key of the uplink
+    const APP_S_KEY: [u8; 16] =
+        [0xec, 0x92, 0x58, 0x02, 0xae, 0x43, 0x0c, 0xa7, 0x7f, 0xd3, 0xdd, 0x73, 0xcb, 0x2c, 0xc5, 0x88];
+
+    #[test]
+    fn data_mic_uplink() {
+        let cipher = Aes128::new(&NWK_S_KEY);
+        let (message, mic) = UPLINK.split_at(UPLINK.len() - MIC_LEN);
+        let computed = data_mic(&cipher, Direction::Up, 0x49be_7df1, 2, message).ok();
+        assert_eq!(computed.as_ref().map(|computed| &computed[..]), Some(mic));
+    }
+
+    #[test]
+    fn crypt_payload_uplink() {
+        let cipher = Aes128::new(&APP_S_KEY);
+        let mut payload = [0x95, 0x43, 0x78, 0x76];
+        crypt_payload(&cipher, Direction::Up, 0x49be_7df1, 2, &mut payload);
+        assert_eq!(&payload, b"test");
+
+        // Encryption and decryption are the same operation
+        crypt_payload(&cipher, Direction::Up, 0x49be_7df1, 2, &mut payload);
+        assert_eq!(payload, [0x95, 0x43, 0x78, 0x76]);
+    }
+}
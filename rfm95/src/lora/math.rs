@@ -0,0 +1,59 @@
+//! Small float helpers for `no-std` environments without `libm`
+//!
+//! # Accuracy
+//! The helpers are intended for rough estimations like ranging or link budgets, and have a relative error below `1e-4`.
+
+use core::f32::consts::{LN_2, LOG10_2, LOG2_10};
+
+/// Computes `10^x`
+pub fn pow10(x: f32) -> f32 {
+    exp2(x * LOG2_10)
+}
+
+/// Computes `log10(x)`, or `NaN` if `x` is not positive
+pub fn log10(x: f32) -> f32 {
+    log2(x) * LOG10_2
+}
+
+/// Computes `2^x`
+pub fn exp2(x: f32) -> f32 {
+    // Clamp the exponent to the representable range and split it into the integer and fractional part
+    let x = x.clamp(-126.0, 127.0);
+    #[allow(clippy::cast_possible_truncation, reason = "The value is clamped to a range that always fits")]
+    let mut integer = x as i32;
+    if integer as f32 > x {
+        // Round towards negative infinity
+        integer = integer.saturating_sub(1);
+    }
+    let fraction = x - integer as f32;
+
+    // Compute `2^integer` via the float exponent bits, and `2^fraction` via the Taylor series of `e^(fraction * ln(2))`
+    // Note: The coefficients are `ln(2)^k / k!`
+    let integer = f32::from_bits((integer.saturating_add(127) as u32) << 23);
+    let fraction = 1.0
+        + fraction
+            * (LN_2
+                + fraction
+                    * (0.240_226_5 + fraction * (0.055_504_1 + fraction * (0.009_618_1 + fraction * 0.001_333_4))));
+    integer * fraction
+}
+
+/// Computes `log2(x)`, or `NaN` if `x` is not positive
+pub fn log2(x: f32) -> f32 {
+    // Validate the input; we also reject subnormal values as they are irrelevant for our use cases
+    if !x.is_normal() || x.is_sign_negative() {
+        // The logarithm is undefined (or irrelevant)
+        return f32::NAN;
+    }
+
+    // Split the value into the exponent and the mantissa in `[1, 2)`
+    let bits = x.to_bits();
+    let exponent = (((bits >> 23) & 0xFF) as i32).saturating_sub(127);
+    let mantissa = f32::from_bits((bits & 0x007F_FFFF) | 0x3F80_0000);
+
+    // Compute `ln(mantissa)` via the series `2 * (t + t^3/3 + t^5/5 + t^7/7)` with `t = (m - 1) / (m + 1)`
+    let t = (mantissa - 1.0) / (mantissa + 1.0);
+    let t2 = t * t;
+    let ln_mantissa = 2.0 * t * (1.0 + t2 * (1.0 / 3.0 + t2 * (1.0 / 5.0 + t2 * (1.0 / 7.0))));
+    exponent as f32 + ln_mantissa / LN_2
+}
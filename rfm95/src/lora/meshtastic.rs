@@ -0,0 +1,397 @@
+//! Meshtastic-compatible radio framing
+//!
+//! # Scope
+//! This module covers the radio layer of [Meshtastic](https://meshtastic.org): the modem presets, the frequency slot
+//! selection, the channel hash and the unencrypted packet header including the hop and flag fields. The payload
+//! encryption (AES-CTR with the channel key) and the protobuf-encoded application payload are out of scope; the payload
+//! is passed verbatim.
+//!
+//! # Usage
+//! Configure the radio via [`ModemPreset::config`] with the frequency from [`MeshRegion::frequency`], then encode
+//! packets via [`PacketHeader::encode`] and decode received packets via [`PacketHeader::parse`]. To act as a relay,
+//! rebroadcast packets with the header returned by [`PacketHeader::relayed`].
+
+use crate::err;
+use crate::error::{BufferTooSmallError, InvalidMessageError};
+use crate::lora::config::Config;
+use crate::lora::types::{
+    Bandwidth, CodingRate, CrcMode, Frequency, HeaderMode, Polarity, PreambleLength, SpreadingFactor, SyncWord,
+};
+
+/// The Meshtastic sync word
+pub const SYNC_WORD: SyncWord = SyncWord::new(0x2B);
+/// The Meshtastic preamble length
+pub const PREAMBLE_LENGTH: PreambleLength = PreambleLength::new(16);
+/// The length of the packet header
+pub const HEADER_LEN: usize = 16;
+/// The broadcast node number
+pub const BROADCAST: u32 = u32::MAX;
+/// The expanded default channel key (i.e. the PSK `AQ==`)
+pub const DEFAULT_KEY: [u8; 16] =
+    [0xD4, 0xF1, 0xBB, 0x3A, 0x20, 0x29, 0x07, 0x59, 0xF0, 0xBC, 0xFF, 0xAB, 0xCF, 0x4E, 0x69, 0x01];
+
+/// A Meshtastic modem preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModemPreset {
+    /// SF7, 500 kHz, CR 4/5
+    ShortTurbo,
+    /// SF7, 250 kHz, CR 4/5
+    ShortFast,
+    /// SF8, 250 kHz, CR 4/5
+    ShortSlow,
+    /// SF9, 250 kHz, CR 4/5
+    MediumFast,
+    /// SF10, 250 kHz, CR 4/5
+    MediumSlow,
+    /// SF11, 250 kHz, CR 4/5 (the default preset)
+    LongFast,
+    /// SF11, 125 kHz, CR 4/8
+    LongModerate,
+    /// SF12, 125 kHz, CR 4/8
+    LongSlow,
+}
+impl ModemPreset {
+    /// The spreading factor, bandwidth and coding rate of the preset
+    pub const fn parameters(self) -> (SpreadingFactor, Bandwidth, CodingRate) {
+        use Bandwidth::*;
+        use CodingRate::*;
+        use SpreadingFactor::*;
+
+        match self {
+            Self::ShortTurbo => (S7, B500, C4_5),
+            Self::ShortFast => (S7, B250, C4_5),
+            Self::ShortSlow => (S8, B250, C4_5),
+            Self::MediumFast => (S9, B250, C4_5),
+            Self::MediumSlow => (S10, B250, C4_5),
+            Self::LongFast => (S11, B250, C4_5),
+            Self::LongModerate => (S11, B125, C4_8),
+            Self::LongSlow => (S12, B125, C4_8),
+        }
+    }
+    /// The default channel name of the preset, which selects the frequency slot and the channel hash
+    pub const fn channel_name(self) -> &'static str {
+        match self {
+            Self::ShortTurbo => "ShortTurbo",
+            Self::ShortFast => "ShortFast",
+            Self::ShortSlow => "ShortSlow",
+            Self::MediumFast => "MediumFast",
+            Self::MediumSlow => "MediumSlow",
+            Self::LongFast => "LongFast",
+            Self::LongModerate => "LongModerate",
+            Self::LongSlow => "LongSlow",
+        }
+    }
+    /// The radio config of the preset on the given frequency
+    pub const fn config(self, frequency: Frequency) -> Config {
+        let (spreading_factor, bandwidth, coding_rate) = self.parameters();
+        Config::builder()
+            .set_spreading_factor(spreading_factor)
+            .set_bandwidth(bandwidth)
+            .set_coding_rate(coding_rate)
+            .set_polarity(Polarity::Normal)
+            .set_header_mode(HeaderMode::Explicit)
+            .set_crc_mode(CrcMode::Enabled)
+            .set_sync_word(SYNC_WORD)
+            .set_preamble_length(PREAMBLE_LENGTH)
+            .set_frequency(frequency)
+    }
+}
+
+/// A Meshtastic region, which defines the band the frequency slots are laid out in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshRegion {
+    /// United States, 902-928 MHz
+    Us,
+    /// EU 433 MHz, 433-434 MHz
+    Eu433,
+    /// EU 868 MHz, 869.4-869.65 MHz
+    Eu868,
+    /// China, 470-510 MHz
+    Cn,
+    /// Australia and New Zealand, 915-928 MHz
+    Anz,
+    /// Russia, 868.7-869.2 MHz
+    Ru,
+    /// South Korea, 920-923 MHz
+    Kr,
+    /// Taiwan, 920-925 MHz
+    Tw,
+    /// India, 865-867 MHz
+    In,
+    /// New Zealand 865 MHz, 864-868 MHz
+    Nz865,
+    /// Thailand, 920-925 MHz
+    Th,
+    /// Ukraine 868 MHz, 868-868.6 MHz
+    Ua868,
+}
+impl MeshRegion {
+    /// The lowest and highest frequency of the band
+    pub const fn frequency_range(self) -> (Frequency, Frequency) {
+        let (start, end) = match self {
+            Self::Us => (902_000_000, 928_000_000),
+            Self::Eu433 => (433_000_000, 434_000_000),
+            Self::Eu868 => (869_400_000, 869_650_000),
+            Self::Cn => (470_000_000, 510_000_000),
+            Self::Anz => (915_000_000, 928_000_000),
+            Self::Ru => (868_700_000, 869_200_000),
+            Self::Kr => (920_000_000, 923_000_000),
+            Self::Tw => (920_000_000, 925_000_000),
+            Self::In => (865_000_000, 867_000_000),
+            Self::Nz865 => (864_000_000, 868_000_000),
+            Self::Th => (920_000_000, 925_000_000),
+            Self::Ua868 => (868_000_000, 868_600_000),
+        };
+        (Frequency::hz(start), Frequency::hz(end))
+    }
+    /// The amount of frequency slots for the given preset
+    pub const fn slot_count(self, preset: ModemPreset) -> u32 {
+        let (start, end) = self.frequency_range();
+        let (_, bandwidth, _) = preset.parameters();
+        let span = end.as_u32().saturating_sub(start.as_u32());
+        match span.checked_div(bandwidth_hz(bandwidth)) {
+            Some(count) => count,
+            None => 0,
+        }
+    }
+    /// The center frequency of the given zero-based frequency slot for the given preset, or `None` if the slot does not
+    /// exist
+    pub const fn slot_frequency(self, preset: ModemPreset, slot: u32) -> Option<Frequency> {
+        if slot >= self.slot_count(preset) {
+            return None;
+        }
+
+        // The slots are laid out back-to-back from the start of the band
+        let (start, _) = self.frequency_range();
+        let (_, bandwidth, _) = preset.parameters();
+        let bandwidth = bandwidth_hz(bandwidth);
+        let center = start.as_u32().saturating_add(bandwidth / 2).saturating_add(slot.saturating_mul(bandwidth));
+        Some(Frequency::hz(center))
+    }
+    /// The center frequency for the given preset and channel name (see [`ModemPreset::channel_name`]), or `None` if
+    /// the band is too narrow for the preset
+    ///
+    /// # Slot Selection
+    /// The frequency slot is the DJB2 hash of the channel name modulo the amount of slots; e.g. the default
+    /// `LongFast` channel in the US uses slot `19` (906.875 MHz).
+    pub fn frequency(self, preset: ModemPreset, channel_name: &str) -> Option<Frequency> {
+        let hash = channel_name.bytes().fold(5381u32, |hash, byte| hash.wrapping_mul(33).wrapping_add(byte as u32));
+        let slot = hash.checked_rem(self.slot_count(preset))?;
+        self.slot_frequency(preset, slot)
+    }
+}
+
+/// The bandwidth in Hz
+const fn bandwidth_hz(bandwidth: Bandwidth) -> u32 {
+    match bandwidth {
+        Bandwidth::B500 => 500_000,
+        Bandwidth::B250 => 250_000,
+        Bandwidth::B125 => 125_000,
+        Bandwidth::B62_5 => 62_500,
+        Bandwidth::B41_7 => 41_700,
+        Bandwidth::B31_25 => 31_250,
+        Bandwidth::B20_8 => 20_800,
+        Bandwidth::B15_6 => 15_600,
+        Bandwidth::B10_4 => 10_400,
+        Bandwidth::B7_8 => 7_800,
+    }
+}
+
+/// Computes the channel hash of the given channel name and the given (expanded) channel key, i.e. the XOR of all bytes
+///
+/// # Default Channel
+/// The default `LongFast` channel with [`DEFAULT_KEY`] has the hash `8`.
+pub fn channel_hash(channel_name: &str, key: &[u8]) -> u8 {
+    channel_name.bytes().chain(key.iter().copied()).fold(0, |hash, byte| hash ^ byte)
+}
+
+/// The flags field of a packet header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PacketFlags(u8);
+impl PacketFlags {
+    /// The mask of the hop limit
+    const HOP_LIMIT: u8 = 0b0000_0111;
+    /// The want-ack flag
+    const WANT_ACK: u8 = 0b0000_1000;
+    /// The via-MQTT flag
+    const VIA_MQTT: u8 = 0b0001_0000;
+    /// The shift of the hop start
+    const HOP_START_SHIFT: u8 = 5;
+
+    /// Creates the flags from the raw field
+    pub const fn new(flags: u8) -> Self {
+        Self(flags)
+    }
+    /// Sets the hop limit and the hop start (the initial hop limit), which are truncated to `0..=7`
+    pub const fn with_hops(self, hop_limit: u8, hop_start: u8) -> Self {
+        let preserved = self.0 & (Self::WANT_ACK | Self::VIA_MQTT);
+        Self(preserved | (hop_limit & Self::HOP_LIMIT) | ((hop_start & Self::HOP_LIMIT) << Self::HOP_START_SHIFT))
+    }
+    /// Sets whether the sender wants an acknowledgement
+    pub const fn with_want_ack(self, want_ack: bool) -> Self {
+        self.with_flag(Self::WANT_ACK, want_ack)
+    }
+    /// Sets whether the packet has been forwarded via MQTT
+    pub const fn with_via_mqtt(self, via_mqtt: bool) -> Self {
+        self.with_flag(Self::VIA_MQTT, via_mqtt)
+    }
+
+    /// The remaining hop limit
+    pub const fn hop_limit(&self) -> u8 {
+        self.0 & Self::HOP_LIMIT
+    }
+    /// The initial hop limit
+    pub const fn hop_start(&self) -> u8 {
+        self.0 >> Self::HOP_START_SHIFT
+    }
+    /// Whether the sender wants an acknowledgement
+    pub const fn want_ack(&self) -> bool {
+        self.0 & Self::WANT_ACK != 0
+    }
+    /// Whether the packet has been forwarded via MQTT
+    pub const fn via_mqtt(&self) -> bool {
+        self.0 & Self::VIA_MQTT != 0
+    }
+    /// The raw field
+    pub const fn as_u8(&self) -> u8 {
+        self.0
+    }
+
+    /// Sets or clears the given flag
+    const fn with_flag(self, flag: u8, value: bool) -> Self {
+        match value {
+            true => Self(self.0 | flag),
+            false => Self(self.0 & !flag),
+        }
+    }
+}
+
+/// The unencrypted header of a Meshtastic packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    /// The destination node number
+    dest: u32,
+    /// The sender node number
+    sender: u32,
+    /// The packet ID
+    id: u32,
+    /// The flags
+    flags: PacketFlags,
+    /// The channel hash
+    channel_hash: u8,
+    /// The last byte of the next-hop node number, or `0` if unknown
+    next_hop: u8,
+    /// The last byte of the relaying node number, or `0` if unknown
+    relay_node: u8,
+}
+impl PacketHeader {
+    /// Creates a new header without next-hop and relay information
+    pub const fn new(dest: u32, sender: u32, id: u32, flags: PacketFlags, channel_hash: u8) -> Self {
+        Self { dest, sender, id, flags, channel_hash, next_hop: 0, relay_node: 0 }
+    }
+    /// Sets the last byte of the next-hop node number
+    pub const fn with_next_hop(mut self, next_hop: u8) -> Self {
+        self.next_hop = next_hop;
+        self
+    }
+    /// Sets the last byte of the relaying node number
+    pub const fn with_relay_node(mut self, relay_node: u8) -> Self {
+        self.relay_node = relay_node;
+        self
+    }
+
+    /// The destination node number (see [`BROADCAST`])
+    pub const fn dest(&self) -> u32 {
+        self.dest
+    }
+    /// The sender node number
+    pub const fn sender(&self) -> u32 {
+        self.sender
+    }
+    /// The packet ID
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+    /// The flags
+    pub const fn flags(&self) -> PacketFlags {
+        self.flags
+    }
+    /// The channel hash (see [`channel_hash`])
+    pub const fn channel_hash(&self) -> u8 {
+        self.channel_hash
+    }
+    /// The last byte of the next-hop node number, or `0` if unknown
+    pub const fn next_hop(&self) -> u8 {
+        self.next_hop
+    }
+    /// The last byte of the relaying node number, or `0` if unknown
+    pub const fn relay_node(&self) -> u8 {
+        self.relay_node
+    }
+    /// Whether the packet is a broadcast
+    pub const fn is_broadcast(&self) -> bool {
+        self.dest == BROADCAST
+    }
+
+    /// The header for rebroadcasting the packet via the given node, or `None` if the hop limit is exhausted
+    pub const fn relayed(&self, node: u32) -> Option<Self> {
+        let Some(hop_limit) = self.flags.hop_limit().checked_sub(1) else {
+            // The packet must not be relayed anymore
+            return None;
+        };
+        let flags = self.flags.with_hops(hop_limit, self.flags.hop_start());
+        let [relay_node, ..] = node.to_le_bytes();
+        Some(Self { flags, relay_node, ..*self })
+    }
+
+    /// Decodes a packet, and returns the header and the (encrypted) payload
+    pub fn parse(frame: &[u8]) -> Result<(Self, &[u8]), InvalidMessageError> {
+        let Some((header, payload)) = frame.split_first_chunk::<HEADER_LEN>() else {
+            return Err(err!(InvalidMessageError, "Packet is too short"));
+        };
+        let [d0, d1, d2, d3, s0, s1, s2, s3, i0, i1, i2, i3, flags, channel_hash, next_hop, relay_node] = *header;
+        let header = Self {
+            dest: u32::from_le_bytes([d0, d1, d2, d3]),
+            sender: u32::from_le_bytes([s0, s1, s2, s3]),
+            id: u32::from_le_bytes([i0, i1, i2, i3]),
+            flags: PacketFlags(flags),
+            channel_hash,
+            next_hop,
+            relay_node,
+        };
+        Ok((header, payload))
+    }
+    /// Encodes the header followed by the (encrypted) payload into `buf` and returns the packet length
+    pub fn encode(&self, payload: &[u8], buf: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+        let len = HEADER_LEN.saturating_add(payload.len());
+        let Some((header, body)) = buf.get_mut(..len).and_then(|buf| buf.split_first_chunk_mut::<HEADER_LEN>()) else {
+            // The packet does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "Buffer is too small for the packet"));
+        };
+
+        // Encode the header fields
+        let [d0, d1, d2, d3] = self.dest.to_le_bytes();
+        let [s0, s1, s2, s3] = self.sender.to_le_bytes();
+        let [i0, i1, i2, i3] = self.id.to_le_bytes();
+        *header = [
+            d0,
+            d1,
+            d2,
+            d3,
+            s0,
+            s1,
+            s2,
+            s3,
+            i0,
+            i1,
+            i2,
+            i3,
+            self.flags.0,
+            self.channel_hash,
+            self.next_hop,
+            self.relay_node,
+        ];
+        body.copy_from_slice(payload);
+        Ok(len)
+    }
+}
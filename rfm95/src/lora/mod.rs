@@ -1,5 +1,34 @@
 //! LoRa specific configuration
 
 pub mod airtime;
+pub mod aprs;
+pub mod channel;
+pub mod checksum;
+pub mod class_b;
+#[cfg(feature = "compression")]
+pub mod compression;
 pub mod config;
+pub mod confirmed;
+pub mod csma;
+pub mod datarate;
+pub mod device_time;
+pub mod downlink;
+pub mod fcnt;
+pub mod join_state;
+pub mod ledger;
+pub mod link_budget;
+pub mod lorawan_crypto;
+mod math;
+#[cfg(feature = "meshtastic")]
+pub mod meshtastic;
+pub mod network;
+pub mod phypayload;
+#[cfg(feature = "provisioning")]
+pub mod provisioning;
+pub mod ranging;
+pub mod region;
+pub mod regulatory;
+pub mod replay;
+pub mod secure_link;
+pub mod sensitivity;
 pub mod types;
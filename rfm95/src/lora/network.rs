@@ -0,0 +1,169 @@
+//! Presets for common public and private LoRaWAN networks
+//!
+//! # Usage
+//! Pick the preset of the network, then hop over the uplink channels of [`NetworkPreset::channels`] and transmit with
+//! [`NetworkPreset::uplink_config`]. Downlinks in the RX2 window are received with [`NetworkPreset::rx2_config`], which
+//! already has the inverted polarity and disabled payload CRC of LoRaWAN downlinks.
+//!
+//! # Gateway Configuration
+//! The presets mirror the default settings of the respective network servers; if the gateway or network server has been
+//! configured differently (e.g. another sub-band, or a private sync word), adjust the preset accordingly (see
+//! [`NetworkPreset::with_sync_word`]), otherwise the gateway will not hear the device.
+
+use crate::lora::channel::ChannelPlan;
+use crate::lora::config::Config;
+use crate::lora::datarate::DataRate;
+use crate::lora::region::Region;
+use crate::lora::types::{CodingRate, CrcMode, Frequency, HeaderMode, Polarity, PreambleLength, SyncWord};
+use core::time::Duration;
+
+/// The settings of a LoRaWAN network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkPreset {
+    /// The region
+    region: Region,
+    /// The uplink channels
+    channels: ChannelPlan,
+    /// The sync word
+    sync_word: SyncWord,
+    /// The RX2 frequency
+    rx2_frequency: Frequency,
+    /// The RX2 data rate
+    rx2_data_rate: DataRate,
+    /// The delay between the end of an uplink and the RX1 window
+    rx1_delay: Duration,
+}
+impl NetworkPreset {
+    /// The Things Network in EU868: 8 uplink channels, RX2 on 869.525 MHz with DR3 (SF9), RX1 delay of 5 seconds
+    pub const TTN_EU868: Self = Self {
+        region: Region::Eu868,
+        channels: ChannelPlan::EU868_TTN,
+        sync_word: SyncWord::LORAWAN_PUBLIC,
+        rx2_frequency: Frequency::hz(869_525_000),
+        rx2_data_rate: DataRate::DR3,
+        rx1_delay: Duration::from_secs(5),
+    };
+    /// The Things Network in US915: sub-band 2, RX2 on 923.3 MHz with DR8 (SF12, 500 kHz), RX1 delay of 5 seconds
+    pub const TTN_US915: Self = Self {
+        region: Region::Us915,
+        channels: ChannelPlan::US915_SUB_BAND_2,
+        sync_word: SyncWord::LORAWAN_PUBLIC,
+        rx2_frequency: Frequency::hz(923_300_000),
+        rx2_data_rate: DataRate::DR8,
+        rx1_delay: Duration::from_secs(5),
+    };
+    /// Helium in US915: sub-band 2, RX2 on 923.3 MHz with DR8 (SF12, 500 kHz), RX1 delay of 1 second
+    pub const HELIUM_US915: Self = Self {
+        region: Region::Us915,
+        channels: ChannelPlan::US915_SUB_BAND_2,
+        sync_word: SyncWord::LORAWAN_PUBLIC,
+        rx2_frequency: Frequency::hz(923_300_000),
+        rx2_data_rate: DataRate::DR8,
+        rx1_delay: Duration::from_secs(1),
+    };
+    /// A ChirpStack network with the default EU868 settings: the 3 default channels, RX2 on 869.525 MHz with DR0
+    /// (SF12), RX1 delay of 1 second
+    pub const CHIRPSTACK_EU868: Self = Self {
+        region: Region::Eu868,
+        channels: ChannelPlan::EU868_DEFAULT,
+        sync_word: SyncWord::LORAWAN_PUBLIC,
+        rx2_frequency: Frequency::hz(869_525_000),
+        rx2_data_rate: DataRate::DR0,
+        rx1_delay: Duration::from_secs(1),
+    };
+    /// A ChirpStack network with the default US915 settings on sub-band 2: RX2 on 923.3 MHz with DR8 (SF12, 500 kHz),
+    /// RX1 delay of 1 second
+    pub const CHIRPSTACK_US915: Self = Self {
+        region: Region::Us915,
+        channels: ChannelPlan::US915_SUB_BAND_2,
+        sync_word: SyncWord::LORAWAN_PUBLIC,
+        rx2_frequency: Frequency::hz(923_300_000),
+        rx2_data_rate: DataRate::DR8,
+        rx1_delay: Duration::from_secs(1),
+    };
+
+    /// Replaces the sync word, e.g. for private networks whose gateways use the private sync word
+    /// [`SyncWord::PRIVATE`]
+    pub const fn with_sync_word(mut self, sync_word: SyncWord) -> Self {
+        self.sync_word = sync_word;
+        self
+    }
+    /// Replaces the uplink channels, e.g. to use another US915 sub-band
+    pub const fn with_channels(mut self, channels: ChannelPlan) -> Self {
+        self.channels = channels;
+        self
+    }
+    /// Replaces the RX2 frequency and data rate, e.g. after a `RXParamSetupReq` MAC command
+    pub const fn with_rx2(mut self, frequency: Frequency, data_rate: DataRate) -> Self {
+        self.rx2_frequency = frequency;
+        self.rx2_data_rate = data_rate;
+        self
+    }
+    /// Replaces the RX1 delay, e.g. after a `RXTimingSetupReq` MAC command
+    pub const fn with_rx1_delay(mut self, rx1_delay: Duration) -> Self {
+        self.rx1_delay = rx1_delay;
+        self
+    }
+
+    /// The region
+    pub const fn region(&self) -> Region {
+        self.region
+    }
+    /// The uplink channels
+    pub const fn channels(&self) -> ChannelPlan {
+        self.channels
+    }
+    /// The sync word
+    pub const fn sync_word(&self) -> SyncWord {
+        self.sync_word
+    }
+    /// The RX2 frequency
+    pub const fn rx2_frequency(&self) -> Frequency {
+        self.rx2_frequency
+    }
+    /// The RX2 data rate
+    pub const fn rx2_data_rate(&self) -> DataRate {
+        self.rx2_data_rate
+    }
+    /// The delay between the end of an uplink and the RX1 window; the RX2 window opens one second later
+    pub const fn rx1_delay(&self) -> Duration {
+        self.rx1_delay
+    }
+    /// The delay between the end of an uplink and the RX2 window
+    pub const fn rx2_delay(&self) -> Duration {
+        self.rx1_delay.saturating_add(Duration::from_secs(1))
+    }
+
+    /// The uplink config for the given frequency and data rate, or `None` if the data rate is not a LoRa data rate in
+    /// the region
+    pub const fn uplink_config(&self, frequency: Frequency, data_rate: DataRate) -> Option<Config> {
+        self.config(frequency, data_rate, Polarity::Normal, CrcMode::Enabled)
+    }
+    /// The RX2 downlink config, or `None` if the RX2 data rate is not a LoRa data rate in the region
+    pub const fn rx2_config(&self) -> Option<Config> {
+        self.config(self.rx2_frequency, self.rx2_data_rate, Polarity::Inverted, CrcMode::Disabled)
+    }
+
+    /// Creates a LoRaWAN config with the given parameters
+    const fn config(
+        &self,
+        frequency: Frequency,
+        data_rate: DataRate,
+        polarity: Polarity,
+        crc_mode: CrcMode,
+    ) -> Option<Config> {
+        let Some(builder) = Config::builder().set_data_rate(data_rate, self.region) else {
+            // The data rate is not a LoRa data rate in the region
+            return None;
+        };
+        let config = builder
+            .set_coding_rate(CodingRate::C4_5)
+            .set_polarity(polarity)
+            .set_header_mode(HeaderMode::Explicit)
+            .set_crc_mode(crc_mode)
+            .set_sync_word(self.sync_word)
+            .set_preamble_length(PreambleLength::L8)
+            .set_frequency(frequency);
+        Some(config)
+    }
+}
@@ -0,0 +1,475 @@
+//! A codec for LoRaWAN PHY payloads
+//!
+//! # Scope
+//! This module only encodes and decodes the frame structure (MHDR, FHDR, FPort, FRMPayload and MIC) as specified by
+//! LoRaWAN 1.0.x/1.1; it does neither verify nor compute the MIC, and does not decrypt the payload. All multi-byte
+//! fields are little-endian on the air.
+//!
+//! # Usage
+//! Decode a received frame via [`PhyPayload::parse`]; the decoded structures borrow from the frame. Encode a data frame
+//! via [`DataPayload::encode`] and a join request via [`JoinRequest::encode`] into a buffer with a zeroed MIC, which
+//! must then be computed over [`mic_input`] and written via [`set_mic`].
+
+use crate::err;
+use crate::error::{BufferTooSmallError, InvalidArgumentError, InvalidMessageError};
+
+/// The length of the MIC
+pub const MIC_LEN: usize = 4;
+/// The maximum length of the frame options
+pub const FOPTS_MAX_LEN: usize = 15;
+
+/// The message type of a PHY payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MType {
+    /// A join request
+    JoinRequest = 0b000,
+    /// A join accept
+    JoinAccept = 0b001,
+    /// An unconfirmed uplink
+    UnconfirmedDataUp = 0b010,
+    /// An unconfirmed downlink
+    UnconfirmedDataDown = 0b011,
+    /// A confirmed uplink
+    ConfirmedDataUp = 0b100,
+    /// A confirmed downlink
+    ConfirmedDataDown = 0b101,
+    /// A rejoin request (LoRaWAN 1.1)
+    RejoinRequest = 0b110,
+    /// A proprietary message
+    Proprietary = 0b111,
+}
+impl MType {
+    /// Whether the message is a data message
+    pub const fn is_data(self) -> bool {
+        matches!(
+            self,
+            Self::UnconfirmedDataUp | Self::UnconfirmedDataDown | Self::ConfirmedDataUp | Self::ConfirmedDataDown
+        )
+    }
+    /// Whether the message is an uplink
+    pub const fn is_uplink(self) -> bool {
+        matches!(self, Self::JoinRequest | Self::UnconfirmedDataUp | Self::ConfirmedDataUp | Self::RejoinRequest)
+    }
+    /// Whether the message is a confirmed data message
+    pub const fn is_confirmed(self) -> bool {
+        matches!(self, Self::ConfirmedDataUp | Self::ConfirmedDataDown)
+    }
+}
+
+/// The MAC header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mhdr {
+    /// The message type
+    mtype: MType,
+}
+impl Mhdr {
+    /// The major version of the frame format (LoRaWAN R1)
+    const MAJOR_R1: u8 = 0b00;
+
+    /// Creates a new MAC header with the given message type
+    pub const fn new(mtype: MType) -> Self {
+        Self { mtype }
+    }
+    /// Decodes the MAC header
+    pub const fn parse(mhdr: u8) -> Result<Self, InvalidMessageError> {
+        // Validate the major version
+        if mhdr & 0b11 != Self::MAJOR_R1 {
+            return Err(err!(InvalidMessageError, "Unsupported LoRaWAN major version"));
+        }
+
+        // Get the message type
+        let mtype = match mhdr >> 5 {
+            0b000 => MType::JoinRequest,
+            0b001 => MType::JoinAccept,
+            0b010 => MType::UnconfirmedDataUp,
+            0b011 => MType::UnconfirmedDataDown,
+            0b100 => MType::ConfirmedDataUp,
+            0b101 => MType::ConfirmedDataDown,
+            0b110 => MType::RejoinRequest,
+            _ => MType::Proprietary,
+        };
+        Ok(Self { mtype })
+    }
+    /// Encodes the MAC header
+    pub const fn as_u8(self) -> u8 {
+        ((self.mtype as u8) << 5) | Self::MAJOR_R1
+    }
+
+    /// The message type
+    pub const fn mtype(&self) -> MType {
+        self.mtype
+    }
+}
+
+/// The frame control flags of the frame header
+///
+/// # Direction
+/// Bit 6 is `ADRACKReq` for uplinks, and reserved for downlinks; bit 4 is `ClassB` for uplinks, and `FPending` for
+/// downlinks. The length of the frame options is not part of the flags, and is derived from the frame options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FCtrl {
+    /// The raw flags in bits 7..4
+    flags: u8,
+}
+impl FCtrl {
+    /// The `ADR` flag
+    const ADR: u8 = 0b1000_0000;
+    /// The `ADRACKReq` flag
+    const ADR_ACK_REQ: u8 = 0b0100_0000;
+    /// The `ACK` flag
+    const ACK: u8 = 0b0010_0000;
+    /// The `FPending`/`ClassB` flag
+    const F_PENDING: u8 = 0b0001_0000;
+
+    /// Creates new frame control flags from the raw `FCtrl` byte; the length of the frame options is ignored
+    pub const fn new(fctrl: u8) -> Self {
+        Self { flags: fctrl & 0xF0 }
+    }
+    /// Sets the `ADR` flag
+    pub const fn with_adr(self, adr: bool) -> Self {
+        self.with_flag(Self::ADR, adr)
+    }
+    /// Sets the `ADRACKReq` flag
+    pub const fn with_adr_ack_req(self, adr_ack_req: bool) -> Self {
+        self.with_flag(Self::ADR_ACK_REQ, adr_ack_req)
+    }
+    /// Sets the `ACK` flag
+    pub const fn with_ack(self, ack: bool) -> Self {
+        self.with_flag(Self::ACK, ack)
+    }
+    /// Sets the `FPending` (downlink) or `ClassB` (uplink) flag
+    pub const fn with_f_pending(self, f_pending: bool) -> Self {
+        self.with_flag(Self::F_PENDING, f_pending)
+    }
+
+    /// The `ADR` flag
+    pub const fn adr(&self) -> bool {
+        self.flags & Self::ADR != 0
+    }
+    /// The `ADRACKReq` flag
+    pub const fn adr_ack_req(&self) -> bool {
+        self.flags & Self::ADR_ACK_REQ != 0
+    }
+    /// The `ACK` flag
+    pub const fn ack(&self) -> bool {
+        self.flags & Self::ACK != 0
+    }
+    /// The `FPending` (downlink) or `ClassB` (uplink) flag
+    pub const fn f_pending(&self) -> bool {
+        self.flags & Self::F_PENDING != 0
+    }
+    /// The raw flags in bits 7..4
+    pub const fn as_u8(&self) -> u8 {
+        self.flags
+    }
+
+    /// Sets or clears the given flag
+    const fn with_flag(mut self, flag: u8, value: bool) -> Self {
+        match value {
+            true => self.flags |= flag,
+            false => self.flags &= !flag,
+        }
+        self
+    }
+}
+
+/// The frame header of a data message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fhdr<'a> {
+    /// The device address
+    dev_addr: u32,
+    /// The frame control flags
+    f_ctrl: FCtrl,
+    /// The 16 least significant bits of the frame counter
+    f_cnt: u16,
+    /// The frame options
+    f_opts: &'a [u8],
+}
+impl<'a> Fhdr<'a> {
+    /// The length of the fixed fields
+    const FIXED_LEN: usize = 7;
+
+    /// Creates a new frame header
+    ///
+    /// # Frame Options
+    /// The frame options may be at most [`FOPTS_MAX_LEN`] bytes long.
+    pub const fn new(dev_addr: u32, f_ctrl: FCtrl, f_cnt: u16, f_opts: &'a [u8]) -> Result<Self, InvalidArgumentError> {
+        if f_opts.len() > FOPTS_MAX_LEN {
+            return Err(err!(InvalidArgumentError, "Frame options are too long"));
+        }
+        Ok(Self { dev_addr, f_ctrl, f_cnt, f_opts })
+    }
+
+    /// The device address
+    pub const fn dev_addr(&self) -> u32 {
+        self.dev_addr
+    }
+    /// The frame control flags
+    pub const fn f_ctrl(&self) -> FCtrl {
+        self.f_ctrl
+    }
+    /// The 16 least significant bits of the frame counter
+    pub const fn f_cnt(&self) -> u16 {
+        self.f_cnt
+    }
+    /// The frame options, i.e. piggybacked MAC commands (encrypted in LoRaWAN 1.1)
+    pub const fn f_opts(&self) -> &'a [u8] {
+        self.f_opts
+    }
+
+    /// The encoded length
+    const fn encoded_len(&self) -> usize {
+        #[allow(clippy::arithmetic_side_effects, reason = "The frame options are at most 15 bytes")]
+        (Self::FIXED_LEN + self.f_opts.len())
+    }
+}
+
+/// A data message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataPayload<'a> {
+    /// The MAC header
+    mhdr: Mhdr,
+    /// The frame header
+    fhdr: Fhdr<'a>,
+    /// The port and the (encrypted) payload, if any
+    port_payload: Option<(u8, &'a [u8])>,
+    /// The MIC
+    mic: [u8; MIC_LEN],
+}
+impl<'a> DataPayload<'a> {
+    /// Creates a new data message with a zeroed MIC
+    ///
+    /// # Port
+    /// Port `0` carries MAC commands in the payload, and must not be combined with frame options.
+    pub const fn new(
+        mtype: MType,
+        fhdr: Fhdr<'a>,
+        port_payload: Option<(u8, &'a [u8])>,
+    ) -> Result<Self, InvalidArgumentError> {
+        // Validate the message type and the port
+        if !mtype.is_data() {
+            return Err(err!(InvalidArgumentError, "Message type is not a data message"));
+        }
+        if let Some((0, _)) = port_payload {
+            if !fhdr.f_opts.is_empty() {
+                return Err(err!(InvalidArgumentError, "Port 0 must not be combined with frame options"));
+            }
+        }
+        Ok(Self { mhdr: Mhdr::new(mtype), fhdr, port_payload, mic: [0; MIC_LEN] })
+    }
+
+    /// The MAC header
+    pub const fn mhdr(&self) -> Mhdr {
+        self.mhdr
+    }
+    /// The frame header
+    pub const fn fhdr(&self) -> Fhdr<'a> {
+        self.fhdr
+    }
+    /// The port, if any
+    pub const fn f_port(&self) -> Option<u8> {
+        match self.port_payload {
+            Some((port, _)) => Some(port),
+            None => None,
+        }
+    }
+    /// The (encrypted) payload, which is empty if there is no port
+    pub const fn frm_payload(&self) -> &'a [u8] {
+        match self.port_payload {
+            Some((_, payload)) => payload,
+            None => &[],
+        }
+    }
+    /// The MIC
+    pub const fn mic(&self) -> [u8; MIC_LEN] {
+        self.mic
+    }
+
+    /// The encoded length
+    pub const fn encoded_len(&self) -> usize {
+        let port_payload_len = match self.port_payload {
+            Some((_, payload)) => payload.len().saturating_add(1),
+            None => 0,
+        };
+        self.fhdr.encoded_len().saturating_add(port_payload_len).saturating_add(1 + MIC_LEN)
+    }
+    /// Encodes the message with its MIC into `buf` and returns the frame length
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+        let Some(frame) = buf.get_mut(..self.encoded_len()) else {
+            // The message does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "Frame buffer is too small"));
+        };
+
+        // Encode the headers
+        #[allow(clippy::cast_possible_truncation, reason = "The frame options are at most 15 bytes")]
+        let f_opts_len = self.fhdr.f_opts.len() as u8;
+        let [a0, a1, a2, a3] = self.fhdr.dev_addr.to_le_bytes();
+        let [c0, c1] = self.fhdr.f_cnt.to_le_bytes();
+        let header = [self.mhdr.as_u8(), a0, a1, a2, a3, self.fhdr.f_ctrl.as_u8() | f_opts_len, c0, c1];
+        let port = self.port_payload.map(|(port, _)| port);
+        let fields =
+            (header.iter().chain(self.fhdr.f_opts).chain(port.as_ref())).chain(self.frm_payload()).chain(&self.mic);
+        for (byte, field) in frame.iter_mut().zip(fields) {
+            *byte = *field;
+        }
+        Ok(frame.len())
+    }
+
+    /// Decodes a data message from the frame without the MAC header
+    fn parse(mhdr: Mhdr, body: &'a [u8]) -> Result<Self, InvalidMessageError> {
+        // Split the MIC and the fixed header fields
+        let Some((body, mic)) = body.split_last_chunk::<MIC_LEN>() else {
+            return Err(err!(InvalidMessageError, "Data message is too short"));
+        };
+        let Some(([a0, a1, a2, a3, f_ctrl, c0, c1], rest)) = body.split_first_chunk::<{ Fhdr::FIXED_LEN }>() else {
+            return Err(err!(InvalidMessageError, "Data message is too short"));
+        };
+
+        // Split the frame options, the port and the payload
+        let Some((f_opts, rest)) = rest.split_at_checked((f_ctrl & 0x0F) as usize) else {
+            return Err(err!(InvalidMessageError, "Frame options exceed the message"));
+        };
+        let port_payload = rest.split_first().map(|(port, payload)| (*port, payload));
+        if let (Some((0, _)), false) = (port_payload, f_opts.is_empty()) {
+            return Err(err!(InvalidMessageError, "Port 0 must not be combined with frame options"));
+        }
+
+        // Assemble the message
+        let dev_addr = u32::from_le_bytes([*a0, *a1, *a2, *a3]);
+        let fhdr = Fhdr { dev_addr, f_ctrl: FCtrl::new(*f_ctrl), f_cnt: u16::from_le_bytes([*c0, *c1]), f_opts };
+        Ok(Self { mhdr, fhdr, port_payload, mic: *mic })
+    }
+}
+
+/// A join request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinRequest {
+    /// The join EUI (AppEUI in LoRaWAN 1.0)
+    join_eui: u64,
+    /// The device EUI
+    dev_eui: u64,
+    /// The device nonce
+    dev_nonce: u16,
+    /// The MIC
+    mic: [u8; MIC_LEN],
+}
+impl JoinRequest {
+    /// The encoded length
+    pub const LEN: usize = 23;
+
+    /// Creates a new join request with a zeroed MIC
+    pub const fn new(join_eui: u64, dev_eui: u64, dev_nonce: u16) -> Self {
+        Self { join_eui, dev_eui, dev_nonce, mic: [0; MIC_LEN] }
+    }
+
+    /// The join EUI (AppEUI in LoRaWAN 1.0)
+    pub const fn join_eui(&self) -> u64 {
+        self.join_eui
+    }
+    /// The device EUI
+    pub const fn dev_eui(&self) -> u64 {
+        self.dev_eui
+    }
+    /// The device nonce
+    pub const fn dev_nonce(&self) -> u16 {
+        self.dev_nonce
+    }
+    /// The MIC
+    pub const fn mic(&self) -> [u8; MIC_LEN] {
+        self.mic
+    }
+
+    /// Encodes the join request with its MIC into `buf` and returns the frame length
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+        let Some(frame) = buf.first_chunk_mut::<{ Self::LEN }>() else {
+            // The message does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "Frame buffer is too small"));
+        };
+
+        // Encode the fields
+        let mhdr = Mhdr::new(MType::JoinRequest).as_u8();
+        let fields = (core::iter::once(mhdr).chain(self.join_eui.to_le_bytes()))
+            .chain(self.dev_eui.to_le_bytes())
+            .chain(self.dev_nonce.to_le_bytes())
+            .chain(self.mic);
+        for (byte, field) in frame.iter_mut().zip(fields) {
+            *byte = field;
+        }
+        Ok(Self::LEN)
+    }
+
+    /// Decodes a join request from the frame without the MAC header
+    fn parse(body: &[u8]) -> Result<Self, InvalidMessageError> {
+        let Ok([j0, j1, j2, j3, j4, j5, j6, j7, d0, d1, d2, d3, d4, d5, d6, d7, n0, n1, m0, m1, m2, m3]) =
+            <[u8; 22]>::try_from(body)
+        else {
+            return Err(err!(InvalidMessageError, "Invalid join request length"));
+        };
+        Ok(Self {
+            join_eui: u64::from_le_bytes([j0, j1, j2, j3, j4, j5, j6, j7]),
+            dev_eui: u64::from_le_bytes([d0, d1, d2, d3, d4, d5, d6, d7]),
+            dev_nonce: u16::from_le_bytes([n0, n1]),
+            mic: [m0, m1, m2, m3],
+        })
+    }
+}
+
+/// A decoded PHY payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhyPayload<'a> {
+    /// A join request
+    JoinRequest(JoinRequest),
+    /// A join accept; the payload (including the MIC) is encrypted, and is 16 or 32 bytes long
+    JoinAccept(&'a [u8]),
+    /// A data message
+    Data(DataPayload<'a>),
+    /// A rejoin request; the payload is passed verbatim
+    RejoinRequest(&'a [u8]),
+    /// A proprietary message; the payload is passed verbatim
+    Proprietary(&'a [u8]),
+}
+impl<'a> PhyPayload<'a> {
+    /// Decodes a PHY payload
+    pub fn parse(frame: &'a [u8]) -> Result<Self, InvalidMessageError> {
+        let Some((mhdr, body)) = frame.split_first() else {
+            return Err(err!(InvalidMessageError, "Empty frame"));
+        };
+
+        // Decode the body according to the message type
+        let mhdr = Mhdr::parse(*mhdr)?;
+        match mhdr.mtype() {
+            MType::JoinRequest => Ok(Self::JoinRequest(JoinRequest::parse(body)?)),
+            MType::JoinAccept if matches!(body.len(), 16 | 32) => Ok(Self::JoinAccept(body)),
+            MType::JoinAccept => Err(err!(InvalidMessageError, "Invalid join accept length")),
+            MType::RejoinRequest => Ok(Self::RejoinRequest(body)),
+            MType::Proprietary => Ok(Self::Proprietary(body)),
+            _ => Ok(Self::Data(DataPayload::parse(mhdr, body)?)),
+        }
+    }
+
+    /// The message type
+    pub const fn mtype(&self) -> MType {
+        match self {
+            Self::JoinRequest(_) => MType::JoinRequest,
+            Self::JoinAccept(_) => MType::JoinAccept,
+            Self::Data(data) => data.mhdr.mtype,
+            Self::RejoinRequest(_) => MType::RejoinRequest,
+            Self::Proprietary(_) => MType::Proprietary,
+        }
+    }
+}
+
+/// The part of an encoded frame that is covered by the MIC, i.e. everything but the trailing MIC
+pub fn mic_input(frame: &[u8]) -> Option<&[u8]> {
+    frame.split_last_chunk::<MIC_LEN>().map(|(input, _)| input)
+}
+/// Overwrites the trailing MIC of an encoded frame
+pub fn set_mic(frame: &mut [u8], mic: [u8; MIC_LEN]) -> Result<(), BufferTooSmallError> {
+    let Some(trailer) = frame.last_chunk_mut::<MIC_LEN>() else {
+        // The frame is too short to contain a MIC
+        return Err(err!(BufferTooSmallError, "Frame is too short to contain a MIC"));
+    };
+    *trailer = mic;
+    Ok(())
+}
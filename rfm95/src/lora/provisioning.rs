@@ -0,0 +1,143 @@
+//! Per-device link key derivation and provisioning
+//!
+//! # Key Hierarchy
+//! Instead of shipping a single shared key in every firmware image, each device gets its own link key, which is derived
+//! from a fleet master key and the device ID via HKDF-SHA256 (RFC 5869). The master key only lives on the provisioning
+//! station and the backend, which can re-derive the key of any device from its ID; a compromised device only exposes
+//! its own key.
+//!
+//! # Usage
+//! The provisioning station derives the device key via [`Provisioner::device_key`] and writes it into the secure
+//! storage of the device via [`Provisioner::provision`]. The device loads its key via [`load_key`], e.g. to create a
+//! [`crate::lora::secure_link::ChaCha20Poly1305`] cipher.
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+/// The length of a link key
+pub const KEY_LEN: usize = 32;
+
+/// A secure storage for the device link key, e.g. a protected flash page or a secure element
+pub trait KeyStorage {
+    /// The storage error
+    type Error;
+
+    /// Loads the link key, or `None` if the device has not been provisioned yet
+    fn load_key(&mut self) -> Result<Option<[u8; KEY_LEN]>, Self::Error>;
+    /// Stores the link key
+    fn store_key(&mut self, key: &[u8; KEY_LEN]) -> Result<(), Self::Error>;
+}
+
+/// A key derivation for a fleet of devices
+///
+/// # Domain Separation
+/// The derivation is bound to a label, so keys for different purposes (e.g. uplink and downlink, or different
+/// applications) can be derived from the same master key without colliding.
+#[derive(Clone)]
+pub struct Provisioner {
+    /// The HKDF state with the extracted master key
+    hkdf: Hkdf<Sha256>,
+}
+impl Provisioner {
+    /// The HKDF salt
+    const SALT: &'static [u8] = b"embedded-lora-rfm95 provisioning";
+    /// The default label for link keys
+    pub const LINK_KEY_LABEL: &'static [u8] = b"link key";
+
+    /// Creates a new key derivation from the given master key
+    pub fn new(master_key: &[u8]) -> Self {
+        Self { hkdf: Hkdf::new(Some(Self::SALT), master_key) }
+    }
+
+    /// Derives the link key of the given device
+    pub fn device_key(&self, device_id: &[u8]) -> [u8; KEY_LEN] {
+        self.derive(Self::LINK_KEY_LABEL, device_id)
+    }
+    /// Derives the key for the given label and device
+    pub fn derive(&self, label: &[u8], device_id: &[u8]) -> [u8; KEY_LEN] {
+        // Length-prefix the label, so label and device ID cannot be shifted into each other
+        let label_len = u8::try_from(label.len()).unwrap_or(u8::MAX);
+        let label = label.get(..label_len as usize).unwrap_or_default();
+
+        // Expansion can only fail for output lengths above 8160 bytes
+        let mut key = [0; KEY_LEN];
+        let _ = self.hkdf.expand_multi_info(&[&[label_len], label, device_id], &mut key);
+        key
+    }
+
+    /// Derives the link key of the given device and writes it into the storage of the device
+    pub fn provision<S>(&self, storage: &mut S, device_id: &[u8]) -> Result<(), S::Error>
+    where
+        S: KeyStorage,
+    {
+        storage.store_key(&self.device_key(device_id))
+    }
+}
+impl core::fmt::Debug for Provisioner {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Do not leak the master key
+        f.debug_struct("Provisioner").finish_non_exhaustive()
+    }
+}
+
+/// Loads the link key of this device, or `None` if the device has not been provisioned yet
+pub fn load_key<S>(storage: &mut S) -> Result<Option<[u8; KEY_LEN]>, S::Error>
+where
+    S: KeyStorage,
+{
+    storage.load_key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A master key of the bytes `0x00..=0x1f`
+    const MASTER_KEY: [u8; 32] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, //
+        0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, //
+    ];
+    /// A device ID
+    const DEVICE_ID: [u8; 8] = [0x70, 0xb3, 0xd5, 0x7e, 0xd0, 0x00, 0x12, 0x34];
+
+    #[test]
+    fn hkdf_rfc5869_case1() {
+        // Pins the HKDF-SHA256 primitive the derivation is built on
+        let ikm = [0x0b; 22];
+        let salt = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c];
+        let info = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+        let mut okm = [0; 42];
+        assert!(Hkdf::<Sha256>::new(Some(&salt), &ikm).expand(&info, &mut okm).is_ok());
+        assert_eq!(
+            okm,
+            [
+                0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36, 0x2f, 0x2a, //
+                0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56, 0xec, 0xc4, 0xc5, 0xbf, //
+                0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65, //
+            ]
+        );
+    }
+
+    #[test]
+    fn device_key_vector() {
+        // HKDF-SHA256 with the crate salt and the info `0x08 || "link key" || device ID`
+        let provisioner = Provisioner::new(&MASTER_KEY);
+        assert_eq!(
+            provisioner.device_key(&DEVICE_ID),
+            [
+                0xef, 0xf7, 0x13, 0xbb, 0x37, 0x1a, 0x75, 0xe0, 0xd1, 0xd1, 0x89, 0xe5, 0x93, 0x08, 0xfe, 0x98, //
+                0xf1, 0xe7, 0xfa, 0xcd, 0xeb, 0x4a, 0xf7, 0x1f, 0xb4, 0xab, 0xe6, 0x60, 0xf0, 0xb5, 0xb0, 0x3d, //
+            ]
+        );
+    }
+
+    #[test]
+    fn derive_separates_domains() {
+        let provisioner = Provisioner::new(&MASTER_KEY);
+        assert_ne!(provisioner.derive(b"uplink", &DEVICE_ID), provisioner.derive(b"downlink", &DEVICE_ID));
+        assert_ne!(provisioner.device_key(b"device a"), provisioner.device_key(b"device b"));
+
+        // The label cannot be shifted into the device ID
+        assert_ne!(provisioner.derive(b"ab", b"c"), provisioner.derive(b"a", b"bc"));
+    }
+}
@@ -0,0 +1,112 @@
+//! RSSI-based distance estimation
+//!
+//! # Accuracy
+//! RSSI-based ranging is inherently rough: multipath propagation, shadowing by obstacles and antenna orientation easily
+//! cause deviations of several dB, which translates to deviations of tens of percent in the estimated distance. The
+//! estimates are thus only useful for coarse proximity detection, not for positioning.
+
+use crate::lora::math;
+
+/// A log-distance path loss model
+///
+/// # Model
+/// The expected signal strength at distance `d` (in meters) is `P(d) = P(1m) + G - 10 * n * log10(d)`, where `P(1m)`
+/// is the reference signal strength at one meter, `G` are the antenna gains relative to the reference measurement and
+/// `n` is the path loss exponent (`2.0` for free space, `2.7..3.5` for urban areas, `4.0..6.0` for obstructed indoor
+/// environments).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathLossModel {
+    /// The path loss exponent
+    exponent: f32,
+    /// The reference signal strength at one meter in dBm
+    reference_rssi: i16,
+    /// The TX antenna gain relative to the reference measurement in dB
+    tx_antenna_gain: i8,
+    /// The RX antenna gain relative to the reference measurement in dB
+    rx_antenna_gain: i8,
+    /// The standard deviation of the signal strength due to shadowing and multipath in dB
+    shadowing: u8,
+}
+impl PathLossModel {
+    /// The default standard deviation of the signal strength due to shadowing and multipath in dB
+    pub const DEFAULT_SHADOWING: u8 = 6;
+
+    /// Creates a new path loss model from the given path loss exponent and reference signal strength at one meter
+    pub const fn new(exponent: f32, reference_rssi: i16) -> Self {
+        Self { exponent, reference_rssi, tx_antenna_gain: 0, rx_antenna_gain: 0, shadowing: Self::DEFAULT_SHADOWING }
+    }
+    /// Sets the TX and RX antenna gains relative to the reference measurement in dB
+    pub const fn with_antenna_gains(mut self, tx_antenna_gain: i8, rx_antenna_gain: i8) -> Self {
+        self.tx_antenna_gain = tx_antenna_gain;
+        self.rx_antenna_gain = rx_antenna_gain;
+        self
+    }
+    /// Sets the standard deviation of the signal strength due to shadowing and multipath in dB, which determines the
+    /// uncertainty bounds of the estimate
+    pub const fn with_shadowing(mut self, shadowing: u8) -> Self {
+        self.shadowing = shadowing;
+        self
+    }
+
+    /// The path loss exponent
+    pub const fn exponent(&self) -> f32 {
+        self.exponent
+    }
+    /// The reference signal strength at one meter in dBm
+    pub const fn reference_rssi(&self) -> i16 {
+        self.reference_rssi
+    }
+    /// The TX antenna gain relative to the reference measurement in dB
+    pub const fn tx_antenna_gain(&self) -> i8 {
+        self.tx_antenna_gain
+    }
+    /// The RX antenna gain relative to the reference measurement in dB
+    pub const fn rx_antenna_gain(&self) -> i8 {
+        self.rx_antenna_gain
+    }
+    /// The standard deviation of the signal strength due to shadowing and multipath in dB
+    pub const fn shadowing(&self) -> u8 {
+        self.shadowing
+    }
+
+    /// Estimates the distance for the given packet RSSI and SNR
+    ///
+    /// # Signal Strength
+    /// If the SNR is negative, the packet was received below the noise floor and the RSSI reflects the noise rather
+    /// than the signal. Like [`crate::rfm95::Rfm95Driver::get_packet_strength`], the signal strength is thus computed
+    /// as `rssi + min(snr, 0)`.
+    pub fn estimate(&self, rssi: i16, snr: i8) -> DistanceEstimate {
+        // Compute the signal strength and the path loss relative to the reference distance
+        let strength = rssi.saturating_add(snr.min(0) as i16);
+        let reference = (self.reference_rssi)
+            .saturating_add(self.tx_antenna_gain as i16)
+            .saturating_add(self.rx_antenna_gain as i16);
+        let path_loss = reference.saturating_sub(strength) as f32;
+
+        // Compute the distance and the bounds; a weaker signal means a larger distance
+        let shadowing = self.shadowing as f32;
+        DistanceEstimate {
+            distance: self.distance(path_loss),
+            min: self.distance(path_loss - shadowing),
+            max: self.distance(path_loss + shadowing),
+        }
+    }
+
+    /// Computes the distance in meters for the given path loss relative to the reference distance
+    fn distance(&self, path_loss: f32) -> f32 {
+        // `d = 10^(path_loss / (10 * n))`
+        let exponent = path_loss / (10.0 * self.exponent);
+        math::pow10(exponent)
+    }
+}
+
+/// A distance estimate with uncertainty bounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceEstimate {
+    /// The estimated distance in meters
+    pub distance: f32,
+    /// The lower bound of the estimated distance in meters (one standard deviation)
+    pub min: f32,
+    /// The upper bound of the estimated distance in meters (one standard deviation)
+    pub max: f32,
+}
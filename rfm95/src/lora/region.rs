@@ -0,0 +1,89 @@
+//! LoRaWAN regions and compile-time region lockout
+
+use crate::lora::types::Frequency;
+
+/// A LoRaWAN region as defined by the LoRaWAN Regional Parameters specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// EU 863-870 MHz ISM band
+    Eu868,
+    /// EU 433 MHz ISM band
+    Eu433,
+    /// US 902-928 MHz ISM band
+    Us915,
+    /// Australia 915-928 MHz ISM band
+    Au915,
+    /// Asia 923 MHz ISM band
+    As923,
+    /// India 865-867 MHz ISM band
+    In865,
+    /// South Korea 920-923 MHz ISM band
+    Kr920,
+}
+impl Region {
+    /// The lowest and highest allowed frequency in the region
+    pub const fn frequency_range(self) -> (Frequency, Frequency) {
+        match self {
+            Self::Eu868 => (Frequency::hz(863_000_000), Frequency::hz(870_000_000)),
+            Self::Eu433 => (Frequency::hz(433_050_000), Frequency::hz(434_790_000)),
+            Self::Us915 => (Frequency::hz(902_000_000), Frequency::hz(928_000_000)),
+            Self::Au915 => (Frequency::hz(915_000_000), Frequency::hz(928_000_000)),
+            Self::As923 => (Frequency::hz(915_000_000), Frequency::hz(928_000_000)),
+            Self::In865 => (Frequency::hz(865_000_000), Frequency::hz(867_000_000)),
+            Self::Kr920 => (Frequency::hz(920_900_000), Frequency::hz(923_300_000)),
+        }
+    }
+    /// Whether the given frequency is within the region
+    pub const fn contains(self, frequency: Frequency) -> bool {
+        let (min, max) = self.frequency_range();
+        frequency.as_u32() >= min.as_u32() && frequency.as_u32() <= max.as_u32()
+    }
+
+    /// The default maximum TX power (EIRP) in dBm within the region
+    ///
+    /// # Note
+    /// The value is the default limit from the LoRaWAN Regional Parameters specification; local regulations may allow
+    /// higher limits for specific sub-bands, or mandate lower limits.
+    pub const fn max_tx_power(self) -> i8 {
+        match self {
+            Self::Eu868 => 16,
+            Self::Eu433 => 12,
+            Self::Us915 => 30,
+            Self::Au915 => 30,
+            Self::As923 => 16,
+            Self::In865 => 30,
+            Self::Kr920 => 14,
+        }
+    }
+}
+
+/// The regions the firmware is locked to via the `region-*` cargo features
+///
+/// # Region Lockout
+/// If this list is not empty, the driver rejects frequencies outside of the listed regions, and limits the TX power to
+/// the limit of the region the configured frequency is in. If the list is empty, no restrictions apply.
+pub const ALLOWED_REGIONS: &[Region] = &[
+    #[cfg(feature = "region-eu868")]
+    Region::Eu868,
+    #[cfg(feature = "region-eu433")]
+    Region::Eu433,
+    #[cfg(feature = "region-us915")]
+    Region::Us915,
+    #[cfg(feature = "region-au915")]
+    Region::Au915,
+    #[cfg(feature = "region-as923")]
+    Region::As923,
+    #[cfg(feature = "region-in865")]
+    Region::In865,
+    #[cfg(feature = "region-kr920")]
+    Region::Kr920,
+];
+
+/// The allowed region for the given frequency, or `None` if the frequency is not within the allowed regions
+///
+/// # Region Lockout
+/// If multiple allowed regions contain the frequency, the first matching region of [`ALLOWED_REGIONS`] is returned.
+/// This function always returns `None` if the region lockout is disabled.
+pub fn allowed_region(frequency: Frequency) -> Option<Region> {
+    ALLOWED_REGIONS.iter().copied().find(|region| region.contains(frequency))
+}
@@ -0,0 +1,106 @@
+//! Regulatory TX power limits
+
+use crate::err;
+use crate::error::InvalidArgumentError;
+use crate::lora::region::Region;
+use crate::lora::types::Frequency;
+
+/// The action to take if the TX power exceeds the regulatory limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitAction {
+    /// Reject the transmission
+    Reject,
+    /// Reduce the TX power to the regulatory limit
+    Clamp,
+}
+
+/// A regulatory policy that limits the effective isotropic radiated power (EIRP)
+///
+/// # EIRP
+/// The EIRP is the conducted TX power plus the antenna gain, so high-gain antennas require a lower TX power to stay
+/// within the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Regulatory {
+    /// The region to apply the limits of
+    region: Region,
+    /// The antenna gain in dBi
+    antenna_gain: i8,
+    /// The action to take if the TX power exceeds the limit
+    action: LimitAction,
+}
+impl Regulatory {
+    /// Creates a new regulatory policy for the given region
+    pub const fn new(region: Region, action: LimitAction) -> Self {
+        Self { region, antenna_gain: 0, action }
+    }
+    /// Sets the antenna gain in dBi
+    pub const fn with_antenna_gain(mut self, antenna_gain: i8) -> Self {
+        self.antenna_gain = antenna_gain;
+        self
+    }
+
+    /// The region to apply the limits of
+    pub const fn region(&self) -> Region {
+        self.region
+    }
+    /// The antenna gain in dBi
+    pub const fn antenna_gain(&self) -> i8 {
+        self.antenna_gain
+    }
+    /// The action to take if the TX power exceeds the limit
+    pub const fn action(&self) -> LimitAction {
+        self.action
+    }
+
+    /// The maximum EIRP in dBm for the given frequency, or `None` if the frequency is outside of the region
+    ///
+    /// # Sub-Bands
+    /// For EU868, the `869.4..=869.65 MHz` sub-band allows `27 dBm`; all other frequencies use the regional default of
+    /// [`Region::max_tx_power`].
+    pub const fn max_eirp(&self, frequency: Frequency) -> Option<i8> {
+        /// The EU868 high-power sub-band
+        const EU868_HIGH_POWER: (u32, u32, i8) = (869_400_000, 869_650_000, 27);
+
+        // Validate the frequency
+        if !self.region.contains(frequency) {
+            return None;
+        }
+
+        // Get the limit for the frequency
+        let (high_power_min, high_power_max, high_power) = EU868_HIGH_POWER;
+        match self.region {
+            Region::Eu868 if frequency.as_u32() >= high_power_min && frequency.as_u32() <= high_power_max => {
+                Some(high_power)
+            }
+            region => Some(region.max_tx_power()),
+        }
+    }
+    /// The maximum conducted TX power in dBm for the given frequency, or `None` if the frequency is outside of the
+    /// region
+    pub const fn max_tx_power(&self, frequency: Frequency) -> Option<i8> {
+        match self.max_eirp(frequency) {
+            Some(max_eirp) => Some(max_eirp.saturating_sub(self.antenna_gain)),
+            None => None,
+        }
+    }
+
+    /// Checks the given conducted TX power in dBm for the given frequency, and returns the TX power to use
+    ///
+    /// # Limit Action
+    /// If the TX power exceeds the limit, an error is returned for [`LimitAction::Reject`], or the limit is returned
+    /// for [`LimitAction::Clamp`]. Frequencies outside of the region are always rejected.
+    pub const fn check(&self, frequency: Frequency, tx_power: i8) -> Result<i8, InvalidArgumentError> {
+        // Get the limit
+        let Some(max_tx_power) = self.max_tx_power(frequency) else {
+            // The frequency is outside of the region
+            return Err(err!(InvalidArgumentError, "Frequency is outside of the regulatory region"));
+        };
+
+        // Apply the limit
+        match self.action {
+            _ if tx_power <= max_tx_power => Ok(tx_power),
+            LimitAction::Clamp => Ok(max_tx_power),
+            LimitAction::Reject => Err(err!(InvalidArgumentError, "TX power exceeds the regulatory limit")),
+        }
+    }
+}
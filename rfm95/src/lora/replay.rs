@@ -0,0 +1,240 @@
+//! Replay protection with per-peer monotonic frame counters
+//!
+//! # Usage
+//! The sender numbers its frames via [`TxCounter`] and includes the counter in each frame; the receiver checks the
+//! counter via [`ReplayGuard::accept`] before acting on the frame. Both persist their counters via a [`CounterStore`],
+//! so a reboot does not reopen the replay window.
+//!
+//! # Important
+//! Replay protection is only effective if the counter is authenticated together with the payload (e.g. as part of the
+//! nonce or the associated data of an AEAD cipher); otherwise, an attacker can simply replace the counter of a recorded
+//! frame. Only accept a frame after it has been authenticated, so forged frames cannot advance the counter.
+
+/// A persistent store for frame counters, e.g. in EEPROM or flash
+///
+/// # Wear
+/// The counter of a peer is stored every time it advances. For flash with limited write endurance, implementations may
+/// store the counter in increments (e.g. round up to the next multiple of 16 and only write when that changes); this
+/// skips some counters after a reboot, but never accepts a counter twice.
+pub trait CounterStore {
+    /// The store error
+    type Error;
+
+    /// Loads the counter of the given peer, or `None` if no counter has been stored yet
+    fn load(&mut self, peer: u32) -> Result<Option<u32>, Self::Error>;
+    /// Stores the counter of the given peer
+    fn store(&mut self, peer: u32, counter: u32) -> Result<(), Self::Error>;
+}
+
+/// A volatile counter store, which does not persist counters across reboots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoCounterStore;
+impl CounterStore for NoCounterStore {
+    type Error = core::convert::Infallible;
+
+    fn load(&mut self, _peer: u32) -> Result<Option<u32>, Self::Error> {
+        Ok(None)
+    }
+    fn store(&mut self, _peer: u32, _counter: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The replay state of a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PeerState {
+    /// The peer address
+    peer: u32,
+    /// The highest accepted counter
+    highest: u32,
+    /// The accepted counters below the highest counter; bit `n` marks `highest - n - 1`
+    seen: u32,
+}
+
+/// A replay guard that accepts every counter of a peer at most once
+///
+/// # Acceptance Window
+/// Counters up to 32 below the highest accepted counter are accepted once each, so slightly reordered frames (e.g. via
+/// a repeater) are not dropped; older counters are rejected. Counters that jump ahead by more than the maximum gap
+/// (see [`Self::with_max_gap`]) are rejected as well, so a single corrupted counter cannot lock out the peer.
+///
+/// # Peers
+/// The guard caches the state of up to `PEERS` peers; if more peers are active, the oldest cache entry is evicted and
+/// reloaded from the store later, with the reorder window of the evicted peer closed. The counter of an unknown peer
+/// without a stored counter is accepted as the first counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayGuard<Store, const PEERS: usize> {
+    /// The counter store
+    store: Store,
+    /// The cached peer states
+    peers: [Option<PeerState>; PEERS],
+    /// The cache slot to evict next
+    evict: usize,
+    /// The maximum counter gap
+    max_gap: u32,
+}
+impl<Store, const PEERS: usize> ReplayGuard<Store, PEERS>
+where
+    Store: CounterStore,
+{
+    /// The default maximum counter gap (like LoRaWAN's `MAX_FCNT_GAP`)
+    pub const DEFAULT_MAX_GAP: u32 = 16_384;
+    /// The width of the reorder window
+    const WINDOW: u32 = u32::BITS;
+
+    /// Creates a new replay guard with the given counter store
+    pub const fn new(store: Store) -> Self {
+        Self { store, peers: [None; PEERS], evict: 0, max_gap: Self::DEFAULT_MAX_GAP }
+    }
+    /// Sets the maximum amount by which a counter may jump ahead of the highest accepted counter
+    pub const fn with_max_gap(mut self, max_gap: u32) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// The maximum counter gap
+    pub const fn max_gap(&self) -> u32 {
+        self.max_gap
+    }
+    /// The counter store
+    pub const fn store(&self) -> &Store {
+        &self.store
+    }
+    /// The counter store
+    pub fn store_mut(&mut self) -> &mut Store {
+        &mut self.store
+    }
+
+    /// Checks whether the given counter of the given peer is fresh, and if so, records it and returns `true`
+    pub fn accept(&mut self, peer: u32, counter: u32) -> Result<bool, Store::Error> {
+        // Get the peer state
+        let Some(mut state) = self.state(peer)? else {
+            // This is the first frame of the peer, so older counters are rejected
+            self.update(PeerState { peer, highest: counter, seen: u32::MAX })?;
+            return Ok(true);
+        };
+
+        // Check the counter against the window
+        match counter.checked_sub(state.highest) {
+            // The counter is ahead of the highest counter
+            Some(gap @ 1..) if gap <= self.max_gap => {
+                // Shift the window and mark the previous highest counter as seen
+                let seen = state.seen.wrapping_shl(1) | 1;
+                state.seen = seen.checked_shl(gap.wrapping_sub(1)).unwrap_or_default();
+                state.highest = counter;
+                self.update(state)?;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            // The counter is within the reorder window
+            None => {
+                let age = state.highest.wrapping_sub(counter).wrapping_sub(1);
+                let mask = 1u32.checked_shl(age).unwrap_or_default();
+                if age >= Self::WINDOW || state.seen & mask != 0 {
+                    return Ok(false);
+                }
+                state.seen |= mask;
+                self.cache(state);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Gets the cached state of the given peer, or loads it from the store
+    fn state(&mut self, peer: u32) -> Result<Option<PeerState>, Store::Error> {
+        let cached = self.peers.iter().flatten().find(|state| state.peer == peer);
+        match cached {
+            Some(state) => Ok(Some(*state)),
+            None => {
+                // A reloaded peer has no reorder window
+                let highest = self.store.load(peer)?;
+                Ok(highest.map(|highest| PeerState { peer, highest, seen: u32::MAX }))
+            }
+        }
+    }
+    /// Stores the highest counter of the given peer and caches its state
+    fn update(&mut self, state: PeerState) -> Result<(), Store::Error> {
+        self.store.store(state.peer, state.highest)?;
+        self.cache(state);
+        Ok(())
+    }
+    /// Caches the state of a peer, evicting the oldest entry if necessary
+    fn cache(&mut self, state: PeerState) {
+        // Update the existing entry
+        let existing = self.peers.iter_mut().flatten().find(|cached| cached.peer == state.peer);
+        if let Some(cached) = existing {
+            *cached = state;
+            return;
+        }
+
+        // Use a free slot, or evict the oldest entry
+        let index = match self.peers.iter().position(Option::is_none) {
+            Some(index) => index,
+            None => {
+                let index = self.evict;
+                self.evict = self.evict.wrapping_add(1).checked_rem(PEERS).unwrap_or_default();
+                index
+            }
+        };
+        if let Some(slot) = self.peers.get_mut(index) {
+            *slot = Some(state);
+        }
+    }
+}
+
+/// A monotonic frame counter for outgoing frames
+///
+/// # Persistence
+/// The counter is loaded from the store on first use and stored after every increment, so a reboot never reuses a
+/// counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxCounter<Store> {
+    /// The counter store
+    store: Store,
+    /// The store key of the counter, e.g. the own address
+    key: u32,
+    /// The last used counter, if loaded
+    last: Option<u32>,
+}
+impl<Store> TxCounter<Store>
+where
+    Store: CounterStore,
+{
+    /// Creates a new counter that is persisted under the given key
+    pub const fn new(store: Store, key: u32) -> Self {
+        Self { store, key, last: None }
+    }
+
+    /// The store key of the counter
+    pub const fn key(&self) -> u32 {
+        self.key
+    }
+    /// The counter store
+    pub const fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Gets the next counter, or `None` if the counter space is exhausted
+    ///
+    /// # Exhaustion
+    /// Once the counter space is exhausted, the keys must be rotated, as counters must never be reused.
+    pub fn next_counter(&mut self) -> Result<Option<u32>, Store::Error> {
+        // Load the last counter and compute the next one
+        let next = match self.last {
+            Some(last) => last.checked_add(1),
+            None => match self.store.load(self.key)? {
+                Some(last) => last.checked_add(1),
+                None => Some(0),
+            },
+        };
+
+        // Store the counter before it is used
+        let Some(next) = next else {
+            // The counter space is exhausted
+            return Ok(None);
+        };
+        self.store.store(self.key, next)?;
+        self.last = Some(next);
+        Ok(Some(next))
+    }
+}
@@ -0,0 +1,147 @@
+//! Authenticated encryption of link frames
+//!
+//! # Frames
+//! A secure frame consists of an 8-byte header with the 4-byte little-endian sender address and the 4-byte
+//! little-endian frame counter, followed by the ciphertext and the 16-byte authentication tag. The header is
+//! authenticated as associated data, and the nonce is derived from the sender address and the counter, so a nonce is
+//! never reused as long as every sender uses a unique address and never reuses a counter (see
+//! [`crate::lora::replay::TxCounter`]).
+//!
+//! # Usage
+//! The sender writes frames via [`seal`]; the receiver verifies and decrypts them via [`open`], and checks the returned
+//! counter via [`crate::lora::replay::ReplayGuard::accept`] before acting on the frame.
+//!
+//! # Ciphers
+//! The AEAD cipher is pluggable via [`LinkCipher`]. The `chacha20poly1305`-feature provides [`ChaCha20Poly1305`], which
+//! is fast in software and thus well suited for microcontrollers without AES acceleration (e.g. Cortex-M0/M0+).
+
+use crate::err;
+use crate::error::BufferTooSmallError;
+
+/// The length of the frame header
+pub const HEADER_LEN: usize = 8;
+/// The length of the authentication tag
+pub const TAG_LEN: usize = 16;
+/// The total frame overhead
+pub const OVERHEAD: usize = HEADER_LEN + TAG_LEN;
+
+/// An AEAD cipher with a 96-bit nonce and a 128-bit tag
+pub trait LinkCipher {
+    /// Encrypts `buf` in place and returns the authentication tag over `aad` and the ciphertext
+    fn encrypt(&self, nonce: &[u8; 12], aad: &[u8], buf: &mut [u8]) -> [u8; TAG_LEN];
+    /// Verifies the authentication tag over `aad` and the ciphertext, and if it is valid, decrypts `buf` in place and
+    /// returns `true`
+    ///
+    /// # Important
+    /// If the tag is invalid, `buf` must be left unmodified or be overwritten; it must never contain partial plaintext.
+    fn decrypt(&self, nonce: &[u8; 12], aad: &[u8], buf: &mut [u8], tag: &[u8; TAG_LEN]) -> bool;
+}
+
+/// The ChaCha20-Poly1305 AEAD cipher (RFC 8439) with a 256-bit key
+#[cfg(feature = "chacha20poly1305")]
+#[derive(Clone)]
+pub struct ChaCha20Poly1305 {
+    /// The cipher instance
+    cipher: chacha20poly1305::ChaCha20Poly1305,
+}
+#[cfg(feature = "chacha20poly1305")]
+impl ChaCha20Poly1305 {
+    /// Creates a new cipher with the given key
+    pub fn new(key: &[u8; 32]) -> Self {
+        use chacha20poly1305::KeyInit;
+        Self { cipher: chacha20poly1305::ChaCha20Poly1305::new(key.into()) }
+    }
+}
+#[cfg(feature = "chacha20poly1305")]
+impl LinkCipher for ChaCha20Poly1305 {
+    fn encrypt(&self, nonce: &[u8; 12], aad: &[u8], buf: &mut [u8]) -> [u8; TAG_LEN] {
+        use chacha20poly1305::AeadInPlace;
+        // Encryption can only fail for buffers larger than 256 GiB
+        let tag = self.cipher.encrypt_in_place_detached(nonce.into(), aad, buf);
+        tag.map(Into::into).unwrap_or_default()
+    }
+    fn decrypt(&self, nonce: &[u8; 12], aad: &[u8], buf: &mut [u8], tag: &[u8; TAG_LEN]) -> bool {
+        use chacha20poly1305::AeadInPlace;
+        self.cipher.decrypt_in_place_detached(nonce.into(), aad, buf, tag.into()).is_ok()
+    }
+}
+#[cfg(feature = "chacha20poly1305")]
+impl core::fmt::Debug for ChaCha20Poly1305 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Do not leak the key
+        f.debug_struct("ChaCha20Poly1305").finish_non_exhaustive()
+    }
+}
+
+/// Encrypts the payload and writes the secure frame into `frame`, and returns the frame length
+///
+/// # Important
+/// Every sender must use a unique address, and must never reuse a counter with the same key.
+pub fn seal<C>(
+    cipher: &C,
+    sender: u32,
+    counter: u32,
+    payload: &[u8],
+    frame: &mut [u8],
+) -> Result<usize, BufferTooSmallError>
+where
+    C: LinkCipher,
+{
+    // Split the frame
+    let len = payload.len().saturating_add(OVERHEAD);
+    let Some(frame) = frame.get_mut(..len) else {
+        // The payload and the overhead do not fit into the frame
+        return Err(err!(BufferTooSmallError, "Frame buffer is too small"));
+    };
+    let (header, rest) = frame.split_at_mut(HEADER_LEN);
+    let (body, trailer) = rest.split_at_mut(payload.len());
+
+    // Write the header and encrypt the payload
+    let nonce = nonce(sender, counter);
+    header.copy_from_slice(nonce.get(..HEADER_LEN).unwrap_or_default());
+    body.copy_from_slice(payload);
+    let tag = cipher.encrypt(&nonce, header, body);
+    trailer.copy_from_slice(&tag);
+    Ok(len)
+}
+/// Verifies and decrypts a secure frame, writes the payload into `payload`, and returns the sender address, the counter
+/// and the payload length, or `None` if the frame is invalid
+///
+/// # Replay Protection
+/// A valid frame can still be a replay; check the counter via [`crate::lora::replay::ReplayGuard::accept`].
+pub fn open<C>(cipher: &C, frame: &[u8], payload: &mut [u8]) -> Result<Option<(u32, u32, usize)>, BufferTooSmallError>
+where
+    C: LinkCipher,
+{
+    // Split the frame
+    let Some((header, rest)) = frame.split_first_chunk::<HEADER_LEN>() else {
+        // The frame is too short
+        return Ok(None);
+    };
+    let Some((body, tag)) = rest.split_last_chunk::<TAG_LEN>() else {
+        // The frame is too short
+        return Ok(None);
+    };
+    let Some(payload) = payload.get_mut(..body.len()) else {
+        // The payload does not fit into the buffer
+        return Err(err!(BufferTooSmallError, "Payload buffer is too small"));
+    };
+
+    // Decode the header and decrypt the payload
+    let [s0, s1, s2, s3, c0, c1, c2, c3] = *header;
+    let (sender, counter) = (u32::from_le_bytes([s0, s1, s2, s3]), u32::from_le_bytes([c0, c1, c2, c3]));
+    payload.copy_from_slice(body);
+    if !cipher.decrypt(&nonce(sender, counter), header, payload, tag) {
+        // Do not leak the unauthenticated ciphertext
+        payload.fill(0);
+        return Ok(None);
+    }
+    Ok(Some((sender, counter, payload.len())))
+}
+
+/// Derives the nonce from the sender address and the counter
+fn nonce(sender: u32, counter: u32) -> [u8; 12] {
+    let [s0, s1, s2, s3] = sender.to_le_bytes();
+    let [c0, c1, c2, c3] = counter.to_le_bytes();
+    [s0, s1, s2, s3, c0, c1, c2, c3, 0, 0, 0, 0]
+}
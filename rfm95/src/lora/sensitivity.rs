@@ -0,0 +1,36 @@
+//! Receiver sensitivity per spreading factor and bandwidth
+
+use crate::lora::types::{Bandwidth, SpreadingFactor};
+
+/// The receiver sensitivity in dBm, indexed by spreading factor (`S7..=S12`) and bandwidth (`B7_8..=B500`)
+///
+/// # Source
+/// The values for 125 kHz, 250 kHz and 500 kHz are taken from the RFM95 datasheet (high frequency band). The values for
+/// the narrower bandwidths are derived from the 125 kHz values by scaling with the bandwidth (i.e. `10 * log10(BW /
+/// 125 kHz)`), and rounded to the nearest dB.
+pub const SENSITIVITY_TABLE: [[i16; 10]; 6] = [
+    // 7.8, 10.4, 15.6, 20.8, 31.25, 41.7, 62.5, 125, 250, 500 kHz
+    [-135, -134, -132, -131, -129, -128, -126, -123, -120, -116], // SF7
+    [-138, -137, -135, -134, -132, -131, -129, -126, -123, -119], // SF8
+    [-141, -140, -138, -137, -135, -134, -132, -129, -125, -122], // SF9
+    [-144, -143, -141, -140, -138, -137, -135, -132, -128, -125], // SF10
+    [-145, -144, -142, -141, -139, -138, -136, -133, -130, -128], // SF11
+    [-148, -147, -145, -144, -142, -141, -139, -136, -133, -130], // SF12
+];
+
+/// The receiver sensitivity in dBm for the given spreading factor and bandwidth
+///
+/// # Note
+/// The sensitivity is the weakest signal strength that can still be demodulated reliably; see [`SENSITIVITY_TABLE`]
+/// for the source of the values.
+#[must_use]
+pub const fn sensitivity(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> i16 {
+    // Compute the table indices from the modem representation
+    #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as the lowest spreading factor is S7")]
+    let spreading_factor = spreading_factor as u8 as usize - SpreadingFactor::S7 as u8 as usize;
+    let bandwidth = bandwidth as u8 as usize;
+
+    // Lookup the sensitivity
+    #[allow(clippy::indexing_slicing, reason = "The indices are always within the table bounds")]
+    SENSITIVITY_TABLE[spreading_factor][bandwidth]
+}
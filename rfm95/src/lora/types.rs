@@ -1,7 +1,8 @@
 //! Small wrappers for type safety
 
 use crate::err;
-use crate::error::IoError;
+use crate::error::{InvalidArgumentError, IoError};
+use core::ops::{Add, Mul, Sub};
 
 /// A LoRa spreading factor
 ///
@@ -30,7 +31,7 @@ pub enum SpreadingFactor {
 }
 impl SpreadingFactor {
     /// Parses `self` from a register value
-    pub(crate) fn parse(value: u8) -> Result<Self, IoError> {
+    pub(crate) const fn parse(value: u8) -> Result<Self, IoError> {
         match value {
             sf if sf == Self::S7 as u8 => Ok(Self::S7),
             sf if sf == Self::S8 as u8 => Ok(Self::S8),
@@ -74,7 +75,7 @@ pub enum Bandwidth {
 }
 impl Bandwidth {
     /// Parses `self` from a register value
-    pub(crate) fn parse(value: u8) -> Result<Self, IoError> {
+    pub(crate) const fn parse(value: u8) -> Result<Self, IoError> {
         match value {
             bw if bw == Self::B500 as u8 => Ok(Self::B500),
             bw if bw == Self::B250 as u8 => Ok(Self::B250),
@@ -110,7 +111,7 @@ pub enum CodingRate {
 }
 impl CodingRate {
     /// Parses `self` from a register value
-    pub(crate) fn parse(value: u8) -> Result<Self, IoError> {
+    pub(crate) const fn parse(value: u8) -> Result<Self, IoError> {
         match value {
             cr if cr == Self::C4_5 as u8 => Ok(Self::C4_5),
             cr if cr == Self::C4_6 as u8 => Ok(Self::C4_6),
@@ -136,7 +137,7 @@ pub enum Polarity {
 }
 impl Polarity {
     /// Parses `self` from a register value
-    pub(crate) fn parse(value: u8) -> Result<Self, IoError> {
+    pub(crate) const fn parse(value: u8) -> Result<Self, IoError> {
         match value {
             polarity if polarity == Self::Normal as u8 => Ok(Self::Normal),
             polarity if polarity == Self::Inverted as u8 => Ok(Self::Inverted),
@@ -160,7 +161,7 @@ pub enum HeaderMode {
 }
 impl HeaderMode {
     /// Parses `self` from a register value
-    pub(crate) fn parse(value: u8) -> Result<Self, IoError> {
+    pub(crate) const fn parse(value: u8) -> Result<Self, IoError> {
         match value {
             mode if mode == Self::Explicit as u8 => Ok(Self::Explicit),
             mode if mode == Self::Implicit as u8 => Ok(Self::Implicit),
@@ -184,7 +185,7 @@ pub enum CrcMode {
 }
 impl CrcMode {
     /// Parses `self` from a register value
-    pub(crate) fn parse(value: u8) -> Result<Self, IoError> {
+    pub(crate) const fn parse(value: u8) -> Result<Self, IoError> {
         match value {
             mode if mode == Self::Disabled as u8 => Ok(Self::Disabled),
             mode if mode == Self::Enabled as u8 => Ok(Self::Enabled),
@@ -194,24 +195,54 @@ impl CrcMode {
 }
 
 /// The LoRa sync word to use
+///
+/// # Matching Behavior
+/// The sync word is not transmitted as a byte; each nibble is encoded as a modulated symbol after the preamble, and the
+/// receiver tolerates small deviations of these symbols. Sync words are thus not a reliable network filter: words that
+/// differ only slightly in one nibble (e.g. `0x12` and `0x13`) may cross-receive, so pick sync words that differ in
+/// both nibbles, and always filter addresses in the payload. Sync words that are one step away from
+/// [`Self::LORAWAN_PUBLIC`] or [`Self::PRIVATE_DEFAULT`] in a single nibble cross-receive with these widespread
+/// networks, so they are rejected by the config validation and the driver (see [`Self::is_ambiguous`]).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct SyncWord(u8);
 impl SyncWord {
-    /// Public sync word
-    pub const PUBLIC: Self = Self(0x34);
-    /// Private sync word
-    pub const PRIVATE: Self = Self(0x12);
+    /// The public sync word used by LoRaWAN networks
+    pub const LORAWAN_PUBLIC: Self = Self(0x34);
+    /// The default private sync word used by most non-LoRaWAN stacks
+    pub const PRIVATE_DEFAULT: Self = Self(0x12);
+    /// Public sync word (alias for [`Self::LORAWAN_PUBLIC`])
+    pub const PUBLIC: Self = Self::LORAWAN_PUBLIC;
+    /// Private sync word (alias for [`Self::PRIVATE_DEFAULT`])
+    pub const PRIVATE: Self = Self::PRIVATE_DEFAULT;
 
     /// Create a new sync word from the given raw sync word
+    ///
+    /// # Ambiguous Values
+    /// This function accepts any value; use [`Self::checked`] to reject ambiguous values early.
     pub const fn new(word: u8) -> Self {
         Self(word)
     }
+    /// Create a new sync word from the given raw sync word, rejecting ambiguous values
+    ///
+    /// # Ambiguous Values
+    /// See [`Self::is_ambiguous`].
+    pub const fn checked(word: u8) -> Result<Self, InvalidArgumentError> {
+        match Self(word) {
+            sync_word if sync_word.is_ambiguous() => Err(err!(InvalidArgumentError, "Ambiguous sync word")),
+            sync_word => Ok(sync_word),
+        }
+    }
 
     /// The sync word as `u8`
     pub const fn as_u8(self) -> u8 {
         self.0
     }
+    /// Whether the sync word cross-receives with [`Self::LORAWAN_PUBLIC`] or [`Self::PRIVATE_DEFAULT`], i.e. it differs
+    /// from one of them by one step in a single nibble (e.g. `0x13` or `0x35`)
+    pub const fn is_ambiguous(self) -> bool {
+        matches!(self.0, 0x24 | 0x44 | 0x33 | 0x35 | 0x02 | 0x22 | 0x11 | 0x13)
+    }
 }
 impl From<u8> for SyncWord {
     fn from(value: u8) -> Self {
@@ -276,6 +307,50 @@ impl Frequency {
     pub const fn as_u32(self) -> u32 {
         self.0
     }
+
+    /// The frequency shifted by the given offset in Hz, saturating at the numeric bounds
+    pub const fn offset(self, offset: i32) -> Self {
+        Self(self.0.saturating_add_signed(offset))
+    }
+    /// The frequency scaled by `1 + ppm / 1_000_000`, saturating at the numeric bounds
+    ///
+    /// # Crystal Correction
+    /// Positive values raise the frequency; to compensate a crystal that runs fast by `n` ppm, use `-n`.
+    pub const fn with_ppm(self, ppm: i32) -> Self {
+        #[allow(clippy::arithmetic_side_effects, reason = "The divider is not zero")]
+        let offset = (self.0 as i64).saturating_mul(ppm as i64) / 1_000_000;
+        let hz = (self.0 as i64).saturating_add(offset);
+        match hz {
+            ..0 => Self(0),
+            #[allow(clippy::cast_possible_truncation, reason = "The value is within the `u32` bounds")]
+            0..=0xFFFF_FFFF => Self(hz as u32),
+            _ => Self(u32::MAX),
+        }
+    }
+}
+impl Add for Frequency {
+    type Output = Self;
+
+    /// Adds two frequencies, saturating at the numeric bounds
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+impl Sub for Frequency {
+    type Output = Self;
+
+    /// Subtracts two frequencies, saturating at zero
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+impl Mul<u32> for Frequency {
+    type Output = Self;
+
+    /// Multiplies the frequency by the given factor, e.g. to compute a channel offset, saturating at the numeric bounds
+    fn mul(self, rhs: u32) -> Self::Output {
+        Self(self.0.saturating_mul(rhs))
+    }
 }
 impl From<u32> for Frequency {
     fn from(value: u32) -> Self {
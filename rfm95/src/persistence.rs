@@ -0,0 +1,404 @@
+//! Persistence of protocol state across reboots
+//!
+//! # Records
+//! State is persisted as small records, which are identified by a 64-bit key. The stateful layers of this crate use the
+//! following records:
+//! - the frame counters of [`crate::lora::replay`] via [`PersistentCounters`] (see [`counter_key`])
+//! - the enabled channels of [`crate::lora::channel::ChannelPlan`] (see [`KEY_CHANNEL_PLAN`])
+//! - the airtime buckets of [`crate::lora::ledger::AirtimeLedger`] (see [`ledger_key`])
+//! - the LoRaWAN frame counters of [`crate::lora::fcnt::FrameCounters`] (see [`KEY_FCNT_UP`] and [`KEY_FCNT_DOWN`])
+//! - the OTAA join state of [`crate::lora::join_state`] via [`crate::lora::join_state::PersistentJoinState`] (see
+//!   [`KEY_JOIN_STATE`])
+//!
+//! # Storage
+//! [`Persistence`] can be implemented for any storage; the `embedded-storage`-feature provides [`StorageSlots`] for
+//! any [`embedded_storage::Storage`] (e.g. EEPROM, or NOR flash via `embedded_storage::nor_flash::RmwNorFlashStorage`).
+
+#[cfg(feature = "embedded-storage")]
+use crate::err;
+#[cfg(feature = "embedded-storage")]
+use crate::error::BufferTooSmallError;
+use crate::lora::replay::CounterStore;
+
+/// The key of the channel plan record
+pub const KEY_CHANNEL_PLAN: u64 = 0x01 << 32;
+/// The key of the OTAA join state record
+pub const KEY_JOIN_STATE: u64 = 0x04 << 32;
+/// The key of the LoRaWAN uplink frame counter record
+pub const KEY_FCNT_UP: u64 = 0x05 << 32;
+/// The key of the LoRaWAN downlink frame counter record
+pub const KEY_FCNT_DOWN: u64 = (0x05 << 32) | 1;
+/// The key of the first airtime ledger record
+const KEY_AIRTIME_LEDGER: u64 = 0x02 << 32;
+/// The key of the first frame counter record
+const KEY_COUNTER: u64 = 0x03 << 32;
+
+/// The key of the frame counter record of the given peer or counter key
+pub const fn counter_key(peer: u32) -> u64 {
+    KEY_COUNTER | peer as u64
+}
+/// The key of the airtime ledger record with the given entry index
+pub const fn ledger_key(index: u32) -> u64 {
+    KEY_AIRTIME_LEDGER | index as u64
+}
+
+/// A persistent key-value storage for small records
+pub trait Persistence {
+    /// The storage error
+    type Error;
+
+    /// Reads the record with the given key into `buf` and returns the record length, or `None` if the record does not
+    /// exist
+    ///
+    /// # Buffer too small
+    /// If the record is longer than `buf`, only the first bytes are read, but the full record length is returned.
+    fn read(&mut self, key: u64, buf: &mut [u8]) -> Result<Option<usize>, Self::Error>;
+    /// Writes the record with the given key, replacing any existing record
+    fn write(&mut self, key: u64, data: &[u8]) -> Result<(), Self::Error>;
+}
+impl<T> Persistence for &mut T
+where
+    T: Persistence + ?Sized,
+{
+    type Error = T::Error;
+
+    fn read(&mut self, key: u64, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+        (**self).read(key, buf)
+    }
+    fn write(&mut self, key: u64, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).write(key, data)
+    }
+}
+
+/// A [`CounterStore`] that persists the frame counters as records (see [`counter_key`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PersistentCounters<P> {
+    /// The underlying persistence
+    persistence: P,
+}
+impl<P> PersistentCounters<P>
+where
+    P: Persistence,
+{
+    /// Creates a new counter store on top of the given persistence
+    pub const fn new(persistence: P) -> Self {
+        Self { persistence }
+    }
+    /// Returns the underlying persistence
+    pub fn into_inner(self) -> P {
+        self.persistence
+    }
+}
+impl<P> CounterStore for PersistentCounters<P>
+where
+    P: Persistence,
+{
+    type Error = P::Error;
+
+    fn load(&mut self, peer: u32) -> Result<Option<u32>, Self::Error> {
+        let mut counter = [0; 4];
+        match self.persistence.read(counter_key(peer), &mut counter)? {
+            Some(4) => Ok(Some(u32::from_le_bytes(counter))),
+            // The record does not exist or is invalid
+            _ => Ok(None),
+        }
+    }
+    fn store(&mut self, peer: u32, counter: u32) -> Result<(), Self::Error> {
+        self.persistence.write(counter_key(peer), &counter.to_le_bytes())
+    }
+}
+
+/// The length of a slot header of [`StorageSlots`]
+#[cfg(feature = "embedded-storage")]
+const SLOT_HEADER_LEN: usize = 10;
+/// The length of the key within a slot header of [`StorageSlots`]
+#[cfg(feature = "embedded-storage")]
+const SLOT_KEY_LEN: usize = 8;
+
+/// The header of a slot of [`StorageSlots`]
+#[cfg(feature = "embedded-storage")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlotHeader {
+    /// The record key
+    key: u64,
+    /// The record length
+    len: u8,
+    /// The sequence number, which is incremented with every update of the record
+    sequence: u8,
+}
+
+/// A storage error
+#[cfg(feature = "embedded-storage")]
+#[derive(Debug, Clone, Copy)]
+pub enum StorageError<E> {
+    /// An error of the underlying storage
+    Storage(E),
+    /// A buffer-too-small error, i.e. the record is too large for a slot, or all slots are occupied
+    BufferTooSmallError(BufferTooSmallError),
+}
+#[cfg(feature = "embedded-storage")]
+impl<E> From<BufferTooSmallError> for StorageError<E> {
+    fn from(error: BufferTooSmallError) -> Self {
+        Self::BufferTooSmallError(error)
+    }
+}
+
+/// A [`Persistence`] on top of an [`embedded_storage::Storage`] with `SLOTS` fixed-size slots of `SLOT_SIZE` bytes
+///
+/// # Layout
+/// The slots are laid out back-to-back from the given offset. Each slot consists of the 8-byte little-endian key, the
+/// 1-byte record length, the 1-byte sequence number and the record data, so a slot can hold up to `SLOT_SIZE - 10`
+/// bytes. A slot with the key `u64::MAX` (i.e. erased flash) is free.
+///
+/// # Torn Writes
+/// A record is never updated in place: It is written to a free slot and committed by writing its key last, and only
+/// then is the slot of the previous record released. An interrupted update thus leaves the previous record intact; if
+/// both records survive, the one with the newer sequence number wins. Updating a record therefore requires one free
+/// slot.
+///
+/// # Wear
+/// Slots are rewritten, so the underlying storage should handle wear-leveling or erase-before-write (e.g. EEPROM, or
+/// `RmwNorFlashStorage`).
+#[cfg(feature = "embedded-storage")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageSlots<S, const SLOTS: usize, const SLOT_SIZE: usize> {
+    /// The underlying storage
+    storage: S,
+    /// The offset of the first slot
+    offset: u32,
+}
+#[cfg(feature = "embedded-storage")]
+impl<S, const SLOTS: usize, const SLOT_SIZE: usize> StorageSlots<S, SLOTS, SLOT_SIZE>
+where
+    S: embedded_storage::Storage,
+{
+    /// The key of a free slot
+    const FREE: u64 = u64::MAX;
+
+    /// Creates a new slot storage at the given offset of the underlying storage
+    pub const fn new(storage: S, offset: u32) -> Self {
+        Self { storage, offset }
+    }
+    /// Returns the underlying storage
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    /// Erases all slots
+    pub fn clear(&mut self) -> Result<(), StorageError<S::Error>> {
+        for slot in 0..SLOTS {
+            self.write_key(slot, Self::FREE)?;
+        }
+        Ok(())
+    }
+
+    /// Finds the newest slot with the given key, and returns the slot index, the record length and the sequence number
+    fn find(&mut self, key: u64) -> Result<Option<(usize, SlotHeader)>, StorageError<S::Error>> {
+        let mut found: Option<(usize, SlotHeader)> = None;
+        for slot in 0..SLOTS {
+            let header = self.read_header(slot)?;
+            if header.key != key {
+                continue;
+            }
+
+            // Prefer the newer record if an update has been interrupted before the previous slot was released
+            #[allow(clippy::cast_possible_wrap, reason = "Sequence numbers are compared via the wrapping distance")]
+            let is_newer = found.is_none_or(|(_, newest)| header.sequence.wrapping_sub(newest.sequence) as i8 > 0);
+            if is_newer {
+                found = Some((slot, header));
+            }
+        }
+        Ok(found)
+    }
+    /// Reads the header of the given slot
+    fn read_header(&mut self, slot: usize) -> Result<SlotHeader, StorageError<S::Error>> {
+        let mut header = [0; SLOT_HEADER_LEN];
+        self.storage.read(self.slot_offset(slot, 0), &mut header).map_err(StorageError::Storage)?;
+        let [k0, k1, k2, k3, k4, k5, k6, k7, len, sequence] = header;
+        Ok(SlotHeader { key: u64::from_le_bytes([k0, k1, k2, k3, k4, k5, k6, k7]), len, sequence })
+    }
+    /// Writes the key of the given slot
+    fn write_key(&mut self, slot: usize, key: u64) -> Result<(), StorageError<S::Error>> {
+        self.storage.write(self.slot_offset(slot, 0), &key.to_le_bytes()).map_err(StorageError::Storage)
+    }
+    /// The storage offset of the given byte within the given slot
+    fn slot_offset(&self, slot: usize, byte: usize) -> u32 {
+        let offset = slot.saturating_mul(SLOT_SIZE).saturating_add(byte);
+        self.offset.saturating_add(u32::try_from(offset).unwrap_or(u32::MAX))
+    }
+}
+#[cfg(feature = "embedded-storage")]
+impl<S, const SLOTS: usize, const SLOT_SIZE: usize> Persistence for StorageSlots<S, SLOTS, SLOT_SIZE>
+where
+    S: embedded_storage::Storage,
+{
+    type Error = StorageError<S::Error>;
+
+    fn read(&mut self, key: u64, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+        let Some((slot, SlotHeader { len, .. })) = self.find(key)? else {
+            // The record does not exist
+            return Ok(None);
+        };
+
+        // Read the record data
+        let len = (len as usize).min(SLOT_SIZE.saturating_sub(SLOT_HEADER_LEN));
+        let data_offset = self.slot_offset(slot, SLOT_HEADER_LEN);
+        let buf_len = buf.len().min(len);
+        let data = buf.get_mut(..buf_len).unwrap_or_default();
+        self.storage.read(data_offset, data).map_err(StorageError::Storage)?;
+        Ok(Some(len))
+    }
+    fn write(&mut self, key: u64, data: &[u8]) -> Result<(), Self::Error> {
+        // Validate the record length
+        let len =
+            u8::try_from(data.len()).ok().filter(|len| (*len as usize) <= SLOT_SIZE.saturating_sub(SLOT_HEADER_LEN));
+        let Some(len) = len else {
+            // The record does not fit into a slot
+            return Err(err!(BufferTooSmallError, "Record exceeds the slot size"))?;
+        };
+
+        // Release the leftovers of interrupted updates, which are older than the current record
+        let previous = self.find(key)?;
+        for stale in (0..SLOTS).filter(|stale| previous.is_some_and(|(slot, _)| *stale != slot)) {
+            if self.read_header(stale)?.key == key {
+                self.write_key(stale, Self::FREE)?;
+            }
+        }
+
+        // Always write to a free slot, so the current record survives a torn write
+        let Some((slot, _)) = self.find(Self::FREE)? else {
+            // There is no free slot
+            return Err(err!(BufferTooSmallError, "All storage slots are occupied"))?;
+        };
+
+        // Write the data and the length first, and commit the record by writing the key last
+        let sequence = previous.map_or(0, |(_, previous)| previous.sequence.wrapping_add(1));
+        self.storage.write(self.slot_offset(slot, SLOT_HEADER_LEN), data).map_err(StorageError::Storage)?;
+        self.storage.write(self.slot_offset(slot, SLOT_KEY_LEN), &[len, sequence]).map_err(StorageError::Storage)?;
+        self.write_key(slot, key)?;
+
+        // Release the previous record
+        if let Some((previous, _)) = previous {
+            self.write_key(previous, Self::FREE)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "embedded-storage"))]
+#[allow(clippy::indexing_slicing, reason = "Test failures are reported via panics")]
+mod tests {
+    use super::*;
+
+    /// An erased RAM storage that fails all writes after a given amount of writes, to simulate a power loss
+    struct RamStorage {
+        /// The storage contents
+        memory: [u8; 64],
+        /// The amount of writes that succeed, or `None` if all writes succeed
+        writes_left: Option<usize>,
+    }
+    impl RamStorage {
+        /// Creates a new erased storage
+        const fn new() -> Self {
+            Self { memory: [0xFF; 64], writes_left: None }
+        }
+    }
+    impl embedded_storage::ReadStorage for RamStorage {
+        type Error = ();
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            bytes.copy_from_slice(&self.memory[offset..][..bytes.len()]);
+            Ok(())
+        }
+        fn capacity(&self) -> usize {
+            self.memory.len()
+        }
+    }
+    impl embedded_storage::Storage for RamStorage {
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            match &mut self.writes_left {
+                Some(0) => return Err(()),
+                Some(writes_left) => *writes_left = writes_left.saturating_sub(1),
+                None => (),
+            }
+            let offset = offset as usize;
+            self.memory[offset..][..bytes.len()].copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Four slots with up to 6 bytes of record data each
+    type Slots = StorageSlots<RamStorage, 4, 16>;
+
+    /// Reads the record with the given key as `u32`
+    fn read_u32(slots: &mut Slots, key: u64) -> Option<u32> {
+        let mut buf = [0; 4];
+        let len = slots.read(key, &mut buf).ok()?;
+        len.map(|_| u32::from_le_bytes(buf))
+    }
+
+    /// Updates the record with the given key, and lets the given amount of storage writes succeed
+    fn torn_write(slots: Slots, key: u64, value: u32, writes: usize) -> Slots {
+        let mut storage = slots.into_inner();
+        storage.writes_left = Some(writes);
+        let mut slots = Slots::new(storage, 0);
+        assert!(slots.write(key, &value.to_le_bytes()).is_err());
+
+        // Reboot
+        let mut storage = slots.into_inner();
+        storage.writes_left = None;
+        Slots::new(storage, 0)
+    }
+
+    #[test]
+    fn write_read() {
+        let mut slots = Slots::new(RamStorage::new(), 0);
+        assert_eq!(read_u32(&mut slots, 1), None);
+        for value in 0..10 {
+            assert!(slots.write(1, &u32::to_le_bytes(value)).is_ok());
+            assert!(slots.write(2, &u32::to_le_bytes(value + 100)).is_ok());
+            assert_eq!(read_u32(&mut slots, 1), Some(value));
+            assert_eq!(read_u32(&mut slots, 2), Some(value + 100));
+        }
+        assert!(matches!(slots.write(3, &[0; 7]), Err(StorageError::BufferTooSmallError(_))));
+    }
+
+    #[test]
+    fn torn_write_keeps_previous_record() {
+        let mut slots = Slots::new(RamStorage::new(), 0);
+        assert!(slots.write(1, &u32::to_le_bytes(7)).is_ok());
+
+        // The update is interrupted before the key is committed (data, length and sequence, key, release)
+        for writes in 0..3 {
+            slots = torn_write(slots, 1, 8, writes);
+            assert_eq!(read_u32(&mut slots, 1), Some(7));
+        }
+
+        // A new record is interrupted before the key is committed
+        slots = torn_write(slots, 2, 9, 2);
+        assert_eq!(read_u32(&mut slots, 2), None);
+        assert!(slots.write(1, &u32::to_le_bytes(10)).is_ok());
+        assert_eq!(read_u32(&mut slots, 1), Some(10));
+    }
+
+    #[test]
+    fn torn_write_recovers_newer_record() {
+        let mut slots = Slots::new(RamStorage::new(), 0);
+        assert!(slots.write(1, &u32::to_le_bytes(7)).is_ok());
+
+        // The update is interrupted after the key is committed, so both records survive and the newer one wins
+        slots = torn_write(slots, 1, 8, 3);
+        assert_eq!(read_u32(&mut slots, 1), Some(8));
+
+        // The next update releases the stale record, so the slots do not fill up
+        for value in 9..20 {
+            assert!(slots.write(1, &u32::to_le_bytes(value)).is_ok());
+            assert_eq!(read_u32(&mut slots, 1), Some(value));
+        }
+        assert!(slots.write(2, &u32::to_le_bytes(1)).is_ok());
+        assert!(slots.write(3, &u32::to_le_bytes(2)).is_ok());
+        assert_eq!(read_u32(&mut slots, 1), Some(19));
+        assert_eq!(read_u32(&mut slots, 3), Some(2));
+    }
+}
@@ -0,0 +1,25 @@
+//! Re-exports of the common API surface
+//!
+//! # Usage
+//! Import everything via `use embedded_lora_rfm95::prelude::*;` to get the driver, the config and its types, the error
+//! types and the SPI constants without individual `use` paths. Less common functionality (e.g. the helpers in
+//! [`crate::lora`]) must still be imported from its module.
+
+pub use crate::error::{
+    BufferTooSmallError, ConfigError, DwellTimeError, InvalidArgumentError, InvalidMessageError, IoError,
+    LoopbackError, ProtocolError, RxCompleteError, RxStartError, TxStartError,
+};
+pub use crate::lora::config::Config;
+pub use crate::lora::types::{
+    Bandwidth, CodingRate, CrcMode, Frequency, HeaderMode, Polarity, PreambleLength, SpreadingFactor, SyncWord,
+};
+pub use crate::rfm95::{
+    Chip, RxMetadata, RxOutcome, Sx127xBuilder, Sx127xDriver, RFM95_FIFO_SIZE, RFM95_SPI_BAUDRATE, RFM95_SPI_FREQUENCY,
+    RFM95_SPI_MODE,
+};
+
+// Expose the drivers of the enabled chip variants
+#[cfg(feature = "rfm95")]
+pub use crate::rfm95::{Rfm95, Rfm95Builder, Rfm95Driver};
+#[cfg(feature = "rfm96")]
+pub use crate::rfm95::{Rfm96, Rfm96Driver, Rfm98, Rfm98Driver};
@@ -0,0 +1,151 @@
+//! A deduplicating receiver on top of a radio set
+
+use crate::error::RxCompleteError;
+use crate::lora::config::Config;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::metadata::RxMetadata;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::radio_set::RadioSet;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A received packet, tagged with the radio and config that heard it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregatedPacket {
+    /// The index of the radio within the radio set
+    radio: usize,
+    /// The config of the radio
+    config: Config,
+    /// The length of the message
+    len: usize,
+    /// The metadata of the packet
+    metadata: RxMetadata,
+}
+impl AggregatedPacket {
+    /// The index of the radio within the radio set
+    pub const fn radio(&self) -> usize {
+        self.radio
+    }
+    /// The config of the radio
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+    /// The length of the message
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the message is empty
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The metadata of the packet
+    pub const fn metadata(&self) -> RxMetadata {
+        self.metadata
+    }
+}
+
+/// An aggregated receiver that yields a single stream of packets from all radios of a [`RadioSet`], and drops
+/// duplicates of frames that have been heard by more than one radio
+///
+/// # Deduplication
+/// The aggregator remembers a 32-bit hash of the last `HISTORY` packets together with their reception time. A packet
+/// with the same hash as a remembered packet within the deduplication window is considered a duplicate; only the first
+/// reception is yielded. Please note that identical frames that are intentionally retransmitted within the window are
+/// dropped as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxAggregator<const HISTORY: usize> {
+    /// The hashes and reception timestamps of the recent packets
+    history: [Option<(u32, u64)>; HISTORY],
+    /// The history slot to overwrite next
+    next: usize,
+    /// The deduplication window in microseconds
+    window: u64,
+    /// The amount of dropped duplicates
+    duplicates: u32,
+}
+impl<const HISTORY: usize> RxAggregator<HISTORY> {
+    /// The FNV-1a offset basis
+    const FNV_OFFSET_BASIS: u32 = 0x811C_9DC5;
+    /// The FNV-1a prime
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    /// Creates a new aggregator with the given deduplication window
+    pub fn new(window: Duration) -> Self {
+        let window = u64::try_from(window.as_micros()).unwrap_or(u64::MAX);
+        Self { history: [None; HISTORY], next: 0, window, duplicates: 0 }
+    }
+
+    /// The deduplication window
+    pub const fn window(&self) -> Duration {
+        Duration::from_micros(self.window)
+    }
+    /// The amount of dropped duplicates
+    pub const fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+
+    /// Polls the radio set, copies the next unique message into `buf` and returns the packet
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. Each radio is polled at most once; if no radio has received a unique packet, it
+    /// returns `Ok(None)`. Corrupt packets and duplicates are skipped.
+    pub fn poll<Device, Variant, Switch, const N: usize>(
+        &mut self,
+        radios: &mut RadioSet<Device, Variant, Switch, N>,
+        buf: &mut [u8],
+        now: u64,
+    ) -> Result<Option<AggregatedPacket>, RxCompleteError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        for _ in 0..N {
+            // Poll the radio set
+            let Some(outcome) = radios.poll(buf)? else {
+                // No radio has received a packet
+                return Ok(None);
+            };
+            let RxOutcome::Received((len, _)) = outcome.outcome() else {
+                // Skip corrupt packets
+                continue;
+            };
+
+            // Skip duplicates
+            let hash = Self::hash(buf.get(..len).unwrap_or_default());
+            if self.is_duplicate(hash, now) {
+                self.duplicates = self.duplicates.saturating_add(1);
+                continue;
+            }
+            self.remember(hash, now);
+
+            // Tag the packet with the radio, its config and the packet metadata
+            let (Some(config), Some(radio)) = (radios.config(outcome.radio()).copied(), radios.radio(outcome.radio()))
+            else {
+                // The radio set has returned an invalid index
+                continue;
+            };
+            let metadata = radio.get_packet_metadata()?;
+            return Ok(Some(AggregatedPacket { radio: outcome.radio(), config, len, metadata }));
+        }
+        Ok(None)
+    }
+
+    /// Whether a packet with the given hash has been received within the deduplication window
+    fn is_duplicate(&self, hash: u32, now: u64) -> bool {
+        (self.history.iter().flatten())
+            .any(|(known, timestamp)| *known == hash && now.saturating_sub(*timestamp) <= self.window)
+    }
+    /// Remembers a packet with the given hash, overwriting the oldest entry
+    fn remember(&mut self, hash: u32, now: u64) {
+        if let Some(slot) = self.history.get_mut(self.next) {
+            *slot = Some((hash, now));
+        }
+        self.next = self.next.wrapping_add(1).checked_rem(HISTORY).unwrap_or_default();
+    }
+    /// Computes the FNV-1a hash of the given message
+    fn hash(message: &[u8]) -> u32 {
+        (message.iter()).fold(Self::FNV_OFFSET_BASIS, |hash, byte| (hash ^ *byte as u32).wrapping_mul(Self::FNV_PRIME))
+    }
+}
@@ -0,0 +1,178 @@
+//! Periodic beacon transmission with jitter and duty-cycle limits
+
+use crate::err;
+use crate::error::{BufferTooSmallError, TxStartError};
+use crate::lora::airtime;
+use crate::lora::csma::Csma;
+use crate::lora::ledger::DutyCycle;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A periodic beacon transmitter, e.g. for discovery, time sync or presence signaling
+///
+/// # Schedule
+/// The beacon is sent once per period plus a random jitter of up to the configured jitter, so beacons of nodes that
+/// were started at the same time do not collide repeatedly. If the duty cycle does not allow the next beacon yet (e.g.
+/// for a short period and a long airtime), the beacon is delayed until the off-time has passed.
+///
+/// # Usage
+/// Set the payload via [`Self::set_payload`] (which may be updated anytime, e.g. with the current time before each
+/// beacon), start the schedule via [`Self::start`], then call [`Self::poll`] regularly. No other operation should be
+/// started on the driver while a beacon is being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Beacon<const MTU: usize> {
+    /// The beacon period in microseconds
+    period: u64,
+    /// The maximum jitter in microseconds
+    jitter: u64,
+    /// The duty cycle limit
+    duty_cycle: DutyCycle,
+    /// The PRNG for the jitter
+    random: Csma,
+    /// The beacon payload
+    payload: [u8; MTU],
+    /// The length of the beacon payload
+    payload_len: usize,
+    /// The clock time in microseconds of the next beacon, or `None` if the schedule is stopped
+    next_at: Option<u64>,
+    /// Whether a TX operation is in progress
+    busy: bool,
+    /// The amount of sent beacons
+    sent: u32,
+}
+impl<const MTU: usize> Beacon<MTU> {
+    /// Creates a new, stopped beacon with an empty payload, the given period and the given seed for the jitter
+    ///
+    /// # Seed
+    /// Seed the jitter via [`Sx127xDriver::random_u32`], so nodes with the same period do not jitter in lockstep. The
+    /// jitter defaults to a tenth of the period (see [`Self::with_jitter`]).
+    pub fn new(period: Duration, seed: u32) -> Self {
+        let period = u64::try_from(period.as_micros()).unwrap_or(u64::MAX);
+        Self {
+            period,
+            jitter: period.checked_div(10).unwrap_or_default(),
+            duty_cycle: DutyCycle::UNLIMITED,
+            random: Csma::new(seed, Duration::ZERO, 0),
+            payload: [0; MTU],
+            payload_len: 0,
+            next_at: None,
+            busy: false,
+            sent: 0,
+        }
+    }
+    /// Sets the maximum random jitter that is added to each period
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = u64::try_from(jitter.as_micros()).unwrap_or(u64::MAX);
+        self
+    }
+    /// Sets the duty cycle limit
+    pub const fn with_duty_cycle(mut self, duty_cycle: DutyCycle) -> Self {
+        self.duty_cycle = duty_cycle;
+        self
+    }
+
+    /// The beacon period
+    pub const fn period(&self) -> Duration {
+        Duration::from_micros(self.period)
+    }
+    /// The maximum random jitter that is added to each period
+    pub const fn jitter(&self) -> Duration {
+        Duration::from_micros(self.jitter)
+    }
+    /// The duty cycle limit
+    pub const fn duty_cycle(&self) -> DutyCycle {
+        self.duty_cycle
+    }
+    /// The beacon payload
+    pub fn payload(&self) -> &[u8] {
+        self.payload.get(..self.payload_len).unwrap_or_default()
+    }
+    /// The clock time in microseconds of the next beacon, or `None` if the schedule is stopped
+    pub const fn next_at(&self) -> Option<u64> {
+        self.next_at
+    }
+    /// The amount of sent beacons
+    pub const fn sent(&self) -> u32 {
+        self.sent
+    }
+
+    /// Sets the beacon payload
+    pub fn set_payload(&mut self, payload: &[u8]) -> Result<(), BufferTooSmallError> {
+        let Some(slice) = self.payload.get_mut(..payload.len()) else {
+            // The payload does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "Payload exceeds the beacon MTU"));
+        };
+        slice.copy_from_slice(payload);
+        self.payload_len = payload.len();
+        Ok(())
+    }
+
+    /// Starts the schedule; the first beacon is sent after a random jitter
+    pub fn start(&mut self, now: u64) {
+        let jitter = self.next_jitter();
+        self.next_at = Some(now.saturating_add(jitter));
+    }
+    /// Stops the schedule
+    ///
+    /// # Important
+    /// A running TX operation is not aborted; it is completed by the next call to [`Self::poll`].
+    pub fn stop(&mut self) {
+        self.next_at = None;
+    }
+
+    /// Completes the current beacon and sends the next beacon once it is due, and returns the length of the started
+    /// beacon
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If a beacon is still being sent, the next beacon is not due yet, or the schedule
+    /// is stopped, it returns `Ok(None)`.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<Option<usize>, TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Complete the current beacon
+        if self.busy {
+            let Some(_) = driver.complete_tx()? else {
+                // The TX operation has not been completed yet
+                return Ok(None);
+            };
+            self.busy = false;
+        }
+
+        // Check if the next beacon is due
+        let Some(next_at) = self.next_at else {
+            // The schedule is stopped
+            return Ok(None);
+        };
+        if now < next_at {
+            return Ok(None);
+        }
+
+        // Send the beacon
+        let airtime = airtime::airtime(self.payload_len, driver.config()?);
+        driver.start_tx(self.payload())?;
+        self.busy = true;
+        self.sent = self.sent.saturating_add(1);
+
+        // Schedule the next beacon, but not before the off-time has passed
+        let airtime_micros = u64::try_from(airtime.as_micros()).unwrap_or(u64::MAX);
+        let interval = self.period.saturating_add(self.next_jitter()).max(self.duty_cycle.period(airtime_micros));
+        self.next_at = Some(now.saturating_add(interval));
+        Ok(Some(self.payload_len))
+    }
+
+    /// Generates a random jitter in microseconds within the maximum jitter
+    fn next_jitter(&mut self) -> u64 {
+        let random = u64::from(self.random.next_random());
+        random.checked_rem(self.jitter.saturating_add(1)).unwrap_or_default()
+    }
+}
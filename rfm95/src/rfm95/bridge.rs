@@ -0,0 +1,332 @@
+//! A transparent serial bridge ("wireless cable") over a LoRa link
+
+use crate::err;
+use crate::error::{BufferTooSmallError, InvalidArgumentError, ProtocolError};
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A transparent serial bridge that tunnels a byte stream (e.g. an existing UART protocol) over a LoRa link
+///
+/// # Framing
+/// The byte stream is chunked into frames of up to `MTU - 1` payload bytes, prefixed with a single header byte. As LoRa
+/// packets carry their own length, no additional framing is required over the air. A frame is sent once it is full, or
+/// once no new bytes have been written for the configured idle time, like the transparent mode of common serial
+/// radio modules.
+///
+/// # Flow Control
+/// [`Self::write`] only accepts as many bytes as there is buffer space left, and returns the amount of accepted bytes.
+/// Use [`Self::free`] to drive the RTS line of the UART.
+///
+/// # Reliability
+/// Optionally, each frame can be acknowledged by the peer and retransmitted if the acknowledgement does not arrive in
+/// time (stop-and-wait with alternating sequence bit, see [`Self::with_reliability`]). Both peers must use the same
+/// MTU; the reliability option only affects the sending side.
+///
+/// # Usage
+/// Call [`Self::poll`] regularly, and write the returned bytes to the UART. The bridge keeps the radio in continuous
+/// RX mode whenever it is not transmitting, so no other operation should be started on the driver in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialBridge<const MTU: usize> {
+    /// The bytes written by the application that have not been framed yet
+    pending: [u8; MTU],
+    /// The amount of pending bytes
+    pending_len: usize,
+    /// The frame that is currently in flight
+    frame: [u8; MTU],
+    /// The length of the frame in flight, or `0` if there is none
+    frame_len: usize,
+    /// The sequence bit of the next frame to send
+    tx_seq: u8,
+    /// The sequence bit of the last reliable frame received
+    rx_seq: Option<u8>,
+    /// The sequence bit of an acknowledgement to send
+    ack: Option<u8>,
+    /// Whether a TX operation is running
+    transmitting: bool,
+    /// Whether the radio is in continuous RX mode
+    receiving: bool,
+    /// The timestamp of the last write in microseconds
+    last_write: u64,
+    /// The idle time after which a partial frame is sent, in microseconds
+    idle: u64,
+    /// The acknowledgement timeout in microseconds and the amount of retransmissions, if reliability is enabled
+    reliability: Option<(u64, u8)>,
+    /// The timestamp the frame in flight was sent at
+    sent_at: u64,
+    /// The amount of retransmissions left for the frame in flight
+    retries_left: u8,
+    /// The amount of frames that have been dropped after all retransmissions failed
+    lost: u32,
+}
+impl<const MTU: usize> SerialBridge<MTU> {
+    /// The header flag of an acknowledgement
+    const HEADER_ACK: u8 = 0b1000_0000;
+    /// The header flag of a frame that requests an acknowledgement
+    const HEADER_RELIABLE: u8 = 0b0000_0010;
+    /// The header bit of the sequence bit
+    const HEADER_SEQ: u8 = 0b0000_0001;
+
+    /// Creates a new serial bridge that sends a partial frame once no new bytes have been written for `idle`
+    ///
+    /// # MTU
+    /// The MTU is the maximum LoRa packet length including the header byte, and must be within `2..=255`.
+    pub fn new(idle: Duration) -> Result<Self, InvalidArgumentError> {
+        // Validate the MTU
+        let 2..=255 = MTU else {
+            // The MTU cannot hold a header and a payload byte, or exceeds the maximum packet length
+            return Err(err!(InvalidArgumentError, "Invalid bridge MTU"));
+        };
+
+        // Init the bridge
+        let idle = u64::try_from(idle.as_micros()).unwrap_or(u64::MAX);
+        Ok(Self {
+            pending: [0; MTU],
+            pending_len: 0,
+            frame: [0; MTU],
+            frame_len: 0,
+            tx_seq: 0,
+            rx_seq: None,
+            ack: None,
+            transmitting: false,
+            receiving: false,
+            last_write: 0,
+            idle,
+            reliability: None,
+            sent_at: 0,
+            retries_left: 0,
+            lost: 0,
+        })
+    }
+    /// Enables acknowledged frames that are retransmitted up to `retries` times if the acknowledgement does not arrive
+    /// within `ack_timeout`
+    ///
+    /// # Acknowledgement Timeout
+    /// The timeout must cover the airtime of a full frame, the airtime of the acknowledgement and the turnaround of the
+    /// peer (also see [`crate::lora::airtime::airtime`]).
+    pub fn with_reliability(mut self, ack_timeout: Duration, retries: u8) -> Self {
+        let ack_timeout = u64::try_from(ack_timeout.as_micros()).unwrap_or(u64::MAX);
+        self.reliability = Some((ack_timeout, retries));
+        self
+    }
+
+    /// The amount of bytes that can be written until the buffer is full
+    pub const fn free(&self) -> usize {
+        MTU.saturating_sub(1).saturating_sub(self.pending_len)
+    }
+    /// The amount of frames that have been dropped after all retransmissions failed
+    pub const fn lost(&self) -> u32 {
+        self.lost
+    }
+    /// Whether all written bytes have been sent (and acknowledged, if reliability is enabled)
+    pub const fn is_flushed(&self) -> bool {
+        self.pending_len == 0 && self.frame_len == 0 && !self.transmitting
+    }
+
+    /// Writes bytes from the serial side into the bridge and returns the amount of bytes accepted
+    ///
+    /// # Flow Control
+    /// If the buffer is full, fewer bytes than given are accepted; the remainder must be written again after the next
+    /// [`Self::poll`].
+    pub fn write(&mut self, data: &[u8], now: u64) -> usize {
+        // Copy as many bytes as possible
+        let free = self.pending.get_mut(self.pending_len..MTU.saturating_sub(1)).unwrap_or_default();
+        let accepted = core::cmp::min(free.len(), data.len());
+        for (slot, byte) in free.iter_mut().zip(data) {
+            *slot = *byte;
+        }
+
+        // Update the buffer state
+        self.pending_len = self.pending_len.saturating_add(accepted);
+        if accepted > 0 {
+            self.last_write = now;
+        }
+        accepted
+    }
+
+    /// Drives the bridge, copies the bytes received from the peer into `buf` and returns the amount of bytes copied
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking; it completes running radio operations, processes a received frame, and starts the
+    /// next transmission if necessary. `buf` must be able to hold `MTU - 1` bytes.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        buf: &mut [u8],
+        now: u64,
+    ) -> Result<usize, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Validate the buffer size
+        if buf.len() < MTU.saturating_sub(1) {
+            // The buffer cannot hold a full frame
+            return Err(err!(BufferTooSmallError, "Bridge buffer is too small"))?;
+        }
+
+        // Complete a running transmission
+        if self.transmitting {
+            let Some(_) = driver.complete_tx()? else {
+                // The transmission is still running
+                return Ok(0);
+            };
+            self.transmitting = false;
+            self.receiving = false;
+        }
+
+        // Make sure the radio is listening
+        if !self.receiving {
+            driver.start_rx_continuous()?;
+            self.receiving = true;
+        }
+
+        // Process a received frame and send the next frame if any
+        let received = self.receive(driver, buf)?;
+        self.transmit(driver, now)?;
+        Ok(received)
+    }
+
+    /// Processes a received frame and copies the payload into `buf`
+    fn receive<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        buf: &mut [u8],
+    ) -> Result<usize, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Receive the next frame
+        let mut frame = [0; MTU];
+        let RxOutcome::Received((len @ 1.., _)) = driver.complete_rx_continuous(&mut frame)? else {
+            // No valid frame has been received
+            return Ok(0);
+        };
+        let (Some(header), Some(payload)) = (frame.first().copied(), frame.get(1..len)) else {
+            // The frame is empty
+            return Ok(0);
+        };
+
+        // Process acknowledgements
+        let seq = header & Self::HEADER_SEQ;
+        if header & Self::HEADER_ACK != 0 {
+            // Complete the frame in flight if the acknowledgement matches
+            if self.frame_len > 0 && seq == self.tx_seq {
+                self.frame_len = 0;
+                self.tx_seq ^= Self::HEADER_SEQ;
+            }
+            return Ok(0);
+        }
+
+        // Acknowledge reliable frames and drop retransmitted duplicates
+        if header & Self::HEADER_RELIABLE != 0 {
+            self.ack = Some(seq);
+            if self.rx_seq.replace(seq) == Some(seq) {
+                return Ok(0);
+            }
+        }
+
+        // Copy the payload
+        for (slot, byte) in buf.iter_mut().zip(payload) {
+            *slot = *byte;
+        }
+        Ok(payload.len())
+    }
+
+    /// Sends a pending acknowledgement, retransmits the frame in flight or sends the next frame if necessary
+    fn transmit<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Acknowledgements take precedence
+        if let Some(seq) = self.ack.take() {
+            driver.start_tx(&[Self::HEADER_ACK | seq])?;
+            self.transmitting = true;
+            return Ok(());
+        }
+
+        // Retransmit the frame in flight if the acknowledgement has timed out
+        if self.frame_len > 0 {
+            let Some((ack_timeout, _)) = self.reliability else {
+                // Unreliable frames are never in flight
+                return Ok(());
+            };
+            if now.saturating_sub(self.sent_at) < ack_timeout {
+                // Wait for the acknowledgement
+                return Ok(());
+            }
+            let Some(retries_left) = self.retries_left.checked_sub(1) else {
+                // Drop the frame after all retransmissions failed
+                self.frame_len = 0;
+                self.tx_seq ^= Self::HEADER_SEQ;
+                self.lost = self.lost.saturating_add(1);
+                return Ok(());
+            };
+            self.retries_left = retries_left;
+            return self.send_frame(driver, now);
+        }
+
+        // Send the next frame if it is full or the stream has been idle long enough
+        let full = self.pending_len >= MTU.saturating_sub(1);
+        let idle = now.saturating_sub(self.last_write) >= self.idle;
+        if self.pending_len == 0 || !(full || idle) {
+            // There is nothing to send yet
+            return Ok(());
+        }
+
+        // Assemble the frame
+        let (Some((header, payload)), Some(pending)) =
+            (self.frame.split_first_mut(), self.pending.get(..self.pending_len))
+        else {
+            // The MTU has been validated on creation
+            return Err(err!(InvalidArgumentError, "Invalid bridge MTU"))?;
+        };
+        *header = match self.reliability {
+            Some(_) => Self::HEADER_RELIABLE | self.tx_seq,
+            None => self.tx_seq,
+        };
+        for (slot, byte) in payload.iter_mut().zip(pending) {
+            *slot = *byte;
+        }
+        self.frame_len = self.pending_len.saturating_add(1);
+        self.pending_len = 0;
+        self.retries_left = self.reliability.map(|(_, retries)| retries).unwrap_or_default();
+        self.send_frame(driver, now)?;
+
+        // Unreliable frames are done once they are sent
+        if self.reliability.is_none() {
+            self.frame_len = 0;
+            self.tx_seq ^= Self::HEADER_SEQ;
+        }
+        Ok(())
+    }
+    /// Starts the transmission of the frame in flight
+    fn send_frame<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let frame = self.frame.get(..self.frame_len).unwrap_or_default();
+        driver.start_tx(frame)?;
+        self.transmitting = true;
+        self.sent_at = now;
+        Ok(())
+    }
+}
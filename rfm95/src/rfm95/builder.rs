@@ -0,0 +1,336 @@
+//! A builder for the RFM95 driver with init options
+
+use crate::err;
+use crate::error::{ConfigError, InvalidArgumentError, IoError};
+use crate::rfm95::capabilities::Capabilities;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::connection::Rfm95Connection;
+use crate::rfm95::driver::Sx127xDriver;
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::time::Duration;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{SpiBus, SpiDevice};
+use embedded_hal_bus::spi::ExclusiveDevice;
+
+/// The operation mode the modem is left in after initialization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitialMode {
+    /// Standby mode; the modem is ready to start TX or RX operations immediately
+    Standby,
+    /// Sleep mode; the modem consumes the least amount of power, but the FIFO is not accessible
+    Sleep,
+}
+
+/// The polarity of the reset line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetPolarity {
+    /// The reset is triggered by pulling the reset line low (the modem's native polarity)
+    ActiveLow,
+    /// The reset is triggered by pulling the reset line high, e.g. if the reset line is inverted by a transistor
+    ActiveHigh,
+}
+
+/// A builder for [`Sx127xDriver`] with init options
+///
+/// # Defaults
+/// The default options match [`Sx127xDriver::new`]: The reset line is active-low and asserted for 1 ms followed by a
+/// 5 ms settle time, the silicon revision is checked, the TX power is set to the maximum and the modem is left in
+/// standby.
+pub struct Sx127xBuilder<Device, Variant> {
+    /// The polarity of the reset line
+    reset_polarity: ResetPolarity,
+    /// The duration the reset line is asserted
+    reset_pulse: Duration,
+    /// The settle time after the reset line is deasserted
+    reset_settle: Duration,
+    /// Whether the silicon revision is checked
+    check_revision: bool,
+    /// Additionally accepted silicon revisions
+    extra_revisions: &'static [u8],
+    /// A callback to decide whether an unknown silicon revision is accepted
+    unknown_revision: Option<fn(u8) -> bool>,
+    /// The pinned chip capabilities, or `None` to use the capabilities of the chip variant
+    capabilities: Option<Capabilities>,
+    /// The initial TX power in dBm
+    tx_power: i8,
+    /// The operation mode the modem is left in after initialization
+    initial_mode: InitialMode,
+    /// The device type and chip variant
+    _device: PhantomData<fn() -> (Device, Variant)>,
+}
+impl<Device, Variant> Sx127xBuilder<Device, Variant>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+{
+    /// The default duration the reset line is asserted (the datasheet requires at least 100 µs)
+    pub const DEFAULT_RESET_PULSE: Duration = Duration::from_millis(1);
+    /// The default settle time after the reset line is deasserted (the datasheet requires at least 5 ms)
+    pub const DEFAULT_RESET_SETTLE: Duration = Duration::from_millis(5);
+
+    /// Creates a new builder with the default options
+    pub const fn new() -> Self {
+        Self {
+            reset_polarity: ResetPolarity::ActiveLow,
+            reset_pulse: Self::DEFAULT_RESET_PULSE,
+            reset_settle: Self::DEFAULT_RESET_SETTLE,
+            check_revision: true,
+            extra_revisions: &[],
+            unknown_revision: None,
+            capabilities: None,
+            tx_power: Sx127xDriver::<Device, Variant>::TX_POWER_MAX,
+            initial_mode: InitialMode::Standby,
+            _device: PhantomData,
+        }
+    }
+
+    /// The polarity of the reset line
+    pub const fn reset_polarity(&self) -> ResetPolarity {
+        self.reset_polarity
+    }
+    /// Sets the polarity of the reset line
+    pub const fn with_reset_polarity(mut self, reset_polarity: ResetPolarity) -> Self {
+        self.reset_polarity = reset_polarity;
+        self
+    }
+
+    /// The duration the reset line is asserted
+    pub const fn reset_pulse(&self) -> Duration {
+        self.reset_pulse
+    }
+    /// The settle time after the reset line is deasserted
+    pub const fn reset_settle(&self) -> Duration {
+        self.reset_settle
+    }
+    /// Sets the duration the reset line is asserted and the settle time after the reset line is deasserted
+    ///
+    /// # Blocking
+    /// The init blocks for the sum of both durations plus the time for the modem transactions. Some clone modules need
+    /// a longer settle time than the datasheet specifies.
+    pub const fn with_reset_timing(mut self, pulse: Duration, settle: Duration) -> Self {
+        self.reset_pulse = pulse;
+        self.reset_settle = settle;
+        self
+    }
+
+    /// Whether the silicon revision is checked
+    pub const fn check_revision(&self) -> bool {
+        self.check_revision
+    }
+    /// Sets whether the silicon revision is checked
+    ///
+    /// # Important
+    /// Other silicon revisions may be incompatible with this driver, so only disable the check if you know that your
+    /// module works (e.g. for clone modules or during bring-up).
+    pub const fn with_check_revision(mut self, check_revision: bool) -> Self {
+        self.check_revision = check_revision;
+        self
+    }
+    /// Additionally accepted silicon revisions
+    pub const fn extra_revisions(&self) -> &'static [u8] {
+        self.extra_revisions
+    }
+    /// Sets additionally accepted silicon revisions besides [`Sx127xDriver::SUPPORTED_SILICON_REVISIONS`]
+    ///
+    /// # Clone Modules
+    /// Clone modules often report other revisions (e.g. `0x13` or `0x22`) and mostly work fine; use this option to
+    /// accept known-good revisions without disabling the check entirely.
+    pub const fn with_extra_revisions(mut self, extra_revisions: &'static [u8]) -> Self {
+        self.extra_revisions = extra_revisions;
+        self
+    }
+    /// Sets a callback to decide whether an unknown silicon revision is accepted
+    ///
+    /// # Callback
+    /// The callback is called with the silicon revision if the revision is neither supported nor in the extra
+    /// revisions. If it returns `true`, the revision is accepted; this can be used to log a warning and continue.
+    pub const fn with_unknown_revision_callback(mut self, callback: fn(u8) -> bool) -> Self {
+        self.unknown_revision = Some(callback);
+        self
+    }
+
+    /// The pinned chip capabilities, or `None` if the capabilities of the chip variant are used
+    pub const fn capabilities(&self) -> Option<Capabilities> {
+        self.capabilities
+    }
+    /// Pins the chip capabilities instead of using the capabilities of the chip variant
+    ///
+    /// # Chip Variants
+    /// This is useful to restrict the driver to the band a module is actually matched for, e.g. a generic SX1276 module
+    /// that is only matched for 433 MHz. See also [`Chip::CAPABILITIES`].
+    pub const fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Checks whether the given silicon revision is accepted
+    pub(super) fn accepts_revision(&self, revision: u8) -> bool {
+        // Check the revision against the supported and extra revisions, and ask the callback as last resort
+        let known = Sx127xDriver::<Device, Variant>::SUPPORTED_SILICON_REVISIONS.contains(&revision)
+            || self.extra_revisions.contains(&revision);
+        match (known, self.unknown_revision) {
+            (true, _) => true,
+            (false, Some(callback)) => callback(revision),
+            (false, None) => false,
+        }
+    }
+
+    /// The initial TX power in dBm
+    pub const fn tx_power(&self) -> i8 {
+        self.tx_power
+    }
+    /// Sets the initial TX power in dBm
+    ///
+    /// # Power Range
+    /// The supported TX power depends on the PA output of the chip variant (e.g. `2..=17` dBm for the PA_BOOST pin, see
+    /// [`crate::rfm95::PaOutput`]); other values are rejected by [`Self::build`].
+    pub const fn with_tx_power(mut self, tx_power: i8) -> Self {
+        self.tx_power = tx_power;
+        self
+    }
+
+    /// The operation mode the modem is left in after initialization
+    pub const fn initial_mode(&self) -> InitialMode {
+        self.initial_mode
+    }
+    /// Sets the operation mode the modem is left in after initialization
+    pub const fn with_initial_mode(mut self, initial_mode: InitialMode) -> Self {
+        self.initial_mode = initial_mode;
+        self
+    }
+
+    /// Creates a new RFM95 driver from an [`SpiDevice`] with the configured options
+    ///
+    /// # Blocking
+    /// This function blocks for at least the configured reset timing plus additional time for the modem transactions.
+    /// If you have tight scheduling requirements, you probably want to initialize this driver before entering your main
+    /// event loop.
+    pub fn build<Reset, Timer>(
+        self,
+        device: Device,
+        mut reset: Reset,
+        mut timer: Timer,
+    ) -> Result<Sx127xDriver<Device, Variant>, ConfigError>
+    where
+        Reset: OutputPin,
+        Timer: DelayNs,
+    {
+        // Validate the options
+        self.validate()?;
+
+        // Fully reset module and init the driver
+        Sx127xDriver::<Device, Variant>::reset_module(&mut reset, &mut timer, &self)?;
+        let mut spi = Rfm95Connection::init(device);
+        Sx127xDriver::<Device, Variant>::wait_ready(&mut spi, &mut timer)?;
+        let driver = Sx127xDriver::setup(spi, &self)?;
+        Ok(driver)
+    }
+
+    /// Creates a new RFM95 driver from an [`SpiDevice`] without a reset pin with the configured options
+    ///
+    /// # Soft Init
+    /// See [`Sx127xDriver::new_without_reset`] for the caveats of initializing the modem without a hardware reset.
+    pub fn build_without_reset<Timer>(
+        self,
+        device: Device,
+        mut timer: Timer,
+    ) -> Result<Sx127xDriver<Device, Variant>, ConfigError>
+    where
+        Timer: DelayNs,
+    {
+        // Validate the options
+        self.validate()?;
+
+        // Soft-init the driver
+        let spi = Rfm95Connection::init(device);
+        let driver = Sx127xDriver::setup_without_reset(spi, &mut timer, &self)?;
+        Ok(driver)
+    }
+
+    /// Validates the options
+    fn validate(&self) -> Result<(), InvalidArgumentError> {
+        // Validate the TX power
+        let tx_power_range =
+            Sx127xDriver::<Device, Variant>::TX_POWER_MIN..=Sx127xDriver::<Device, Variant>::TX_POWER_MAX;
+        let true = tx_power_range.contains(&self.tx_power) else {
+            // The TX power is not supported
+            return Err(err!(InvalidArgumentError, "Unsupported TX power"));
+        };
+        Ok(())
+    }
+}
+impl<Bus, Select, Delay, Variant> Sx127xBuilder<ExclusiveDevice<Bus, Select, Delay>, Variant>
+where
+    Bus: SpiBus,
+    Select: OutputPin,
+    Delay: DelayNs,
+    Variant: Chip,
+{
+    /// Creates a new RFM95 driver from an SpiBus with the configured options
+    ///
+    /// # Blocking
+    /// This function blocks for at least the configured reset timing plus additional time for the modem transactions.
+    /// If you have tight scheduling requirements, you probably want to initialize this driver before entering your main
+    /// event loop.
+    pub fn build_from_bus<Reset>(
+        self,
+        mut bus: Bus,
+        mut select: Select,
+        mut reset: Reset,
+        mut timer: Delay,
+    ) -> Result<Sx127xDriver<ExclusiveDevice<Bus, Select, Delay>, Variant>, ConfigError>
+    where
+        Reset: OutputPin,
+    {
+        // Validate the options
+        self.validate()?;
+
+        // Fully reset module and wait until it answers via a temporary device handle that leaves the timer to us
+        Sx127xDriver::<ExclusiveDevice<Bus, Select, Delay>, Variant>::reset_module(&mut reset, &mut timer, &self)?;
+        let probe = ExclusiveDevice::new_no_delay(&mut bus, &mut select)
+            .map_err(|_| err!(IoError, "Failed to pull chip select line to high"))?;
+        let mut probe = Rfm95Connection::init(probe);
+        Sx127xDriver::<ExclusiveDevice<Bus, Select, Delay>, Variant>::wait_ready(&mut probe, &mut timer)?;
+
+        // Create exclusive device handle
+        let device = ExclusiveDevice::new(bus, select, timer)
+            .map_err(|_| err!(IoError, "Failed to pull chip select line to high"))?;
+
+        // Connect to and setup module and init the driver
+        let spi = Rfm95Connection::init(device);
+        let driver = Sx127xDriver::setup(spi, &self)?;
+        Ok(driver)
+    }
+}
+impl<Device, Variant> Default for Sx127xBuilder<Device, Variant>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<Device, Variant> Clone for Sx127xBuilder<Device, Variant> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Device, Variant> Copy for Sx127xBuilder<Device, Variant> {}
+impl<Device, Variant> Debug for Sx127xBuilder<Device, Variant> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        f.debug_struct("Sx127xBuilder")
+            .field("reset_polarity", &self.reset_polarity)
+            .field("reset_pulse", &self.reset_pulse)
+            .field("reset_settle", &self.reset_settle)
+            .field("check_revision", &self.check_revision)
+            .field("extra_revisions", &self.extra_revisions)
+            .field("unknown_revision", &self.unknown_revision)
+            .field("capabilities", &self.capabilities)
+            .field("tx_power", &self.tx_power)
+            .field("initial_mode", &self.initial_mode)
+            .finish()
+    }
+}
@@ -0,0 +1,120 @@
+//! Chip capabilities of the SX127x family
+
+use crate::err;
+use crate::error::InvalidArgumentError;
+use crate::lora::config::Config;
+use crate::lora::types::{Bandwidth, Frequency, SpreadingFactor};
+
+/// The capabilities of a SX127x chip variant
+///
+/// # Detection
+/// All SX127x variants report the same silicon revision, so the variant cannot be read via SPI. The driver thus uses
+/// the capabilities of its chip variant (see [`crate::rfm95::Chip`]); they can be pinned via
+/// [`crate::rfm95::Sx127xBuilder::with_capabilities`], or probed at runtime via a heuristic (see
+/// [`crate::rfm95::Sx127xDriver::probe_capabilities`]).
+///
+/// # Spreading Factors
+/// The datasheet ranges of all variants start at SF6, which is not implemented by this crate (see
+/// [`SpreadingFactor`]); the lowest usable spreading factor is thus SF7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The lowest supported frequency
+    frequency_min: Frequency,
+    /// The highest supported frequency
+    frequency_max: Frequency,
+    /// The highest supported spreading factor
+    spreading_factor_max: SpreadingFactor,
+    /// The widest supported bandwidth
+    bandwidth_max: Bandwidth,
+}
+impl Capabilities {
+    /// The SX1276 capabilities (137 - 1020 MHz, SF6 - SF12, up to 500 kHz)
+    pub const SX1276: Self =
+        Self::new(Frequency::hz(137_000_000), Frequency::hz(1_020_000_000), SpreadingFactor::S12, Bandwidth::B500);
+    /// The SX1277 capabilities (137 - 1020 MHz, SF6 - SF9, up to 500 kHz)
+    pub const SX1277: Self =
+        Self::new(Frequency::hz(137_000_000), Frequency::hz(1_020_000_000), SpreadingFactor::S9, Bandwidth::B500);
+    /// The SX1278 capabilities (137 - 525 MHz, SF6 - SF12, up to 500 kHz)
+    pub const SX1278: Self =
+        Self::new(Frequency::hz(137_000_000), Frequency::hz(525_000_000), SpreadingFactor::S12, Bandwidth::B500);
+    /// The SX1279 capabilities (137 - 960 MHz, SF6 - SF12, up to 500 kHz)
+    pub const SX1279: Self =
+        Self::new(Frequency::hz(137_000_000), Frequency::hz(960_000_000), SpreadingFactor::S12, Bandwidth::B500);
+    /// The RFM95 capabilities, which is a SX1276 with a matching network for 868/915 MHz
+    pub const RFM95: Self =
+        Self::new(Frequency::hz(862_000_000), Frequency::hz(1_020_000_000), SpreadingFactor::S12, Bandwidth::B500);
+    /// The RFM96/RFM98 capabilities, which is a SX1278 with a matching network for 433/470 MHz
+    pub const RFM96: Self =
+        Self::new(Frequency::hz(410_000_000), Frequency::hz(525_000_000), SpreadingFactor::S12, Bandwidth::B500);
+
+    /// The upper bound of the low frequency band, where bandwidths above 125 kHz are not supported
+    const BAND3_MAX_HZ: u32 = 175_000_000;
+
+    /// Creates new capabilities
+    pub const fn new(
+        frequency_min: Frequency,
+        frequency_max: Frequency,
+        spreading_factor_max: SpreadingFactor,
+        bandwidth_max: Bandwidth,
+    ) -> Self {
+        Self { frequency_min, frequency_max, spreading_factor_max, bandwidth_max }
+    }
+
+    /// The lowest supported frequency
+    pub const fn frequency_min(&self) -> Frequency {
+        self.frequency_min
+    }
+    /// The highest supported frequency
+    pub const fn frequency_max(&self) -> Frequency {
+        self.frequency_max
+    }
+    /// The highest supported spreading factor
+    pub const fn spreading_factor_max(&self) -> SpreadingFactor {
+        self.spreading_factor_max
+    }
+    /// The widest supported bandwidth
+    pub const fn bandwidth_max(&self) -> Bandwidth {
+        self.bandwidth_max
+    }
+
+    /// Whether the given frequency is supported
+    pub const fn supports_frequency(&self, frequency: Frequency) -> bool {
+        frequency.as_u32() >= self.frequency_min.as_u32() && frequency.as_u32() <= self.frequency_max.as_u32()
+    }
+    /// Whether the given spreading factor is supported
+    pub const fn supports_spreading_factor(&self, spreading_factor: SpreadingFactor) -> bool {
+        spreading_factor as u8 <= self.spreading_factor_max as u8
+    }
+    /// Whether the given bandwidth is supported at the given frequency
+    ///
+    /// # Low Frequency Band
+    /// Bandwidths above 125 kHz are not supported in the low frequency band around 169 MHz.
+    pub const fn supports_bandwidth(&self, bandwidth: Bandwidth, frequency: Frequency) -> bool {
+        // Get the widest supported bandwidth for the given frequency
+        let bandwidth_max = match frequency.as_u32() {
+            ..=Self::BAND3_MAX_HZ if self.bandwidth_max as u8 > Bandwidth::B125 as u8 => Bandwidth::B125,
+            _ => self.bandwidth_max,
+        };
+        bandwidth as u8 <= bandwidth_max as u8
+    }
+
+    /// Validates the given config against the capabilities, and returns the config if it is supported
+    pub const fn validate(&self, config: Config) -> Result<Config, InvalidArgumentError> {
+        // Validate the frequency
+        if !self.supports_frequency(config.frequency()) {
+            // The frequency is not supported by the chip
+            return Err(err!(InvalidArgumentError, "Frequency is not supported by the chip"));
+        }
+
+        // Validate the modulation parameters
+        if !self.supports_spreading_factor(config.spreading_factor()) {
+            // The spreading factor is not supported by the chip
+            return Err(err!(InvalidArgumentError, "Spreading factor is not supported by the chip"));
+        }
+        if !self.supports_bandwidth(config.bandwidth(), config.frequency()) {
+            // The bandwidth is not supported by the chip at this frequency
+            return Err(err!(InvalidArgumentError, "Bandwidth is not supported by the chip at this frequency"));
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,98 @@
+//! Chip variants of the SX127x family
+
+use crate::rfm95::capabilities::Capabilities;
+
+/// The power amplifier output pin a module is wired to
+///
+/// # Representation
+/// The enum is represented as the `PaSelect` field of `RegPaConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PaOutput {
+    /// The high-power PA_BOOST pin, which supports a TX power of `2..=17` dBm (used by most modules)
+    PaBoost = 0b1,
+    /// The RFO pin, which supports a TX power of `0..=15` dBm
+    Rfo = 0b0,
+}
+impl PaOutput {
+    /// Parses `self` from a register value
+    pub(crate) const fn parse(value: u8) -> Self {
+        match value {
+            pa_select if pa_select == Self::PaBoost as u8 => Self::PaBoost,
+            _ => Self::Rfo,
+        }
+    }
+
+    /// The minimum TX power in dBm
+    pub const fn tx_power_min(self) -> i8 {
+        match self {
+            Self::PaBoost => 2,
+            Self::Rfo => 0,
+        }
+    }
+    /// The maximum TX power in dBm
+    pub const fn tx_power_max(self) -> i8 {
+        match self {
+            Self::PaBoost => 17,
+            Self::Rfo => 15,
+        }
+    }
+}
+
+/// A SX127x chip variant or module, which controls the band limits and PA options of the driver
+///
+/// # Custom Modules
+/// This trait can be implemented for custom modules, e.g. a SX1276 breakout that is wired to the RFO pin.
+pub trait Chip {
+    /// The chip capabilities consulted by the config validation
+    const CAPABILITIES: Capabilities;
+    /// The power amplifier output pin the module is wired to
+    const PA_OUTPUT: PaOutput;
+}
+
+/// Declares a chip marker type
+macro_rules! chip {
+    ($doc:expr, $feature:literal, $type:ident, $capabilities:expr, $pa_output:expr) => {
+        #[doc = $doc]
+        #[cfg(feature = $feature)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $type;
+        #[cfg(feature = $feature)]
+        impl Chip for $type {
+            const CAPABILITIES: Capabilities = $capabilities;
+            const PA_OUTPUT: PaOutput = $pa_output;
+        }
+    };
+}
+
+// Module definitions
+chip! {
+    "The HopeRF RFM95 module (SX1276 with a matching network for 868/915 MHz)",
+    "rfm95", Rfm95, Capabilities::RFM95, PaOutput::PaBoost
+}
+chip! {
+    "The HopeRF RFM96 module (SX1278 with a matching network for 433/470 MHz)",
+    "rfm96", Rfm96, Capabilities::RFM96, PaOutput::PaBoost
+}
+chip! {
+    "The HopeRF RFM98 module (SX1278 with a matching network for 433/470 MHz)",
+    "rfm96", Rfm98, Capabilities::RFM96, PaOutput::PaBoost
+}
+
+// Generic chip definitions; most breakout boards use the PA_BOOST pin
+chip! {
+    "A generic SX1276 module using the PA_BOOST pin",
+    "sx127x", Sx1276, Capabilities::SX1276, PaOutput::PaBoost
+}
+chip! {
+    "A generic SX1277 module using the PA_BOOST pin",
+    "sx127x", Sx1277, Capabilities::SX1277, PaOutput::PaBoost
+}
+chip! {
+    "A generic SX1278 module using the PA_BOOST pin",
+    "sx127x", Sx1278, Capabilities::SX1278, PaOutput::PaBoost
+}
+chip! {
+    "A generic SX1279 module using the PA_BOOST pin",
+    "sx127x", Sx1279, Capabilities::SX1279, PaOutput::PaBoost
+}
@@ -0,0 +1,69 @@
+//! Channel-clear policies for polite transmitters
+
+use core::time::Duration;
+
+/// A policy to assess whether the channel is clear before transmitting (see
+/// [`crate::rfm95::Sx127xDriver::start_tx_when_clear`])
+///
+/// # Channel Assessment
+/// The channel is considered clear if none of the CAD cycles detects LoRa activity, and, if an RSSI threshold is set,
+/// the channel RSSI is below the threshold. CAD detects LoRa preambles with the current spreading factor and bandwidth
+/// even below the noise floor, while the RSSI check also detects other modulations and ongoing payloads.
+///
+/// # Retries
+/// If the channel is busy, the assessment is repeated after the backoff delay up to the given amount of retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClearChannelPolicy {
+    /// The amount of CAD cycles per assessment
+    cad_cycles: u8,
+    /// The RSSI threshold in dBm, if any
+    rssi_threshold: Option<i16>,
+    /// The amount of retries if the channel is busy
+    retries: u8,
+    /// The delay between two assessments
+    backoff: Duration,
+}
+impl ClearChannelPolicy {
+    /// Creates a new policy with a single CAD cycle, no RSSI check and no retries
+    pub const fn new() -> Self {
+        Self { cad_cycles: 1, rssi_threshold: None, retries: 0, backoff: Duration::ZERO }
+    }
+    /// Sets the amount of CAD cycles per assessment
+    pub const fn with_cad_cycles(mut self, cad_cycles: u8) -> Self {
+        self.cad_cycles = cad_cycles;
+        self
+    }
+    /// Additionally requires the channel RSSI to be below the given threshold in dBm
+    pub const fn with_rssi_threshold(mut self, rssi_threshold: i16) -> Self {
+        self.rssi_threshold = Some(rssi_threshold);
+        self
+    }
+    /// Sets the amount of retries and the delay between two assessments if the channel is busy
+    pub const fn with_retries(mut self, retries: u8, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// The amount of CAD cycles per assessment
+    pub const fn cad_cycles(&self) -> u8 {
+        self.cad_cycles
+    }
+    /// The RSSI threshold in dBm, if any
+    pub const fn rssi_threshold(&self) -> Option<i16> {
+        self.rssi_threshold
+    }
+    /// The amount of retries if the channel is busy
+    pub const fn retries(&self) -> u8 {
+        self.retries
+    }
+    /// The delay between two assessments
+    pub const fn backoff(&self) -> Duration {
+        self.backoff
+    }
+}
+impl Default for ClearChannelPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,41 @@
+//! The clock output of the modem
+
+/// The clock output (`ClkOut`) on DIO5, derived from the crystal oscillator (FXOSC)
+///
+/// # Representation
+/// The enum is represented as the `ClkOut` field of the FSK-bank `RegOsc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ClkOut {
+    /// The crystal oscillator frequency (32 MHz)
+    Fxosc = 0b000,
+    /// The crystal oscillator frequency divided by 2 (16 MHz)
+    Fxosc2 = 0b001,
+    /// The crystal oscillator frequency divided by 4 (8 MHz)
+    Fxosc4 = 0b010,
+    /// The crystal oscillator frequency divided by 8 (4 MHz)
+    Fxosc8 = 0b011,
+    /// The crystal oscillator frequency divided by 16 (2 MHz)
+    Fxosc16 = 0b100,
+    /// The crystal oscillator frequency divided by 32 (1 MHz)
+    Fxosc32 = 0b101,
+    /// The internal RC oscillator
+    Rc = 0b110,
+    /// The clock output is disabled, which saves power
+    Off = 0b111,
+}
+impl ClkOut {
+    /// Parses `self` from a register value
+    pub(crate) const fn parse(value: u8) -> Self {
+        match value {
+            0b000 => Self::Fxosc,
+            0b001 => Self::Fxosc2,
+            0b010 => Self::Fxosc4,
+            0b011 => Self::Fxosc8,
+            0b100 => Self::Fxosc16,
+            0b101 => Self::Fxosc32,
+            0b110 => Self::Rc,
+            _ => Self::Off,
+        }
+    }
+}
@@ -13,6 +13,9 @@ where
 {
     /// The SPI device
     device: Device,
+    /// The amount of failed SPI transactions
+    #[cfg(feature = "stats")]
+    io_errors: u32,
 }
 impl<Device> Rfm95Connection<Device>
 where
@@ -22,10 +25,22 @@ where
     const RO: u8 = 0b0000_0000;
     /// A register write operation
     const RW: u8 = 0b1000_0000;
+    /// The address of the operation mode register
+    #[cfg(feature = "defmt-trace")]
+    const REG_OPMODE: u8 = 0x01;
 
     /// Creates a new RFM95 SPI connection
     pub const fn init(device: Device) -> Self {
-        Self { device }
+        Self {
+            device,
+            #[cfg(feature = "stats")]
+            io_errors: 0,
+        }
+    }
+
+    /// Releases the SPI device
+    pub fn release(self) -> Device {
+        self.device
     }
 
     /// Reads a RFM95 register via SPI
@@ -53,10 +68,28 @@ where
             self.register(Self::RW, register.address(), value)?;
         }
 
+        // Trace mode transitions
+        #[cfg(feature = "defmt-trace")]
+        if register.address() == Self::REG_OPMODE {
+            let mode = self.register(Self::RO, Self::REG_OPMODE, 0x00)?;
+            defmt::trace!("rfm95: mode -> {=str}", Self::mode_name(mode));
+        }
+
         // Operation successful
         Ok(())
     }
 
+    /// The amount of failed SPI transactions
+    #[cfg(feature = "stats")]
+    pub const fn io_errors(&self) -> u32 {
+        self.io_errors
+    }
+    /// Resets the amount of failed SPI transactions
+    #[cfg(feature = "stats")]
+    pub fn reset_io_errors(&mut self) {
+        self.io_errors = 0;
+    }
+
     /// Performs RFM95-specific SPI register access
     fn register(&mut self, operation: u8, address: u8, payload: u8) -> Result<u8, IoError> {
         // Build command
@@ -64,8 +97,12 @@ where
         let mut command = [operation | address, payload];
 
         // Do transaction
-        (self.device.transfer_in_place(&mut command))
-            .map_err(|_| err!(IoError, "Failed to do GPIO operation or SPI transaction"))?;
+        let transaction = self.device.transfer_in_place(&mut command);
+        #[cfg(feature = "stats")]
+        if transaction.is_err() {
+            self.io_errors = self.io_errors.saturating_add(1);
+        }
+        transaction.map_err(|_| err!(IoError, "Failed to do GPIO operation or SPI transaction"))?;
 
         // SPI debug callback
         #[cfg(feature = "debug")]
@@ -79,9 +116,34 @@ where
             embeddedrfm95_spidebug_AwiUzTRu(operation, address, payload, command[1]);
         }
 
+        // Trace the transaction
+        #[cfg(feature = "defmt-trace")]
+        defmt::trace!(
+            "rfm95: {=str} [0x{=u8:02X}] tx:0x{=u8:02X} rx:0x{=u8:02X}",
+            if operation == Self::RW { "write" } else { "read" },
+            address,
+            payload,
+            command[1]
+        );
+
         // Return the previous register value
         Ok(command[1])
     }
+
+    /// Gets a human readable name for the device mode of the given `RegOpMode` value
+    #[cfg(feature = "defmt-trace")]
+    const fn mode_name(opmode: u8) -> &'static str {
+        match opmode & 0b111 {
+            0b000 => "SLEEP",
+            0b001 => "STDBY",
+            0b010 => "FSTX",
+            0b011 => "TX",
+            0b100 => "FSRX",
+            0b101 => "RXCONTINUOUS",
+            0b110 => "RXSINGLE",
+            _ => "CAD",
+        }
+    }
 }
 impl<Device> Debug for Rfm95Connection<Device>
 where
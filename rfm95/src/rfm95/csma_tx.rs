@@ -0,0 +1,180 @@
+//! A non-blocking CSMA/CA transmitter on top of channel activity detection
+
+use crate::err;
+use crate::error::{BufferTooSmallError, TxStartError};
+use crate::lora::csma::Csma;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::spi::SpiDevice;
+
+/// The outcome of a poll of a CSMA/CA transmitter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsmaOutcome {
+    /// The transmitter is idle, i.e. no frame is queued
+    Idle,
+    /// The transmitter is backing off or sensing the channel
+    Pending,
+    /// The channel was clear and the TX operation has been started; use
+    /// [`crate::rfm95::Sx127xDriver::complete_tx`] to check if it is done
+    Started,
+    /// The channel was busy for all attempts and the frame has been dropped
+    ChannelBusy,
+}
+
+/// The state of a CSMA/CA transmitter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsmaState {
+    /// No frame is queued
+    Idle,
+    /// Backing off until the given timestamp
+    Backoff(u64),
+    /// A CAD operation is running
+    Sensing,
+}
+
+/// A non-blocking CSMA/CA transmitter with binary exponential backoff
+///
+/// # Algorithm
+/// Before a frame is sent, the transmitter waits for a random initial backoff within the backoff window of the
+/// [`Csma`] generator, and senses the channel via CAD. If the channel is busy, the backoff window is doubled (up to the
+/// maximum window) and the transmitter backs off again, up to the given amount of attempts. If the channel is clear,
+/// the TX operation is started.
+///
+/// # Usage
+/// Queue a frame via [`Self::send`], then call [`Self::poll`] regularly until it returns something else than
+/// [`CsmaOutcome::Pending`]. No other operation should be started on the driver in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsmaTransmitter<const MTU: usize> {
+    /// The backoff generator
+    csma: Csma,
+    /// The maximum backoff window in slots
+    max_window: u16,
+    /// The maximum amount of channel assessments per frame
+    max_attempts: u8,
+    /// The queued frame
+    frame: [u8; MTU],
+    /// The length of the queued frame
+    frame_len: usize,
+    /// The amount of channel assessments of the queued frame
+    attempts: u8,
+    /// The state of the transmitter
+    state: CsmaState,
+}
+impl<const MTU: usize> CsmaTransmitter<MTU> {
+    /// Creates a new transmitter with the given backoff generator and maximum amount of channel assessments per frame
+    ///
+    /// # Backoff Window
+    /// The backoff window of `csma` is used as initial window; the maximum window defaults to 32 times the initial
+    /// window (see [`Self::with_max_window`]).
+    pub fn new(csma: Csma, max_attempts: u8) -> Self {
+        let max_window = csma.window().saturating_mul(32);
+        Self { csma, max_window, max_attempts, frame: [0; MTU], frame_len: 0, attempts: 0, state: CsmaState::Idle }
+    }
+    /// Sets the maximum backoff window in slots
+    pub const fn with_max_window(mut self, max_window: u16) -> Self {
+        self.max_window = max_window;
+        self
+    }
+
+    /// The maximum backoff window in slots
+    pub const fn max_window(&self) -> u16 {
+        self.max_window
+    }
+    /// The maximum amount of channel assessments per frame
+    pub const fn max_attempts(&self) -> u8 {
+        self.max_attempts
+    }
+    /// Whether no frame is queued
+    pub const fn is_idle(&self) -> bool {
+        matches!(self.state, CsmaState::Idle)
+    }
+    /// The timestamp at which the current backoff elapses, or `None` if the transmitter is not backing off
+    pub const fn backoff_until(&self) -> Option<u64> {
+        match self.state {
+            CsmaState::Backoff(until) => Some(until),
+            CsmaState::Idle | CsmaState::Sensing => None,
+        }
+    }
+
+    /// Queues the given frame for transmission, replacing any queued frame, and starts the initial backoff
+    pub fn send(&mut self, data: &[u8], now: u64) -> Result<(), BufferTooSmallError> {
+        // Copy the frame
+        let Some(frame) = self.frame.get_mut(..data.len()) else {
+            // The frame does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "Frame exceeds the transmitter MTU"));
+        };
+        frame.copy_from_slice(data);
+        self.frame_len = data.len();
+
+        // Start the initial backoff
+        self.attempts = 0;
+        self.backoff(now);
+        Ok(())
+    }
+    /// Drops the queued frame, if any
+    ///
+    /// # Important
+    /// A running CAD operation is not aborted; it completes on its own.
+    pub fn cancel(&mut self) {
+        self.state = CsmaState::Idle;
+    }
+
+    /// Advances the transmitter
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. It starts a CAD operation once the backoff has elapsed, and starts the TX
+    /// operation once the CAD operation reports a clear channel.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<CsmaOutcome, TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match self.state {
+            CsmaState::Idle => Ok(CsmaOutcome::Idle),
+            CsmaState::Backoff(until) if now < until => Ok(CsmaOutcome::Pending),
+            CsmaState::Backoff(_) => {
+                // Sense the channel
+                driver.start_cad()?;
+                self.state = CsmaState::Sensing;
+                Ok(CsmaOutcome::Pending)
+            }
+            CsmaState::Sensing => {
+                let Some(busy) = driver.complete_cad()? else {
+                    // The CAD operation has not been completed yet
+                    return Ok(CsmaOutcome::Pending);
+                };
+
+                // Transmit if the channel is clear
+                self.attempts = self.attempts.saturating_add(1);
+                if !busy {
+                    self.state = CsmaState::Idle;
+                    driver.start_tx(self.frame.get(..self.frame_len).unwrap_or_default())?;
+                    return Ok(CsmaOutcome::Started);
+                }
+
+                // Give up or back off with a doubled window
+                if self.attempts >= self.max_attempts {
+                    self.state = CsmaState::Idle;
+                    return Ok(CsmaOutcome::ChannelBusy);
+                }
+                self.backoff(now);
+                Ok(CsmaOutcome::Pending)
+            }
+        }
+    }
+
+    /// Starts a random backoff within the window of the current attempt
+    fn backoff(&mut self, now: u64) {
+        let window = (self.csma.window() as u32).checked_shl(self.attempts as u32).unwrap_or(u32::MAX);
+        let window = u16::try_from(window).unwrap_or(u16::MAX).min(self.max_window);
+        let backoff = self.csma.backoff_within(window);
+        let backoff = u64::try_from(backoff.as_micros()).unwrap_or(u64::MAX);
+        self.state = CsmaState::Backoff(now.saturating_add(backoff));
+    }
+}
@@ -0,0 +1,250 @@
+//! Morse code keyed carrier (CW)
+
+use crate::err;
+use crate::error::{InvalidArgumentError, IoError, TxStartError};
+use crate::lora::types::Frequency;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::keying::KeyedTx;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::spi::SpiDevice;
+
+/// A keyer that transmits a message in Morse code as an on-off keyed unmodulated carrier (CW)
+///
+/// # Timing
+/// The timing follows the `PARIS` standard: a dot is one unit of `1.2 / WPM` seconds long, a dash three units, and the
+/// gaps between elements, characters and words are one, three and seven units long.
+///
+/// # Signal
+/// Each element keys the unmodulated carrier (see [`Sx127xDriver::start_carrier`]), so the emission is A1A. The modem
+/// stays in carrier mode for the whole message; the LoRa config is restored once the keyer stops.
+///
+/// # Usage
+/// Create the keyer with the message, then start it via [`Self::start`], and call [`Self::poll`] regularly until it
+/// returns `false`. The LoRa config and the previous frequency and TX power are restored once the message has been sent
+/// or the keyer has been stopped via [`Self::stop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CwKeyer<'a> {
+    /// The message to send
+    message: &'a [u8],
+    /// The keyed transmission
+    keyed: KeyedTx,
+    /// The length of a dot in microseconds
+    unit: u64,
+    /// The index of the current character within the message
+    index: usize,
+    /// The index of the current element within the character
+    element: u8,
+}
+impl<'a> CwKeyer<'a> {
+    /// The length of a dot at 1 WPM in microseconds
+    const UNIT_1WPM: u64 = 1_200_000;
+    /// The gap between two elements of a character in units
+    const ELEMENT_GAP: u64 = 1;
+    /// The gap between two characters in units
+    const CHARACTER_GAP: u64 = 3;
+    /// The gap between two words in units
+    const WORD_GAP: u64 = 7;
+
+    /// Creates a new keyer that sends the given message on the given frequency and TX power in dBm at 20 WPM
+    ///
+    /// # Message
+    /// The message may contain letters, digits, spaces and the characters `/`, `?`, `.`, `,`, `=` and `-`; letters are
+    /// case-insensitive.
+    pub fn new(message: &'a str, frequency: Frequency, tx_power: i8) -> Result<Self, InvalidArgumentError> {
+        let message = message.as_bytes();
+        if !message.iter().any(|&character| character != b' ') {
+            return Err(err!(InvalidArgumentError, "Message is empty"));
+        }
+        if !message.iter().all(|&character| character == b' ' || Self::morse(character).is_some()) {
+            return Err(err!(InvalidArgumentError, "Message contains characters without Morse code"));
+        }
+
+        let unit = Self::UNIT_1WPM / 20;
+        Ok(Self { message, keyed: KeyedTx::new(frequency, tx_power), unit, index: 0, element: 0 })
+    }
+    /// Sets the speed in words per minute
+    ///
+    /// # Range
+    /// The speed is clamped to `1..=60` WPM.
+    pub fn with_wpm(mut self, wpm: u8) -> Self {
+        let wpm = u64::from(wpm.clamp(1, 60));
+        self.unit = Self::UNIT_1WPM.checked_div(wpm).unwrap_or(Self::UNIT_1WPM);
+        self
+    }
+
+    /// The frequency to transmit on
+    pub const fn frequency(&self) -> Frequency {
+        self.keyed.frequency()
+    }
+    /// The TX power in dBm
+    pub const fn tx_power(&self) -> i8 {
+        self.keyed.tx_power()
+    }
+    /// Whether the keyer is sending the message
+    pub const fn is_running(&self) -> bool {
+        self.keyed.is_running()
+    }
+    /// Whether the transmitter is keyed
+    pub const fn is_keyed(&self) -> bool {
+        self.keyed.is_keyed()
+    }
+
+    /// Tunes to the frequency and TX power, and starts sending the message from the beginning
+    pub fn start<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Tune and start with the first character
+        self.keyed.tune(driver)?;
+        self.index = self.message.iter().position(|&character| character != b' ').unwrap_or(self.message.len());
+        self.element = 0;
+        self.key(driver, now)
+    }
+    /// Leaves the carrier mode, restores the previous frequency and TX power, and stops the keyer
+    pub fn stop<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.keyed.stop(driver)
+    }
+
+    /// Keys and unkeys the transmitter according to the message, and returns whether the keyer is still running
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. Once the message has been sent, the keyer is stopped.
+    ///
+    /// # Timing Accuracy
+    /// Each element lasts until the first poll after its end, so poll at least every few milliseconds to keep the
+    /// timing readable.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<bool, TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Advance once the current element or gap has passed
+        match (self.keyed.is_due(now), self.keyed.is_keyed()) {
+            (true, true) => self.unkey(driver, now)?,
+            (true, false) => self.key(driver, now)?,
+            (false, _) => (),
+        }
+        Ok(self.is_running())
+    }
+
+    /// Keys the transmitter for the current element, or stops the keyer at the end of the message
+    fn key<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let Some((_, pattern)) = self.message.get(self.index).and_then(|&character| Self::morse(character)) else {
+            // The message has been sent
+            return self.stop(driver);
+        };
+
+        // Key for a dot or a dash
+        let units = match (pattern >> self.element) & 1 {
+            0 => 1,
+            _ => 3,
+        };
+        self.keyed.key(driver, now, self.unit.saturating_mul(units))
+    }
+    /// Unkeys the transmitter, and advances to the next element for the gap after the current element
+    fn unkey<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Advance to the next element, character or word
+        let len = self.message.get(self.index).and_then(|&character| Self::morse(character)).map_or(0, |(len, _)| len);
+        self.element = self.element.saturating_add(1);
+        let mut units = Self::ELEMENT_GAP;
+        if self.element >= len {
+            self.element = 0;
+            self.index = self.index.saturating_add(1);
+            units = Self::CHARACTER_GAP;
+            while self.message.get(self.index) == Some(&b' ') {
+                self.index = self.index.saturating_add(1);
+                units = Self::WORD_GAP;
+            }
+        }
+        self.keyed.unkey(driver, now, self.unit.saturating_mul(units))
+    }
+
+    /// The Morse code of a character as amount of elements and element pattern, where bit `n` is set if the `n`-th
+    /// element is a dash
+    const fn morse(character: u8) -> Option<(u8, u8)> {
+        let code = match character.to_ascii_uppercase() {
+            b'A' => (2, 0b10),
+            b'B' => (4, 0b0001),
+            b'C' => (4, 0b0101),
+            b'D' => (3, 0b001),
+            b'E' => (1, 0b0),
+            b'F' => (4, 0b0100),
+            b'G' => (3, 0b011),
+            b'H' => (4, 0b0000),
+            b'I' => (2, 0b00),
+            b'J' => (4, 0b1110),
+            b'K' => (3, 0b101),
+            b'L' => (4, 0b0010),
+            b'M' => (2, 0b11),
+            b'N' => (2, 0b01),
+            b'O' => (3, 0b111),
+            b'P' => (4, 0b0110),
+            b'Q' => (4, 0b1011),
+            b'R' => (3, 0b010),
+            b'S' => (3, 0b000),
+            b'T' => (1, 0b1),
+            b'U' => (3, 0b100),
+            b'V' => (4, 0b1000),
+            b'W' => (3, 0b110),
+            b'X' => (4, 0b1001),
+            b'Y' => (4, 0b1101),
+            b'Z' => (4, 0b0011),
+            b'0' => (5, 0b11111),
+            b'1' => (5, 0b11110),
+            b'2' => (5, 0b11100),
+            b'3' => (5, 0b11000),
+            b'4' => (5, 0b10000),
+            b'5' => (5, 0b00000),
+            b'6' => (5, 0b00001),
+            b'7' => (5, 0b00011),
+            b'8' => (5, 0b00111),
+            b'9' => (5, 0b01111),
+            b'/' => (5, 0b01001),
+            b'?' => (6, 0b001100),
+            b'.' => (6, 0b101010),
+            b',' => (6, 0b110011),
+            b'=' => (5, 0b10001),
+            b'-' => (6, 0b100001),
+            _ => return None,
+        };
+        Some(code)
+    }
+}
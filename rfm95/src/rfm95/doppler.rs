@@ -0,0 +1,124 @@
+//! Doppler shift compensation for moving nodes
+
+use crate::error::ConfigError;
+use crate::lora::airtime;
+use crate::lora::types::{Bandwidth, Frequency};
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::frequency_word::FrequencyWord;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::spi::SpiDevice;
+
+/// A Doppler compensation for a link between moving nodes, e.g. balloons, vehicles or satellites
+///
+/// # Velocity
+/// The relative velocity is given in m/s along the line of sight; positive values mean the nodes approach each other,
+/// which raises the received frequency. The velocity is usually derived from GNSS positions or orbit predictions.
+///
+/// # Usage
+/// Compute the velocity before each packet and retune the carrier via [`Self::retune_rx`] before receiving or via
+/// [`Self::retune_tx`] before transmitting. Usually only one side of the link (e.g. the ground station) compensates the
+/// shift, so the other side can stay on the nominal frequency. The carrier must not be retuned during a packet.
+///
+/// # Tolerance
+/// The modem tolerates a static frequency offset between transmitter and receiver of up to 25 % of the bandwidth (see
+/// [`Self::max_offset`]); smaller shifts do not strictly require compensation. However, at SF11 and SF12 with narrow
+/// bandwidths, the drift of the offset during a long packet matters as well: prefer wider bandwidths or lower spreading
+/// factors for fast-moving nodes, and retune before every packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Doppler {
+    /// The nominal carrier frequency
+    nominal: Frequency,
+}
+impl Doppler {
+    /// The speed of light in m/s
+    const SPEED_OF_LIGHT: f32 = 299_792_458.0;
+
+    /// Creates a new Doppler compensation for the given nominal carrier frequency
+    pub const fn new(nominal: Frequency) -> Self {
+        Self { nominal }
+    }
+
+    /// The nominal carrier frequency
+    pub const fn nominal(&self) -> Frequency {
+        self.nominal
+    }
+
+    /// The Doppler shift in Hz of the given frequency for the given relative velocity in m/s
+    pub fn shift(frequency: Frequency, velocity: f32) -> i32 {
+        let shift = frequency.as_u32() as f32 * velocity / Self::SPEED_OF_LIGHT;
+        #[allow(clippy::cast_possible_truncation, reason = "Float-to-int casts saturate")]
+        let shift = shift as i32;
+        shift
+    }
+    /// The maximum frequency offset in Hz between transmitter and receiver that the modem tolerates for the given
+    /// bandwidth
+    pub fn max_offset(bandwidth: Bandwidth) -> u32 {
+        // The bandwidth is the inverse of the chip duration
+        let chip_nanos = u32::try_from(airtime::chip_duration(bandwidth).as_nanos()).unwrap_or(u32::MAX);
+        let bandwidth_hz = 1_000_000_000u32.checked_div(chip_nanos).unwrap_or_default();
+        bandwidth_hz.checked_div(4).unwrap_or_default()
+    }
+    /// Whether the Doppler shift for the given relative velocity in m/s is within the tolerance of the given bandwidth
+    pub fn is_tolerable(&self, velocity: f32, bandwidth: Bandwidth) -> bool {
+        Self::shift(self.nominal, velocity).unsigned_abs() <= Self::max_offset(bandwidth)
+    }
+
+    /// The frequency to receive on, to compensate the shift for the given relative velocity in m/s
+    pub fn rx_frequency(&self, velocity: f32) -> Frequency {
+        self.nominal.offset(Self::shift(self.nominal, velocity))
+    }
+    /// The frequency to transmit on, to pre-compensate the shift for the given relative velocity in m/s, so the peer
+    /// receives on the nominal frequency
+    pub fn tx_frequency(&self, velocity: f32) -> Frequency {
+        self.nominal.offset(Self::shift(self.nominal, velocity).saturating_neg())
+    }
+
+    /// Retunes the carrier to receive with the given relative velocity in m/s and returns the tuned frequency
+    ///
+    /// # Fast Path
+    /// The carrier is retuned via [`Sx127xDriver::set_frequency_fast`]; the frequency correction of the driver (see
+    /// [`Sx127xDriver::set_frequency_correction_ppm`]) is applied.
+    pub fn retune_rx<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        velocity: f32,
+    ) -> Result<Frequency, ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        Self::retune(driver, self.rx_frequency(velocity))
+    }
+    /// Retunes the carrier to transmit with the given relative velocity in m/s and returns the tuned frequency
+    ///
+    /// # Fast Path
+    /// See [`Self::retune_rx`].
+    pub fn retune_tx<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        velocity: f32,
+    ) -> Result<Frequency, ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        Self::retune(driver, self.tx_frequency(velocity))
+    }
+    /// Retunes the carrier to the given frequency
+    fn retune<Device, Variant, Switch>(
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        frequency: Frequency,
+    ) -> Result<Frequency, ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let corrected = frequency.with_ppm(driver.frequency_correction_ppm());
+        driver.set_frequency_fast(&FrequencyWord::new(corrected))?;
+        Ok(frequency)
+    }
+}
@@ -1,64 +1,339 @@
 //! RFM95 driver for LoRa operations
 
+use crate::clock::Clock;
 use crate::err;
+#[cfg(feature = "compression")]
+use crate::error::ProtocolError;
 use crate::error::{
-    InvalidArgumentError, InvalidMessageError, IoError, RxCompleteError, RxStartError, TimeoutError, TxStartError,
+    BufferTooSmallError, BusyError, ConfigError, DwellTimeError, InvalidArgumentError, IoError, RxCompleteError,
+    RxStartError, TxStartError,
 };
 use crate::lora::airtime;
+use crate::lora::checksum::{self, Checksum};
+#[cfg(feature = "compression")]
+use crate::lora::compression;
 use crate::lora::config::Config;
+use crate::lora::region;
+use crate::lora::regulatory::Regulatory;
 use crate::lora::types::*;
+use crate::rfm95::builder::{InitialMode, ResetPolarity, Sx127xBuilder};
+use crate::rfm95::capabilities::Capabilities;
+use crate::rfm95::chip::{Chip, PaOutput};
+use crate::rfm95::clear_channel::ClearChannelPolicy;
+use crate::rfm95::clk_out::ClkOut;
 use crate::rfm95::connection::Rfm95Connection;
+use crate::rfm95::dump::{DecodedRegisters, REGISTER_DUMP_SIZE};
+#[cfg(feature = "debug")]
+use crate::rfm95::dump::{FifoDump, FIFO_DUMP_SIZE};
+use crate::rfm95::event::Event;
+use crate::rfm95::fifo::FifoReader;
+use crate::rfm95::frequency_word::FrequencyWord;
+use crate::rfm95::hooks::RadioHooks;
+use crate::rfm95::metadata::RxMetadata;
+use crate::rfm95::outcome::RxOutcome;
 use crate::rfm95::registers::*;
-use crate::rfm95::RFM95_FIFO_SIZE;
+use crate::rfm95::rf_switch::{NoRfSwitch, RfSwitch, RfSwitchState};
+use crate::rfm95::rx_counters::RxCounters;
+use crate::rfm95::self_test::SelfTestReport;
+#[cfg(feature = "stats")]
+use crate::rfm95::stats::DriverStats;
 use core::cmp;
 use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
 use core::time::Duration;
 use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::OutputPin;
+use embedded_hal::digital::{InputPin, OutputPin};
 use embedded_hal::spi::{SpiBus, SpiDevice};
 use embedded_hal_bus::spi::ExclusiveDevice;
 
-/// Raw SPI command interface for RFM95
-pub struct Rfm95Driver<Device>
+/// Raw SPI command interface for the SX127x family, e.g. the RFM95
+///
+/// # Chip Variants
+/// The `Variant` marker type controls the band limits and PA options of the driver (see [`Chip`]). For the supported
+/// modules, there are type aliases like [`crate::rfm95::Rfm95Driver`].
+///
+/// # RF Switch
+/// The `Switch` type controls an external RF switch or front-end module around mode changes (see [`RfSwitch`]). By
+/// default, no RF switch is controlled; to install one, use [`Self::with_rf_switch`].
+pub struct Sx127xDriver<Device, Variant, Switch = NoRfSwitch>
 where
     Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
 {
     /// The SPI connection to the RFM95 radio
     spi: Rfm95Connection<Device>,
+    /// Whether a prepared RX operation should be started once the current TX operation has completed
+    rx_after_tx: bool,
+    /// The regulatory policy consulted before each TX operation
+    regulatory: Option<Regulatory>,
+    /// The maximum dwell time per transmission
+    max_dwell: Option<Duration>,
+    /// The frequency correction in ppm applied by `set_frequency`
+    frequency_correction_ppm: i32,
+    /// The chip capabilities consulted by the config validation
+    capabilities: Capabilities,
+    /// The valid packet count observed by the last continuous RX poll
+    rx_packet_count: u16,
+    /// The amount of continuous RX packets that have been dropped since the last retrieved packet
+    rx_dropped: u16,
+    /// The FIFO address and remaining length of a partially read message
+    rx_cursor: Option<(u8, u8)>,
+    /// The external RF switch
+    rf_switch: Switch,
+    /// The RF state the modem was last put into by the driver
+    rf_state: RfSwitchState,
+    /// The hooks invoked around TX and RX operations
+    hooks: RadioHooks,
+    /// The LoRa state to restore while the modem is in carrier mode
+    carrier: Option<LoraState>,
+    /// The statistics counters
+    #[cfg(feature = "stats")]
+    stats: DriverStats,
+    /// The chip variant
+    _variant: PhantomData<fn() -> Variant>,
 }
-impl<Device> Rfm95Driver<Device>
+impl<Device, Variant, Switch> Sx127xDriver<Device, Variant, Switch>
 where
     Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
 {
     /// Supported silicon revisions for compatibility check
-    #[cfg(not(feature = "debug"))]
-    const SUPPORTED_SILICON_REVISIONS: [u8; 2] = [0x11, 0x12];
-    /// The frequency divider to compute the frequency in milli-hertz
-    const FREQUENCY_DIVIDER_MILLIHZ: u64 = 61_035;
+    pub const SUPPORTED_SILICON_REVISIONS: [u8; 2] = [0x11, 0x12];
+    /// The time budget in microseconds until the chip must answer after a reset
+    const READY_TIMEOUT_US: u32 = 10_000;
+    /// The interval in microseconds between two polls until the chip answers
+    const READY_POLL_INTERVAL_US: u32 = 100;
+    /// The frequency step of the modem in Hz, rounded up
+    const FREQUENCY_STEP_HZ: u32 = 62;
+    /// The minimum TX power in dBm for the PA output of the chip variant
+    pub const TX_POWER_MIN: i8 = Variant::PA_OUTPUT.tx_power_min();
+    /// The maximum TX power in dBm for the PA output of the chip variant
+    pub const TX_POWER_MAX: i8 = Variant::PA_OUTPUT.tx_power_max();
+    /// The maximum RX timeout in symbols supported by the modem
+    pub const SYMBOL_TIMEOUT_MAX: u16 = 1023;
     /// The threshold for switching between low-frequency mode (below 525 MHz) and high frequency mode (above 779 MHz)
-    const HIGH_FREQUENCY_THRESHOLD: Frequency = Frequency::hz(652_000_000);
+    const HIGH_FREQUENCY_THRESHOLD: Frequency = FrequencyWord::HIGH_FREQUENCY_THRESHOLD;
 
     /// The register value to put the device to LoRa mode
     const REG_OPMODE_LONGRANGEMODE_LORA: u8 = 0b1;
+    /// The register value to put the device to FSK/OOK mode
+    const REG_OPMODE_LONGRANGEMODE_FSK: u8 = 0b0;
+    /// The register value to select the FSK modulation in FSK/OOK mode
+    const REG_OPMODE_MODULATIONTYPE_FSK: u8 = 0b00;
     /// The register value to set the shared registers to LoRa mode
     const REG_OPMODE_ACCESSSHAREDREG_LORA: u8 = 0b0;
+    /// The register value to map the shared registers to the FSK register bank
+    const REG_OPMODE_ACCESSSHAREDREG_FSK: u8 = 0b1;
     /// The pre-assembled register value for the operation mode register to put the device to sleep
     const REG_OPMODE_MODE_SLEEP: u8 = 0b000;
     /// The pre-assembled register value for the operation mode register to go into standby during LoRa mode
     const REG_OPMODE_MODE_STANDBY: u8 = 0b001;
+    /// The pre-assembled register value for the operation mode register to enter the TX frequency synthesis mode
+    const REG_OPMODE_MODE_FSTX: u8 = 0b010;
     /// The pre-assembled register value for the operation mode register to start a single LoRa TX transmission
     const REG_OPMODE_MODE_TXSINGLE: u8 = 0b011;
+    /// The pre-assembled register value for the operation mode register to enter the RX frequency synthesis mode
+    const REG_OPMODE_MODE_FSRX: u8 = 0b100;
+    /// The pre-assembled register value for the operation mode register to start a continuous LoRa RX reception
+    const REG_OPMODE_MODE_RXCONTINUOUS: u8 = 0b101;
     /// The pre-assembled register value for the operation mode register to start a single LoRa RX reception
     const REG_OPMODE_MODE_RXSINGLE: u8 = 0b110;
+    /// The pre-assembled register value for the operation mode register to start a channel activity detection
+    const REG_OPMODE_MODE_CAD: u8 = 0b111;
+    /// The `RxTimeout` flag of the IRQ flags register
+    const REG_IRQFLAGS_RXTIMEOUT: u8 = 0b1000_0000;
+    /// The `RxDone` flag of the IRQ flags register
+    const REG_IRQFLAGS_RXDONE: u8 = 0b0100_0000;
+    /// The `PayloadCrcError` flag of the IRQ flags register
+    const REG_IRQFLAGS_PAYLOADCRCERROR: u8 = 0b0010_0000;
+    /// The `TxDone` flag of the IRQ flags register
+    const REG_IRQFLAGS_TXDONE: u8 = 0b0000_1000;
+    /// The `CadDone` flag of the IRQ flags register
+    const REG_IRQFLAGS_CADDONE: u8 = 0b0000_0100;
+    /// The `FhssChangeChannel` flag of the IRQ flags register
+    const REG_IRQFLAGS_FHSSCHANGECHANNEL: u8 = 0b0000_0010;
+    /// The `CadDetected` flag of the IRQ flags register
+    const REG_IRQFLAGS_CADDETECTED: u8 = 0b0000_0001;
+    /// The register value to select the continuous data mode in FSK/OOK mode
+    const REG_PACKETCONFIG2_DATAMODE_CONTINUOUS: u8 = 0b0;
+    /// The register value to map DIO0 and DIO1 to signals that do not toggle during a continuous FSK transmission
+    const REG_DIOMAPPING1_CARRIER: u8 = 0b1111_0000;
+    /// The register value to map DIO5 to `ModeReady`
+    const REG_DIOMAPPING2_DIO5_MODEREADY: u8 = 0b00;
+    /// The register value to map DIO5 to `ClkOut`
+    const REG_DIOMAPPING2_DIO5_CLKOUT: u8 = 0b01;
+    /// The highest `MaxPower` field value (i.e. `Pmax = 15 dBm` for the RFO pin)
+    const REG_PACONFIG_MAXPOWER_MAX: u8 = 0b111;
+    /// The highest `OutputPower` field value
+    const REG_PACONFIG_OUTPUTPOWER_MAX: u8 = 0b1111;
     /// When operating in the high frequency range the RSSI register values are offset by this much.
     const HF_RSSI_OFFSET: i16 = -157;
     /// When operating in the low frequency range the RSSI register values are offset by this much.
     const LF_RSSI_OFFSET: i16 = -164;
 
+    /// Installs the given external RF switch, which is toggled automatically around mode changes
+    ///
+    /// # Important
+    /// The RF switch is not touched until the next mode change, so it should be initialized to
+    /// [`RfSwitchState::Off`] by the caller. Raw operation mode writes via [`Self::write_register`] bypass the RF
+    /// switch.
+    pub fn with_rf_switch<NewSwitch>(self, rf_switch: NewSwitch) -> Sx127xDriver<Device, Variant, NewSwitch>
+    where
+        NewSwitch: RfSwitch,
+    {
+        Sx127xDriver {
+            spi: self.spi,
+            rx_after_tx: self.rx_after_tx,
+            regulatory: self.regulatory,
+            max_dwell: self.max_dwell,
+            frequency_correction_ppm: self.frequency_correction_ppm,
+            capabilities: self.capabilities,
+            rx_packet_count: self.rx_packet_count,
+            rx_dropped: self.rx_dropped,
+            rx_cursor: self.rx_cursor,
+            rf_switch,
+            rf_state: self.rf_state,
+            hooks: self.hooks,
+            carrier: self.carrier,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+            _variant: PhantomData,
+        }
+    }
+    /// The external RF switch
+    pub const fn rf_switch(&self) -> &Switch {
+        &self.rf_switch
+    }
+    /// Removes the external RF switch and returns it together with the driver
+    pub fn release_rf_switch(self) -> (Sx127xDriver<Device, Variant>, Switch) {
+        let driver = Sx127xDriver {
+            spi: self.spi,
+            rx_after_tx: self.rx_after_tx,
+            regulatory: self.regulatory,
+            max_dwell: self.max_dwell,
+            frequency_correction_ppm: self.frequency_correction_ppm,
+            capabilities: self.capabilities,
+            rx_packet_count: self.rx_packet_count,
+            rx_dropped: self.rx_dropped,
+            rx_cursor: self.rx_cursor,
+            rf_switch: NoRfSwitch,
+            rf_state: self.rf_state,
+            hooks: self.hooks,
+            carrier: self.carrier,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+            _variant: PhantomData,
+        };
+        (driver, self.rf_switch)
+    }
+    /// Puts the modem to sleep and releases the SPI device and the external RF switch, so the bus can be reused or the
+    /// radio can be powered down
+    ///
+    /// # Errors
+    /// If the modem cannot be put to sleep, the driver is returned together with the error, so the operation can be
+    /// retried.
+    ///
+    /// # Reset Pin
+    /// The reset pin is only used during construction and is not owned by the driver, so it is already available to the
+    /// caller; it must stay released (i.e. not asserted), or the modem leaves sleep.
+    #[allow(clippy::result_large_err, reason = "The driver is returned so the operation can be retried")]
+    pub fn release(mut self) -> Result<(Device, Switch), (Self, IoError)> {
+        if let Err(e) = self.sleep() {
+            return Err((self, e));
+        }
+        Ok(self.free())
+    }
+    /// Releases the SPI device and the external RF switch without touching the modem
+    ///
+    /// # Important
+    /// The modem keeps its current mode and config, so the driver can be rebuilt later via [`Self::from_parts`] (e.g.
+    /// after a deep sleep of the MCU). To power the modem down, use [`Self::release`] instead.
+    pub fn free(self) -> (Device, Switch) {
+        (self.spi.release(), self.rf_switch)
+    }
+    /// The statistics counters
+    #[cfg(feature = "stats")]
+    pub const fn stats(&self) -> DriverStats {
+        self.stats.with_io_errors(self.spi.io_errors())
+    }
+    /// Resets all statistics counters to zero
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.stats = DriverStats::new();
+        self.spi.reset_io_errors();
+    }
+    /// Enters the given operation mode and toggles the external RF switch accordingly
+    fn set_mode(&mut self, mode: u8) -> Result<(), IoError> {
+        // Map the mode to the RF switch state
+        let state = match mode {
+            Self::REG_OPMODE_MODE_TXSINGLE => RfSwitchState::Tx,
+            Self::REG_OPMODE_MODE_RXCONTINUOUS | Self::REG_OPMODE_MODE_RXSINGLE | Self::REG_OPMODE_MODE_CAD => {
+                RfSwitchState::Rx
+            }
+            _ => RfSwitchState::Off,
+        };
+
+        // Set up the antenna path before entering an active mode, and power it down after leaving it
+        if state == RfSwitchState::Off {
+            self.spi.write(RegOpModeMode, mode)?;
+            self.set_rf_state(state)
+        } else {
+            self.set_rf_state(state)?;
+            self.spi.write(RegOpModeMode, mode)
+        }
+    }
+    /// Ensures that the modem is not in an active TX, RX or CAD operation, so config registers can be written safely
+    fn ensure_idle(&mut self) -> Result<(), ConfigError> {
+        self.ensure_lora()?;
+        match self.spi.read(RegOpModeMode)? {
+            Self::REG_OPMODE_MODE_TXSINGLE
+            | Self::REG_OPMODE_MODE_RXCONTINUOUS
+            | Self::REG_OPMODE_MODE_RXSINGLE
+            | Self::REG_OPMODE_MODE_CAD => {
+                Err(err!(BusyError, "Modem is busy; enter standby before changing the config").into())
+            }
+            _ => Ok(()),
+        }
+    }
+    /// Ensures that the modem is not in carrier mode, so LoRa registers can be accessed
+    fn ensure_lora(&self) -> Result<(), BusyError> {
+        match self.carrier {
+            Some(_) => Err(err!(BusyError, "Modem is in carrier mode; stop the carrier first")),
+            None => Ok(()),
+        }
+    }
+    /// Toggles the external RF switch to the given state and invokes the hooks if the state has changed
+    fn set_rf_state(&mut self, state: RfSwitchState) -> Result<(), IoError> {
+        // Skip redundant transitions
+        let previous = self.rf_state;
+        if previous == state {
+            return Ok(());
+        }
+
+        // Leave the previous state and enter the new one
+        self.rf_state = state;
+        if state == RfSwitchState::Off {
+            self.rf_switch.set_state(state)?;
+            self.hooks.leave(previous);
+        } else {
+            self.hooks.leave(previous);
+            self.hooks.enter(state);
+            self.rf_switch.set_state(state)?;
+        }
+        Ok(())
+    }
+}
+impl<Device, Variant> Sx127xDriver<Device, Variant>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+{
     /// Creates a new raw SPI command interface for RFM95 from an [`SpiDevice`]
     ///
     /// # Blocking
-    /// This function blocks for at least `11ms` plus additional time for the modem transactions. If you have tight
+    /// This function blocks for at least `6ms` plus additional time for the modem transactions. If you have tight
     /// scheduling requirements, you probably want to initialize this driver before entering your main event loop.
     ///
     /// # Important
@@ -70,37 +345,206 @@ where
         Timer: DelayNs,
     {
         // Fully reset module
-        Self::reset_module(&mut reset, &mut timer)?;
+        Self::reset_module(&mut reset, &mut timer, &Sx127xBuilder::new())?;
 
         // Connect to and setup module and init `self`
         let mut spi = Rfm95Connection::init(device);
-        Self::setup_module(&mut spi)?;
-        Ok(Self { spi })
+        Self::wait_ready(&mut spi, &mut timer)?;
+        Self::setup(spi, &Sx127xBuilder::new())
+    }
+    /// Creates a new raw SPI command interface for RFM95 from an [`SpiDevice`] without a reset pin
+    ///
+    /// # Blocking
+    /// This function blocks for at least `10ms` plus additional time for the modem transactions. If you have tight
+    /// scheduling requirements, you probably want to initialize this driver before entering your main event loop.
+    ///
+    /// # Soft Init
+    /// This constructor is intended for boards where the reset pin is tied to the MCU reset line or is not wired at
+    /// all. Instead of a hardware reset, it performs a best-effort soft init: Any ongoing operation is aborted by
+    /// entering sleep, the modem is switched to LoRa-mode and put to standby, and stale interrupts are cleared.
+    ///
+    /// # Important
+    /// Unlike a hardware reset, the soft init does not restore the register defaults. If the MCU is reset without the
+    /// modem (e.g. by a watchdog), the modem keeps the configuration of the previous session, so you should always
+    /// apply a full config (also see [`Self::set_config`]). If the modem is wedged in an undefined state, only a power
+    /// cycle can recover it.
+    pub fn new_without_reset<Timer>(device: Device, mut timer: Timer) -> Result<Self, IoError>
+    where
+        Timer: DelayNs,
+    {
+        let spi = Rfm95Connection::init(device);
+        Self::setup_without_reset(spi, &mut timer, &Sx127xBuilder::new())
+    }
+    /// Rebuilds a driver around an already initialized modem (e.g. one released via [`Self::free`]) without a reset
+    ///
+    /// # Non-Blocking
+    /// This function does not communicate with the modem; the modem must already be in LoRa-mode.
+    ///
+    /// # Important
+    /// Only the modem retains its config; the driver-side settings (e.g. [`Self::set_max_dwell`], the frequency
+    /// correction, the hooks or the external RF switch) start out with their defaults and must be applied again.
+    pub fn from_parts(device: Device) -> Self {
+        Self {
+            spi: Rfm95Connection::init(device),
+            rx_after_tx: false,
+            regulatory: None,
+            max_dwell: None,
+            frequency_correction_ppm: 0,
+            capabilities: Variant::CAPABILITIES,
+            rx_packet_count: 0,
+            rx_dropped: 0,
+            rx_cursor: None,
+            rf_switch: NoRfSwitch,
+            rf_state: RfSwitchState::Off,
+            hooks: RadioHooks::new(),
+            carrier: None,
+            #[cfg(feature = "stats")]
+            stats: DriverStats::new(),
+            _variant: PhantomData,
+        }
+    }
+    /// Creates a new raw SPI command interface for RFM95 from an [`SpiDevice`], applies the given config and verifies
+    /// that the config has been applied correctly
+    ///
+    /// # Blocking
+    /// This function blocks for at least `6ms` plus additional time for the modem transactions. If you have tight
+    /// scheduling requirements, you probably want to initialize this driver before entering your main event loop.
+    ///
+    /// # Verification
+    /// The config is read back from the modem after it has been applied (see [`Self::config`]). If the read-back config
+    /// does not match, an I/O error is returned, as this usually indicates a faulty SPI connection.
+    pub fn new_with_config<Reset, Timer>(
+        device: Device,
+        reset: Reset,
+        timer: Timer,
+        config: &Config,
+    ) -> Result<Self, ConfigError>
+    where
+        Reset: OutputPin,
+        Timer: DelayNs,
+    {
+        // Init the driver and apply the config
+        let mut this = Self::new(device, reset, timer)?;
+        this.set_config(config)?;
+
+        // Verify the config
+        this.verify_config(config)?;
+        Ok(this)
+    }
+    /// Creates a new builder to initialize the driver with custom init options
+    pub const fn builder() -> Sx127xBuilder<Device, Variant> {
+        Sx127xBuilder::new()
     }
 
-    /// Resets the module
-    fn reset_module<Reset, Timer>(reset: &mut Reset, timer: &mut Timer) -> Result<(), IoError>
+    /// Resets the module with the given init options
+    pub(super) fn reset_module<Reset, Timer>(
+        reset: &mut Reset,
+        timer: &mut Timer,
+        options: &Sx127xBuilder<Device, Variant>,
+    ) -> Result<(), IoError>
     where
         Reset: OutputPin,
         Timer: DelayNs,
     {
-        // Pull reset to low and wait until the reset is triggered
-        reset.set_low().map_err(|_| err!(IoError, "Failed to pull reset line to low"))?;
-        timer.delay_ms(1);
+        // Assert reset and wait until the reset is triggered
+        let assert_reset = match options.reset_polarity() {
+            ResetPolarity::ActiveLow => reset.set_low(),
+            ResetPolarity::ActiveHigh => reset.set_high(),
+        };
+        assert_reset.map_err(|_| err!(IoError, "Failed to assert reset line"))?;
+        timer.delay_us(Self::duration_us(options.reset_pulse()));
 
-        // Pull reset to high again and give the chip some time to boot
-        reset.set_high().map_err(|_| err!(IoError, "Failed to pull reset line to high"))?;
+        // Deassert reset again and give the chip some time to boot
+        let deassert_reset = match options.reset_polarity() {
+            ResetPolarity::ActiveLow => reset.set_high(),
+            ResetPolarity::ActiveHigh => reset.set_low(),
+        };
+        deassert_reset.map_err(|_| err!(IoError, "Failed to deassert reset line"))?;
+        timer.delay_us(Self::duration_us(options.reset_settle()));
+        Ok(())
+    }
+    /// Converts a duration into microseconds for the delay functions, saturating at `u32::MAX`
+    fn duration_us(duration: Duration) -> u32 {
+        u32::try_from(duration.as_micros()).unwrap_or(u32::MAX)
+    }
+    /// Soft-inits the module without a hardware reset with the given init options and creates `self`
+    pub(super) fn setup_without_reset<Timer>(
+        mut spi: Rfm95Connection<Device>,
+        timer: &mut Timer,
+        options: &Sx127xBuilder<Device, Variant>,
+    ) -> Result<Self, IoError>
+    where
+        Timer: DelayNs,
+    {
+        // Give the chip some time to boot in case it has just been powered up
         timer.delay_ms(10);
+        Self::wait_ready(&mut spi, timer)?;
+
+        // Setup the module and clear stale interrupts of a previous session
+        let mut this = Self::setup(spi, options)?;
+        this.spi.write(RegIrqFlags, 0xFF)?;
+        Ok(this)
+    }
+    /// Waits until the chip answers via the given connection, polling against a time budget of `10ms`
+    pub(super) fn wait_ready<Probe, Timer>(spi: &mut Rfm95Connection<Probe>, timer: &mut Timer) -> Result<(), IoError>
+    where
+        Probe: SpiDevice,
+        Timer: DelayNs,
+    {
+        // Before the chip is ready, the SPI data line usually floats to `0x00` or `0xFF`
+        let mut waited_us = 0;
+        while let 0x00 | 0xFF = spi.read(RegVersion)? {
+            // Poll again after a short delay
+            let true = waited_us < Self::READY_TIMEOUT_US else {
+                // The chip did not answer in time
+                return Err(err!(IoError, "Modem did not become ready"));
+            };
+            timer.delay_us(Self::READY_POLL_INTERVAL_US);
+            waited_us = waited_us.saturating_add(Self::READY_POLL_INTERVAL_US);
+        }
         Ok(())
     }
+    /// Setups the module with the given init options and creates `self`
+    ///
+    /// # Important
+    /// The chip must be ready to answer (see [`Self::wait_ready`]).
+    pub(super) fn setup(
+        mut spi: Rfm95Connection<Device>,
+        options: &Sx127xBuilder<Device, Variant>,
+    ) -> Result<Self, IoError> {
+        Self::setup_module(&mut spi, options)?;
+
+        // Use the pinned capabilities or the capabilities of the chip variant
+        let capabilities = options.capabilities().unwrap_or(Variant::CAPABILITIES);
+        Ok(Self {
+            spi,
+            rx_after_tx: false,
+            regulatory: None,
+            max_dwell: None,
+            frequency_correction_ppm: 0,
+            capabilities,
+            rx_packet_count: 0,
+            rx_dropped: 0,
+            rx_cursor: None,
+            rf_switch: NoRfSwitch,
+            rf_state: RfSwitchState::Off,
+            hooks: RadioHooks::new(),
+            carrier: None,
+            #[cfg(feature = "stats")]
+            stats: DriverStats::new(),
+            _variant: PhantomData,
+        })
+    }
     /// Setups the module for LoRa by setting the minimum amount of required settings
-    fn setup_module(spi: &mut Rfm95Connection<Device>) -> Result<(), IoError> {
+    fn setup_module(
+        spi: &mut Rfm95Connection<Device>,
+        options: &Sx127xBuilder<Device, Variant>,
+    ) -> Result<(), IoError> {
         // Validate chip revision to assure the protocol matches
-        #[cfg(not(feature = "debug"))]
-        {
+        if options.check_revision() {
             // Get chip revision
             let silicon_revision = spi.read(RegVersion)?;
-            let true = Self::SUPPORTED_SILICON_REVISIONS.contains(&silicon_revision) else {
+            let true = options.accepts_revision(silicon_revision) else {
                 // Raise an error here since other revisions may be incompatible
                 return Err(err!(IoError, "Unsupported silicon revision"));
             };
@@ -112,27 +556,180 @@ where
         spi.write(RegOpModeMode, Self::REG_OPMODE_MODE_STANDBY)?;
         spi.write(RegOpModeAccessSharedReg, Self::REG_OPMODE_ACCESSSHAREDREG_LORA)?;
 
-        // Set TX and RX base address to 0 to use the entire available FIFO space, and the power amplifier to max
+        // Set TX and RX base address to 0 to use the entire available FIFO space
         spi.write(RegFifoTxBaseAddr, 0x00)?;
         spi.write(RegFifoRxBaseAddr, 0x00)?;
-        spi.write(RegPaConfig, 0xFF)?;
+
+        // Select the PA output of the chip variant, with the maximum power for the RFO pin
+        spi.write(RegPaConfigPaSelect, Variant::PA_OUTPUT as u8)?;
+        spi.write(RegPaConfigMaxPower, Self::REG_PACONFIG_MAXPOWER_MAX)?;
+
+        // Apply the initial TX power
+        let output_power = options.tx_power().saturating_sub(Self::TX_POWER_MIN) as u8;
+        spi.write(RegPaConfigOutputPower, output_power)?;
+
+        // Enter the initial mode
+        if options.initial_mode() == InitialMode::Sleep {
+            spi.write(RegOpModeMode, Self::REG_OPMODE_MODE_SLEEP)?;
+        }
         Ok(())
     }
+}
+impl<Device, Variant, Switch> Sx127xDriver<Device, Variant, Switch>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
+{
+    /// The chip capabilities consulted by the config validation
+    pub const fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+    /// Sets the chip capabilities consulted by the config validation, e.g. the result of [`Self::probe_capabilities`]
+    ///
+    /// # Important
+    /// The current config is not validated against the new capabilities; it is validated on the next change.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+    /// Probes the capabilities of the chip variant, e.g. to select the capabilities of a mixed module population at
+    /// runtime (see [`Self::set_capabilities`])
+    ///
+    /// # Heuristic
+    /// All SX127x variants report the same silicon revision, so the variant is inferred from the behavior of the modem:
+    /// the PLL must lock at 915 MHz to rule out the SX1278 (137 - 525 MHz) and at 1000 MHz to rule out the SX1279
+    /// (137 - 960 MHz), and the spreading factor register must retain SF12 to rule out the SX1277 (up to SF9). This is
+    /// best effort: the variants are screened versions of the same silicon, so a chip that still works beyond its
+    /// specified range is reported with the wider capabilities. Modules with a matching network for a single band (e.g.
+    /// the RFM95) cannot be told apart from the bare chip either; if the module population is known, prefer its chip
+    /// variant (see [`Chip`]) or pinned capabilities (see [`Sx127xBuilder::with_capabilities`]).
+    ///
+    /// # Blocking
+    /// This function blocks for about 2 ms to let the PLL settle. The frequency and the spreading factor are restored,
+    /// and the modem is left in standby; the PA is not enabled.
+    ///
+    /// # Standby
+    /// If a TX, RX or CAD operation is running, a [`BusyError`] is returned; enter standby (see [`Self::standby`])
+    /// first.
+    pub fn probe_capabilities<Timer>(&mut self, timer: &mut Timer) -> Result<Capabilities, ConfigError>
+    where
+        Timer: DelayNs,
+    {
+        self.ensure_idle()?;
+
+        // Probe the bands, and always restore the frequency, even if the probe failed
+        let frequency_word = self.frequency_word()?;
+        let bands = self.probe_pll_lock(Frequency::hz(915_000_000), timer).and_then(|high_band| {
+            let extended_band = self.probe_pll_lock(Frequency::hz(1_000_000_000), timer)?;
+            Ok((high_band, extended_band))
+        });
+        self.standby()?;
+        self.write_frequency(&frequency_word)?;
+        let (high_band, extended_band) = bands?;
+
+        // Probe the highest spreading factor, and restore the spreading factor
+        let spreading_factor = self.spi.read(RegModemConfig2SpreadingFactor)?;
+        self.spi.write(RegModemConfig2SpreadingFactor, SpreadingFactor::S12 as u8)?;
+        let spreading_factor_max = self.spi.read(RegModemConfig2SpreadingFactor)?;
+        self.spi.write(RegModemConfig2SpreadingFactor, spreading_factor)?;
+
+        // Select the variant
+        match (high_band, extended_band, spreading_factor_max == SpreadingFactor::S12 as u8) {
+            (false, _, _) => Ok(Capabilities::SX1278),
+            (true, false, _) => Ok(Capabilities::SX1279),
+            (true, true, false) => Ok(Capabilities::SX1277),
+            (true, true, true) => Ok(Capabilities::SX1276),
+        }
+    }
+    /// Enters FSTX at the given frequency without validation, and returns whether the PLL has locked
+    fn probe_pll_lock<Timer>(&mut self, frequency: Frequency, timer: &mut Timer) -> Result<bool, IoError>
+    where
+        Timer: DelayNs,
+    {
+        /// The time to let the PLL settle in microseconds
+        const PLL_SETTLE_US: u32 = 1000;
+
+        // Tune in standby and give the PLL time to lock
+        self.standby()?;
+        self.write_frequency(&FrequencyWord::new(frequency))?;
+        self.start_fstx()?;
+        timer.delay_us(PLL_SETTLE_US);
+        let locked = self.access_fsk_registers(|spi| spi.read(RegIrqFlags1PllLock))?;
+        Ok(locked == 1)
+    }
 
     /// Applies the given config (useful for initialization)
-    pub fn set_config(&mut self, config: &Config) -> Result<(), IoError> {
+    ///
+    /// # Validation
+    /// The config is validated via [`Config::validate`] and against the chip [`Capabilities`] before any register is
+    /// written.
+    ///
+    /// # Standby
+    /// The modem config must not be changed during an active TX, RX or CAD operation. If such an operation is running,
+    /// a [`BusyError`] is returned; enter standby (see [`Self::standby`]) first. The same applies to the individual
+    /// modem config setters (e.g. [`Self::set_spreading_factor`]).
+    pub fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        // Validate the config
+        let config = config.validate()?;
+        let config = self.capabilities.validate(config)?;
+
+        // Apply the config
+        self.ensure_idle()?;
+        self.write_modem_config(&config)?;
+        self.set_frequency(config.frequency())?;
+        Ok(())
+    }
+    /// Writes the given validated config except for the frequency
+    fn write_modem_config(&mut self, config: &Config) -> Result<(), ConfigError> {
         self.set_spreading_factor(config.spreading_factor())?;
-        self.set_bandwidth(config.bandwidth())?;
+        self.write_bandwidth(config.bandwidth())?;
         self.set_coding_rate(config.coding_rate())?;
         self.set_polarity(config.polarity())?;
         self.set_header_mode(config.header_mode())?;
         self.set_crc_mode(config.crc_mode())?;
         self.set_sync_word(config.sync_word())?;
         self.set_preamble_len(config.preamble_len())?;
-        self.set_frequency(config.frequency())?;
         Ok(())
     }
 
+    /// Verifies that the given config matches the config read back from the modem
+    fn verify_config(&mut self, config: &Config) -> Result<(), IoError> {
+        // Read the config back and compare it, ignoring the frequency for now
+        let applied = self.config()?;
+        let true = applied.with_frequency(config.frequency()) == *config else {
+            // The config does not match
+            return Err(err!(IoError, "Read-back config does not match the applied config"));
+        };
+
+        // The modem stores the frequency in steps of ~61 Hz, so we allow a deviation of up to one step
+        let deviation = applied.frequency().as_u32().abs_diff(config.frequency().as_u32());
+        let 0..=Self::FREQUENCY_STEP_HZ = deviation else {
+            // The frequency does not match
+            return Err(err!(IoError, "Read-back frequency does not match the applied frequency"));
+        };
+        Ok(())
+    }
+
+    /// Reads the current config back from the modem
+    ///
+    /// # Note
+    /// This is useful to verify that the modem state matches the expected config, e.g. by comparing it against the
+    /// config previously applied via [`Self::set_config`]. Please note that the modem stores the frequency in steps of
+    /// ~61 Hz, so the read-back frequency may slightly differ from the configured frequency.
+    pub fn config(&mut self) -> Result<Config, IoError> {
+        let config = Config::builder()
+            .set_spreading_factor(self.spreading_factor()?)
+            .set_bandwidth(self.bandwidth()?)
+            .set_coding_rate(self.coding_rate()?)
+            .set_polarity(self.polarity()?)
+            .set_header_mode(self.header_mode()?)
+            .set_crc_mode(self.crc_mode()?)
+            .set_sync_word(self.sync_word()?)
+            .set_preamble_length(self.preamble_len()?)
+            .set_frequency(self.frequency()?);
+        Ok(config)
+    }
+
     /// The current spreading factor
     pub fn spreading_factor(&mut self) -> Result<SpreadingFactor, IoError> {
         let spreading_factor_raw = self.spi.read(RegModemConfig2SpreadingFactor)?;
@@ -140,12 +737,19 @@ where
         Ok(spreading_factor)
     }
     /// Set the spreading factor
-    pub fn set_spreading_factor<T>(&mut self, spreading_factor: T) -> Result<(), IoError>
+    pub fn set_spreading_factor<T>(&mut self, spreading_factor: T) -> Result<(), ConfigError>
     where
         T: Into<SpreadingFactor>,
     {
-        // Get config to determine the need for LDO
+        self.ensure_idle()?;
+        // Validate the spreading factor against the chip capabilities
         let spreading_factor = spreading_factor.into();
+        if !self.capabilities.supports_spreading_factor(spreading_factor) {
+            // The spreading factor is not supported by the chip
+            return Err(err!(InvalidArgumentError, "Spreading factor is not supported by the chip"))?;
+        }
+
+        // Get config to determine the need for LDO
         let bandwidth = self.bandwidth()?;
         let needs_ldo = airtime::needs_ldo(spreading_factor, bandwidth);
 
@@ -161,12 +765,27 @@ where
         Bandwidth::parse(bandwidth)
     }
     /// Sets the bandwidth
-    pub fn set_bandwidth<T>(&mut self, bandwidth: T) -> Result<(), IoError>
+    ///
+    /// # Low Frequency Band
+    /// The bandwidth is validated against the chip capabilities at the current frequency, so bandwidths above 125 kHz
+    /// are rejected in the low frequency band around 169 MHz. To change both, use [`Self::set_config`].
+    pub fn set_bandwidth<T>(&mut self, bandwidth: T) -> Result<(), ConfigError>
     where
         T: Into<Bandwidth>,
     {
-        // Get config to determine the need for LDO
+        self.ensure_idle()?;
+        // Validate the bandwidth against the chip capabilities at the current frequency
         let bandwidth = bandwidth.into();
+        let frequency = self.frequency()?;
+        if !self.capabilities.supports_bandwidth(bandwidth, frequency) {
+            // The bandwidth is not supported by the chip at this frequency
+            return Err(err!(InvalidArgumentError, "Bandwidth is not supported by the chip at this frequency"))?;
+        }
+        self.write_bandwidth(bandwidth)
+    }
+    /// Writes the bandwidth and the matching LDO flag without validation
+    fn write_bandwidth(&mut self, bandwidth: Bandwidth) -> Result<(), ConfigError> {
+        // Get config to determine the need for LDO
         let spreading_factor = self.spreading_factor()?;
         let needs_ldo = airtime::needs_ldo(spreading_factor, bandwidth);
 
@@ -182,12 +801,14 @@ where
         CodingRate::parse(coding_rate)
     }
     /// Sets the coding rate
-    pub fn set_coding_rate<T>(&mut self, coding_rate: T) -> Result<(), IoError>
+    pub fn set_coding_rate<T>(&mut self, coding_rate: T) -> Result<(), ConfigError>
     where
         T: Into<CodingRate>,
     {
+        self.ensure_idle()?;
         let coding_rate = coding_rate.into();
-        self.spi.write(RegModemConfig1CodingRate, coding_rate as u8)
+        self.spi.write(RegModemConfig1CodingRate, coding_rate as u8)?;
+        Ok(())
     }
 
     /// The current IQ polarity
@@ -196,12 +817,14 @@ where
         Polarity::parse(polarity)
     }
     /// Sets the IQ polarity
-    pub fn set_polarity<T>(&mut self, polarity: T) -> Result<(), IoError>
+    pub fn set_polarity<T>(&mut self, polarity: T) -> Result<(), ConfigError>
     where
         T: Into<Polarity>,
     {
+        self.ensure_idle()?;
         let polarity = polarity.into();
-        self.spi.write(RegInvertIQ, polarity as u8)
+        self.spi.write(RegInvertIQ, polarity as u8)?;
+        Ok(())
     }
 
     /// The current header mode
@@ -210,12 +833,24 @@ where
         HeaderMode::parse(header_mode)
     }
     /// Sets the header mode
-    pub fn set_header_mode<T>(&mut self, header_mode: T) -> Result<(), IoError>
+    pub fn set_header_mode<T>(&mut self, header_mode: T) -> Result<(), ConfigError>
     where
         T: Into<HeaderMode>,
     {
+        self.ensure_idle()?;
         let header_mode = header_mode.into();
-        self.spi.write(RegModemConfig1ImplicitHeaderModeOn, header_mode as u8)
+        self.spi.write(RegModemConfig1ImplicitHeaderModeOn, header_mode as u8)?;
+        Ok(())
+    }
+    /// Sets the expected payload length for RX operations in implicit header mode
+    ///
+    /// # Implicit Header
+    /// In implicit header mode, the payload length is not transmitted, so the receiver must know it in advance (e.g.
+    /// the fixed length of a LoRaWAN Class B beacon). The length is overwritten by the next TX operation.
+    pub fn set_implicit_rx_len(&mut self, len: u8) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        self.spi.write(RegPayloadLength, len)?;
+        Ok(())
     }
 
     /// The current CRC mode
@@ -224,12 +859,14 @@ where
         CrcMode::parse(crc_mode)
     }
     /// Sets the CRC mode
-    pub fn set_crc_mode<T>(&mut self, crc: T) -> Result<(), IoError>
+    pub fn set_crc_mode<T>(&mut self, crc: T) -> Result<(), ConfigError>
     where
         T: Into<CrcMode>,
     {
+        self.ensure_idle()?;
         let crc = crc.into();
-        self.spi.write(RegModemConfig2RxPayloadCrcOn, crc as u8)
+        self.spi.write(RegModemConfig2RxPayloadCrcOn, crc as u8)?;
+        Ok(())
     }
 
     /// The current sync word
@@ -238,12 +875,20 @@ where
         Ok(SyncWord::new(sync_word))
     }
     /// Sets the sync word
-    pub fn set_sync_word<T>(&mut self, sync_word: T) -> Result<(), IoError>
+    ///
+    /// # Ambiguous Values
+    /// Sync words that cross-receive with well-known networks are rejected, see [`SyncWord::is_ambiguous`].
+    pub fn set_sync_word<T>(&mut self, sync_word: T) -> Result<(), ConfigError>
     where
         T: Into<SyncWord>,
     {
+        self.ensure_idle()?;
         let sync_word = sync_word.into();
-        self.spi.write(RegSyncWord, sync_word.into())
+        if sync_word.is_ambiguous() {
+            return Err(err!(InvalidArgumentError, "Sync word cross-receives with a well-known sync word"))?;
+        }
+        self.spi.write(RegSyncWord, sync_word.into())?;
+        Ok(())
     }
 
     /// The current preamble length
@@ -257,147 +902,858 @@ where
         Ok(PreambleLength::new(preamble_len))
     }
     /// Sets the preamble length
-    pub fn set_preamble_len<T>(&mut self, len: T) -> Result<(), IoError>
+    pub fn set_preamble_len<T>(&mut self, len: T) -> Result<(), ConfigError>
     where
         T: Into<PreambleLength>,
     {
+        self.ensure_idle()?;
         let [preamble_len_msb, preamble_len_lsb] = u16::from(len.into()).to_be_bytes();
         self.spi.write(RegPreambleMsb, preamble_len_msb)?;
-        self.spi.write(RegPreambleLsb, preamble_len_lsb)
+        self.spi.write(RegPreambleLsb, preamble_len_lsb)?;
+        Ok(())
     }
 
     /// The current frequency
+    ///
+    /// # Frequency Correction
+    /// The frequency correction (see [`Self::set_frequency_correction_ppm`]) is removed, so the nominal frequency is
+    /// returned.
     pub fn frequency(&mut self) -> Result<Frequency, IoError> {
         // Read frequency from registers
-        let frequency_msb = self.spi.read(RegFrMsb)?;
-        let frequency_mid = self.spi.read(RegFrMid)?;
-        let frequency_lsb = self.spi.read(RegFrLsb)?;
-        let frequency_raw = u64::from_be_bytes([0, 0, 0, 0, 0, frequency_msb, frequency_mid, frequency_lsb]);
+        let frequency = self.frequency_word()?.frequency().as_u32();
 
-        // Translate crystal native frequency into Hz
-        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
-        let frequency_khz = frequency_raw * Self::FREQUENCY_DIVIDER_MILLIHZ;
-        let frequency = (frequency_khz / 1000) as u32;
-        Ok(Frequency::hz(frequency))
+        // Remove the frequency correction
+        let divider = 1_000_000i64.saturating_add(self.frequency_correction_ppm as i64);
+        let nominal = (frequency as i64).saturating_mul(1_000_000).checked_div(divider).unwrap_or_default();
+        Ok(Frequency::hz(u32::try_from(nominal).unwrap_or(frequency)))
     }
     /// Sets the frequency
-    pub fn set_frequency<T>(&mut self, frequency: T) -> Result<(), IoError>
+    ///
+    /// # Frequency Correction
+    /// The frequency correction (see [`Self::set_frequency_correction_ppm`]) is applied transparently.
+    ///
+    /// # Region Lockout
+    /// If a `region-*` cargo feature is enabled, frequencies outside of the selected regions are rejected, and the TX
+    /// power is reduced to the limit of the region if necessary. See [`crate::lora::region::ALLOWED_REGIONS`].
+    pub fn set_frequency<T>(&mut self, frequency: T) -> Result<(), ConfigError>
     where
         T: Into<Frequency>,
     {
+        let frequency = frequency.into().with_ppm(self.frequency_correction_ppm);
+        let frequency_word = FrequencyWord::new(frequency);
+        self.set_frequency_fast(&frequency_word)
+    }
+    /// The frequency correction in ppm
+    pub const fn frequency_correction_ppm(&self) -> i32 {
+        self.frequency_correction_ppm
+    }
+    /// Sets the frequency correction in ppm, which is applied by [`Self::set_frequency`] (see [`Frequency::with_ppm`])
+    ///
+    /// # Crystal Trim
+    /// This allows to apply a per-unit crystal correction, e.g. determined during factory calibration, without touching
+    /// every call site. To compensate a crystal that runs fast by `n` ppm, use `-n`.
+    ///
+    /// # Important
+    /// The correction takes effect on the next call to [`Self::set_frequency`], and is not applied to precomputed
+    /// frequency words passed to [`Self::set_frequency_fast`]; these must be computed from the corrected frequency.
+    pub fn set_frequency_correction_ppm(&mut self, ppm: i32) {
+        self.frequency_correction_ppm = ppm;
+    }
+    /// Sets the frequency from precomputed register values
+    ///
+    /// # Fast Hopping
+    /// Unlike [`Self::set_frequency`], this function does not need to translate the frequency into the register values,
+    /// which makes channel switches of hopping schemes and multi-channel scanners cheaper (see [`FrequencyWord`]).
+    ///
+    /// # Region Lockout
+    /// See [`Self::set_frequency`].
+    pub fn set_frequency_fast(&mut self, frequency_word: &FrequencyWord) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        // Validate the frequency against the chip capabilities
+        let frequency = frequency_word.frequency();
+        if !self.capabilities.supports_frequency(frequency) {
+            // The frequency is not supported by the chip
+            return Err(err!(InvalidArgumentError, "Frequency is not supported by the chip"))?;
+        }
+
+        // Validate the frequency against the region lockout
+        if !region::ALLOWED_REGIONS.is_empty() {
+            // Get the region and the TX power limit
+            let Some(region) = region::allowed_region(frequency) else {
+                // The frequency is outside of the allowed regions
+                return Err(err!(InvalidArgumentError, "Frequency is outside of the allowed regions"))?;
+            };
+
+            // Reduce the TX power if necessary
+            let tx_power_max = cmp::min(region.max_tx_power(), Self::TX_POWER_MAX);
+            if self.tx_power()? > tx_power_max {
+                self.write_tx_power(tx_power_max)?;
+            }
+        }
+
+        // Write the frequency
+        self.write_frequency(frequency_word)?;
+        Ok(())
+    }
+    /// The raw frequency register values, without the frequency correction
+    fn frequency_word(&mut self) -> Result<FrequencyWord, IoError> {
+        let frequency_msb = self.spi.read(RegFrMsb)?;
+        let frequency_mid = self.spi.read(RegFrMid)?;
+        let frequency_lsb = self.spi.read(RegFrLsb)?;
+        Ok(FrequencyWord::from_frf([frequency_msb, frequency_mid, frequency_lsb]))
+    }
+    /// Writes the given frequency without validation
+    fn write_frequency(&mut self, frequency_word: &FrequencyWord) -> Result<(), IoError> {
         // Set the modem to high- or low-frequency mode (low-frequency is `1`)
-        let frequency = frequency.into();
-        let frequency_mode = (frequency < Self::HIGH_FREQUENCY_THRESHOLD) as u8;
+        let frequency_mode = frequency_word.low_frequency_mode() as u8;
         self.spi.write(RegOpModeLowFrequencyModeOn, frequency_mode)?;
 
-        // Translate the frequency into the crystal native frequency
-        // Note: We go via kHz/mHz to keep higher precision without floats
-        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
-        let frequency_khz = u32::from(frequency) as u64 * 1000;
-        let [_, _, _, _, _, frequency_msb, frequency_mid, frequency_lsb] =
-            (frequency_khz / Self::FREQUENCY_DIVIDER_MILLIHZ).to_be_bytes();
-
         // Write the frequency to the registers
+        let [frequency_msb, frequency_mid, frequency_lsb] = frequency_word.frf();
         self.spi.write(RegFrMsb, frequency_msb)?;
         self.spi.write(RegFrMid, frequency_mid)?;
-        self.spi.write(RegFrLsb, frequency_lsb)?;
-        Ok(())
+        self.spi.write(RegFrLsb, frequency_lsb)
     }
 
-    /// Schedules a single TX operation with the given data and returns immediately
+    /// The current TX power in dBm
     ///
-    /// # Non-Blocking
-    /// This functions schedules the TX operation and returns immediately. To check if the TX operation is done, use
-    /// [`Self::complete_tx`].
-    pub fn start_tx(&mut self, data: &[u8]) -> Result<(), TxStartError> {
-        // Validate input length
-        let 1..=RFM95_FIFO_SIZE = data.len() else {
-            // The message is empty or too long
-            return Err(err!(InvalidArgumentError, "Invalid TX data length"))?;
+    /// # PA Output
+    /// The TX power is computed from the current PA config fields. For the RFO pin, the maximum power
+    /// (`10.8 + 0.6 * MaxPower` dBm) is rounded down to whole dBm.
+    pub fn tx_power(&mut self) -> Result<i8, IoError> {
+        // Get the PA config fields
+        let pa_output = self.pa_output()?;
+        let output_power = self.output_power()? as i8;
+
+        // Compute the TX power
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow as the fields are 3 and 4 bit")]
+        match pa_output {
+            PaOutput::PaBoost => Ok(17 - (15 - output_power)),
+            PaOutput::Rfo => {
+                let max_power = self.max_power()? as i8;
+                let pmax = (108 + 6 * max_power) / 10;
+                Ok(pmax - (15 - output_power))
+            }
+        }
+    }
+    /// Sets the TX power in dBm
+    ///
+    /// # Power Range
+    /// The supported TX power depends on the PA output of the chip variant (e.g. `2..=17` dBm for the PA_BOOST pin, see
+    /// [`crate::rfm95::PaOutput`]); other values are rejected.
+    ///
+    /// # Region Lockout
+    /// If a `region-*` cargo feature is enabled, TX powers above the limit of the region of the current frequency are
+    /// rejected. See [`crate::lora::region::ALLOWED_REGIONS`].
+    pub fn set_tx_power(&mut self, power: i8) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        // Validate the TX power
+        let true = (Self::TX_POWER_MIN..=Self::TX_POWER_MAX).contains(&power) else {
+            // The TX power is not supported
+            return Err(err!(InvalidArgumentError, "Unsupported TX power"))?;
         };
 
-        // Copy packet into FIFO...
-        for (index, byte) in data.iter().enumerate() {
-            // Set destination address and write byte
-            self.spi.write(RegFifoAddrPtr, index as u8)?;
-            self.spi.write(RegFifo, *byte)?;
+        // Validate the TX power against the region lockout
+        if !region::ALLOWED_REGIONS.is_empty() {
+            // Get the region of the current frequency
+            let frequency = self.frequency()?;
+            let Some(region) = region::allowed_region(frequency) else {
+                // The current frequency is outside of the allowed regions
+                return Err(err!(InvalidArgumentError, "Frequency is outside of the allowed regions"))?;
+            };
+
+            // Validate the TX power
+            if power > region.max_tx_power() {
+                return Err(err!(InvalidArgumentError, "TX power exceeds the regional limit"))?;
+            }
         }
-        // ... and set packet length
-        self.spi.write(RegPayloadLength, data.len() as u8)?;
 
-        // Enable and reset possible old interrupt
-        self.spi.write(RegIrqFlagsMaskTxDoneMask, 0)?;
-        self.spi.write(RegIrqFlagsTxDone, 1)?;
+        // Write the TX power
+        self.write_tx_power(power)?;
+        Ok(())
+    }
+    /// Writes the given TX power in dBm without validation
+    fn write_tx_power(&mut self, power: i8) -> Result<(), IoError> {
+        // Select the PA output of the chip variant and write the output power
+        let output_power = power.saturating_sub(Self::TX_POWER_MIN) as u8;
+        self.spi.write(RegPaConfigPaSelect, Variant::PA_OUTPUT as u8)?;
+        self.spi.write(RegPaConfigMaxPower, Self::REG_PACONFIG_MAXPOWER_MAX)?;
+        self.spi.write(RegPaConfigOutputPower, output_power)
+    }
 
-        // Start TX
-        self.spi.write(RegOpModeMode, Self::REG_OPMODE_MODE_TXSINGLE)?;
+    /// The selected PA output pin
+    pub fn pa_output(&mut self) -> Result<PaOutput, IoError> {
+        let pa_select = self.spi.read(RegPaConfigPaSelect)?;
+        Ok(PaOutput::parse(pa_select))
+    }
+    /// Selects the PA output pin
+    ///
+    /// # Important
+    /// The PA output must match the pin the antenna is wired to; RFO-wired modules do not transmit (or may be damaged)
+    /// with the PA_BOOST pin selected. Usually, the PA output is set by the chip variant (see [`Chip::PA_OUTPUT`]) and
+    /// [`Self::set_tx_power`] re-selects it; this function is intended for low-level control.
+    pub fn set_pa_output(&mut self, pa_output: PaOutput) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        self.spi.write(RegPaConfigPaSelect, pa_output as u8)?;
         Ok(())
     }
-    /// Checks if a single TX operation has completed, and returns the amount of bytes sent
+    /// The raw `MaxPower` field, which sets the maximum power of the RFO pin to `10.8 + 0.6 * MaxPower` dBm
+    pub fn max_power(&mut self) -> Result<u8, IoError> {
+        self.spi.read(RegPaConfigMaxPower)
+    }
+    /// Sets the raw `MaxPower` field, which sets the maximum power of the RFO pin to `10.8 + 0.6 * MaxPower` dBm
     ///
-    /// # Non-Blocking
-    /// This function is non-blocking. If the TX operation is not done yet, it returns `Ok(None)`.
-    pub fn complete_tx(&mut self) -> Result<Option<usize>, IoError> {
-        // Check for TX done
-        let 0b1 = self.spi.read(RegIrqFlagsTxDone)? else {
-            // The TX operation has not been completed yet
-            return Ok(None);
+    /// # Range
+    /// The field supports a value of `0..=7`; other values are rejected.
+    pub fn set_max_power(&mut self, max_power: u8) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        // Validate the field value
+        let 0..=Self::REG_PACONFIG_MAXPOWER_MAX = max_power else {
+            // The value does not fit into the field
+            return Err(err!(InvalidArgumentError, "Unsupported max power"))?;
         };
 
-        // Get and return the amount of bytes sent
-        let written = self.spi.read(RegPayloadLength)?;
-        Ok(Some(written as usize))
+        // Write the field
+        self.spi.write(RegPaConfigMaxPower, max_power)?;
+        Ok(())
     }
-
-    /// Computes the maximum RX timeout for the current configured spreading factor and bandwidth
+    /// The raw `OutputPower` field
+    pub fn output_power(&mut self) -> Result<u8, IoError> {
+        self.spi.read(RegPaConfigOutputPower)
+    }
+    /// Sets the raw `OutputPower` field, which sets the TX power to `17 - (15 - OutputPower)` dBm for the PA_BOOST pin,
+    /// or `Pmax - (15 - OutputPower)` dBm for the RFO pin
     ///
-    /// # Maximum Timeout
-    /// The maximum timeout is dependent on the symbol length, which in turn depends on the configured spreading factor
-    /// and bandwidth. This means that computed timeout is only valid for a given spreading-factor+bandwidth combination
-    /// and must be recomputed if those parameters change.
+    /// # Range
+    /// The field supports a value of `0..=15`; other values are rejected.
     ///
-    /// # Implementation details
-    /// The RFM95 timeout counter works by counting symbols, and supports a maximum timeout of 1023 symbols. To compute
-    /// the maximum timeout, we take the configured [`Self::spreading_factor`] and [`Self::bandwidth`], and get the
-    /// duration of a single symbol via [`crate::lora::airtime::symbol_airtime`]. The maximum timeout is the duration of
-    /// a single symbol, multiplied with `1023`.
-    pub fn rx_timeout_max(&mut self) -> Result<Duration, IoError> {
-        // Get current config
-        let spreading_factor = self.spreading_factor()?;
-        let bandwidth = self.bandwidth()?;
+    /// # Important
+    /// Unlike [`Self::set_tx_power`], this function bypasses the region lockout and the regulatory policy checks at
+    /// this point; the regulatory policy is still consulted before each TX operation.
+    pub fn set_output_power(&mut self, output_power: u8) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        // Validate the field value
+        let 0..=Self::REG_PACONFIG_OUTPUTPOWER_MAX = output_power else {
+            // The value does not fit into the field
+            return Err(err!(InvalidArgumentError, "Unsupported output power"))?;
+        };
 
-        // Compute timeout
-        let airtime_symbol = airtime::symbol_airtime(spreading_factor, bandwidth);
-        Ok(airtime_symbol.saturating_mul(1023))
+        // Write the field
+        self.spi.write(RegPaConfigOutputPower, output_power)?;
+        Ok(())
     }
-    /// Schedules a single RX operation and returns immediately
+
+    /// The regulatory policy consulted before each TX operation
+    pub const fn regulatory(&self) -> Option<Regulatory> {
+        self.regulatory
+    }
+    /// Sets the regulatory policy consulted before each TX operation, or `None` to disable the policy
     ///
-    /// # Non-Blocking
-    /// This functions schedules the RX operation and returns immediately. To check if the TX operation is done and to
-    /// get the received data, use [`Self::complete_tx`].
+    /// # EIRP Limit
+    /// If a policy is set, each TX operation validates the current TX power against the regulatory limit for the
+    /// current frequency, and either rejects the transmission or reduces the TX power, depending on the policy.
+    pub fn set_regulatory(&mut self, regulatory: Option<Regulatory>) {
+        self.regulatory = regulatory;
+    }
+
+    /// The maximum dwell time per transmission
+    pub const fn max_dwell(&self) -> Option<Duration> {
+        self.max_dwell
+    }
+    /// Sets the maximum dwell time per transmission (e.g. 400 ms for US915), or `None` to disable the limit
     ///
-    /// # Maximum Timeout
-    /// The RFM95 timeout counter works by counting symbols, and is thus dependent on the configured spreading factor
-    /// and bandwidth. See also [`Self::rx_timeout_max`].
+    /// # Dwell Time Guard
+    /// If a limit is set, each TX operation computes the time-on-air of the packet with the current config (see
+    /// [`crate::lora::airtime::airtime`]) and rejects transmissions that exceed the limit with a
+    /// [`TxStartError::DwellTimeError`], which carries the computed airtime. The caller can then reduce the spreading
+    /// factor or shrink the payload.
+    pub fn set_max_dwell(&mut self, max_dwell: Option<Duration>) {
+        self.max_dwell = max_dwell;
+    }
+
+    /// The hooks invoked around TX and RX operations
+    pub const fn hooks(&self) -> RadioHooks {
+        self.hooks
+    }
+    /// Sets the hooks invoked around TX and RX operations (see [`RadioHooks`])
+    pub fn set_hooks(&mut self, hooks: RadioHooks) {
+        self.hooks = hooks;
+    }
+
+    /// Aborts any ongoing operation and puts the modem into standby
+    pub fn standby(&mut self) -> Result<(), IoError> {
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_STANDBY)
+    }
+    /// Aborts any ongoing operation and puts the modem to sleep
+    ///
+    /// # Important
+    /// The config registers are retained during sleep, but the FIFO contents are lost. Any subsequent operation wakes
+    /// the modem up.
+    pub fn sleep(&mut self) -> Result<(), IoError> {
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_SLEEP)
+    }
+    /// Enters the TX frequency synthesis mode (FSTX) to pre-lock the PLL for a subsequent TX operation
+    ///
+    /// # Fast Turnaround
+    /// In FSTX mode, the synthesizer is locked to the configured frequency while the PA stays off. A subsequent
+    /// [`Self::start_tx`] then skips the synthesizer settling time, which shortens the turnaround of latency-critical
+    /// request/response protocols. The FIFO can be accessed in FSTX mode. Changing the frequency or entering standby
+    /// or sleep discards the lock.
+    pub fn start_fstx(&mut self) -> Result<(), IoError> {
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_FSTX)
+    }
+    /// Enters the RX frequency synthesis mode (FSRX) to pre-lock the PLL for a subsequent RX operation
+    ///
+    /// # Fast Turnaround
+    /// In FSRX mode, the synthesizer is locked to the configured frequency while the receiver stays off. A subsequent
+    /// single RX operation (e.g. [`Self::start_rx`]) then skips the synthesizer settling time. Changing the frequency
+    /// or entering standby or sleep discards the lock; note that [`Self::start_rx_continuous`] enters standby first.
+    pub fn start_fsrx(&mut self) -> Result<(), IoError> {
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_FSRX)
+    }
+
+    /// Schedules a single TX operation with the given data and returns immediately
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the TX operation and returns immediately. To check if the TX operation is done, use
+    /// [`Self::complete_tx`].
+    pub fn start_tx(&mut self, data: &[u8]) -> Result<(), TxStartError> {
+        self.start_tx_vectored(&[data])
+    }
+    /// Schedules a single TX operation with the given data compressed, and returns immediately
+    ///
+    /// # Compression
+    /// The data is packed via [`crate::lora::compression::pack`], which falls back to the raw data if compression does
+    /// not save space. The receiver must use [`Self::complete_rx_decompressed`].
+    ///
+    /// # Non-Blocking
+    /// See [`Self::start_tx`].
+    #[cfg(feature = "compression")]
+    pub fn start_tx_compressed(&mut self, data: &[u8]) -> Result<(), TxStartError> {
+        let mut frame = [0; crate::rfm95::RFM95_FIFO_SIZE];
+        let Ok(len) = compression::pack(data, &mut frame) else {
+            // The data does not fit into a single frame
+            return Err(err!(InvalidArgumentError, "TX data exceeds the FIFO size"))?;
+        };
+        self.start_tx(frame.get(..len).unwrap_or_default())
+    }
+    /// Schedules a single TX operation with the given data followed by its checksum, and returns immediately
+    ///
+    /// # Checksum
+    /// The checksum is appended as described in [`crate::lora::checksum`]. The receiver must use
+    /// [`Self::complete_rx_checked`] with the same checksum.
+    ///
+    /// # Non-Blocking
+    /// See [`Self::start_tx`].
+    pub fn start_tx_checked<C>(&mut self, data: &[u8], checksum: &C) -> Result<(), TxStartError>
+    where
+        C: Checksum,
+    {
+        let trailer = checksum.checksum(data).to_le_bytes();
+        self.start_tx_vectored(&[data, &trailer])
+    }
+    /// Schedules a single TX operation with the given data, followed by a single RX operation, and returns immediately
+    ///
+    /// # Turnaround
+    /// The RX operation is fully prepared before the TX operation is started, so once [`Self::complete_tx`] observes
+    /// the TX completion, it only needs a single register write to enter RX mode. To minimize the dead time before the
+    /// reply window, call [`Self::complete_tx`] from the DIO0 interrupt handler (DIO0 signals `TxDone` by default).
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the TX operation and returns immediately. To check if the TX operation is done, use
+    /// [`Self::complete_tx`]; to check if the subsequent RX operation is done, use [`Self::complete_rx`].
+    ///
+    /// # Maximum Timeout
+    /// The RFM95 timeout counter works by counting symbols, and is thus dependent on the configured spreading factor
+    /// and bandwidth. See also [`Self::rx_timeout_max`].
+    pub fn start_tx_then_rx(&mut self, data: &[u8], rx_timeout: Duration) -> Result<(), TxStartError> {
+        // Prepare the RX operation
+        let rx_timeout_symbols = self.rx_timeout_symbols(rx_timeout)?;
+        self.prepare_rx(rx_timeout_symbols)?;
+
+        // Start TX and arm the turnaround
+        self.start_tx(data)?;
+        self.rx_after_tx = true;
+        Ok(())
+    }
+    /// Assesses the channel according to the given policy and schedules a single TX operation with the given data once
+    /// the channel is clear, and returns whether the TX operation has been started
+    ///
+    /// # Blocking
+    /// This function blocks during the channel assessments and the backoff delays, and returns once the TX operation
+    /// has been started or the channel has been busy for all retries. To check if the TX operation is done, use
+    /// [`Self::complete_tx`].
+    pub fn start_tx_when_clear<Timer>(
+        &mut self,
+        data: &[u8],
+        policy: &ClearChannelPolicy,
+        timer: &mut Timer,
+    ) -> Result<bool, TxStartError>
+    where
+        Timer: DelayNs,
+    {
+        for attempt in 0..=policy.retries() {
+            // Back off before each retry
+            if attempt > 0 {
+                let backoff = u32::try_from(policy.backoff().as_micros()).unwrap_or(u32::MAX);
+                timer.delay_us(backoff);
+            }
+
+            // Transmit if the channel is clear
+            if self.is_channel_clear(policy, timer)? {
+                self.start_tx(data)?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    /// Assesses the channel once according to the given policy (ignoring the retries), and returns whether the channel
+    /// is clear
+    ///
+    /// # Blocking
+    /// This function blocks until the CAD cycles and the RSSI check have completed, and leaves the modem in standby;
+    /// any ongoing operation is aborted.
+    pub fn is_channel_clear<Timer>(&mut self, policy: &ClearChannelPolicy, timer: &mut Timer) -> Result<bool, IoError>
+    where
+        Timer: DelayNs,
+    {
+        // Run the CAD cycles
+        let symbol_airtime = airtime::symbol_airtime(self.spreading_factor()?, self.bandwidth()?);
+        for _ in 0..policy.cad_cycles() {
+            if self.run_cad(symbol_airtime, timer)? {
+                return Ok(false);
+            }
+        }
+
+        // Check the RSSI
+        if let Some(rssi_threshold) = policy.rssi_threshold() {
+            // Sample the RSSI for one symbol in continuous RX
+            self.rx_after_tx = false;
+            self.set_mode(Self::REG_OPMODE_MODE_RXCONTINUOUS)?;
+            timer.delay_us(u32::try_from(symbol_airtime.as_micros()).unwrap_or(u32::MAX));
+            let rssi = self.rssi()?;
+            self.standby()?;
+            return Ok(rssi < rssi_threshold);
+        }
+        Ok(true)
+    }
+    /// Runs a single CAD cycle and returns whether LoRa activity was detected
+    fn run_cad<Timer>(&mut self, symbol_airtime: Duration, timer: &mut Timer) -> Result<bool, IoError>
+    where
+        Timer: DelayNs,
+    {
+        /// The poll interval in microseconds
+        const POLL_INTERVAL_US: u32 = 10;
+
+        // A CAD cycle takes roughly two symbols, so we allow four symbols before giving up
+        let mut remaining = symbol_airtime.saturating_mul(4).as_micros();
+        self.start_cad()?;
+        loop {
+            // Poll the CAD result
+            if let Some(detected) = self.complete_cad()? {
+                return Ok(detected);
+            }
+
+            // Wait for the next poll
+            let Some(next_remaining) = remaining.checked_sub(POLL_INTERVAL_US as u128) else {
+                // The modem has not completed the CAD operation
+                self.standby()?;
+                return Err(err!(IoError, "CAD operation did not complete"));
+            };
+            remaining = next_remaining;
+            timer.delay_us(POLL_INTERVAL_US);
+        }
+    }
+    /// Schedules a single TX operation with the concatenation of the given data slices and returns immediately
+    ///
+    /// # Scatter-Gather
+    /// The slices are written into the FIFO one after another, so e.g. a protocol header and payload can be sent
+    /// without concatenating them into a temporary buffer first.
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the TX operation and returns immediately. To check if the TX operation is done, use
+    /// [`Self::complete_tx`].
+    pub fn start_tx_vectored(&mut self, data: &[&[u8]]) -> Result<(), TxStartError> {
+        // Validate input length
+        let len = data.iter().try_fold(0usize, |len, slice| len.checked_add(slice.len()));
+        let Some(Ok(len @ 1..)) = len.map(u8::try_from) else {
+            // The message is empty or too long
+            return Err(err!(InvalidArgumentError, "Invalid TX data length"))?;
+        };
+
+        self.ensure_lora()?;
+        self.apply_tx_policy()?;
+
+        // Validate the airtime against the dwell time limit
+        if let Some(max_dwell) = self.max_dwell {
+            let airtime = airtime::airtime(usize::from(len), self.config()?);
+            if airtime > max_dwell {
+                // The transmission would exceed the maximum dwell time
+                return Err(DwellTimeError {
+                    airtime,
+                    max_dwell,
+                    #[cfg(feature = "backtrace")]
+                    file: file!(),
+                    #[cfg(feature = "backtrace")]
+                    line: line!(),
+                    #[cfg(feature = "backtrace")]
+                    description: "Transmission exceeds the maximum dwell time",
+                })?;
+            }
+        }
+
+        // Copy packet into FIFO...
+        for (index, byte) in (0..=u8::MAX).zip(data.iter().copied().flatten()) {
+            // Set destination address and write byte
+            self.spi.write(RegFifoAddrPtr, index)?;
+            self.spi.write(RegFifo, *byte)?;
+        }
+        // ... and set packet length
+        self.spi.write(RegPayloadLength, len)?;
+
+        // Enable and reset possible old interrupt
+        self.spi.write(RegIrqFlagsMaskTxDoneMask, 0)?;
+        self.spi.write(RegIrqFlagsTxDone, 1)?;
+
+        // Start TX
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_TXSINGLE)?;
+        Ok(())
+    }
+    /// Validates the current frequency and TX power against the region lockout and the regulatory policy, and reduces
+    /// the TX power if necessary
+    fn apply_tx_policy(&mut self) -> Result<(), TxStartError> {
+        // Validate the frequency against the region lockout
+        if !region::ALLOWED_REGIONS.is_empty() {
+            let frequency = self.frequency()?;
+            let Some(_) = region::allowed_region(frequency) else {
+                // The current frequency is outside of the allowed regions
+                return Err(err!(InvalidArgumentError, "Frequency is outside of the allowed regions"))?;
+            };
+        }
+
+        // Validate the TX power against the regulatory policy
+        if let Some(regulatory) = self.regulatory {
+            // Check the TX power
+            let frequency = self.frequency()?;
+            let tx_power = self.tx_power()?;
+            let allowed_tx_power = regulatory.check(frequency, tx_power)?;
+
+            // Reduce the TX power if necessary
+            if allowed_tx_power < Self::TX_POWER_MIN {
+                // The limit is below the lowest supported TX power
+                return Err(err!(InvalidArgumentError, "Regulatory limit is below the minimum TX power"))?;
+            } else if allowed_tx_power < tx_power {
+                self.write_tx_power(allowed_tx_power)?;
+            }
+        }
+        Ok(())
+    }
+    /// Checks if a single TX operation has completed, and returns the amount of bytes sent
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If the TX operation is not done yet, it returns `Ok(None)`.
+    ///
+    /// # Turnaround
+    /// If the TX operation was started via [`Self::start_tx_then_rx`], the prepared RX operation is started immediately
+    /// once the TX operation has completed.
+    pub fn complete_tx(&mut self) -> Result<Option<usize>, IoError> {
+        // Check for TX done
+        let 0b1 = self.spi.read(RegIrqFlagsTxDone)? else {
+            // The TX operation has not been completed yet
+            return Ok(None);
+        };
+
+        // Complete the TX operation, and get and return the amount of bytes sent
+        self.complete_tx_state()?;
+        let written = self.spi.read(RegPayloadLength)?;
+        Ok(Some(written as usize))
+    }
+    /// Starts the prepared RX operation if any, or powers down the RF switch as the modem has returned to standby
+    fn complete_tx_state(&mut self) -> Result<(), IoError> {
+        #[cfg(feature = "stats")]
+        self.stats.count_tx_packet();
+        if self.rx_after_tx {
+            self.rx_after_tx = false;
+            self.set_mode(Self::REG_OPMODE_MODE_RXSINGLE)?;
+        } else {
+            self.set_rf_state(RfSwitchState::Off)?;
+        }
+        Ok(())
+    }
+    /// Starts a continuous transmission that repeats the given data until [`Self::stop_tx_continuous`] is called
+    ///
+    /// # Test Mode
+    /// This mode is intended for RF testing (e.g. antenna matching or conducted power measurements), not for normal
+    /// operation. The region lockout and the regulatory TX power policy apply, and the dwell-time limit applies to a
+    /// single repetition; however, the total transmission time is not limited.
+    ///
+    /// # Standby
+    /// If a TX, RX or CAD operation is running, a [`BusyError`] is returned; enter standby (see [`Self::standby`])
+    /// first.
+    ///
+    /// # Non-Blocking
+    /// This functions starts the continuous transmission and returns immediately.
+    pub fn start_tx_continuous(&mut self, data: &[u8]) -> Result<(), TxStartError> {
+        self.ensure_idle()?;
+        self.spi.write(RegModemConfig2TxContinuousMode, 1)?;
+        let result = self.start_tx(data);
+        if result.is_err() {
+            // Do not leave the continuous mode enabled for regular transmissions
+            self.spi.write(RegModemConfig2TxContinuousMode, 0)?;
+        }
+        result
+    }
+    /// Stops a continuous transmission and puts the modem into standby
+    pub fn stop_tx_continuous(&mut self) -> Result<(), IoError> {
+        self.standby()?;
+        self.spi.write(RegModemConfig2TxContinuousMode, 0)
+    }
+    /// Starts transmitting an unmodulated carrier on the current frequency with the current TX power until
+    /// [`Self::stop_carrier`] is called
+    ///
+    /// # Carrier Mode
+    /// The LoRa modem cannot transmit an unmodulated carrier, so the modem is switched to the FSK modem in continuous
+    /// mode with a frequency deviation of zero. DIO0 and DIO1 are remapped, so the FSK signals do not trigger the LoRa
+    /// interrupt handlers. The LoRa config is saved when the carrier mode is entered and restored by
+    /// [`Self::stop_carrier`]; until then, the config setters and the LoRa TX and RX operations return a
+    /// [`BusyError`].
+    ///
+    /// # Keying
+    /// The carrier is keyed off via [`Self::standby`] and on again via this function without leaving the carrier mode,
+    /// e.g. for Morse code (see [`crate::rfm95::CwKeyer`]).
+    ///
+    /// # Test Mode
+    /// Like [`Self::start_tx_continuous`], this mode is not intended for normal operation. The region lockout and the
+    /// regulatory TX power policy apply when the carrier mode is entered; however, the total transmission time is not
+    /// limited. For antenna matching with duty-cycle and session limits, see [`crate::rfm95::TuningAid`].
+    ///
+    /// # Errors
+    /// If a TX, RX or CAD operation is running, a [`BusyError`] is returned. If the carrier cannot be started, the LoRa
+    /// mode and config are restored before the error is returned.
+    pub fn start_carrier(&mut self) -> Result<(), TxStartError> {
+        // Enter the carrier mode once, so the carrier can be keyed without switching the modem each time
+        if self.carrier.is_none() {
+            self.ensure_idle()?;
+            self.apply_tx_policy()?;
+            if let Err(e) = self.enter_carrier_mode() {
+                // Do not leave the modem in FSK mode
+                self.stop_carrier()?;
+                return Err(e.into());
+            }
+        }
+
+        // Key the carrier
+        self.rx_after_tx = false;
+        if let Err(e) = self.set_mode(Self::REG_OPMODE_MODE_TXSINGLE) {
+            // Do not leave the modem in FSK mode
+            self.stop_carrier()?;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    /// Saves the LoRa state and switches the modem to an unmodulated FSK transmission in standby
+    fn enter_carrier_mode(&mut self) -> Result<(), IoError> {
+        // Save the LoRa state first, so it can be restored if switching fails midway
+        let state = LoraState {
+            config: self.config()?,
+            symbol_timeout: self.symbol_timeout()?,
+            payload_len: self.spi.read(RegPayloadLength)?,
+            dio_mapping: self.spi.read(RegDioMapping1)?,
+        };
+        self.carrier = Some(state);
+
+        // Go to sleep, switch to FSK and enter standby
+        self.set_mode(Self::REG_OPMODE_MODE_SLEEP)?;
+        self.spi.write(RegOpModeLongRangeMode, Self::REG_OPMODE_LONGRANGEMODE_FSK)?;
+        self.spi.write(RegOpModeModulationType, Self::REG_OPMODE_MODULATIONTYPE_FSK)?;
+        self.spi.write(RegOpModeMode, Self::REG_OPMODE_MODE_STANDBY)?;
+
+        // Disable the frequency deviation and the packet handler, and silence the DIO interrupts
+        self.spi.write(RegFdevMsb, 0)?;
+        self.spi.write(RegFdevLsb, 0)?;
+        self.spi.write(RegPacketConfig2DataMode, Self::REG_PACKETCONFIG2_DATAMODE_CONTINUOUS)?;
+        self.spi.write(RegDioMapping1, Self::REG_DIOMAPPING1_CARRIER)?;
+        Ok(())
+    }
+    /// Stops the carrier, switches the modem back to LoRa, restores the LoRa config and puts the modem into standby
+    ///
+    /// # Restored State
+    /// The modem config (see [`Self::config`]), the RX timeout, the implicit-header payload length, the DIO0 to DIO3
+    /// mapping and the FIFO base addresses are restored; the frequency and TX power are shared between both modems and
+    /// thus kept. If the modem is not in carrier mode, this function does nothing.
+    pub fn stop_carrier(&mut self) -> Result<(), ConfigError> {
+        let Some(state) = self.carrier else {
+            // The modem is already in LoRa mode
+            return Ok(());
+        };
+
+        // Go to sleep, switch back to LoRa and enter standby
+        self.set_mode(Self::REG_OPMODE_MODE_SLEEP)?;
+        self.spi.write(RegOpModeLongRangeMode, Self::REG_OPMODE_LONGRANGEMODE_LORA)?;
+        self.spi.write(RegOpModeMode, Self::REG_OPMODE_MODE_STANDBY)?;
+        self.spi.write(RegOpModeAccessSharedReg, Self::REG_OPMODE_ACCESSSHAREDREG_LORA)?;
+        self.carrier = None;
+
+        // Restore the LoRa state
+        self.spi.write(RegDioMapping1, state.dio_mapping)?;
+        self.spi.write(RegFifoTxBaseAddr, 0x00)?;
+        self.spi.write(RegFifoRxBaseAddr, 0x00)?;
+        self.spi.write(RegModemConfig2TxContinuousMode, 0)?;
+        self.write_modem_config(&state.config)?;
+        self.write_symbol_timeout(state.symbol_timeout)?;
+        self.spi.write(RegPayloadLength, state.payload_len)?;
+        Ok(())
+    }
+    /// Whether the modem is in carrier mode (see [`Self::start_carrier`])
+    pub const fn is_carrier_mode(&self) -> bool {
+        self.carrier.is_some()
+    }
+
+    /// The current RX timeout in symbols
+    pub fn symbol_timeout(&mut self) -> Result<u16, IoError> {
+        // Read registers
+        let symbol_timeout_msb = self.spi.read(RegModemConfig2SymbTimeout98)?;
+        let symbol_timeout_lsb = self.spi.read(RegSymbTimeoutLsb)?;
+
+        // Create symbol timeout
+        Ok(u16::from_be_bytes([symbol_timeout_msb, symbol_timeout_lsb]))
+    }
+    /// Sets the RX timeout in symbols
+    ///
+    /// # Maximum Timeout
+    /// The RFM95 timeout counter supports a maximum timeout of 1023 symbols; larger values are rejected. Please note
+    /// that [`Self::start_rx`] overwrites this value with the timeout passed to it.
+    pub fn set_symbol_timeout(&mut self, symbols: u16) -> Result<(), ConfigError> {
+        self.ensure_idle()?;
+        self.write_symbol_timeout(symbols)
+    }
+    /// Writes the given RX timeout in symbols without checking whether the modem is idle
+    fn write_symbol_timeout(&mut self, symbols: u16) -> Result<(), ConfigError> {
+        // Validate the timeout
+        let 0..=Self::SYMBOL_TIMEOUT_MAX = symbols else {
+            // This timeout is too large to be configured
+            return Err(err!(InvalidArgumentError, "Symbol timeout is too large"))?;
+        };
+
+        // Write the timeout to the registers
+        let [symbol_timeout_msb, symbol_timeout_lsb] = symbols.to_be_bytes();
+        self.spi.write(RegModemConfig2SymbTimeout98, symbol_timeout_msb)?;
+        self.spi.write(RegSymbTimeoutLsb, symbol_timeout_lsb)?;
+        Ok(())
+    }
+
+    /// Computes the maximum RX timeout for the current configured spreading factor and bandwidth
+    ///
+    /// # Maximum Timeout
+    /// The maximum timeout is dependent on the symbol length, which in turn depends on the configured spreading factor
+    /// and bandwidth. This means that computed timeout is only valid for a given spreading-factor+bandwidth combination
+    /// and must be recomputed if those parameters change.
+    ///
+    /// # Implementation details
+    /// The RFM95 timeout counter works by counting symbols, and supports a maximum timeout of 1023 symbols. To compute
+    /// the maximum timeout, we take the configured [`Self::spreading_factor`] and [`Self::bandwidth`], and get the
+    /// duration of a single symbol via [`crate::lora::airtime::symbol_airtime`]. The maximum timeout is the duration of
+    /// a single symbol, multiplied with `1023`.
+    pub fn rx_timeout_max(&mut self) -> Result<Duration, IoError> {
+        // Get current config
+        let spreading_factor = self.spreading_factor()?;
+        let bandwidth = self.bandwidth()?;
+
+        // Compute timeout
+        let airtime_symbol = airtime::symbol_airtime(spreading_factor, bandwidth);
+        Ok(airtime_symbol.saturating_mul(Self::SYMBOL_TIMEOUT_MAX as u32))
+    }
+    /// Computes the maximum RX timeout as `fugit` duration; see [`Self::rx_timeout_max`]
+    #[cfg(feature = "fugit")]
+    pub fn rx_timeout_max_fugit(&mut self) -> Result<fugit::MicrosDurationU64, IoError> {
+        Ok(airtime::to_fugit(self.rx_timeout_max()?))
+    }
+    /// Schedules a single RX operation and returns immediately
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the RX operation and returns immediately. To check if the TX operation is done and to
+    /// get the received data, use [`Self::complete_tx`].
+    ///
+    /// # Maximum Timeout
+    /// The RFM95 timeout counter works by counting symbols, and is thus dependent on the configured spreading factor
+    /// and bandwidth. See also [`Self::rx_timeout_max`].
     pub fn start_rx(&mut self, timeout: Duration) -> Result<(), RxStartError> {
+        let timeout_symbols = self.rx_timeout_symbols(timeout)?;
+        self.start_rx_symbols(timeout_symbols)
+    }
+    /// Schedules a single RX operation with a `fugit` timeout and returns immediately; see [`Self::start_rx`]
+    #[cfg(feature = "fugit")]
+    pub fn start_rx_fugit(&mut self, timeout: fugit::MicrosDurationU64) -> Result<(), RxStartError> {
+        self.start_rx(airtime::from_fugit(timeout))
+    }
+    /// Schedules a single RX operation with the maximum timeout and returns immediately
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the RX operation and returns immediately. To check if the RX operation is done and to
+    /// get the received data, use [`Self::complete_rx`].
+    ///
+    /// # Maximum Timeout
+    /// The timeout is set to the hardware maximum of 1023 symbols, which is equivalent to
+    /// `start_rx(rx_timeout_max()?)` without reading the current config. See also [`Self::rx_timeout_max`].
+    pub fn start_rx_max(&mut self) -> Result<(), RxStartError> {
+        self.start_rx_symbols(Self::SYMBOL_TIMEOUT_MAX)
+    }
+    /// Schedules a single RX operation with a timeout in symbols and returns immediately
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the RX operation and returns immediately. To check if the RX operation is done and to
+    /// get the received data, use [`Self::complete_rx`].
+    ///
+    /// # Maximum Timeout
+    /// The RFM95 timeout counter supports a maximum timeout of 1023 symbols. Unlike [`Self::start_rx`], this function
+    /// does not need to read the current config to convert the timeout, and avoids any rounding errors.
+    pub fn start_rx_symbols(&mut self, symbols: u16) -> Result<(), RxStartError> {
+        // Prepare RX
+        self.prepare_rx(symbols)?;
+
+        // Start RX
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_RXSINGLE)?;
+        Ok(())
+    }
+    /// Converts the given RX timeout into symbols for the current configured spreading factor and bandwidth
+    fn rx_timeout_symbols(&mut self, timeout: Duration) -> Result<u16, ConfigError> {
         // Get the current symbol airtime in microseconds
         let spreading_factor = self.spreading_factor()?;
         let bandwidth = self.bandwidth()?;
         let symbol_airtime = airtime::symbol_airtime(spreading_factor, bandwidth);
-        let symbol_airtime_micros = symbol_airtime.as_micros() as i32;
+        let symbol_airtime_micros = i32::try_from(symbol_airtime.as_micros())
+            .map_err(|_| err!(InvalidArgumentError, "Symbol airtime is too long"))?;
 
         // Compute the raw timeout
         let timeout_micros =
             i32::try_from(timeout.as_micros()).map_err(|_| err!(InvalidArgumentError, "Timeout is too long"))?;
-        let timeout_symbols @ 0..1024 = airtime::ceildiv(timeout_micros, symbol_airtime_micros) as u32 else {
+        let Ok(timeout_symbols @ 0..=Self::SYMBOL_TIMEOUT_MAX) =
+            u16::try_from(airtime::ceildiv(timeout_micros, symbol_airtime_micros))
+        else {
             // This timeout is too large to be configured
             return Err(err!(InvalidArgumentError, "Effective timeout is too large"))?;
         };
-
-        // Configure the timeout and reset the address pointer
-        self.spi.write(RegModemConfig2SymbTimeout98, (timeout_symbols >> 8) as u8)?;
-        self.spi.write(RegSymbTimeoutLsb, timeout_symbols as u8)?;
+        Ok(timeout_symbols)
+    }
+    /// Prepares a single RX operation with a timeout in symbols, without entering RX mode
+    fn prepare_rx(&mut self, symbols: u16) -> Result<(), ConfigError> {
+        self.ensure_lora()?;
+        // Configure the timeout and reset the address pointer; a running RX operation is restarted by the caller
+        self.write_symbol_timeout(symbols)?;
         self.spi.write(RegFifoAddrPtr, 0x00)?;
+        self.reset_rx()?;
+        Ok(())
+    }
+    /// Enables and resets the RX interrupts and discards any partially read message
+    fn reset_rx(&mut self) -> Result<(), IoError> {
+        // Discard any partially read message
+        self.rx_cursor = None;
 
         // Enable interrupts
         self.spi.write(RegIrqFlagsMaskRxDoneMask, 0)?;
@@ -408,55 +1764,511 @@ where
         self.spi.write(RegIrqFlagsRxDone, 1)?;
         self.spi.write(RegIrqFlagsRxTimeout, 1)?;
         self.spi.write(RegIrqFlagsPayloadCrcError, 1)?;
-
-        // Start RX
-        self.spi.write(RegOpModeMode, Self::REG_OPMODE_MODE_RXSINGLE)?;
         Ok(())
     }
     /// Checks if a single RX operation has completed, copies the message into `buf` and returns the amount of bytes
     /// received
     ///
     /// # Non-Blocking
-    /// This function is non-blocking. If the RX operation is not done yet, it returns `Ok(None)`.
+    /// This function is non-blocking. If the RX operation is not done yet, it returns `Ok(RxOutcome::Pending)`.
     ///
     /// # Timeout or CRC errors
-    /// If the receive operation times out or the received message is corrupt,
-    #[allow(clippy::missing_panics_doc, reason = "The panic should never occur during regular operation")]
-    pub fn complete_rx(&mut self, buf: &mut [u8]) -> Result<Option<usize>, RxCompleteError> {
+    /// If the receive operation times out or the received message is corrupt, `Ok(RxOutcome::Timeout)` or
+    /// `Ok(RxOutcome::CrcError)` is returned respectively.
+    ///
+    /// # Buffer too small
+    /// If the received message is longer than `buf`, an error is returned and nothing is copied. The message is left in
+    /// the FIFO, so the call can be retried with a larger buffer; a buffer of [`crate::rfm95::RFM95_FIFO_SIZE`] bytes
+    /// is always sufficient. To read the message in chunks instead, use [`Self::complete_rx_partial`].
+    pub fn complete_rx(&mut self, buf: &mut [u8]) -> Result<RxOutcome, RxCompleteError> {
+        // Get packet begin and length
+        let (start, len) = match self.rx_packet()?.into_received() {
+            Ok(packet) => packet,
+            // The RX operation has not been completed successfully
+            Err(outcome) => return Ok(outcome),
+        };
+
+        // Copy data from FIFO and return the amount of bytes copied
+        let len = self.copy_rx_packet(start, len, buf)?;
+        Ok(RxOutcome::Received(len))
+    }
+    /// Checks if a single RX operation has completed and returns the received message
+    ///
+    /// # Non-Blocking, Outcomes
+    /// See [`Self::complete_rx`].
+    ///
+    /// # Capacity too small
+    /// If the received message is longer than `N`, an error is returned and nothing is copied. The message is left in
+    /// the FIFO, so the call can be retried with a larger capacity; a capacity of [`crate::rfm95::RFM95_FIFO_SIZE`]
+    /// bytes is always sufficient.
+    #[cfg(feature = "heapless")]
+    pub fn complete_rx_vec<const N: usize>(&mut self) -> Result<RxOutcome<heapless::Vec<u8, N>>, RxCompleteError> {
+        // Get packet begin and length
+        let (start, len) = match self.rx_packet()?.into_received() {
+            Ok(packet) => packet,
+            // The RX operation has not been completed successfully
+            Err(outcome) => return Ok(outcome),
+        };
+
+        // Allocate the message and copy data from FIFO
+        let mut message = heapless::Vec::new();
+        let Ok(()) = message.resize_default(len as usize) else {
+            // The message does not fit into the vector
+            return Err(err!(BufferTooSmallError, "RX vector capacity is too small"))?;
+        };
+        let len = self.copy_rx_packet(start, len, &mut message)?;
+        message.truncate(len);
+        Ok(RxOutcome::Received(message))
+    }
+    /// Checks if a single RX operation has completed, decompresses the message into `buf` and returns the amount of
+    /// decompressed bytes
+    ///
+    /// # Compression
+    /// The message is unpacked via [`crate::lora::compression::unpack`]; it must have been sent via
+    /// [`Self::start_tx_compressed`]. If the message is not a valid compression frame, an invalid-argument error is
+    /// returned.
+    ///
+    /// # Non-Blocking, Outcomes
+    /// See [`Self::complete_rx`].
+    ///
+    /// # Buffer too small
+    /// If the decompressed message is longer than `buf`, an error is returned. Unlike [`Self::complete_rx`], the
+    /// message is consumed in this case.
+    #[cfg(feature = "compression")]
+    pub fn complete_rx_decompressed(&mut self, buf: &mut [u8]) -> Result<RxOutcome, ProtocolError> {
+        let mut frame = [0; crate::rfm95::RFM95_FIFO_SIZE];
+        let len = match self.complete_rx(&mut frame)?.into_received() {
+            Ok(len) => len,
+            // The RX operation has not been completed successfully
+            Err(outcome) => return Ok(outcome),
+        };
+        let len = compression::unpack(frame.get(..len).unwrap_or_default(), buf)?;
+        Ok(RxOutcome::Received(len))
+    }
+    /// Checks if a single RX operation has completed, verifies the checksum of the message, copies the message without
+    /// the checksum into `buf` and returns the amount of payload bytes
+    ///
+    /// # Checksum
+    /// The message must have been sent via [`Self::start_tx_checked`] with the same checksum. If the checksum is
+    /// invalid or the message is too short to contain a checksum, `Ok(RxOutcome::CrcError)` is returned, like for a
+    /// hardware CRC error.
+    ///
+    /// # Non-Blocking, Outcomes and Buffer too small
+    /// See [`Self::complete_rx`]; `buf` must be able to hold the checksum as well.
+    pub fn complete_rx_checked<C>(&mut self, buf: &mut [u8], checksum: &C) -> Result<RxOutcome, RxCompleteError>
+    where
+        C: Checksum,
+    {
+        let len = match self.complete_rx(buf)?.into_received() {
+            Ok(len) => len,
+            // The RX operation has not been completed successfully
+            Err(outcome) => return Ok(outcome),
+        };
+        match checksum::open(buf.get(..len).unwrap_or_default(), checksum) {
+            Some(payload) => Ok(RxOutcome::Received(payload.len())),
+            None => Ok(RxOutcome::CrcError),
+        }
+    }
+    /// Checks if a single RX operation has completed, copies the message into `buf` and returns the amount of bytes
+    /// received together with the reception timestamp
+    ///
+    /// # Timestamp
+    /// The timestamp is taken from `clock` as soon as the RX completion is observed, before the message is copied. To
+    /// get an accurate timestamp of the actual reception, call this function from the DIO0 interrupt handler (DIO0
+    /// signals `RxDone` by default).
+    ///
+    /// # Non-Blocking, Outcomes and Errors
+    /// See [`Self::complete_rx`].
+    pub fn complete_rx_timestamped<C>(
+        &mut self,
+        buf: &mut [u8],
+        clock: &C,
+    ) -> Result<RxOutcome<(usize, u64)>, RxCompleteError>
+    where
+        C: Clock,
+    {
+        // Get packet begin and length and take the timestamp
+        let (start, len) = match self.rx_packet()?.into_received() {
+            Ok(packet) => packet,
+            // The RX operation has not been completed successfully
+            Err(outcome) => return Ok(outcome),
+        };
+        let timestamp = clock.now();
+
+        // Copy data from FIFO and return the amount of bytes copied and the timestamp
+        let len = self.copy_rx_packet(start, len, buf)?;
+        Ok(RxOutcome::Received((len, timestamp)))
+    }
+    /// Checks if a single RX operation has completed, and passes a sequential reader for the received message to `f`
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If the RX operation is not done yet, it returns `Ok(RxOutcome::Pending)` without
+    /// calling `f`.
+    ///
+    /// # Zero-Copy
+    /// Unlike [`Self::complete_rx`], this function does not require an intermediate buffer, so protocol parsers can
+    /// decode the message directly from the modem FIFO. The reader can be dropped at any time; the message stays in the
+    /// FIFO until the next RX operation.
+    ///
+    /// # Timeout or CRC errors
+    /// If the receive operation times out or the received message is corrupt, `Ok(RxOutcome::Timeout)` or
+    /// `Ok(RxOutcome::CrcError)` is returned respectively without calling `f`.
+    pub fn complete_rx_with<F, T>(&mut self, f: F) -> Result<RxOutcome<T>, IoError>
+    where
+        F: FnOnce(&mut FifoReader<Device>) -> T,
+    {
+        // Get packet begin and length
+        let (start, len) = match self.rx_packet()?.into_received() {
+            Ok(packet) => packet,
+            // The RX operation has not been completed successfully
+            Err(outcome) => return Ok(outcome),
+        };
+
+        // Pass the reader to the callback
+        let mut reader = FifoReader::new(&mut self.spi, start, len);
+        Ok(RxOutcome::Received(f(&mut reader)))
+    }
+    /// Checks if a single RX operation has completed, copies as much of the message as fits into `buf`, and returns the
+    /// amount of bytes copied together with the amount of bytes remaining
+    ///
+    /// # Non-Blocking, Outcomes and Errors
+    /// See [`Self::complete_rx`].
+    ///
+    /// # Partial Reads
+    /// Unlike [`Self::complete_rx`], a message that is longer than `buf` is not rejected; instead, the remainder can be
+    /// retrieved in chunks via subsequent calls until the amount of remaining bytes is `0`. This allows
+    /// memory-constrained parsers to process a message without buffering it entirely. The read position is kept until
+    /// the next RX operation is started.
+    pub fn complete_rx_partial(&mut self, buf: &mut [u8]) -> Result<RxOutcome<(usize, usize)>, IoError> {
+        // Continue a partially read message, or get packet begin and length
+        let (start, len) = match self.rx_cursor {
+            Some(cursor) => cursor,
+            None => match self.rx_packet()?.into_received() {
+                Ok(packet) => packet,
+                // The RX operation has not been completed successfully
+                Err(outcome) => return Ok(outcome),
+            },
+        };
+
+        // Copy the next chunk from FIFO and keep the read position
+        let mut reader = FifoReader::new(&mut self.spi, start, len);
+        let copied = reader.read(buf)?;
+        let (address, remaining) = reader.position();
+        self.rx_cursor = Some((address, remaining));
+        Ok(RxOutcome::Received((copied, remaining as usize)))
+    }
+    /// Copies the received message at the given FIFO address into `buf` and returns the amount of bytes copied
+    fn copy_rx_packet(&mut self, start: u8, len: u8, buf: &mut [u8]) -> Result<usize, RxCompleteError> {
+        // Validate the buffer size
+        let Some(buf) = buf.get_mut(..len as usize) else {
+            // The message does not fit into the buffer
+            return Err(err!(BufferTooSmallError, "RX buffer is too small"))?;
+        };
+
+        // Copy data from FIFO
+        let mut reader = FifoReader::new(&mut self.spi, start, len);
+        Ok(reader.read(buf)?)
+    }
+    /// Checks if a single RX operation has completed, and returns the FIFO address and length of the received message
+    fn rx_packet(&mut self) -> Result<RxOutcome<(u8, u8)>, IoError> {
         // Check for errors
         let 0b0 = self.spi.read(RegIrqFlagsRxTimeout)? else {
             // The RX operation has timeouted
-            return Err(err!(TimeoutError, "RX timeout"))?;
+            #[cfg(feature = "stats")]
+            self.stats.count_rx_timeout();
+            self.set_rf_state(RfSwitchState::Off)?;
+            return Ok(RxOutcome::Timeout);
         };
         let 0b0 = self.spi.read(RegIrqFlagsPayloadCrcError)? else {
             // The RX operation has failed
-            return Err(err!(InvalidMessageError, "RX CRC error"))?;
+            #[cfg(feature = "stats")]
+            self.stats.count_crc_error();
+            self.set_rf_state(RfSwitchState::Off)?;
+            return Ok(RxOutcome::CrcError);
         };
 
         // Check for RX done
         let 0b1 = self.spi.read(RegIrqFlagsRxDone)? else {
             // The RX operation has not been completed yet
-            return Ok(None);
+            return Ok(RxOutcome::Pending);
         };
 
+        // Power down the RF switch as the modem has returned to standby
+        #[cfg(feature = "stats")]
+        self.stats.count_rx_packet();
+        self.set_rf_state(RfSwitchState::Off)?;
+
         // Get packet begin and length
         let start = self.spi.read(RegFifoRxCurrentAddr)?;
         let len = self.spi.read(RegRxNbBytes)?;
-        let to_copy = cmp::min(len as usize, buf.len());
+        Ok(RxOutcome::Received((start, len)))
+    }
 
-        // Copy data from FIFO
-        for (index, slot) in buf.iter_mut().take(to_copy).enumerate() {
-            // Validate the index
-            #[allow(clippy::expect_used, reason = "The values from the modem should be always valid")]
-            let offset = start.checked_add(index as u8).expect("FIFO out of bound access");
+    /// Starts a continuous RX operation and returns immediately
+    ///
+    /// # Non-Blocking
+    /// This functions starts the continuous RX operation and returns immediately. The modem keeps receiving until
+    /// another operation is started. To get the received packets, use [`Self::complete_rx_continuous`].
+    pub fn start_rx_continuous(&mut self) -> Result<(), RxStartError> {
+        self.ensure_lora()?;
+        // Enter standby to reset the packet counter and the FIFO pointers, and prepare RX
+        self.set_mode(Self::REG_OPMODE_MODE_STANDBY)?;
+        self.spi.write(RegFifoAddrPtr, 0x00)?;
+        self.reset_rx()?;
 
-            // Set source address and read byte
-            self.spi.write(RegFifoAddrPtr, offset)?;
-            *slot = self.spi.read(RegFifo)?;
+        // Start RX
+        self.rx_after_tx = false;
+        self.rx_packet_count = 0;
+        self.rx_dropped = 0;
+        self.set_mode(Self::REG_OPMODE_MODE_RXCONTINUOUS)?;
+        Ok(())
+    }
+    /// Checks if a continuous RX operation has received a packet, copies the message into `buf` and returns the amount
+    /// of bytes received together with the amount of dropped packets
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If no new packet has been received, it returns `Ok(RxOutcome::Pending)`. If a
+    /// corrupt packet has been received, it returns `Ok(RxOutcome::CrcError)`.
+    ///
+    /// # Dropped Packets
+    /// The modem stores subsequent packets back-to-back in the FIFO ring buffer, but only reports the address and
+    /// length of the latest one. If several packets arrive between two polls, only the latest one can be retrieved; the
+    /// others are reported as dropped. Similarly, if the latest packet is overwritten by the next one while it is
+    /// copied, it is dropped and reported together with the next retrieved packet. A return value of `(len, 0)` thus
+    /// means that no packet has been lost since the last retrieved packet.
+    ///
+    /// # Buffer too small
+    /// If the received message is longer than `buf`, an error is returned and nothing is copied; the packet remains
+    /// pending, so the call can be retried with a larger buffer.
+    pub fn complete_rx_continuous(&mut self, buf: &mut [u8]) -> Result<RxOutcome<(usize, u16)>, RxCompleteError> {
+        // Check for CRC errors; in continuous mode, the interrupts must be reset manually
+        if self.spi.read(RegIrqFlagsPayloadCrcError)? == 0b1 {
+            self.spi.write(RegIrqFlagsPayloadCrcError, 1)?;
+            self.spi.write(RegIrqFlagsRxDone, 1)?;
+            #[cfg(feature = "stats")]
+            self.stats.count_crc_error();
+            return Ok(RxOutcome::CrcError);
+        }
+
+        // Check for new valid packets
+        let packet_count = self.rx_packet_count()?;
+        let Some(missed) = packet_count.wrapping_sub(self.rx_packet_count).checked_sub(1) else {
+            // No new packet has been received yet
+            return Ok(RxOutcome::Pending);
+        };
+
+        // Get the latest packet, and re-check the counter so the address and length belong to the counted packet
+        let start = self.spi.read(RegFifoRxCurrentAddr)?;
+        let len = self.spi.read(RegRxNbBytes)?;
+        if self.rx_packet_count()? != packet_count {
+            // A newer packet has arrived in between, so retrieve it with the next poll
+            return Ok(RxOutcome::Pending);
+        }
+
+        // Copy the latest packet from the FIFO
+        let copied = self.copy_rx_packet(start, len, buf)?;
+
+        // Check whether the receiver has wrapped around and overwritten the packet while it was copied; the pointer
+        // holds the address of the last byte written, so it is at `len - 1` for an intact packet, beyond it if the next
+        // packet is being received, and wraps around to below it once the next packet overwrites its start
+        let last_written = self.spi.read(RegFifoRxByteAddr)?.wrapping_sub(start);
+        self.spi.write(RegIrqFlagsRxDone, 1)?;
+        self.rx_packet_count = packet_count;
+        let dropped = self.rx_dropped.saturating_add(missed);
+        if u16::from(last_written).saturating_add(1) < u16::from(len) {
+            // The packet is corrupt, so drop it and report it with the next packet
+            self.rx_dropped = dropped.saturating_add(1);
+            return Ok(RxOutcome::Pending);
+        }
+
+        // Reset the dropped counter and return the packet
+        #[cfg(feature = "stats")]
+        self.stats.count_rx_packet();
+        self.rx_dropped = 0;
+        Ok(RxOutcome::Received((copied, dropped)))
+    }
+
+    /// The hardware-side valid header and packet counters since the last transition into RX mode
+    ///
+    /// # Diagnostics
+    /// Comparing the hardware counters with the amount of packets the application has consumed reveals polling gaps
+    /// (also see [`RxCounters`]).
+    pub fn rx_counters(&mut self) -> Result<RxCounters, IoError> {
+        // Read the header count registers
+        let header_count_msb = self.spi.read(RegRxHeaderCntValueMsb)?;
+        let header_count_lsb = self.spi.read(RegRxHeaderCntValueLsb)?;
+        let header_count = u16::from_be_bytes([header_count_msb, header_count_lsb]);
+
+        // Read the packet count registers
+        let packet_count = self.rx_packet_count()?;
+        Ok(RxCounters::new(header_count, packet_count))
+    }
+    /// Reads the amount of valid packets received since the last transition into RX mode
+    fn rx_packet_count(&mut self) -> Result<u16, IoError> {
+        let packet_count_msb = self.spi.read(RegRxPacketCntValueMsb)?;
+        let packet_count_lsb = self.spi.read(RegRxPacketCntValueLsb)?;
+        Ok(u16::from_be_bytes([packet_count_msb, packet_count_lsb]))
+    }
+
+    /// Schedules a single channel activity detection (CAD) and returns immediately
+    ///
+    /// # Non-Blocking
+    /// This functions schedules the CAD operation and returns immediately. To check if the CAD operation is done and to
+    /// get the result, use [`Self::complete_cad`]. A CAD operation takes roughly two symbols.
+    pub fn start_cad(&mut self) -> Result<(), IoError> {
+        // Enable interrupts
+        self.spi.write(RegIrqFlagsMaskCadDoneMask, 0)?;
+        self.spi.write(RegIrqFlagsMaskCadDetectedMask, 0)?;
+
+        // Reset possible old interrupts
+        self.spi.write(RegIrqFlagsCadDone, 1)?;
+        self.spi.write(RegIrqFlagsCadDetected, 1)?;
+
+        // Start CAD
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_CAD)?;
+        Ok(())
+    }
+    /// Checks if a channel activity detection has completed, and returns whether LoRa activity was detected
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If the CAD operation is not done yet, it returns `Ok(None)`. Once the CAD
+    /// operation is done, the modem automatically returns to standby.
+    pub fn complete_cad(&mut self) -> Result<Option<bool>, IoError> {
+        // Check for CAD done
+        let 0b1 = self.spi.read(RegIrqFlagsCadDone)? else {
+            // The CAD operation has not been completed yet
+            return Ok(None);
+        };
+
+        // Get the CAD result and power down the RF switch as the modem has returned to standby
+        self.set_rf_state(RfSwitchState::Off)?;
+        let detected = self.spi.read(RegIrqFlagsCadDetected)?;
+        Ok(Some(detected == 0b1))
+    }
+
+    /// Handles a pending interrupt of the modem, clears the handled flags and returns the event, or `None` if no
+    /// interrupt is pending
+    ///
+    /// # Interrupt Handlers
+    /// This function reads the IRQ flags once, and handles the most relevant pending flag with as few SPI transactions
+    /// as possible, so it can be called directly from a DIO interrupt handler. If several interrupts are pending, it
+    /// returns one event per call, so it should be called until it returns `None`.
+    ///
+    /// # Important
+    /// This function is an alternative to polling via [`Self::complete_tx`], [`Self::complete_rx`] and
+    /// [`Self::complete_cad`]; as the flags are cleared, these functions will not observe the handled events anymore.
+    /// A received message can be read via [`Self::complete_rx_partial`]. Packets of a continuous RX operation can still
+    /// be retrieved via [`Self::complete_rx_continuous`], as it tracks the packet counters instead.
+    pub fn handle_interrupt(&mut self) -> Result<Option<Event>, IoError> {
+        // Read the flags and select the most relevant pending flag
+        let flags = self.spi.read(RegIrqFlags)?;
+        let relevance = [
+            Self::REG_IRQFLAGS_RXTIMEOUT,
+            Self::REG_IRQFLAGS_PAYLOADCRCERROR,
+            Self::REG_IRQFLAGS_RXDONE,
+            Self::REG_IRQFLAGS_TXDONE,
+            Self::REG_IRQFLAGS_CADDONE,
+            Self::REG_IRQFLAGS_FHSSCHANGECHANNEL,
+        ];
+        let Some(flag) = relevance.into_iter().find(|flag| flags & flag != 0) else {
+            // No interrupt is pending
+            return Ok(None);
+        };
+
+        // Clear the flag together with its companion flags, and complete the event
+        let event = match flag {
+            Self::REG_IRQFLAGS_RXTIMEOUT => {
+                self.spi.write(RegIrqFlags, flag)?;
+                #[cfg(feature = "stats")]
+                self.stats.count_rx_timeout();
+                self.complete_rx_state()?;
+                Event::RxTimeout
+            }
+            Self::REG_IRQFLAGS_PAYLOADCRCERROR => {
+                self.spi.write(RegIrqFlags, flag | Self::REG_IRQFLAGS_RXDONE)?;
+                #[cfg(feature = "stats")]
+                self.stats.count_crc_error();
+                self.complete_rx_state()?;
+                Event::CrcError
+            }
+            Self::REG_IRQFLAGS_RXDONE => {
+                // Keep the message position for partial reads
+                self.spi.write(RegIrqFlags, flag)?;
+                let start = self.spi.read(RegFifoRxCurrentAddr)?;
+                let len = self.spi.read(RegRxNbBytes)?;
+                self.rx_cursor = Some((start, len));
+                #[cfg(feature = "stats")]
+                self.stats.count_rx_packet();
+                self.complete_rx_state()?;
+                Event::RxDone(len as usize)
+            }
+            Self::REG_IRQFLAGS_TXDONE => {
+                self.spi.write(RegIrqFlags, flag)?;
+                self.complete_tx_state()?;
+                Event::TxDone
+            }
+            Self::REG_IRQFLAGS_CADDONE => {
+                self.spi.write(RegIrqFlags, flag | Self::REG_IRQFLAGS_CADDETECTED)?;
+                self.set_rf_state(RfSwitchState::Off)?;
+                Event::CadDone(flags & Self::REG_IRQFLAGS_CADDETECTED != 0)
+            }
+            _ => {
+                self.spi.write(RegIrqFlags, flag)?;
+                Event::FhssChange(self.spi.read(RegHopChannelFhssPresentChannel)?)
+            }
+        };
+        Ok(Some(event))
+    }
+    /// Powers down the RF switch after a single RX operation has completed; continuous RX operations keep running
+    fn complete_rx_state(&mut self) -> Result<(), IoError> {
+        if self.spi.read(RegOpModeMode)? != Self::REG_OPMODE_MODE_RXCONTINUOUS {
+            self.set_rf_state(RfSwitchState::Off)?;
         }
+        Ok(())
+    }
+
+    /// Generates a random `u32` from the wideband RSSI noise
+    ///
+    /// # Blocking
+    /// This function enters continuous RX mode to sample the wideband RSSI once per millisecond, i.e. it blocks for
+    /// about 32 ms, and returns to standby afterwards; any ongoing operation is aborted.
+    ///
+    /// # Interrupts
+    /// All IRQs are masked while sampling, so a packet received in the meantime does not trigger the DIO interrupt
+    /// handlers. Afterwards, the IRQ flags are cleared and the previous IRQ mask is restored, even if sampling failed.
+    ///
+    /// # Randomness
+    /// The random value is derived from the least significant bit of the wideband RSSI. Closely spaced samples are
+    /// correlated, so the samples are taken 1 ms apart as recommended by Semtech AN1200.24. The value is suitable to
+    /// seed backoff timers or channel selection, but it is __not__ cryptographically secure.
+    pub fn random_u32<Timer>(&mut self, timer: &mut Timer) -> Result<u32, IoError>
+    where
+        Timer: DelayNs,
+    {
+        /// The interval between two samples in microseconds
+        const SAMPLE_INTERVAL_US: u32 = 1000;
 
-        // Return the amount of bytes copied
-        Ok(Some(len as usize))
+        // Mask all interrupts, and enter continuous RX to sample the wideband RSSI
+        let irq_mask = self.spi.read(RegIrqFlagsMask)?;
+        self.spi.write(RegIrqFlagsMask, u8::MAX)?;
+        self.rx_after_tx = false;
+        let random = self.set_mode(Self::REG_OPMODE_MODE_RXCONTINUOUS).and_then(|()| {
+            // Sample the least significant bits
+            let mut random = 0;
+            for _ in 0..u32::BITS {
+                timer.delay_us(SAMPLE_INTERVAL_US);
+                let rssi_wideband = self.spi.read(RegRssiWideband)?;
+                random = (random << 1) | (rssi_wideband as u32 & 0b1);
+            }
+            Ok(random)
+        });
+
+        // Return to standby and restore the interrupts, even if sampling failed
+        self.set_mode(Self::REG_OPMODE_MODE_STANDBY)?;
+        self.spi.write(RegIrqFlags, u8::MAX)?;
+        self.spi.write(RegIrqFlagsMask, irq_mask)?;
+        random
     }
 
     /// Get the Relative Signal Strength Indicator (RSSI) of the last received packet.
@@ -473,6 +2285,117 @@ where
         Ok(rssi_raw as i16 + rssi_offset)
     }
 
+    /// Get the current Relative Signal Strength Indicator (RSSI) of the channel
+    ///
+    /// # Important
+    /// The value is only updated while the modem is in RX mode, so this function is only useful during an ongoing RX
+    /// operation.
+    pub fn rssi(&mut self) -> Result<i16, IoError> {
+        // Get raw RSSI value and frequency-dependent RSSI offset
+        let rssi_raw = self.spi.read(RegRssiValue)?;
+        let rssi_offset = match self.frequency()? < Self::HIGH_FREQUENCY_THRESHOLD {
+            true => Self::LF_RSSI_OFFSET,
+            false => Self::HF_RSSI_OFFSET,
+        };
+
+        // Compute final RSSI value
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
+        Ok(rssi_raw as i16 + rssi_offset)
+    }
+    /// Sweeps the carrier from `start` to `stop` (inclusive) in steps of `step`, samples the peak RSSI in dBm at each
+    /// point for `dwell`, writes the RSSI values into `buf` and returns the amount of points
+    ///
+    /// # Blocking
+    /// This function blocks for roughly `dwell` per point. The sweep ends early once `buf` is full.
+    ///
+    /// # Important
+    /// The RSSI is measured within the configured bandwidth, so the step should usually match the bandwidth. The scan
+    /// only receives, so the region lockout and the TX power policy do not apply; the range must be supported by the
+    /// chip though. The previous frequency and mode are restored afterwards, even if the scan failed.
+    ///
+    /// # Standby
+    /// If a TX, RX or CAD operation is running, a [`BusyError`] is returned; enter standby (see [`Self::standby`])
+    /// first.
+    pub fn spectrum_scan<Timer>(
+        &mut self,
+        start: Frequency,
+        stop: Frequency,
+        step: Frequency,
+        dwell: Duration,
+        buf: &mut [i16],
+        timer: &mut Timer,
+    ) -> Result<usize, ConfigError>
+    where
+        Timer: DelayNs,
+    {
+        // Validate the range
+        if step.as_u32() == 0 || start > stop {
+            return Err(err!(InvalidArgumentError, "Invalid spectrum scan range"))?;
+        }
+        if !self.capabilities.supports_frequency(start) || !self.capabilities.supports_frequency(stop) {
+            return Err(err!(InvalidArgumentError, "Spectrum scan range is not supported by the chip"))?;
+        }
+        self.ensure_idle()?;
+
+        // Sweep the range, and always restore the previous frequency and mode, even if the sweep failed
+        let mode = self.spi.read(RegOpModeMode)?;
+        let frequency_word = self.frequency_word()?;
+        let frequencies = (start.as_u32()..=stop.as_u32()).step_by(step.as_u32() as usize);
+        let points = self.sweep_peak_rssi(frequencies.map(Frequency::hz), dwell, buf, timer);
+        self.standby()?;
+        self.write_frequency(&frequency_word)?;
+        self.set_mode(mode)?;
+        Ok(points?)
+    }
+    /// Samples the peak RSSI at each of the given frequencies, writes the RSSI values into `buf` and returns the amount
+    /// of points
+    fn sweep_peak_rssi<Frequencies, Timer>(
+        &mut self,
+        frequencies: Frequencies,
+        dwell: Duration,
+        buf: &mut [i16],
+        timer: &mut Timer,
+    ) -> Result<usize, IoError>
+    where
+        Frequencies: Iterator<Item = Frequency>,
+        Timer: DelayNs,
+    {
+        let mut points = 0usize;
+        for (slot, frequency) in buf.iter_mut().zip(frequencies) {
+            // Retune in standby without the TX policy checks, and sample the RSSI in continuous RX
+            self.standby()?;
+            let frequency = frequency.with_ppm(self.frequency_correction_ppm);
+            self.write_frequency(&FrequencyWord::new(frequency))?;
+            *slot = self.sample_peak_rssi(dwell, timer)?;
+            points = points.saturating_add(1);
+        }
+        Ok(points)
+    }
+    /// Samples the peak RSSI in continuous RX for the given duration
+    fn sample_peak_rssi<Timer>(&mut self, dwell: Duration, timer: &mut Timer) -> Result<i16, IoError>
+    where
+        Timer: DelayNs,
+    {
+        /// The sample interval in microseconds
+        const SAMPLE_INTERVAL_US: u32 = 100;
+
+        // Start RX and take the first sample after one interval
+        self.rx_after_tx = false;
+        self.set_mode(Self::REG_OPMODE_MODE_RXCONTINUOUS)?;
+        let mut remaining = dwell.as_micros();
+        let mut peak = i16::MIN;
+        loop {
+            timer.delay_us(SAMPLE_INTERVAL_US);
+            peak = peak.max(self.rssi()?);
+
+            // Check whether the dwell time has passed
+            let Some(next_remaining) = remaining.checked_sub(SAMPLE_INTERVAL_US as u128) else {
+                return Ok(peak);
+            };
+            remaining = next_remaining;
+        }
+    }
+
     /// Get the signal strength of the last received packet
     ///
     /// # Note
@@ -493,80 +2416,292 @@ where
         Ok((self.spi.read(RegPktSnrValue)? as i8) / 4)
     }
 
-    /// Dumps all used registers; usefule for debugging purposes
-    #[cfg(feature = "debug")]
-    pub fn dump_registers(&mut self) -> Result<[u8; REGISTER_MAX as usize + 1], IoError> {
-        // A dynamic register for dumping purposes
-        struct DynamicRegister(u8);
-        impl Register for DynamicRegister {
-            fn address(&self) -> u8 {
-                self.0
+    /// Get the coding rate of the last received packet
+    ///
+    /// # Explicit Header
+    /// In explicit header mode, the coding rate is taken from the packet header, so receivers can detect and adapt to
+    /// peers using another coding rate. The value is updated once the header has been received.
+    pub fn get_packet_coding_rate(&mut self) -> Result<CodingRate, IoError> {
+        let coding_rate = self.spi.read(RegModemStatRxCodingRate)?;
+        CodingRate::parse(coding_rate)
+    }
+
+    /// Get the metadata of the last received packet
+    pub fn get_packet_metadata(&mut self) -> Result<RxMetadata, IoError> {
+        let rssi = self.get_packet_rssi()?;
+        let snr = self.get_packet_snr()?;
+        let coding_rate = self.get_packet_coding_rate()?;
+        Ok(RxMetadata::new(rssi, snr, coding_rate))
+    }
+
+    /// Waits until the modem signals that the current mode is ready via a DIO5 pin mapped to `ModeReady`
+    ///
+    /// # Blocking
+    /// This function blocks until the DIO5 pin goes high or the timeout expires, and returns whether the mode is ready.
+    ///
+    /// # DIO5 Mapping
+    /// DIO5 is mapped to `ModeReady` by default after reset (see [`Self::map_dio5_mode_ready`]). The `ModeReady`
+    /// signal is useful after mode changes (e.g. leaving sleep), as the oscillator and PLL need some time to settle.
+    pub fn wait_mode_ready<Pin, Timer>(
+        &mut self,
+        dio5: &mut Pin,
+        timer: &mut Timer,
+        timeout: Duration,
+    ) -> Result<bool, IoError>
+    where
+        Pin: InputPin,
+        Timer: DelayNs,
+    {
+        /// The poll interval in microseconds
+        const POLL_INTERVAL_US: u32 = 10;
+
+        // Poll the DIO5 pin until it goes high or the timeout expires
+        let mut remaining = timeout.as_micros();
+        loop {
+            // Check the pin
+            let ready = dio5.is_high().map_err(|_| err!(IoError, "Failed to read DIO5 pin"))?;
+            if ready {
+                return Ok(true);
             }
+
+            // Wait for the next poll
+            let Some(next_remaining) = remaining.checked_sub(POLL_INTERVAL_US as u128) else {
+                // The timeout has expired
+                return Ok(false);
+            };
+            remaining = next_remaining;
+            timer.delay_us(POLL_INTERVAL_US);
         }
+    }
+    /// Maps the DIO5 pin to `ModeReady`, e.g. if it has been remapped to `ClkOut`
+    pub fn map_dio5_mode_ready(&mut self) -> Result<(), IoError> {
+        self.spi.write(RegDioMapping2Dio5Mapping, Self::REG_DIOMAPPING2_DIO5_MODEREADY)
+    }
+    /// Maps the DIO5 pin to `ClkOut` (also see [`Self::set_clk_out`])
+    ///
+    /// # Important
+    /// [`Self::wait_mode_ready`] requires DIO5 to be mapped to `ModeReady`; use [`Self::map_dio5_mode_ready`] to
+    /// restore the mapping.
+    pub fn map_dio5_clk_out(&mut self) -> Result<(), IoError> {
+        self.spi.write(RegDioMapping2Dio5Mapping, Self::REG_DIOMAPPING2_DIO5_CLKOUT)
+    }
+
+    /// The current clock output
+    pub fn clk_out(&mut self) -> Result<ClkOut, IoError> {
+        let clk_out = self.access_fsk_registers(|spi| spi.read(RegOscClkOut))?;
+        Ok(ClkOut::parse(clk_out))
+    }
+    /// Sets the clock output, e.g. to feed the modem clock to the MCU or to disable it to save power
+    ///
+    /// # Shared Registers
+    /// `RegOsc` is only accessible via the FSK register bank; the bank is switched for the access and restored to LoRa
+    /// afterwards.
+    pub fn set_clk_out(&mut self, clk_out: ClkOut) -> Result<(), IoError> {
+        self.access_fsk_registers(|spi| spi.write(RegOscClkOut, clk_out as u8))
+    }
+    /// Maps the shared registers to the FSK register bank for the duration of `f`
+    fn access_fsk_registers<F, T>(&mut self, f: F) -> Result<T, IoError>
+    where
+        F: FnOnce(&mut Rfm95Connection<Device>) -> Result<T, IoError>,
+    {
+        // Switch to the FSK bank and always restore the LoRa bank, even if the access failed
+        self.spi.write(RegOpModeAccessSharedReg, Self::REG_OPMODE_ACCESSSHAREDREG_FSK)?;
+        let result = f(&mut self.spi);
+        self.spi.write(RegOpModeAccessSharedReg, Self::REG_OPMODE_ACCESSSHAREDREG_LORA)?;
+        result
+    }
 
+    /// The silicon revision of the modem
+    pub fn silicon_revision(&mut self) -> Result<u8, IoError> {
+        self.spi.read(RegVersion)
+    }
+
+    /// Performs a power-on self test of the SPI connection and the digital part of the modem, and returns a report
+    ///
+    /// # Checks
+    /// The self test checks operation mode transitions between sleep and standby, performs a round-trip of test
+    /// patterns through a scratch register, and writes and reads back a test pattern to the entire FIFO. This is
+    /// intended as a quick go/no-go check for solder and SPI integrity in production, before functional RF tests.
+    ///
+    /// # Important
+    /// Any ongoing operation is aborted, the FIFO contents are overwritten, and the modem is left in standby. I/O
+    /// errors are returned as errors and not as failed checks.
+    pub fn self_test(&mut self) -> Result<SelfTestReport, IoError> {
+        /// The test patterns for the scratch register
+        const SCRATCH_PATTERNS: [u8; 4] = [0x55, 0xAA, 0x00, 0xFF];
+        /// The seed of the FIFO test pattern
+        const FIFO_PATTERN: u8 = 0xA5;
+
+        // Test the operation mode transitions
+        self.rx_after_tx = false;
+        let mut opmode = true;
+        for mode in [Self::REG_OPMODE_MODE_SLEEP, Self::REG_OPMODE_MODE_STANDBY] {
+            // Enter the mode and verify it and the LoRa mode
+            self.set_mode(mode)?;
+            opmode &= self.spi.read(RegOpModeMode)? == mode;
+            opmode &= self.spi.read(RegOpModeLongRangeMode)? == Self::REG_OPMODE_LONGRANGEMODE_LORA;
+        }
+
+        // Round-trip the scratch patterns through the symbol timeout register, which has no side effects in standby
+        let scratch_backup = self.spi.read(RegSymbTimeoutLsb)?;
+        let mut scratch = true;
+        for pattern in SCRATCH_PATTERNS {
+            self.spi.write(RegSymbTimeoutLsb, pattern)?;
+            scratch &= self.spi.read(RegSymbTimeoutLsb)? == pattern;
+        }
+        self.spi.write(RegSymbTimeoutLsb, scratch_backup)?;
+
+        // Write the FIFO pattern
+        let addr_ptr = self.spi.read(RegFifoAddrPtr)?;
+        self.spi.write(RegFifoAddrPtr, 0x00)?;
+        for address in 0..=u8::MAX {
+            self.spi.write(RegFifo, address ^ FIFO_PATTERN)?;
+        }
+
+        // Read back and verify the FIFO pattern
+        let mut fifo_errors: u16 = 0;
+        self.spi.write(RegFifoAddrPtr, 0x00)?;
+        for address in 0..=u8::MAX {
+            let byte = self.spi.read(RegFifo)?;
+            fifo_errors = fifo_errors.saturating_add(u16::from(byte != address ^ FIFO_PATTERN));
+        }
+        self.spi.write(RegFifoAddrPtr, addr_ptr)?;
+
+        // Assemble the report
+        let silicon_revision = self.spi.read(RegVersion)?;
+        Ok(SelfTestReport::new(silicon_revision, scratch, fifo_errors, opmode))
+    }
+
+    /// Reads the raw value of the register at the given address
+    ///
+    /// # Low-Level Access
+    /// This function gives unchecked access to the modem's register map, and is intended as escape hatch for chip
+    /// features that are not modelled by this driver. The driver does not track raw register accesses, so the caller
+    /// is responsible to ensure that they do not interfere with the driver's operation.
+    pub fn read_register(&mut self, address: u8) -> Result<u8, IoError> {
+        self.spi.read(DynamicRegister(address))
+    }
+    /// Overwrites the register at the given address with the given raw value
+    ///
+    /// # Low-Level Access
+    /// This function gives unchecked access to the modem's register map, and is intended as escape hatch for chip
+    /// features that are not modelled by this driver. Writing invalid values or changing registers that are managed by
+    /// the driver may leave the modem in an inconsistent state; use with care.
+    pub fn write_register(&mut self, address: u8, value: u8) -> Result<(), IoError> {
+        self.spi.write(DynamicRegister(address), value)
+    }
+
+    /// Dumps all used registers; useful for debugging purposes
+    ///
+    /// # Important
+    /// Reading `RegFifo` advances the FIFO address pointer, so the dump should not be taken during an ongoing TX or RX
+    /// operation.
+    pub fn dump_registers(&mut self) -> Result<DecodedRegisters, IoError> {
         // Dump all registers
-        let mut dump = [0; REGISTER_MAX as usize + 1];
-        for (register, slot) in dump.iter_mut().enumerate() {
+        let mut dump = [0; REGISTER_DUMP_SIZE];
+        for (register, slot) in (0..=REGISTER_MAX).zip(dump.iter_mut()) {
             // Read register
-            let register = DynamicRegister(register as u8);
+            let register = DynamicRegister(register);
             *slot = self.spi.read(register)?;
         }
-        Ok(dump)
+        Ok(DecodedRegisters::new(dump))
     }
-    /// Dumps the entire FIFO contents
+    /// Dumps the entire FIFO contents together with the current FIFO pointers
     #[cfg(feature = "debug")]
-    pub fn dump_fifo(&mut self) -> Result<[u8; RFM95_FIFO_SIZE], IoError> {
-        // Save FIFO position
-        let fifo_position = self.spi.read(RegFifoAddrPtr)?;
+    pub fn dump_fifo(&mut self) -> Result<FifoDump, IoError> {
+        // Save FIFO pointers
+        let addr_ptr = self.spi.read(RegFifoAddrPtr)?;
+        let tx_base_addr = self.spi.read(RegFifoTxBaseAddr)?;
+        let rx_base_addr = self.spi.read(RegFifoRxBaseAddr)?;
+        let rx_current_addr = self.spi.read(RegFifoRxCurrentAddr)?;
 
-        // Dump all registers
-        let mut dump = [0; RFM95_FIFO_SIZE];
-        for (index, slot) in dump.iter_mut().enumerate() {
+        // Dump the entire FIFO address space
+        let mut dump = [0; FIFO_DUMP_SIZE];
+        for (index, slot) in (0..=u8::MAX).zip(dump.iter_mut()) {
             // Read register
-            self.spi.write(RegFifoAddrPtr, index as u8)?;
+            self.spi.write(RegFifoAddrPtr, index)?;
             *slot = self.spi.read(RegFifo)?;
         }
 
         // Re-apply old FIFO position
-        self.spi.write(RegFifoAddrPtr, fifo_position)?;
-        Ok(dump)
+        self.spi.write(RegFifoAddrPtr, addr_ptr)?;
+        Ok(FifoDump::new(dump, addr_ptr, tx_base_addr, rx_base_addr, rx_current_addr))
     }
 }
-impl<Bus, Select, Delay> Rfm95Driver<ExclusiveDevice<Bus, Select, Delay>>
+impl<Bus, Select, Delay, Variant> Sx127xDriver<ExclusiveDevice<Bus, Select, Delay>, Variant>
 where
     Bus: SpiBus,
     Select: OutputPin,
     Delay: DelayNs,
+    Variant: Chip,
 {
     /// Creates a new raw SPI command interface for RFM95 from an SpiBus
     ///
     /// # Blocking
-    /// This function blocks for at least `11ms` plus additional time for the modem transactions. If you have tight
+    /// This function blocks for at least `6ms` plus additional time for the modem transactions. If you have tight
     /// scheduling requirements, you probably want to initialize this driver before entering your main event loop.
     ///
     /// # Important
     /// The RFM95 modem is initialized to LoRa-mode and put to standby. All other configurations are left untouched, so
     /// you probably want to configure the modem initially (also see [`Self::set_config`]).
-    pub fn new_from_bus<Reset>(bus: Bus, select: Select, mut reset: Reset, mut timer: Delay) -> Result<Self, IoError>
+    pub fn new_from_bus<Reset>(
+        mut bus: Bus,
+        mut select: Select,
+        mut reset: Reset,
+        mut timer: Delay,
+    ) -> Result<Self, IoError>
     where
         Reset: OutputPin,
     {
-        // Fully reset module and create exclusive device handle
-        Self::reset_module(&mut reset, &mut timer)?;
+        // Fully reset module and wait until it answers via a temporary device handle that leaves the timer to us
+        Self::reset_module(&mut reset, &mut timer, &Sx127xBuilder::new())?;
+        let probe = ExclusiveDevice::new_no_delay(&mut bus, &mut select)
+            .map_err(|_| err!(IoError, "Failed to pull chip select line to high"))?;
+        Self::wait_ready(&mut Rfm95Connection::init(probe), &mut timer)?;
+
+        // Create exclusive device handle
         let device = ExclusiveDevice::new(bus, select, timer)
             .map_err(|_| err!(IoError, "Failed to pull chip select line to high"))?;
 
         // Connect to and setup module and init `self`
-        let mut spi = Rfm95Connection::init(device);
-        Self::setup_module(&mut spi)?;
-        Ok(Self { spi })
+        let spi = Rfm95Connection::init(device);
+        Self::setup(spi, &Sx127xBuilder::new())
     }
 }
-impl<Device> Debug for Rfm95Driver<Device>
+/// The LoRa state that is saved while the modem is in carrier mode
+#[derive(Debug, Clone, Copy)]
+struct LoraState {
+    /// The modem config
+    config: Config,
+    /// The RX timeout in symbols
+    symbol_timeout: u16,
+    /// The payload length for the implicit header mode
+    payload_len: u8,
+    /// The mapping of DIO0 to DIO3
+    dio_mapping: u8,
+}
+
+impl<Device, Variant, Switch> Debug for Sx127xDriver<Device, Variant, Switch>
 where
     Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch + Debug,
 {
     fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
-        f.debug_struct("Rfm95Driver").field("device", &self.spi).finish()
+        f.debug_struct("Sx127xDriver")
+            .field("device", &self.spi)
+            .field("rx_after_tx", &self.rx_after_tx)
+            .field("regulatory", &self.regulatory)
+            .field("max_dwell", &self.max_dwell)
+            .field("frequency_correction_ppm", &self.frequency_correction_ppm)
+            .field("capabilities", &self.capabilities)
+            .field("rx_packet_count", &self.rx_packet_count)
+            .field("rx_dropped", &self.rx_dropped)
+            .field("rx_cursor", &self.rx_cursor)
+            .field("rf_switch", &self.rf_switch)
+            .field("rf_state", &self.rf_state)
+            .field("hooks", &self.hooks)
+            .field("carrier", &self.carrier)
+            .finish()
     }
 }
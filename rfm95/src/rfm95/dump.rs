@@ -0,0 +1,275 @@
+//! Human readable register dumps for debugging purposes
+
+use crate::rfm95::registers::*;
+use core::fmt::{Debug, Display, Formatter};
+
+/// The amount of registers in a register dump
+pub const REGISTER_DUMP_SIZE: usize = REGISTER_MAX as usize + 1;
+/// The amount of bytes in a FIFO dump (i.e. the entire address space of the FIFO)
+pub const FIFO_DUMP_SIZE: usize = u8::MAX as usize + 1;
+
+/// The names of the LoRa-mode registers by address
+const REGISTER_NAMES: &[(u8, &str)] = &[
+    (0x00, "RegFifo"),
+    (0x01, "RegOpMode"),
+    (0x06, "RegFrMsb"),
+    (0x07, "RegFrMid"),
+    (0x08, "RegFrLsb"),
+    (0x09, "RegPaConfig"),
+    (0x0A, "RegPaRamp"),
+    (0x0B, "RegOcp"),
+    (0x0C, "RegLna"),
+    (0x0D, "RegFifoAddrPtr"),
+    (0x0E, "RegFifoTxBaseAddr"),
+    (0x0F, "RegFifoRxBaseAddr"),
+    (0x10, "RegFifoRxCurrentAddr"),
+    (0x11, "RegIrqFlagsMask"),
+    (0x12, "RegIrqFlags"),
+    (0x13, "RegRxNbBytes"),
+    (0x14, "RegRxHeaderCntValueMsb"),
+    (0x15, "RegRxHeaderCntValueLsb"),
+    (0x16, "RegRxPacketCntValueMsb"),
+    (0x17, "RegRxPacketCntValueLsb"),
+    (0x18, "RegModemStat"),
+    (0x19, "RegPktSnrValue"),
+    (0x1A, "RegPktRssiValue"),
+    (0x1B, "RegRssiValue"),
+    (0x1C, "RegHopChannel"),
+    (0x1D, "RegModemConfig1"),
+    (0x1E, "RegModemConfig2"),
+    (0x1F, "RegSymbTimeoutLsb"),
+    (0x20, "RegPreambleMsb"),
+    (0x21, "RegPreambleLsb"),
+    (0x22, "RegPayloadLength"),
+    (0x23, "RegMaxPayloadLength"),
+    (0x24, "RegHopPeriod"),
+    (0x25, "RegFifoRxByteAddr"),
+    (0x26, "RegModemConfig3"),
+    (0x27, "RegPpmCorrection"),
+    (0x28, "RegFeiMsb"),
+    (0x29, "RegFeiMid"),
+    (0x2A, "RegFeiLsb"),
+    (0x2C, "RegRssiWideband"),
+    (0x2F, "RegIfFreq2"),
+    (0x30, "RegIfFreq1"),
+    (0x31, "RegDetectOptimize"),
+    (0x33, "RegInvertIQ"),
+    (0x36, "RegHighBwOptimize1"),
+    (0x37, "RegDetectionThreshold"),
+    (0x39, "RegSyncWord"),
+    (0x3A, "RegHighBwOptimize2"),
+    (0x3B, "RegInvertIQ2"),
+    (0x40, "RegDioMapping1"),
+    (0x41, "RegDioMapping2"),
+    (0x42, "RegVersion"),
+    (0x4B, "RegTcxo"),
+    (0x4D, "RegPaDac"),
+    (0x5B, "RegFormerTemp"),
+    (0x61, "RegAgcRef"),
+    (0x62, "RegAgcThresh1"),
+    (0x63, "RegAgcThresh2"),
+    (0x64, "RegAgcThresh3"),
+];
+
+/// The register fields known to the driver
+const REGISTER_FIELDS: &[(&str, &(dyn Register + Sync))] = &[
+    ("LongRangeMode", &RegOpModeLongRangeMode),
+    ("AccessSharedReg", &RegOpModeAccessSharedReg),
+    ("LowFrequencyModeOn", &RegOpModeLowFrequencyModeOn),
+    ("Mode", &RegOpModeMode),
+    ("PaSelect", &RegPaConfigPaSelect),
+    ("MaxPower", &RegPaConfigMaxPower),
+    ("OutputPower", &RegPaConfigOutputPower),
+    ("RxTimeoutMask", &RegIrqFlagsMaskRxTimeoutMask),
+    ("RxDoneMask", &RegIrqFlagsMaskRxDoneMask),
+    ("PayloadCrcErrorMask", &RegIrqFlagsMaskPayloadCrcErrorMask),
+    ("TxDoneMask", &RegIrqFlagsMaskTxDoneMask),
+    ("CadDoneMask", &RegIrqFlagsMaskCadDoneMask),
+    ("CadDetectedMask", &RegIrqFlagsMaskCadDetectedMask),
+    ("RxTimeout", &RegIrqFlagsRxTimeout),
+    ("RxDone", &RegIrqFlagsRxDone),
+    ("PayloadCrcError", &RegIrqFlagsPayloadCrcError),
+    ("TxDone", &RegIrqFlagsTxDone),
+    ("CadDone", &RegIrqFlagsCadDone),
+    ("CadDetected", &RegIrqFlagsCadDetected),
+    ("RxCodingRate", &RegModemStatRxCodingRate),
+    ("Bw", &RegModemConfig1Bw),
+    ("CodingRate", &RegModemConfig1CodingRate),
+    ("ImplicitHeaderModeOn", &RegModemConfig1ImplicitHeaderModeOn),
+    ("SpreadingFactor", &RegModemConfig2SpreadingFactor),
+    ("RxPayloadCrcOn", &RegModemConfig2RxPayloadCrcOn),
+    ("SymbTimeout(9:8)", &RegModemConfig2SymbTimeout98),
+    ("LowDataRateOptimize", &RegModemConfig3LowDataRateOptimize),
+    ("InvertIQ", &RegInvertIQ),
+    ("Dio5Mapping", &RegDioMapping2Dio5Mapping),
+];
+
+/// A decoded register dump
+///
+/// # Formatting
+/// The [`Display`] implementation renders one line per register with its address, name and raw value, followed by the
+/// decoded fields that are known to the driver. The [`Debug`] implementation renders the named registers as struct.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DecodedRegisters {
+    /// The raw register values, indexed by address
+    registers: [u8; REGISTER_DUMP_SIZE],
+}
+impl DecodedRegisters {
+    /// Creates a new decoded register dump from the given raw register values
+    pub const fn new(registers: [u8; REGISTER_DUMP_SIZE]) -> Self {
+        Self { registers }
+    }
+
+    /// The raw register values, indexed by address
+    pub const fn raw(&self) -> &[u8; REGISTER_DUMP_SIZE] {
+        &self.registers
+    }
+
+    /// Gets the value of the register at the given address
+    fn register(&self, address: u8) -> u8 {
+        self.registers.get(address as usize).copied().unwrap_or_default()
+    }
+    /// Extracts the value of the given register field
+    fn field(&self, field: &dyn Register) -> u8 {
+        (self.register(field.address()) & field.mask()) >> field.offset()
+    }
+}
+impl From<[u8; REGISTER_DUMP_SIZE]> for DecodedRegisters {
+    fn from(registers: [u8; REGISTER_DUMP_SIZE]) -> Self {
+        Self { registers }
+    }
+}
+impl Debug for DecodedRegisters {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        let mut debug = f.debug_struct("DecodedRegisters");
+        for (address, name) in REGISTER_NAMES {
+            // Format the register value as hex
+            debug.field(name, &format_args!("{:#04X}", self.register(*address)));
+        }
+        debug.finish()
+    }
+}
+impl Display for DecodedRegisters {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        for (address, name) in REGISTER_NAMES {
+            // Print the raw register value
+            writeln!(f, "[{address:#04X}] {name}: {:#04X}", self.register(*address))?;
+
+            // Print the known fields of the register
+            let fields = REGISTER_FIELDS.iter().filter(|(_, field)| field.address() == *address);
+            for (field_name, field) in fields {
+                writeln!(f, "    {field_name}: {:#04X}", self.field(*field))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A FIFO dump together with the FIFO pointers at the time of the dump
+///
+/// # Formatting
+/// The [`Display`] implementation renders a conventional hexdump with 16 bytes per row. The byte at the SPI address
+/// pointer is marked with `>`, and the start of the last received packet is marked with `*`. The [`Debug`]
+/// implementation renders the pointers, followed by the same hexdump.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FifoDump {
+    /// The raw FIFO contents
+    fifo: [u8; FIFO_DUMP_SIZE],
+    /// The SPI interface address pointer (`RegFifoAddrPtr`)
+    addr_ptr: u8,
+    /// The TX base address (`RegFifoTxBaseAddr`)
+    tx_base_addr: u8,
+    /// The RX base address (`RegFifoRxBaseAddr`)
+    rx_base_addr: u8,
+    /// The start address of the last received packet (`RegFifoRxCurrentAddr`)
+    rx_current_addr: u8,
+}
+impl FifoDump {
+    /// The amount of bytes per hexdump row
+    const ROW_LEN: usize = 16;
+
+    /// Creates a new FIFO dump from the given raw FIFO contents and pointers
+    pub const fn new(
+        fifo: [u8; FIFO_DUMP_SIZE],
+        addr_ptr: u8,
+        tx_base_addr: u8,
+        rx_base_addr: u8,
+        rx_current_addr: u8,
+    ) -> Self {
+        Self { fifo, addr_ptr, tx_base_addr, rx_base_addr, rx_current_addr }
+    }
+
+    /// The raw FIFO contents
+    pub const fn raw(&self) -> &[u8; FIFO_DUMP_SIZE] {
+        &self.fifo
+    }
+    /// The SPI interface address pointer (`RegFifoAddrPtr`) at the time of the dump
+    pub const fn addr_ptr(&self) -> u8 {
+        self.addr_ptr
+    }
+    /// The TX base address (`RegFifoTxBaseAddr`) at the time of the dump
+    pub const fn tx_base_addr(&self) -> u8 {
+        self.tx_base_addr
+    }
+    /// The RX base address (`RegFifoRxBaseAddr`) at the time of the dump
+    pub const fn rx_base_addr(&self) -> u8 {
+        self.rx_base_addr
+    }
+    /// The start address of the last received packet (`RegFifoRxCurrentAddr`) at the time of the dump
+    pub const fn rx_current_addr(&self) -> u8 {
+        self.rx_current_addr
+    }
+
+    /// Writes the hexdump rows
+    fn hexdump(&self, f: &mut Formatter) -> core::fmt::Result {
+        for (row_index, row) in self.fifo.chunks(Self::ROW_LEN).enumerate() {
+            // Print the row address
+            #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
+            let row_address = row_index * Self::ROW_LEN;
+            write!(f, "{row_address:02X}:")?;
+
+            // Print the bytes together with the pointer markers
+            for (index, byte) in row.iter().enumerate() {
+                #[allow(
+                    clippy::arithmetic_side_effects,
+                    clippy::cast_possible_truncation,
+                    reason = "The FIFO address space is exactly 256 bytes"
+                )]
+                let address = (row_address + index) as u8;
+                let marker = match address {
+                    address if address == self.addr_ptr => '>',
+                    address if address == self.rx_current_addr => '*',
+                    _ => ' ',
+                };
+                write!(f, "{marker}{byte:02X}")?;
+            }
+
+            // Print the ASCII representation
+            write!(f, "  |")?;
+            for byte in row {
+                let char = match byte.is_ascii_graphic() || *byte == b' ' {
+                    true => *byte as char,
+                    false => '.',
+                };
+                write!(f, "{char}")?;
+            }
+            writeln!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+impl Debug for FifoDump {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        writeln!(
+            f,
+            "FifoDump {{ addr_ptr: {:#04X}, tx_base_addr: {:#04X}, rx_base_addr: {:#04X}, rx_current_addr: {:#04X} }}",
+            self.addr_ptr, self.tx_base_addr, self.rx_base_addr, self.rx_current_addr
+        )?;
+        self.hexdump(f)
+    }
+}
+impl Display for FifoDump {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        self.hexdump(f)
+    }
+}
@@ -0,0 +1,22 @@
+//! Typed interrupt events
+
+/// An interrupt event of the modem, as returned by [`crate::rfm95::Sx127xDriver::handle_interrupt`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A TX operation has completed
+    TxDone,
+    /// A message with the given length has been received; it can be read via
+    /// [`crate::rfm95::Sx127xDriver::complete_rx_partial`]
+    RxDone(usize),
+    /// A single RX operation has timed out without receiving a message
+    RxTimeout,
+    /// A message has been received, but the payload CRC is invalid
+    CrcError,
+    /// A channel activity detection has completed; the flag indicates whether LoRa activity was detected
+    CadDone(bool),
+    /// The frequency hopping period has elapsed; the value is the current hopping channel
+    FhssChange(u8),
+    /// An RX operation has neither completed nor timed out long after its timeout, and the modem has been returned to
+    /// standby (see [`crate::rfm95::RxWatchdog`])
+    Stalled,
+}
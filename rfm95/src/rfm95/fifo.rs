@@ -0,0 +1,101 @@
+//! Sequential FIFO access
+
+use crate::error::IoError;
+use crate::rfm95::connection::Rfm95Connection;
+use crate::rfm95::registers::{RegFifo, RegFifoAddrPtr};
+use core::fmt::{Debug, Formatter};
+use embedded_hal::spi::SpiDevice;
+
+/// A sequential reader for a packet in the modem FIFO
+///
+/// # Zero-Copy
+/// The reader reads the packet directly from the modem FIFO, so no intermediate buffer is required.
+pub struct FifoReader<'a, Device>
+where
+    Device: SpiDevice,
+{
+    /// The SPI connection to the RFM95 radio
+    spi: &'a mut Rfm95Connection<Device>,
+    /// The FIFO address of the next byte to read
+    address: u8,
+    /// The amount of remaining bytes
+    remaining: u8,
+}
+impl<'a, Device> FifoReader<'a, Device>
+where
+    Device: SpiDevice,
+{
+    /// Creates a new FIFO reader for the packet at the given FIFO address
+    pub(crate) fn new(spi: &'a mut Rfm95Connection<Device>, address: u8, len: u8) -> Self {
+        Self { spi, address, remaining: len }
+    }
+
+    /// The FIFO address of the next byte to read and the amount of remaining bytes
+    pub(crate) const fn position(&self) -> (u8, u8) {
+        (self.address, self.remaining)
+    }
+
+    /// The amount of remaining bytes
+    pub const fn len(&self) -> usize {
+        self.remaining as usize
+    }
+    /// Whether all bytes have been read
+    pub const fn is_empty(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Reads the next byte, or returns `None` if all bytes have been read
+    pub fn read_u8(&mut self) -> Result<Option<u8>, IoError> {
+        // Get the next remaining byte
+        let Some(remaining) = self.remaining.checked_sub(1) else {
+            // All bytes have been read
+            return Ok(None);
+        };
+
+        // Set source address and read byte
+        self.spi.write(RegFifoAddrPtr, self.address)?;
+        let byte = self.spi.read(RegFifo)?;
+
+        // Advance the reader; the FIFO address space wraps around
+        self.address = self.address.wrapping_add(1);
+        self.remaining = remaining;
+        Ok(Some(byte))
+    }
+    /// Reads the next bytes into `buf` and returns the amount of bytes read
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        let mut read = 0;
+        for slot in buf.iter_mut() {
+            // Read the next byte
+            let Some(byte) = self.read_u8()? else {
+                // All bytes have been read
+                break;
+            };
+
+            // Store the byte
+            *slot = byte;
+            #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
+            (read += 1);
+        }
+        Ok(read)
+    }
+    /// Skips the next `len` bytes and returns the amount of bytes skipped
+    pub fn skip(&mut self, len: usize) -> usize {
+        // Skip the bytes by simply advancing the reader
+        let skipped = u8::try_from(len).unwrap_or(u8::MAX).min(self.remaining);
+        self.address = self.address.wrapping_add(skipped);
+        self.remaining = self.remaining.saturating_sub(skipped);
+        skipped as usize
+    }
+}
+impl<Device> Debug for FifoReader<'_, Device>
+where
+    Device: SpiDevice,
+{
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        (f.debug_struct("FifoReader"))
+            .field("spi", &self.spi)
+            .field("address", &self.address)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
@@ -0,0 +1,73 @@
+//! Precomputed frequency register values
+
+use crate::lora::types::Frequency;
+
+/// A frequency together with the precomputed register values of the modem
+///
+/// # Fast Hopping
+/// Translating a frequency into the crystal native register value requires a 64-bit division, which is expensive on
+/// small MCUs. Frequency hopping schemes and multi-channel scanners can precompute the words of their channels once at
+/// config time, and switch channels via [`crate::rfm95::Sx127xDriver::set_frequency_fast`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrequencyWord {
+    /// The frequency
+    frequency: Frequency,
+    /// The `RegFrMsb`, `RegFrMid` and `RegFrLsb` register values
+    frf: [u8; 3],
+    /// Whether the modem must be set to low-frequency mode
+    low_frequency_mode: bool,
+}
+impl FrequencyWord {
+    /// The frequency divider to compute the frequency in milli-hertz
+    pub(crate) const FREQUENCY_DIVIDER_MILLIHZ: u64 = 61_035;
+    /// The threshold for switching between low-frequency mode (below 525 MHz) and high frequency mode (above 779 MHz)
+    pub(crate) const HIGH_FREQUENCY_THRESHOLD: Frequency = Frequency::hz(652_000_000);
+
+    /// Precomputes the register values for the given frequency
+    pub const fn new(frequency: Frequency) -> Self {
+        // Translate the frequency into the crystal native frequency
+        // Note: We go via kHz/mHz to keep higher precision without floats
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
+        let frequency_khz = frequency.as_u32() as u64 * 1000;
+        #[allow(clippy::arithmetic_side_effects, reason = "The divider is not zero")]
+        let [_, _, _, _, _, frequency_msb, frequency_mid, frequency_lsb] =
+            (frequency_khz / Self::FREQUENCY_DIVIDER_MILLIHZ).to_be_bytes();
+
+        // Select the high- or low-frequency mode
+        let low_frequency_mode = frequency.as_u32() < Self::HIGH_FREQUENCY_THRESHOLD.as_u32();
+        Self { frequency, frf: [frequency_msb, frequency_mid, frequency_lsb], low_frequency_mode }
+    }
+
+    /// Creates the frequency word for the given `RegFrMsb`, `RegFrMid` and `RegFrLsb` register values
+    pub(crate) const fn from_frf(frf: [u8; 3]) -> Self {
+        // Translate the crystal native frequency into Hz
+        let [frequency_msb, frequency_mid, frequency_lsb] = frf;
+        let frequency_raw = u64::from_be_bytes([0, 0, 0, 0, 0, frequency_msb, frequency_mid, frequency_lsb]);
+        #[allow(clippy::arithmetic_side_effects, reason = "Can never overflow")]
+        let frequency_millihz = frequency_raw * Self::FREQUENCY_DIVIDER_MILLIHZ;
+        #[allow(clippy::cast_possible_truncation, reason = "A 24 bit register value times the divider always fits")]
+        let frequency = Frequency::hz((frequency_millihz / 1000) as u32);
+
+        // Select the high- or low-frequency mode
+        let low_frequency_mode = frequency.as_u32() < Self::HIGH_FREQUENCY_THRESHOLD.as_u32();
+        Self { frequency, frf, low_frequency_mode }
+    }
+
+    /// The frequency
+    pub const fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+    /// The `RegFrMsb`, `RegFrMid` and `RegFrLsb` register values
+    pub const fn frf(&self) -> [u8; 3] {
+        self.frf
+    }
+    /// Whether the modem must be set to low-frequency mode
+    pub const fn low_frequency_mode(&self) -> bool {
+        self.low_frequency_mode
+    }
+}
+impl From<Frequency> for FrequencyWord {
+    fn from(frequency: Frequency) -> Self {
+        Self::new(frequency)
+    }
+}
@@ -0,0 +1,94 @@
+//! Lightweight hooks around TX and RX operations
+
+use crate::rfm95::rf_switch::RfSwitchState;
+
+/// Lightweight hooks that are invoked before entering and after leaving the TX and RX modes
+///
+/// # Invocation
+/// The `before_*` hooks are invoked before the RF switch is toggled and the modem enters the respective mode; the
+/// `after_*` hooks are invoked once the driver observes that the modem has left the mode (e.g. in
+/// [`crate::rfm95::Sx127xDriver::complete_tx`]), after the RF switch has been powered down. CAD operations count as RX.
+///
+/// # Important
+/// The hooks are called synchronously from the driver, possibly from an interrupt handler, so they should only toggle
+/// pins or set flags, e.g. for external PA bias control, LED indicators or power-rail sequencing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RadioHooks {
+    /// The hook invoked before entering TX mode
+    before_tx: Option<fn()>,
+    /// The hook invoked after leaving TX mode
+    after_tx: Option<fn()>,
+    /// The hook invoked before entering RX mode
+    before_rx: Option<fn()>,
+    /// The hook invoked after leaving RX mode
+    after_rx: Option<fn()>,
+}
+impl RadioHooks {
+    /// Creates a new set of hooks without any hook registered
+    pub const fn new() -> Self {
+        Self { before_tx: None, after_tx: None, before_rx: None, after_rx: None }
+    }
+
+    /// The hook invoked before entering TX mode
+    pub const fn before_tx(&self) -> Option<fn()> {
+        self.before_tx
+    }
+    /// Registers the hook invoked before entering TX mode
+    pub const fn with_before_tx(mut self, hook: fn()) -> Self {
+        self.before_tx = Some(hook);
+        self
+    }
+
+    /// The hook invoked after leaving TX mode
+    pub const fn after_tx(&self) -> Option<fn()> {
+        self.after_tx
+    }
+    /// Registers the hook invoked after leaving TX mode
+    pub const fn with_after_tx(mut self, hook: fn()) -> Self {
+        self.after_tx = Some(hook);
+        self
+    }
+
+    /// The hook invoked before entering RX mode
+    pub const fn before_rx(&self) -> Option<fn()> {
+        self.before_rx
+    }
+    /// Registers the hook invoked before entering RX mode
+    pub const fn with_before_rx(mut self, hook: fn()) -> Self {
+        self.before_rx = Some(hook);
+        self
+    }
+
+    /// The hook invoked after leaving RX mode
+    pub const fn after_rx(&self) -> Option<fn()> {
+        self.after_rx
+    }
+    /// Registers the hook invoked after leaving RX mode
+    pub const fn with_after_rx(mut self, hook: fn()) -> Self {
+        self.after_rx = Some(hook);
+        self
+    }
+
+    /// Invokes the hook before entering the given state, if any
+    pub(crate) fn enter(&self, state: RfSwitchState) {
+        let hook = match state {
+            RfSwitchState::Off => None,
+            RfSwitchState::Tx => self.before_tx,
+            RfSwitchState::Rx => self.before_rx,
+        };
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+    /// Invokes the hook after leaving the given state, if any
+    pub(crate) fn leave(&self, state: RfSwitchState) {
+        let hook = match state {
+            RfSwitchState::Off => None,
+            RfSwitchState::Tx => self.after_tx,
+            RfSwitchState::Rx => self.after_rx,
+        };
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
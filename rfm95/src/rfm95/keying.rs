@@ -0,0 +1,139 @@
+//! Keyed unmodulated carrier
+
+use crate::error::{IoError, TxStartError};
+use crate::lora::types::Frequency;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::spi::SpiDevice;
+
+/// The state of a keyed transmission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyState {
+    /// The keyed transmission is stopped
+    Stopped,
+    /// The transmitter is keyed until the given timestamp
+    On(u64),
+    /// The transmitter is unkeyed until the given timestamp
+    Off(u64),
+}
+
+/// An unmodulated carrier on a dedicated frequency and TX power that is keyed on and off
+///
+/// # Signal
+/// While keyed, the modem transmits an unmodulated carrier (see [`Sx127xDriver::start_carrier`]). The modem stays in
+/// carrier mode while unkeyed, and only returns to LoRa mode once stopped.
+///
+/// # Usage
+/// Tune via [`Self::tune`], then alternate [`Self::key`] and [`Self::unkey`] whenever [`Self::is_due`]. The LoRa config
+/// and the previous frequency and TX power are restored via [`Self::stop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct KeyedTx {
+    /// The frequency to transmit on
+    frequency: Frequency,
+    /// The TX power in dBm
+    tx_power: i8,
+    /// The frequency and TX power to restore
+    restore: Option<(Frequency, i8)>,
+    /// The state of the keyed transmission
+    state: KeyState,
+}
+impl KeyedTx {
+    /// Creates a new stopped keyed transmission for the given frequency and TX power in dBm
+    pub const fn new(frequency: Frequency, tx_power: i8) -> Self {
+        Self { frequency, tx_power, restore: None, state: KeyState::Stopped }
+    }
+
+    /// The frequency to transmit on
+    pub const fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+    /// The TX power in dBm
+    pub const fn tx_power(&self) -> i8 {
+        self.tx_power
+    }
+    /// Whether the keyed transmission is running
+    pub const fn is_running(&self) -> bool {
+        !matches!(self.state, KeyState::Stopped)
+    }
+    /// Whether the transmitter is keyed
+    pub const fn is_keyed(&self) -> bool {
+        matches!(self.state, KeyState::On(_))
+    }
+    /// Whether the current keyed or unkeyed period has passed
+    pub const fn is_due(&self, now: u64) -> bool {
+        match self.state {
+            KeyState::Stopped => false,
+            KeyState::On(until) | KeyState::Off(until) => now >= until,
+        }
+    }
+
+    /// Remembers the previous frequency and TX power, and tunes to the frequency and TX power
+    pub fn tune<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Only remember the settings once, so a restart does not restore our own settings
+        if self.restore.is_none() {
+            self.restore = Some((driver.frequency()?, driver.tx_power()?));
+        }
+        driver.set_frequency(self.frequency)?;
+        driver.set_tx_power(self.tx_power)?;
+        Ok(())
+    }
+    /// Keys the transmitter for the given duration in microseconds
+    pub fn key<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+        duration: u64,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        driver.start_carrier()?;
+        self.state = KeyState::On(now.saturating_add(duration));
+        Ok(())
+    }
+    /// Unkeys the transmitter for the given duration in microseconds
+    pub fn unkey<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+        duration: u64,
+    ) -> Result<(), IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        driver.standby()?;
+        self.state = KeyState::Off(now.saturating_add(duration));
+        Ok(())
+    }
+    /// Leaves the carrier mode, restores the previous frequency and TX power, and stops the keyed transmission
+    pub fn stop<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.state = KeyState::Stopped;
+        driver.stop_carrier()?;
+        if let Some((frequency, tx_power)) = self.restore.take() {
+            driver.set_frequency(frequency)?;
+            driver.set_tx_power(tx_power)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! Receive windows beyond the hardware timeout limit
+
+use crate::clock::Clock;
+use crate::err;
+use crate::error::{InvalidArgumentError, RxStartError};
+use crate::lora::airtime;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::fifo::FifoReader;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A receive window of arbitrary length, implemented by chaining single RX operations until a software deadline
+///
+/// # Usage
+/// Start the window via [`Self::start`], then call [`Self::poll`] until it returns something else than
+/// [`RxOutcome::Pending`]. If a single RX operation times out before the deadline, the next one is started
+/// transparently; once the deadline has passed, [`RxOutcome::Timeout`] is returned.
+///
+/// # Hardware Limit
+/// The modem timeout counter supports at most 1023 symbols, which is only a few hundred milliseconds at high
+/// bandwidths (see [`Sx127xDriver::rx_timeout_max`]). A packet whose preamble arrives exactly while the next RX
+/// operation is started may be missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongRx {
+    /// The length of the receive window
+    window: Duration,
+    /// The symbol airtime in microseconds of the current configuration
+    symbol_micros: u64,
+    /// The deadline in clock microseconds, or `None` if the window has not been started yet
+    deadline: Option<u64>,
+}
+impl LongRx {
+    /// Creates a new receive window with the given length
+    pub const fn new(window: Duration) -> Self {
+        Self { window, symbol_micros: 0, deadline: None }
+    }
+
+    /// The length of the receive window
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+    /// The deadline in clock microseconds, or `None` if the window has not been started yet
+    pub const fn deadline(&self) -> Option<u64> {
+        self.deadline
+    }
+
+    /// Starts the receive window
+    ///
+    /// # Non-Blocking
+    /// This function schedules the first RX operation and returns immediately. To check if a message has been received,
+    /// use [`Self::poll`].
+    ///
+    /// # Configuration
+    /// The symbol airtime is read once from the current config; if the spreading factor or bandwidth is changed, the
+    /// window must be restarted.
+    pub fn start<Device, Variant, Switch, C>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        clock: &C,
+    ) -> Result<(), RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+        C: Clock,
+    {
+        self.start_at(driver, clock.now())
+    }
+    /// Starts the receive window at the given clock time in microseconds
+    ///
+    /// # Non-Blocking, Configuration
+    /// See [`Self::start`].
+    pub fn start_at<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Get the symbol airtime and compute the deadline
+        let symbol_airtime = airtime::symbol_airtime(driver.spreading_factor()?, driver.bandwidth()?);
+        self.symbol_micros = u64::try_from(symbol_airtime.as_micros()).unwrap_or(u64::MAX);
+        let window_micros = u64::try_from(self.window.as_micros()).unwrap_or(u64::MAX);
+        let deadline = now.saturating_add(window_micros);
+        self.deadline = Some(deadline);
+
+        // Start the first RX operation
+        self.start_rx(driver, deadline, now)
+    }
+
+    /// Checks if a message has been received, and passes a sequential reader for the received message to `f`
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If no message has been received yet and the deadline has not passed, it returns
+    /// `Ok(RxOutcome::Pending)` without calling `f`; the next RX operation is started if necessary.
+    ///
+    /// # Timeout or CRC errors
+    /// If the deadline has passed or the received message is corrupt, `Ok(RxOutcome::Timeout)` or
+    /// `Ok(RxOutcome::CrcError)` is returned respectively without calling `f`. See also
+    /// [`Sx127xDriver::complete_rx_with`].
+    pub fn poll<Device, Variant, Switch, C, F, T>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        clock: &C,
+        f: F,
+    ) -> Result<RxOutcome<T>, RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+        C: Clock,
+        F: FnOnce(&mut FifoReader<Device>) -> T,
+    {
+        // Check the state of the current RX operation
+        let Some(deadline) = self.deadline else {
+            // The window has not been started yet
+            return Err(err!(InvalidArgumentError, "The receive window has not been started"))?;
+        };
+        match driver.complete_rx_with(f)? {
+            // Chain the next RX operation if the deadline has not passed yet
+            RxOutcome::Timeout if clock.now() < deadline => {
+                self.start_rx(driver, deadline, clock.now())?;
+                Ok(RxOutcome::Pending)
+            }
+            outcome => Ok(outcome),
+        }
+    }
+    /// Chains the next RX operation after a single RX operation has timed out, and returns `false` if the deadline has
+    /// passed at the given clock time in microseconds
+    ///
+    /// # Manual Completion
+    /// This allows to complete the single RX operations via another function than [`Self::poll`], e.g.
+    /// [`Sx127xDriver::complete_rx`].
+    pub fn chain<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<bool, RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match self.deadline {
+            Some(deadline) if now < deadline => {
+                self.start_rx(driver, deadline, now)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Starts a single RX operation that lasts until the deadline, or the maximum hardware timeout
+    fn start_rx<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        deadline: u64,
+        now: u64,
+    ) -> Result<(), RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Compute the remaining symbols, rounded up and clamped to the supported range
+        let remaining_micros = deadline.saturating_sub(now);
+        let remaining_symbols = remaining_micros.div_ceil(self.symbol_micros.max(1));
+        let symbols = u16::try_from(remaining_symbols)
+            .unwrap_or(u16::MAX)
+            .clamp(1, Sx127xDriver::<Device, Variant, Switch>::SYMBOL_TIMEOUT_MAX);
+        driver.start_rx_symbols(symbols)
+    }
+}
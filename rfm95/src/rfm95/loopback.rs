@@ -0,0 +1,124 @@
+//! Bench loopback test between two radios
+
+use crate::error::{IoError, LoopbackError};
+use crate::lora::airtime;
+use crate::lora::config::Config;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::fifo::FifoReader;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::spi::SpiDevice;
+
+/// The known test pattern that is transmitted during a loopback test
+pub const LOOPBACK_PATTERN: [u8; 16] = *b"RFM95-LOOPBACK\x55\xAA";
+
+/// The report of a loopback test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopbackReport {
+    /// The RX outcome; the received value is the amount of pattern bytes that did not match
+    outcome: RxOutcome<u16>,
+    /// The RSSI of the received packet in dBm
+    rssi: i16,
+    /// The SNR of the received packet in dB
+    snr: i8,
+}
+impl LoopbackReport {
+    /// The RX outcome; the received value is the amount of pattern bytes that did not match (including missing or
+    /// surplus bytes)
+    pub const fn outcome(&self) -> RxOutcome<u16> {
+        self.outcome
+    }
+    /// The RSSI of the received packet in dBm, or `0` if nothing has been received
+    pub const fn rssi(&self) -> i16 {
+        self.rssi
+    }
+    /// The SNR of the received packet in dB, or `0` if nothing has been received
+    pub const fn snr(&self) -> i8 {
+        self.snr
+    }
+
+    /// Whether the pattern has been received without errors
+    pub const fn passed(&self) -> bool {
+        matches!(self.outcome, RxOutcome::Received(0))
+    }
+}
+
+/// Transmits a known pattern on `tx_radio`, verifies the reception on `rx_radio` and returns a report
+///
+/// # Blocking
+/// This function blocks until the pattern has been received, or until twice the pattern airtime plus 100 ms have
+/// passed. It is intended for manufacturing and regression testing of RF paths, not for normal operation.
+///
+/// # Important
+/// The config is applied to both radios, and both radios are left in the state after the test. Make sure the radios are
+/// placed close to each other or connected via an attenuator, and that the TX power is suitable for the setup.
+pub fn loopback_test<TxDevice, TxVariant, TxSwitch, RxDevice, RxVariant, RxSwitch, Timer>(
+    tx_radio: &mut Sx127xDriver<TxDevice, TxVariant, TxSwitch>,
+    rx_radio: &mut Sx127xDriver<RxDevice, RxVariant, RxSwitch>,
+    config: &Config,
+    mut timer: Timer,
+) -> Result<LoopbackReport, LoopbackError>
+where
+    TxDevice: SpiDevice,
+    TxVariant: Chip,
+    TxSwitch: RfSwitch,
+    RxDevice: SpiDevice,
+    RxVariant: Chip,
+    RxSwitch: RfSwitch,
+    Timer: DelayNs,
+{
+    /// The additional time to wait for the reception
+    const MARGIN_MS: u32 = 100;
+
+    // Configure both radios and compute the time to wait for the reception
+    tx_radio.set_config(config)?;
+    rx_radio.set_config(config)?;
+    let airtime_ms = airtime::airtime(LOOPBACK_PATTERN.len(), *config).as_millis();
+    let wait_ms = u32::try_from(airtime_ms).unwrap_or(u32::MAX).saturating_mul(2).saturating_add(MARGIN_MS);
+
+    // Start the reception before the transmission
+    rx_radio.start_rx_max()?;
+    tx_radio.start_tx(&LOOPBACK_PATTERN)?;
+
+    // Wait for the reception
+    let mut outcome = RxOutcome::Pending;
+    for _ in 0..wait_ms {
+        // Poll the receiver
+        timer.delay_ms(1);
+        outcome = rx_radio.complete_rx_with(verify_pattern)?;
+        if !outcome.is_pending() {
+            break;
+        }
+    }
+
+    // Complete the transmission and assemble the report; a reception that has not been completed in time is a timeout
+    tx_radio.complete_tx()?;
+    let (outcome, rssi, snr) = match outcome {
+        RxOutcome::Received(errors) => {
+            (RxOutcome::Received(errors?), rx_radio.get_packet_rssi()?, rx_radio.get_packet_snr()?)
+        }
+        RxOutcome::CrcError => (RxOutcome::CrcError, rx_radio.get_packet_rssi()?, rx_radio.get_packet_snr()?),
+        RxOutcome::Pending | RxOutcome::Timeout => (RxOutcome::Timeout, 0, 0),
+    };
+    Ok(LoopbackReport { outcome, rssi, snr })
+}
+
+/// Compares the received message against the test pattern and returns the amount of mismatching bytes
+fn verify_pattern<Device>(reader: &mut FifoReader<Device>) -> Result<u16, IoError>
+where
+    Device: SpiDevice,
+{
+    // Count surplus bytes as errors
+    let surplus = reader.len().saturating_sub(LOOPBACK_PATTERN.len());
+    let mut errors = u16::try_from(surplus).unwrap_or(u16::MAX);
+
+    // Compare the message against the pattern; missing bytes are counted as errors
+    for expected in LOOPBACK_PATTERN {
+        if reader.read_u8()? != Some(expected) {
+            errors = errors.saturating_add(1);
+        }
+    }
+    Ok(errors)
+}
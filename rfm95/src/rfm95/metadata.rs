@@ -0,0 +1,43 @@
+//! Metadata of received packets
+
+use crate::lora::types::CodingRate;
+
+/// The metadata of the last received packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxMetadata {
+    /// The RSSI in dBm
+    rssi: i16,
+    /// The SNR in dB
+    snr: i8,
+    /// The coding rate of the packet
+    coding_rate: CodingRate,
+}
+impl RxMetadata {
+    /// Creates new packet metadata
+    pub const fn new(rssi: i16, snr: i8, coding_rate: CodingRate) -> Self {
+        Self { rssi, snr, coding_rate }
+    }
+
+    /// The RSSI in dBm
+    pub const fn rssi(&self) -> i16 {
+        self.rssi
+    }
+    /// The SNR in dB
+    pub const fn snr(&self) -> i8 {
+        self.snr
+    }
+    /// The signal strength in dBm, which accounts for LoRa's ability to receive packets below the noise floor
+    pub const fn strength(&self) -> i16 {
+        // Add the negative SNR to the RSSI
+        let snr = if self.snr < 0 { self.snr } else { 0 };
+        self.rssi.saturating_add(snr as i16)
+    }
+    /// The coding rate of the packet
+    ///
+    /// # Explicit Header
+    /// The coding rate is taken from the packet header, so it can differ from the configured coding rate if the peer
+    /// uses another coding rate. In implicit header mode, the configured coding rate is reported.
+    pub const fn coding_rate(&self) -> CodingRate {
+        self.coding_rate
+    }
+}
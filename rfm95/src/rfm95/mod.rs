@@ -1,8 +1,46 @@
 //! RFM95 LoRa implementation
 
+mod aggregator;
+mod beacon;
+mod bridge;
+mod builder;
+mod capabilities;
+mod chip;
+mod clear_channel;
+mod clk_out;
 mod connection;
+mod csma_tx;
+mod cw_keyer;
+mod doppler;
 mod driver;
+mod dump;
+mod event;
+mod fifo;
+mod frequency_word;
+mod hooks;
+mod keying;
+mod long_rx;
+mod loopback;
+mod metadata;
+mod multicast;
+mod outcome;
+mod per_test;
+mod power_control;
+mod radio_set;
 mod registers;
+mod remote;
+mod rf_switch;
+#[cfg(feature = "rtic-monotonics")]
+mod rtic;
+mod rx_counters;
+mod rx_watchdog;
+mod scanner;
+mod self_test;
+#[cfg(feature = "stats")]
+mod stats;
+mod tuning;
+mod tx_queue;
+mod wake_on_radio;
 
 use crate::lora::types::Frequency;
 use embedded_hal::spi::{Mode, MODE_0};
@@ -17,4 +55,62 @@ pub const RFM95_SPI_MODE: Mode = MODE_0;
 pub const RFM95_FIFO_SIZE: usize = 0xFF;
 
 // Expose the driver implementation
-pub use crate::rfm95::driver::Rfm95Driver;
+pub use crate::rfm95::aggregator::{AggregatedPacket, RxAggregator};
+pub use crate::rfm95::beacon::Beacon;
+pub use crate::rfm95::bridge::SerialBridge;
+pub use crate::rfm95::builder::{InitialMode, ResetPolarity, Sx127xBuilder};
+pub use crate::rfm95::capabilities::Capabilities;
+pub use crate::rfm95::chip::{Chip, PaOutput};
+pub use crate::rfm95::clear_channel::ClearChannelPolicy;
+pub use crate::rfm95::clk_out::ClkOut;
+pub use crate::rfm95::csma_tx::{CsmaOutcome, CsmaTransmitter};
+pub use crate::rfm95::cw_keyer::CwKeyer;
+pub use crate::rfm95::doppler::Doppler;
+pub use crate::rfm95::driver::Sx127xDriver;
+pub use crate::rfm95::dump::{DecodedRegisters, FifoDump, FIFO_DUMP_SIZE, REGISTER_DUMP_SIZE};
+pub use crate::rfm95::event::Event;
+pub use crate::rfm95::fifo::FifoReader;
+pub use crate::rfm95::frequency_word::FrequencyWord;
+pub use crate::rfm95::hooks::RadioHooks;
+pub use crate::rfm95::long_rx::LongRx;
+pub use crate::rfm95::loopback::{loopback_test, LoopbackReport, LOOPBACK_PATTERN};
+pub use crate::rfm95::metadata::RxMetadata;
+pub use crate::rfm95::multicast::MulticastSession;
+pub use crate::rfm95::outcome::RxOutcome;
+pub use crate::rfm95::per_test::{PerAnalyzer, PerGenerator, PerReport, TestPattern, PER_HEADER_LEN, PER_MAGIC};
+pub use crate::rfm95::power_control::PowerControl;
+pub use crate::rfm95::radio_set::{RadioOutcome, RadioSet};
+pub use crate::rfm95::remote::RemoteControl;
+pub use crate::rfm95::rf_switch::{NoRfSwitch, PinRfSwitch, RfSwitch, RfSwitchState};
+#[cfg(feature = "rtic-monotonics")]
+pub use crate::rfm95::rtic::{delay_until, drive_csma, wait_rx, wait_tx};
+pub use crate::rfm95::rx_counters::RxCounters;
+pub use crate::rfm95::rx_watchdog::RxWatchdog;
+pub use crate::rfm95::scanner::Scanner;
+pub use crate::rfm95::self_test::SelfTestReport;
+#[cfg(feature = "stats")]
+pub use crate::rfm95::stats::DriverStats;
+pub use crate::rfm95::tuning::TuningAid;
+pub use crate::rfm95::tx_queue::TxQueue;
+pub use crate::rfm95::wake_on_radio::WakeOnRadio;
+
+// Expose the chip variants
+#[cfg(feature = "rfm95")]
+pub use crate::rfm95::chip::Rfm95;
+#[cfg(feature = "rfm96")]
+pub use crate::rfm95::chip::{Rfm96, Rfm98};
+#[cfg(feature = "sx127x")]
+pub use crate::rfm95::chip::{Sx1276, Sx1277, Sx1278, Sx1279};
+
+/// The RFM95 driver
+#[cfg(feature = "rfm95")]
+pub type Rfm95Driver<Device, Switch = NoRfSwitch> = Sx127xDriver<Device, Rfm95, Switch>;
+/// A builder for the RFM95 driver with init options
+#[cfg(feature = "rfm95")]
+pub type Rfm95Builder<Device> = Sx127xBuilder<Device, Rfm95>;
+/// The RFM96 driver
+#[cfg(feature = "rfm96")]
+pub type Rfm96Driver<Device, Switch = NoRfSwitch> = Sx127xDriver<Device, Rfm96, Switch>;
+/// The RFM98 driver
+#[cfg(feature = "rfm96")]
+pub type Rfm98Driver<Device, Switch = NoRfSwitch> = Sx127xDriver<Device, Rfm98, Switch>;
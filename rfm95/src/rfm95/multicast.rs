@@ -0,0 +1,273 @@
+//! Multicast group sessions with scheduled listening windows
+
+use crate::error::{ProtocolError, TxStartError};
+use crate::lora::types::Frequency;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A listening window schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Schedule {
+    /// The clock time in microseconds at which the next window opens
+    next_start: u64,
+    /// The window period in microseconds
+    period: u64,
+    /// The window length in microseconds
+    window: u64,
+}
+
+/// The state of a multicast session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionState {
+    /// The session is stopped
+    Stopped,
+    /// The session waits for the next listening window
+    Waiting,
+    /// The session listens until the given timestamp and restores the given frequency afterwards
+    Listening(u64, Frequency),
+}
+
+/// A multicast group session, so a single transmission can reach many nodes (e.g. for firmware updates or mass
+/// reconfiguration)
+///
+/// # Group Address
+/// Every group frame starts with the 4-byte big-endian group address, followed by the payload. Received frames of other
+/// groups are dropped and counted (see [`Self::foreign`]); [`Self::start_tx`] prepends the address automatically.
+///
+/// # Listening Windows
+/// By default, the session listens continuously (like a LoRaWAN class C device). With a schedule (see
+/// [`Self::with_schedule`]), the session only listens during periodic windows, e.g. a slot announced by the group
+/// sender, and leaves the modem in standby in between. During a window, the carrier is tuned to the session frequency;
+/// the previous frequency is restored once the window closes.
+///
+/// # Group Key
+/// The session does not encrypt or authenticate payloads. To protect group frames with a shared group key, seal the
+/// payload via [`crate::lora::secure_link::seal`] with a cipher created from the group key before [`Self::start_tx`],
+/// and open received payloads via [`crate::lora::secure_link::open`] with the same cipher.
+///
+/// # Usage
+/// Start the session via [`Self::start`], then call [`Self::poll`] regularly, at least once per window. No other
+/// operation should be started on the driver while a window is open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MulticastSession {
+    /// The group address
+    group: u32,
+    /// The session frequency
+    frequency: Frequency,
+    /// The listening window schedule, or `None` to listen continuously
+    schedule: Option<Schedule>,
+    /// The state of the session
+    state: SessionState,
+    /// The amount of received frames of other groups
+    foreign: u32,
+}
+impl MulticastSession {
+    /// The length of the group address header
+    pub const HEADER_LEN: usize = 4;
+
+    /// Creates a new, stopped session for the given group address on the given frequency that listens continuously
+    pub const fn new(group: u32, frequency: Frequency) -> Self {
+        Self { group, frequency, schedule: None, state: SessionState::Stopped, foreign: 0 }
+    }
+    /// Listens only during windows of the given length, which open once per period starting at the given clock time in
+    /// microseconds
+    pub fn with_schedule(mut self, start: u64, period: Duration, window: Duration) -> Self {
+        let period = u64::try_from(period.as_micros()).unwrap_or(u64::MAX);
+        let window = u64::try_from(window.as_micros()).unwrap_or(u64::MAX);
+        self.schedule = Some(Schedule { next_start: start, period, window });
+        self
+    }
+
+    /// The group address
+    pub const fn group(&self) -> u32 {
+        self.group
+    }
+    /// The session frequency
+    pub const fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+    /// The clock time in microseconds at which the next listening window opens, or `None` if the session listens
+    /// continuously
+    pub const fn next_window(&self) -> Option<u64> {
+        match self.schedule {
+            Some(schedule) => Some(schedule.next_start),
+            None => None,
+        }
+    }
+    /// Whether a listening window is open
+    pub const fn is_listening(&self) -> bool {
+        matches!(self.state, SessionState::Listening(..))
+    }
+    /// The amount of received frames of other groups
+    pub const fn foreign(&self) -> u32 {
+        self.foreign
+    }
+
+    /// Starts the session; the first window opens with the next call to [`Self::poll`] once it is due
+    pub fn start(&mut self) {
+        if matches!(self.state, SessionState::Stopped) {
+            self.state = SessionState::Waiting;
+        }
+    }
+    /// Stops the session and closes an open listening window
+    pub fn stop<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.close_window(driver)?;
+        self.state = SessionState::Stopped;
+        Ok(())
+    }
+
+    /// Opens and closes listening windows, copies the payload of a received group frame into `buf` and returns the
+    /// payload length
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. It returns `Ok(RxOutcome::Pending)` if no group frame has been received. Frames
+    /// of other groups and frames that are too short for the group address are not reported.
+    ///
+    /// # Buffer too small
+    /// `buf` must be able to hold the entire frame including the group address header; the payload is moved to the
+    /// start of `buf`.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        buf: &mut [u8],
+        now: u64,
+    ) -> Result<RxOutcome, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match self.state {
+            SessionState::Stopped => Ok(RxOutcome::Pending),
+            SessionState::Waiting => {
+                self.open_window(driver, now)?;
+                Ok(RxOutcome::Pending)
+            }
+            SessionState::Listening(until, _) if now >= until => {
+                self.close_window(driver)?;
+                self.state = SessionState::Waiting;
+                Ok(RxOutcome::Pending)
+            }
+            SessionState::Listening(..) => self.receive(driver, buf),
+        }
+    }
+
+    /// Schedules a single TX operation of a group frame with the given payload on the session frequency
+    ///
+    /// # Non-Blocking
+    /// This function schedules the TX operation and returns immediately. To check if the TX operation is done, use
+    /// [`Sx127xDriver::complete_tx`].
+    ///
+    /// # Important
+    /// The carrier remains tuned to the session frequency after the transmission.
+    pub fn start_tx<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        data: &[u8],
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        driver.set_frequency(self.frequency)?;
+        driver.start_tx_vectored(&[&self.group.to_be_bytes(), data])
+    }
+
+    /// Opens the listening window if it is due
+    fn open_window<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Compute the end of the window
+        let until = match self.schedule.as_mut() {
+            Some(schedule) if now < schedule.next_start => return Ok(()),
+            Some(schedule) => {
+                // Skip missed windows, and schedule the next window
+                let until = schedule.next_start.saturating_add(schedule.window);
+                let missed = now.saturating_sub(schedule.next_start).checked_div(schedule.period).unwrap_or_default();
+                let periods = missed.saturating_add(1).saturating_mul(schedule.period.max(1));
+                schedule.next_start = schedule.next_start.saturating_add(periods);
+                until
+            }
+            None => u64::MAX,
+        };
+        if now >= until {
+            // The window has already been missed
+            return Ok(());
+        }
+
+        // Tune to the session frequency and start listening
+        let restore = driver.frequency()?;
+        driver.set_frequency(self.frequency)?;
+        driver.start_rx_continuous()?;
+        self.state = SessionState::Listening(until, restore);
+        Ok(())
+    }
+    /// Closes an open listening window and restores the previous frequency
+    fn close_window<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        if let SessionState::Listening(_, restore) = self.state {
+            driver.standby()?;
+            driver.set_frequency(restore)?;
+        }
+        Ok(())
+    }
+    /// Receives a group frame during an open listening window
+    fn receive<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        buf: &mut [u8],
+    ) -> Result<RxOutcome, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let RxOutcome::Received((len, _)) = driver.complete_rx_continuous(buf)? else {
+            // No frame has been received, or the frame is corrupt
+            return Ok(RxOutcome::Pending);
+        };
+
+        // Check the group address
+        let frame = buf.get(..len).unwrap_or_default();
+        let Some((address, _)) = frame.split_first_chunk::<{ Self::HEADER_LEN }>() else {
+            // The frame is too short to be a group frame
+            return Ok(RxOutcome::Pending);
+        };
+        if u32::from_be_bytes(*address) != self.group {
+            // The frame belongs to another group
+            self.foreign = self.foreign.saturating_add(1);
+            return Ok(RxOutcome::Pending);
+        }
+
+        // Move the payload to the start of the buffer
+        buf.copy_within(Self::HEADER_LEN..len, 0);
+        Ok(RxOutcome::Received(len.saturating_sub(Self::HEADER_LEN)))
+    }
+}
@@ -0,0 +1,54 @@
+//! Outcomes of RX operations
+
+/// The outcome of an RX operation
+///
+/// # Expected Outcomes
+/// Timeouts and corrupt messages are expected outcomes of radio operations, so they are reported as outcome rather than
+/// as error. This allows to propagate actual I/O errors via `?` while matching on the radio outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RxOutcome<T = usize> {
+    /// The RX operation has not been completed yet
+    Pending,
+    /// A message has been received
+    Received(T),
+    /// The RX operation has timed out without receiving a message
+    Timeout,
+    /// A message has been received, but the payload CRC is invalid
+    CrcError,
+}
+impl<T> RxOutcome<T> {
+    /// Whether the RX operation has not been completed yet
+    pub const fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending)
+    }
+
+    /// The received value if a message has been received
+    pub fn received(self) -> Option<T> {
+        match self {
+            Self::Received(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Splits `self` into the received value, or the non-received outcome with another value type
+    pub(crate) fn into_received<U>(self) -> Result<T, RxOutcome<U>> {
+        match self {
+            Self::Received(value) => Ok(value),
+            Self::Pending => Err(RxOutcome::Pending),
+            Self::Timeout => Err(RxOutcome::Timeout),
+            Self::CrcError => Err(RxOutcome::CrcError),
+        }
+    }
+    /// Maps the received value via `f`
+    pub fn map<F, U>(self, f: F) -> RxOutcome<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            Self::Pending => RxOutcome::Pending,
+            Self::Received(value) => RxOutcome::Received(f(value)),
+            Self::Timeout => RxOutcome::Timeout,
+            Self::CrcError => RxOutcome::CrcError,
+        }
+    }
+}
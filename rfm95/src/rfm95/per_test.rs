@@ -0,0 +1,513 @@
+//! Packet error rate (PER) measurement between two nodes
+
+use crate::error::{RxCompleteError, RxStartError, TxStartError};
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use crate::rfm95::RFM95_FIFO_SIZE;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// The magic prefix of PER test frames
+pub const PER_MAGIC: [u8; 4] = *b"PER\x01";
+/// The length of the PER test frame header (magic, 2-byte sequence number and 2-byte frame count, big-endian)
+pub const PER_HEADER_LEN: usize = 8;
+
+/// A deterministic payload pattern for test frames
+///
+/// # Frame Layout
+/// The pattern starts at the first byte of the frame; the first [`PER_HEADER_LEN`] bytes are then replaced by the
+/// header, so the payload after the header is the same for every frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TestPattern {
+    /// Each byte is its index within the frame (wrapping around at 256)
+    #[default]
+    Counter,
+    /// The PN9 pseudo-random sequence (`x^9 + x^5 + 1`, seed `0x1FF`, starting with `FF E1 1D 9A`)
+    Pn9,
+    /// All bits cleared
+    Zeros,
+    /// All bits set
+    Ones,
+}
+impl TestPattern {
+    /// Fills the given buffer with the pattern
+    pub fn fill(self, buf: &mut [u8]) {
+        let mut pn9 = 0x1FF;
+        for (index, byte) in buf.iter_mut().enumerate() {
+            *byte = match self {
+                #[allow(clippy::cast_possible_truncation, reason = "The pattern is intended to wrap around")]
+                Self::Counter => index as u8,
+                Self::Pn9 => {
+                    let [low, _] = u16::to_le_bytes(pn9);
+                    pn9 = Self::pn9_next(pn9);
+                    low
+                }
+                Self::Zeros => 0x00,
+                Self::Ones => 0xFF,
+            };
+        }
+    }
+
+    /// Advances the PN9 generator by 8 bits
+    fn pn9_next(state: u16) -> u16 {
+        (0..8).fold(state, |state, _| {
+            let feedback = (state ^ (state >> 5)) & 1;
+            (state >> 1) | (feedback << 8)
+        })
+    }
+}
+
+/// The generator side of a PER test, which sends a fixed amount of numbered frames at a fixed interval
+///
+/// # Test Patterns
+/// The frames carry a deterministic payload (see [`Self::with_pattern`]), so they can also be used for receiver
+/// validation and regulatory testing; the [`PerAnalyzer`] verifies the payload if configured with the same pattern and
+/// frame length.
+///
+/// # Usage
+/// Start the test via [`Self::start`], then call [`Self::poll`] regularly until [`Self::is_done`] returns `true`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerGenerator {
+    /// The amount of frames to send
+    total: u16,
+    /// The interval between two frames in microseconds
+    interval: u64,
+    /// The frame length including the header
+    frame_len: usize,
+    /// The payload pattern
+    pattern: TestPattern,
+    /// The sequence number of the next frame
+    next_seq: u16,
+    /// The clock time in microseconds of the next frame
+    next_at: u64,
+    /// Whether a TX operation is in progress
+    busy: bool,
+}
+impl PerGenerator {
+    /// Creates a new generator that sends the given amount of frames at the given interval
+    ///
+    /// # Frame Length
+    /// The frames default to the header length; longer frames are padded with a test pattern (see
+    /// [`Self::with_frame_len`] and [`Self::with_pattern`]).
+    pub fn new(total: u16, interval: Duration) -> Self {
+        let interval = u64::try_from(interval.as_micros()).unwrap_or(u64::MAX);
+        let pattern = TestPattern::Counter;
+        Self { total, interval, frame_len: PER_HEADER_LEN, pattern, next_seq: total, next_at: 0, busy: false }
+    }
+    /// Sets the frame length including the header, clamped to `PER_HEADER_LEN..=RFM95_FIFO_SIZE`
+    pub fn with_frame_len(mut self, frame_len: usize) -> Self {
+        self.frame_len = frame_len.clamp(PER_HEADER_LEN, RFM95_FIFO_SIZE);
+        self
+    }
+    /// Sets the payload pattern ([`TestPattern::Counter`] by default)
+    pub const fn with_pattern(mut self, pattern: TestPattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// The amount of frames to send
+    pub const fn total(&self) -> u16 {
+        self.total
+    }
+    /// The interval between two frames
+    pub const fn interval(&self) -> Duration {
+        Duration::from_micros(self.interval)
+    }
+    /// The frame length including the header
+    pub const fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+    /// The payload pattern
+    pub const fn pattern(&self) -> TestPattern {
+        self.pattern
+    }
+    /// The amount of frames sent so far
+    pub const fn sent(&self) -> u16 {
+        self.next_seq
+    }
+    /// Whether all frames have been sent
+    pub const fn is_done(&self) -> bool {
+        self.next_seq >= self.total && !self.busy
+    }
+
+    /// Starts the test; the first frame is sent immediately
+    pub fn start(&mut self, now: u64) {
+        self.next_seq = 0;
+        self.next_at = now;
+    }
+
+    /// Completes the current frame and sends the next frame once it is due, and returns its sequence number
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If a frame is still being sent, the next frame is not due yet, or all frames have
+    /// been sent, it returns `Ok(None)`.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<Option<u16>, TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Complete the current frame
+        if self.busy {
+            let Some(_) = driver.complete_tx()? else {
+                // The TX operation has not been completed yet
+                return Ok(None);
+            };
+            self.busy = false;
+        }
+
+        // Check if the next frame is due
+        if self.next_seq >= self.total || now < self.next_at {
+            return Ok(None);
+        }
+
+        // Assemble and send the frame
+        let mut frame = [0; RFM95_FIFO_SIZE];
+        let seq = self.next_seq;
+        self.pattern.fill(&mut frame);
+        if let Some(header) = frame.first_chunk_mut::<PER_HEADER_LEN>() {
+            *header = frame_header(seq, self.total);
+        }
+        driver.start_tx(frame.get(..self.frame_len).unwrap_or_default())?;
+        self.busy = true;
+        self.next_seq = seq.saturating_add(1);
+        self.next_at = self.next_at.saturating_add(self.interval).max(now);
+        Ok(Some(seq))
+    }
+}
+
+/// The summary of a PER test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerReport {
+    /// The amount of frames the generator sent, or `0` if no frame has been received
+    expected: u16,
+    /// The amount of distinct frames received
+    received: u16,
+    /// The amount of frames received with an invalid CRC
+    crc_errors: u32,
+    /// The amount of duplicate frames received
+    duplicates: u32,
+    /// The amount of frames received with a valid CRC but an unexpected payload
+    pattern_errors: u32,
+    /// The amount of bit errors within the payloads of these frames
+    bit_errors: u32,
+    /// The minimum, mean and maximum RSSI in dBm
+    rssi: (i16, i16, i16),
+    /// The minimum, mean and maximum SNR in dB
+    snr: (i8, i8, i8),
+}
+impl PerReport {
+    /// The amount of frames the generator sent, or `0` if no frame has been received
+    pub const fn expected(&self) -> u16 {
+        self.expected
+    }
+    /// The amount of distinct frames received
+    pub const fn received(&self) -> u16 {
+        self.received
+    }
+    /// The amount of frames that have not been received (including frames with an invalid CRC or payload)
+    pub const fn missing(&self) -> u16 {
+        self.expected.saturating_sub(self.received)
+    }
+    /// The amount of frames received with an invalid CRC
+    pub const fn crc_errors(&self) -> u32 {
+        self.crc_errors
+    }
+    /// The amount of duplicate frames received
+    pub const fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+    /// The amount of frames received with a valid CRC but an unexpected payload (see [`PerAnalyzer::with_pattern`])
+    pub const fn pattern_errors(&self) -> u32 {
+        self.pattern_errors
+    }
+    /// The amount of bit errors within the payloads of frames with an unexpected payload
+    pub const fn bit_errors(&self) -> u32 {
+        self.bit_errors
+    }
+    /// The minimum, mean and maximum RSSI in dBm of the received frames
+    pub const fn rssi(&self) -> (i16, i16, i16) {
+        self.rssi
+    }
+    /// The minimum, mean and maximum SNR in dB of the received frames
+    pub const fn snr(&self) -> (i8, i8, i8) {
+        self.snr
+    }
+    /// The packet error rate in per-mille, or `None` if no frame has been received
+    pub fn per_permille(&self) -> Option<u16> {
+        let missing = u32::from(self.missing()).saturating_mul(1000);
+        let per = missing.checked_div(u32::from(self.expected))?;
+        Some(u16::try_from(per).unwrap_or(u16::MAX))
+    }
+}
+
+/// The analyzer side of a PER test, which counts the received, missing and corrupt frames of a [`PerGenerator`]
+///
+/// # Per-Frame Samples
+/// The analyzer keeps the RSSI and SNR of the first `N` frames (see [`Self::sample`]); frames with higher sequence
+/// numbers are only included in the summary, and their duplicates cannot be detected.
+///
+/// # Payload Verification
+/// If a payload pattern is configured via [`Self::with_pattern`], the payload and the length of each frame are compared
+/// to the pattern and the frame length; frames with an unexpected payload or length (e.g. truncated frames) are counted
+/// as pattern errors instead of received frames.
+///
+/// # Usage
+/// Start the analyzer via [`Self::start`] before the generator starts, then call [`Self::poll`] regularly until the
+/// generator is done, and get the summary via [`Self::report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerAnalyzer<const N: usize> {
+    /// The RSSI in dBm and SNR in dB of the received frames by sequence number
+    samples: [Option<(i16, i8)>; N],
+    /// The amount of frames the generator sends, once known
+    expected: u16,
+    /// The amount of distinct frames received
+    received: u16,
+    /// The amount of frames received with an invalid CRC
+    crc_errors: u32,
+    /// The amount of duplicate frames received
+    duplicates: u32,
+    /// The expected payload pattern and frame length, if the payload is verified
+    pattern: Option<(TestPattern, usize)>,
+    /// The amount of frames received with a valid CRC but an unexpected payload
+    pattern_errors: u32,
+    /// The amount of bit errors within the payloads of these frames
+    bit_errors: u32,
+    /// The minimum and maximum RSSI in dBm
+    rssi_range: (i16, i16),
+    /// The minimum and maximum SNR in dB
+    snr_range: (i8, i8),
+    /// The sum of the RSSI values in dBm
+    rssi_sum: i32,
+    /// The sum of the SNR values in dB
+    snr_sum: i32,
+}
+impl<const N: usize> PerAnalyzer<N> {
+    /// Creates a new analyzer
+    pub const fn new() -> Self {
+        Self {
+            samples: [None; N],
+            expected: 0,
+            received: 0,
+            crc_errors: 0,
+            duplicates: 0,
+            pattern: None,
+            pattern_errors: 0,
+            bit_errors: 0,
+            rssi_range: (i16::MAX, i16::MIN),
+            snr_range: (i8::MAX, i8::MIN),
+            rssi_sum: 0,
+            snr_sum: 0,
+        }
+    }
+    /// Verifies the payload and the length of each frame against the given pattern and frame length including the
+    /// header, which must match the pattern and the frame length of the generator
+    pub fn with_pattern(mut self, pattern: TestPattern, frame_len: usize) -> Self {
+        self.pattern = Some((pattern, frame_len.clamp(PER_HEADER_LEN, RFM95_FIFO_SIZE)));
+        self
+    }
+
+    /// The RSSI in dBm and SNR in dB of the frame with the given sequence number, or `None` if it has not been
+    /// received or is not sampled
+    pub fn sample(&self, seq: u16) -> Option<(i16, i8)> {
+        self.samples.get(seq as usize).copied().flatten()
+    }
+
+    /// Resets the analyzer (except for the payload pattern) and starts a continuous RX operation
+    pub fn start<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        *self = Self { pattern: self.pattern, ..Self::new() };
+        driver.start_rx_continuous()
+    }
+
+    /// Processes the next received frame, and returns its sequence number if it is a valid PER test frame
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. Frames that are not PER test frames are ignored, and frames with an unexpected
+    /// payload are only counted as pattern errors.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<Option<u16>, RxCompleteError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Receive the next frame
+        let mut frame = [0; RFM95_FIFO_SIZE];
+        let len = match driver.complete_rx_continuous(&mut frame)? {
+            RxOutcome::Received((len, _)) => len,
+            RxOutcome::CrcError => {
+                self.crc_errors = self.crc_errors.saturating_add(1);
+                return Ok(None);
+            }
+            RxOutcome::Pending | RxOutcome::Timeout => return Ok(None),
+        };
+
+        // Parse the header
+        let frame = frame.get(..len).unwrap_or_default();
+        let Some((seq, total)) = parse_header(frame) else {
+            // The frame is not a PER test frame
+            return Ok(None);
+        };
+        self.expected = total;
+
+        // Verify the payload
+        let bit_errors = self.pattern.map_or(0, |(pattern, frame_len)| count_bit_errors(frame, pattern, frame_len));
+        if bit_errors > 0 {
+            self.pattern_errors = self.pattern_errors.saturating_add(1);
+            self.bit_errors = self.bit_errors.saturating_add(bit_errors);
+            return Ok(None);
+        }
+
+        // Record the sample, unless the frame is a duplicate
+        let sample = (driver.get_packet_rssi()?, driver.get_packet_snr()?);
+        match self.samples.get_mut(seq as usize) {
+            Some(Some(_)) => {
+                self.duplicates = self.duplicates.saturating_add(1);
+                return Ok(Some(seq));
+            }
+            Some(slot) => *slot = Some(sample),
+            None => (),
+        }
+        self.record(sample);
+        Ok(Some(seq))
+    }
+
+    /// The summary of the test so far
+    pub fn report(&self) -> PerReport {
+        let received = i32::from(self.received);
+        let (rssi_mean, snr_mean) = match self.received {
+            0 => (0, 0),
+            _ => (
+                self.rssi_sum.checked_div(received).unwrap_or_default(),
+                self.snr_sum.checked_div(received).unwrap_or_default(),
+            ),
+        };
+        let (rssi_min, rssi_max) = if self.received == 0 { (0, 0) } else { self.rssi_range };
+        let (snr_min, snr_max) = if self.received == 0 { (0, 0) } else { self.snr_range };
+        PerReport {
+            expected: self.expected,
+            received: self.received,
+            crc_errors: self.crc_errors,
+            duplicates: self.duplicates,
+            pattern_errors: self.pattern_errors,
+            bit_errors: self.bit_errors,
+            rssi: (rssi_min, i16::try_from(rssi_mean).unwrap_or_default(), rssi_max),
+            snr: (snr_min, i8::try_from(snr_mean).unwrap_or_default(), snr_max),
+        }
+    }
+
+    /// Records the RSSI and SNR of a distinct frame
+    fn record(&mut self, (rssi, snr): (i16, i8)) {
+        self.received = self.received.saturating_add(1);
+        self.rssi_range = (self.rssi_range.0.min(rssi), self.rssi_range.1.max(rssi));
+        self.snr_range = (self.snr_range.0.min(snr), self.snr_range.1.max(snr));
+        self.rssi_sum = self.rssi_sum.saturating_add(i32::from(rssi));
+        self.snr_sum = self.snr_sum.saturating_add(i32::from(snr));
+    }
+}
+impl<const N: usize> Default for PerAnalyzer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Assembles the header of a PER test frame
+fn frame_header(seq: u16, total: u16) -> [u8; PER_HEADER_LEN] {
+    let [m0, m1, m2, m3] = PER_MAGIC;
+    let [s0, s1] = seq.to_be_bytes();
+    let [t0, t1] = total.to_be_bytes();
+    [m0, m1, m2, m3, s0, s1, t0, t1]
+}
+/// Parses the header of a PER test frame and returns the sequence number and the frame count
+fn parse_header(frame: &[u8]) -> Option<(u16, u16)> {
+    let (header, _) = frame.split_first_chunk::<PER_HEADER_LEN>()?;
+    let [m0, m1, m2, m3, s0, s1, t0, t1] = *header;
+    ([m0, m1, m2, m3] == PER_MAGIC).then_some((u16::from_be_bytes([s0, s1]), u16::from_be_bytes([t0, t1])))
+}
+/// Counts the bit errors of the payload after the header compared to the given pattern and frame length, where each
+/// missing or excess byte counts as eight bit errors
+fn count_bit_errors(frame: &[u8], pattern: TestPattern, frame_len: usize) -> u32 {
+    let mut expected = [0; RFM95_FIFO_SIZE];
+    pattern.fill(&mut expected);
+    let expected = expected.get(..frame_len).unwrap_or_default();
+
+    // Compare the common part, and count the length mismatch
+    let payload = frame.iter().zip(expected.iter()).skip(PER_HEADER_LEN);
+    let bit_errors =
+        payload.fold(0u32, |errors, (received, expected)| errors.saturating_add((received ^ expected).count_ones()));
+    let len_mismatch = u32::try_from(frame.len().abs_diff(frame_len)).unwrap_or(u32::MAX);
+    bit_errors.saturating_add(len_mismatch.saturating_mul(8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pn9_sequence() {
+        // The PN9 whitening sequence from seed `0x1FF`, as used by e.g. the TI CC1101
+        let mut buf = [0; 16];
+        TestPattern::Pn9.fill(&mut buf);
+        assert_eq!(
+            buf,
+            [
+            0xFF, 0xE1, 0x1D, 0x9A, 0xED, 0x85, 0x33, 0x24, 0xEA, 0x7A, 0xD2, 0x39, 0x70, 0x97, 0x57, 0x0A, //
+        ]
+        );
+    }
+
+    #[test]
+    fn simple_patterns() {
+        let mut buf = [0; 4];
+        TestPattern::Counter.fill(&mut buf);
+        assert_eq!(buf, [0, 1, 2, 3]);
+        TestPattern::Ones.fill(&mut buf);
+        assert_eq!(buf, [0xFF; 4]);
+        TestPattern::Zeros.fill(&mut buf);
+        assert_eq!(buf, [0x00; 4]);
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let header = frame_header(0x1234, 0xABCD);
+        assert_eq!(parse_header(&header), Some((0x1234, 0xABCD)));
+        assert_eq!(parse_header(&header[..PER_HEADER_LEN - 1]), None);
+
+        let mut foreign = header;
+        foreign[0] = b'X';
+        assert_eq!(parse_header(&foreign), None);
+    }
+
+    #[test]
+    fn bit_errors() {
+        let mut frame = [0; 32];
+        TestPattern::Pn9.fill(&mut frame);
+        frame[..PER_HEADER_LEN].copy_from_slice(&frame_header(0, 1));
+        assert_eq!(count_bit_errors(&frame, TestPattern::Pn9, frame.len()), 0);
+
+        // Bit errors within the header are not counted
+        frame[0] ^= 0xFF;
+        frame[PER_HEADER_LEN] ^= 0b101;
+        assert_eq!(count_bit_errors(&frame, TestPattern::Pn9, frame.len()), 2);
+
+        // Each missing or excess byte counts as eight bit errors
+        assert_eq!(count_bit_errors(&frame[..30], TestPattern::Pn9, frame.len()), 2 + 16);
+        assert_eq!(count_bit_errors(&frame, TestPattern::Pn9, 31), 2 + 8);
+    }
+}
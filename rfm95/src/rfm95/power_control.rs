@@ -0,0 +1,123 @@
+//! Open-loop TX power control from peer link reports
+
+use crate::error::ConfigError;
+use crate::lora::sensitivity;
+use crate::lora::types::{Bandwidth, SpreadingFactor};
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::spi::SpiDevice;
+
+/// An open-loop TX power controller, which adjusts the TX power from the RSSI and SNR reports of a peer to keep a
+/// configurable link margin
+///
+/// # Reports
+/// The peer reports the RSSI and SNR of the packets it received from us (e.g. as part of the application protocol),
+/// which are recorded via [`Self::report`]. The controller keeps the last `HISTORY` reports and uses the weakest one,
+/// so a single strong packet does not cause an aggressive reduction.
+///
+/// # Control
+/// The received signal strength is estimated as `rssi + min(snr, 0)`, like [`crate::lora::ranging`], and compared to
+/// the receiver sensitivity (see [`crate::lora::sensitivity::sensitivity`]). The excess over the sensitivity plus the
+/// margin is the amount of power that can be saved; if the margin is not met, the power is increased again. Each
+/// adjustment is limited to a maximum step to avoid oscillation, and reports are cleared after each adjustment as they
+/// no longer reflect the new TX power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerControl<const HISTORY: usize> {
+    /// The recent peer reports as RSSI in dBm and SNR in dB
+    reports: [Option<(i16, i8)>; HISTORY],
+    /// The report slot to overwrite next
+    next: usize,
+    /// The link margin to keep in dB
+    margin: u8,
+    /// The maximum adjustment per step in dB
+    max_step: u8,
+}
+impl<const HISTORY: usize> PowerControl<HISTORY> {
+    /// The default maximum adjustment per step in dB
+    pub const DEFAULT_MAX_STEP: u8 = 3;
+
+    /// Creates a new power controller that keeps the given link margin in dB
+    pub const fn new(margin: u8) -> Self {
+        Self { reports: [None; HISTORY], next: 0, margin, max_step: Self::DEFAULT_MAX_STEP }
+    }
+    /// Sets the maximum adjustment per step in dB
+    pub const fn with_max_step(mut self, max_step: u8) -> Self {
+        self.max_step = max_step;
+        self
+    }
+
+    /// The link margin to keep in dB
+    pub const fn margin(&self) -> u8 {
+        self.margin
+    }
+    /// The maximum adjustment per step in dB
+    pub const fn max_step(&self) -> u8 {
+        self.max_step
+    }
+
+    /// Records a report of the peer with the RSSI in dBm and SNR in dB at which it received our packet
+    pub fn report(&mut self, rssi: i16, snr: i8) {
+        if let Some(slot) = self.reports.get_mut(self.next) {
+            *slot = Some((rssi, snr));
+        }
+        self.next = self.next.wrapping_add(1).checked_rem(HISTORY).unwrap_or_default();
+    }
+    /// Removes all reports
+    pub fn clear(&mut self) {
+        self.reports = [None; HISTORY];
+        self.next = 0;
+    }
+
+    /// The recommended TX power in dBm for the given current TX power and data rate, or `None` if there are no reports
+    ///
+    /// # Important
+    /// The recommendation is not clamped to the TX power range of the modem.
+    pub fn recommend(&self, tx_power: i8, spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> Option<i8> {
+        // Get the weakest received signal strength
+        let strength =
+            (self.reports.iter().flatten()).map(|(rssi, snr)| rssi.saturating_add((*snr).min(0) as i16)).min()?;
+
+        // Compute the excess over the sensitivity and the margin, and limit it to the maximum step
+        let floor = sensitivity::sensitivity(spreading_factor, bandwidth).saturating_add(self.margin as i16);
+        let max_step = self.max_step as i16;
+        let excess = strength.saturating_sub(floor).clamp(max_step.saturating_neg(), max_step);
+
+        // Compute the recommended TX power
+        let recommended = (tx_power as i16).saturating_sub(excess);
+        Some(i8::try_from(recommended).unwrap_or(tx_power))
+    }
+    /// Applies the recommended TX power to the driver, clamped to the TX power range of the chip variant, and returns
+    /// the new TX power, or `None` if there are no reports
+    ///
+    /// # Important
+    /// The reports are cleared after an adjustment, as they no longer reflect the new TX power.
+    pub fn apply<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<Option<i8>, ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Compute the recommendation for the current config
+        let config = driver.config()?;
+        let tx_power = driver.tx_power()?;
+        let Some(recommended) = self.recommend(tx_power, config.spreading_factor(), config.bandwidth()) else {
+            // There are no reports yet
+            return Ok(None);
+        };
+
+        // Apply the clamped recommendation
+        let recommended = recommended.clamp(
+            Sx127xDriver::<Device, Variant, Switch>::TX_POWER_MIN,
+            Sx127xDriver::<Device, Variant, Switch>::TX_POWER_MAX,
+        );
+        if recommended != tx_power {
+            driver.set_tx_power(recommended)?;
+            self.clear();
+        }
+        Ok(Some(recommended))
+    }
+}
@@ -0,0 +1,158 @@
+//! A manager for several radios, e.g. on a shared SPI bus
+
+use crate::error::{RxCompleteError, RxStartError};
+use crate::lora::config::Config;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::fmt::{Debug, Formatter};
+use embedded_hal::spi::SpiDevice;
+
+/// The outcome of a poll of a radio set, tagged with the index of the radio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RadioOutcome {
+    /// The index of the radio within the radio set
+    radio: usize,
+    /// The outcome of the radio as returned by [`Sx127xDriver::complete_rx_continuous`]
+    outcome: RxOutcome<(usize, u16)>,
+}
+impl RadioOutcome {
+    /// The index of the radio within the radio set
+    pub const fn radio(&self) -> usize {
+        self.radio
+    }
+    /// The outcome of the radio as returned by [`Sx127xDriver::complete_rx_continuous`]
+    pub const fn outcome(&self) -> RxOutcome<(usize, u16)> {
+        self.outcome
+    }
+}
+
+/// A set of `N` radios with a config per radio, which listen concurrently in continuous RX mode
+///
+/// # Shared Bus
+/// The radios can share a single SPI bus with separate chip select lines, e.g. via the shared-bus devices of
+/// [`embedded_hal_bus::spi`]. Reset and DIO lines are handled per radio when the drivers are created.
+///
+/// # Usage
+/// Start all radios via [`Self::start`], then call [`Self::poll`] regularly. The radios are polled in a round-robin
+/// fashion, so a busy radio cannot starve the others. For interrupt driven designs, a single radio can be polled via
+/// [`Self::poll_radio`] from its DIO0 handler.
+pub struct RadioSet<Device, Variant, Switch, const N: usize>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
+{
+    /// The radios
+    radios: [Sx127xDriver<Device, Variant, Switch>; N],
+    /// The configs of the radios
+    configs: [Config; N],
+    /// The index of the radio to poll first
+    next: usize,
+}
+impl<Device, Variant, Switch, const N: usize> RadioSet<Device, Variant, Switch, N>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
+{
+    /// Creates a new radio set from the given radios and their configs
+    ///
+    /// # Important
+    /// The configs are not applied until [`Self::start`] is called.
+    pub const fn new(radios: [Sx127xDriver<Device, Variant, Switch>; N], configs: [Config; N]) -> Self {
+        Self { radios, configs, next: 0 }
+    }
+
+    /// The radio with the given index
+    pub fn radio(&mut self, index: usize) -> Option<&mut Sx127xDriver<Device, Variant, Switch>> {
+        self.radios.get_mut(index)
+    }
+    /// The config of the radio with the given index
+    pub fn config(&self, index: usize) -> Option<&Config> {
+        self.configs.get(index)
+    }
+    /// Sets the config of the radio with the given index; the config is applied on the next call to [`Self::start`]
+    pub fn set_config(&mut self, index: usize, config: Config) -> Option<Config> {
+        let slot = self.configs.get_mut(index)?;
+        Some(core::mem::replace(slot, config))
+    }
+    /// Releases the radios and their configs
+    pub fn release(self) -> ([Sx127xDriver<Device, Variant, Switch>; N], [Config; N]) {
+        (self.radios, self.configs)
+    }
+
+    /// Applies the config of each radio and starts a continuous RX operation on all radios
+    ///
+    /// # Non-Blocking
+    /// This function starts the RX operations and returns immediately. To get the received packets, use
+    /// [`Self::poll`].
+    pub fn start(&mut self) -> Result<(), RxStartError> {
+        for (radio, config) in self.radios.iter_mut().zip(self.configs.iter()) {
+            // Apply the config and start RX
+            radio.standby()?;
+            radio.set_config(config)?;
+            radio.start_rx_continuous()?;
+        }
+        Ok(())
+    }
+
+    /// Polls all radios, copies the first received message into `buf` and returns the outcome tagged with the index of
+    /// the radio
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If no radio has an outcome, it returns `Ok(None)`. Otherwise, it returns the
+    /// first outcome that is not [`RxOutcome::Pending`], i.e. a received packet or a CRC error, as returned by
+    /// [`Sx127xDriver::complete_rx_continuous`].
+    ///
+    /// # Fairness
+    /// The radios are polled in a round-robin fashion, starting after the radio that returned the previous outcome.
+    pub fn poll(&mut self, buf: &mut [u8]) -> Result<Option<RadioOutcome>, RxCompleteError> {
+        for offset in 0..N {
+            // Poll the next radio
+            let index = self.next.wrapping_add(offset).checked_rem(N).unwrap_or_default();
+            let Some(outcome) = self.poll_radio(index, buf)? else {
+                // The index is out of bounds
+                continue;
+            };
+
+            // Return the first outcome and continue with the next radio afterwards
+            if !outcome.is_pending() {
+                self.next = index.wrapping_add(1);
+                return Ok(Some(RadioOutcome { radio: index, outcome }));
+            }
+        }
+        Ok(None)
+    }
+    /// Polls the radio with the given index and copies the received message into `buf`, or returns `None` if the index
+    /// is out of bounds
+    ///
+    /// # Non-Blocking
+    /// See [`Sx127xDriver::complete_rx_continuous`].
+    pub fn poll_radio(
+        &mut self,
+        index: usize,
+        buf: &mut [u8],
+    ) -> Result<Option<RxOutcome<(usize, u16)>>, RxCompleteError> {
+        let Some(radio) = self.radios.get_mut(index) else {
+            // There is no radio with the given index
+            return Ok(None);
+        };
+        Ok(Some(radio.complete_rx_continuous(buf)?))
+    }
+}
+impl<Device, Variant, Switch, const N: usize> Debug for RadioSet<Device, Variant, Switch, N>
+where
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch + Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        f.debug_struct("RadioSet")
+            .field("radios", &self.radios)
+            .field("configs", &self.configs)
+            .field("next", &self.next)
+            .finish()
+    }
+}
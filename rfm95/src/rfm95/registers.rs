@@ -13,6 +13,15 @@ pub trait Register {
         u8::MAX
     }
 }
+/// A dynamic, full-width register at an arbitrary address
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicRegister(pub u8);
+impl Register for DynamicRegister {
+    fn address(&self) -> u8 {
+        self.0
+    }
+}
+
 /// Declares a register type
 macro_rules! register {
     ($doc:expr, $type:ident < $address:literal, $offset:literal, $length:literal >) => {
@@ -54,6 +63,10 @@ register! {
     "This bit operates when device is in Lora mode (see datasheet for more info)",
     RegOpModeAccessSharedReg<0x01, 6, 1>
 }
+register! {
+    "00 -> FSK, 01 -> OOK; FSK/OOK mode only, overlaps `AccessSharedReg` in LoRa mode",
+    RegOpModeModulationType<0x01, 5, 2>
+}
 register! {
     "Access Low Frequency Mode registers (see datasheet for more info)",
     RegOpModeLowFrequencyModeOn<0x01, 3, 1>
@@ -62,6 +75,14 @@ register! {
     "Device modes (see datasheet for more info)",
     RegOpModeMode<0x01, 0, 3>
 }
+register! {
+    "MSB of the FSK frequency deviation; FSK/OOK mode only",
+    RegFdevMsb<0x04, 0, 6>
+}
+register! {
+    "LSB of the FSK frequency deviation; FSK/OOK mode only",
+    RegFdevLsb<0x05, 0, 8>
+}
 register! {
     "MSB of RF carrier frequency",
     RegFrMsb<0x06, 0, 8>
@@ -75,8 +96,16 @@ register! {
     RegFrLsb<0x08, 0, 8>
 }
 register! {
-    "RegPaConfig (see datasheet for more info)",
-    RegPaConfig<0x09, 0, 8>
+    "Selects PA output pin: 0 -> RFO pin, 1 -> PA_BOOST pin",
+    RegPaConfigPaSelect<0x09, 7, 1>
+}
+register! {
+    "Select max output power for the RFO pin: `Pmax = 10.8 + 0.6 * MaxPower` dBm",
+    RegPaConfigMaxPower<0x09, 4, 3>
+}
+register! {
+    "Output power: `Pout = 17 - (15 - OutputPower)` if PaSelect = 1 (PA_BOOST pin), `Pout = Pmax - (15 - OutputPower)` if PaSelect = 0 (RFO pin)",
+    RegPaConfigOutputPower<0x09, 0, 4>
 }
 register! {
     "SPI interface address pointer in FIFO data buffer",
@@ -94,6 +123,10 @@ register! {
     "Start address (in data buffer) of last packet received",
     RegFifoRxCurrentAddr<0x10, 0, 8>
 }
+register! {
+    "All interrupt masks; setting a bit masks the corresponding IRQ in RegIrqFlags",
+    RegIrqFlagsMask<0x11, 0, 8>
+}
 register! {
     "Timeout interrupt mask: setting this bit masks the corresponding IRQ in RegIrqFlags",
     RegIrqFlagsMaskRxTimeoutMask<0x11, 7, 1>
@@ -110,6 +143,18 @@ register! {
     "FIFO Payload transmission complete interrupt mask: setting this bit masks the corresponding IRQ in RegIrqFlags",
     RegIrqFlagsMaskTxDoneMask<0x11, 3, 1>
 }
+register! {
+    "CAD complete interrupt mask: setting this bit masks the corresponding IRQ in RegIrqFlags",
+    RegIrqFlagsMaskCadDoneMask<0x11, 2, 1>
+}
+register! {
+    "Valid LoRa signal detected during CAD operation interrupt mask: setting this bit masks the corresponding IRQ in RegIrqFlags",
+    RegIrqFlagsMaskCadDetectedMask<0x11, 0, 1>
+}
+register! {
+    "All interrupt flags; writing a 1 to a bit clears the corresponding IRQ",
+    RegIrqFlags<0x12, 0, 8>
+}
 register! {
     "Timeout interrupt: writing a 1 clears the IRQ",
     RegIrqFlagsRxTimeout<0x12, 7, 1>
@@ -126,10 +171,38 @@ register! {
     "FIFO Payload transmission complete interrupt: writing a 1 clears the IRQ",
     RegIrqFlagsTxDone<0x12, 3, 1>
 }
+register! {
+    "CAD complete: write to clear; writing a 1 clears the IRQ",
+    RegIrqFlagsCadDone<0x12, 2, 1>
+}
+register! {
+    "Valid LoRa signal detected during CAD operation: writing a 1 clears the IRQ",
+    RegIrqFlagsCadDetected<0x12, 0, 1>
+}
 register! {
     "Number of payload bytes of latest packet received",
     RegRxNbBytes<0x13, 0, 8>
 }
+register! {
+    "Number of valid headers received since last transition into RX mode, MSB",
+    RegRxHeaderCntValueMsb<0x14, 0, 8>
+}
+register! {
+    "Number of valid headers received since last transition into RX mode, LSB",
+    RegRxHeaderCntValueLsb<0x15, 0, 8>
+}
+register! {
+    "Number of valid packets received since last transition into RX mode, MSB",
+    RegRxPacketCntValueMsb<0x16, 0, 8>
+}
+register! {
+    "Number of valid packets received since last transition into RX mode, LSB",
+    RegRxPacketCntValueLsb<0x17, 0, 8>
+}
+register! {
+    "Coding rate of last header received",
+    RegModemStatRxCodingRate<0x18, 5, 3>
+}
 register! {
     "SNR of last packet recieved",
     RegPktSnrValue<0x19, 0, 8>
@@ -138,6 +211,14 @@ register! {
     "RSSI of last packet recieved",
     RegPktRssiValue<0x1A, 0, 8>
 }
+register! {
+    "Current RSSI value",
+    RegRssiValue<0x1B, 0, 8>
+}
+register! {
+    "Current value of frequency hopping channel in use",
+    RegHopChannelFhssPresentChannel<0x1C, 0, 6>
+}
 register! {
     "Signal bandwidth (see datasheet for more info)",
     RegModemConfig1Bw<0x1D, 4, 4>
@@ -154,6 +235,10 @@ register! {
     "SF rate (expressed as a base-2 logarithm, see datasheet for more info)",
     RegModemConfig2SpreadingFactor<0x1E, 4, 4>
 }
+register! {
+    "0 -> Normal mode, a single packet is sent; 1 -> Continuous mode, the FIFO content is sent repeatedly",
+    RegModemConfig2TxContinuousMode<0x1E, 3, 1>
+}
 register! {
     "Enable CRC generation, and check on payload: 0 -> CRC disable, 1 -> CRC enable (see datasheet for more info)",
     RegModemConfig2RxPayloadCrcOn<0x1E, 2, 1>
@@ -178,10 +263,26 @@ register! {
     "Payload length in bytes; the register needs to be set in implicit header mode for the expected packet length (a `0` value is not permitted)",
     RegPayloadLength<0x22, 0, 8>
 }
+register! {
+    "Selects CLKOUT frequency: `FXOSC / 2^ClkOut` for `0..=5`, `110` -> RC, `111` -> Off; FSK register bank only, requires `AccessSharedReg`",
+    RegOscClkOut<0x24, 0, 3>
+}
+register! {
+    "Current value of RX databuffer pointer (address of last byte written by LoRa receiver)",
+    RegFifoRxByteAddr<0x25, 0, 8>
+}
 register! {
     "0 -> Disabled, 1 -> Enabled; mandated for when the symbol length exceeds 16ms",
     RegModemConfig3LowDataRateOptimize<0x26, 3, 1>
 }
+register! {
+    "Wideband RSSI measurement used to locally generate a random number",
+    RegRssiWideband<0x2C, 0, 8>
+}
+register! {
+    "0 -> Continuous mode, 1 -> Packet mode; FSK/OOK mode only",
+    RegPacketConfig2DataMode<0x31, 6, 1>
+}
 register! {
     "Invert the LoRa I and Q signals; 0 -> normal mode, 1 -> I and Q signals are inverted",
     RegInvertIQ<0x33, 6, 1>
@@ -190,12 +291,22 @@ register! {
     "LoRa Sync Word; value 0x34 is used for LoRaWAN networks",
     RegSyncWord<0x39, 0, 8>
 }
-#[cfg(not(feature = "debug"))]
+register! {
+    "Set when the PLL is locked in FS, RX or TX mode; FSK register bank only, requires `AccessSharedReg` in LoRa mode",
+    RegIrqFlags1PllLock<0x3E, 4, 1>
+}
+register! {
+    "Mapping of pins DIO0 to DIO3 (two bits each, DIO0 in the upper bits); the meaning depends on the mode",
+    RegDioMapping1<0x40, 0, 8>
+}
+register! {
+    "Mapping of pin DIO5; `00` maps `ModeReady` in LoRa mode",
+    RegDioMapping2Dio5Mapping<0x41, 4, 2>
+}
 register! {
     "Semtech ID relating the silicon revision",
     RegVersion<0x42, 0, 8>
 }
 
 /// The highest reasonable register address for dumping
-#[cfg(feature = "debug")]
 pub const REGISTER_MAX: u8 = 0x64;
@@ -0,0 +1,364 @@
+//! A remote AT-style control protocol over the radio itself
+
+use crate::err;
+use crate::error::{BufferTooSmallError, ConfigError, ProtocolError};
+use crate::lora::config::Config;
+use crate::lora::region;
+use crate::lora::types::{Bandwidth, Frequency, SpreadingFactor};
+use crate::rfm95::capabilities::Capabilities;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::fmt::Write;
+use embedded_hal::spi::SpiDevice;
+
+/// The supported bandwidths in hertz
+const BANDWIDTHS: [(u32, Bandwidth); 10] = [
+    (7_800, Bandwidth::B7_8),
+    (10_400, Bandwidth::B10_4),
+    (15_600, Bandwidth::B15_6),
+    (20_800, Bandwidth::B20_8),
+    (31_250, Bandwidth::B31_25),
+    (41_700, Bandwidth::B41_7),
+    (62_500, Bandwidth::B62_5),
+    (125_000, Bandwidth::B125),
+    (250_000, Bandwidth::B250),
+    (500_000, Bandwidth::B500),
+];
+/// The supported spreading factors
+const SPREADING_FACTORS: [SpreadingFactor; 6] = [
+    SpreadingFactor::S7,
+    SpreadingFactor::S8,
+    SpreadingFactor::S9,
+    SpreadingFactor::S10,
+    SpreadingFactor::S11,
+    SpreadingFactor::S12,
+];
+
+/// A handler for a remote AT-style command protocol, to query and reconfigure a deployed node from a handheld node
+/// over the radio itself
+///
+/// # Commands
+/// A request is an ASCII command, optionally followed by `\r\n`, and is answered with a single ASCII response:
+/// - `AT`: Responds with `OK`
+/// - `AT+FREQ?`, `AT+SF?`, `AT+BW?`, `AT+PWR?`: Responds with the active value as `+FREQ:<hz>`, `+SF:<7..=12>`,
+///   `+BW:<hz>` or `+PWR:<dBm>`
+/// - `AT+FREQ=<hz>`, `AT+SF=<7..=12>`, `AT+BW=<hz>`: Stages a config change and responds with `OK`, if the changed
+///   config is supported by the chip capabilities and allowed by the region lockout
+/// - `AT+PWR=<dBm>`: Sets the TX power immediately and responds with `OK`
+/// - `AT+VER?`: Responds with the silicon revision as `+VER:<revision>`
+/// - `AT+STATS?`: Responds with the driver statistics as `+STATS:<tx>,<rx>,<crc errors>,<rx timeouts>,<io errors>`
+///   (requires the `stats` feature)
+/// - `AT+SELFTEST`: Performs a self test and responds with `+SELFTEST:PASS` or `+SELFTEST:FAIL`
+///
+/// Unknown commands and invalid values are answered with `ERROR`.
+///
+/// # Staged Config Changes
+/// Config changes are not applied immediately, as this would break the link before the response has been sent.
+/// Instead, transmit the response with the current config, and call [`Self::apply`] afterwards.
+///
+/// # Access Token
+/// Requests can be required to be prefixed with an access token; requests without the token are ignored. Please note
+/// that the token is sent in plain text and can be replayed, so it only protects against accidental reconfiguration
+/// and is not a security mechanism.
+///
+/// # Important
+/// The self test aborts any ongoing operation and leaves the modem in standby, so RX must be restarted afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteControl<'a> {
+    /// The access token that prefixes each request
+    token: &'a [u8],
+    /// The staged config change, if any
+    pending: Option<Config>,
+}
+impl<'a> RemoteControl<'a> {
+    /// Creates a new remote control handler without an access token
+    pub const fn new() -> Self {
+        Self { token: &[], pending: None }
+    }
+    /// Requires each request to be prefixed with the given access token
+    pub const fn with_token(mut self, token: &'a [u8]) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// The access token that prefixes each request
+    pub const fn token(&self) -> &'a [u8] {
+        self.token
+    }
+    /// The staged config change, if any
+    pub const fn pending(&self) -> Option<&Config> {
+        self.pending.as_ref()
+    }
+    /// Discards the staged config change, if any
+    pub fn discard(&mut self) -> Option<Config> {
+        self.pending.take()
+    }
+
+    /// Handles a received request, writes the response into `response` and returns the length of the response, or
+    /// `None` if the message is not a command (e.g. regular application traffic or a missing access token)
+    pub fn handle<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        request: &[u8],
+        response: &mut [u8],
+    ) -> Result<Option<usize>, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Validate the access token and the command prefix
+        let Some(command) = request.strip_prefix(self.token) else {
+            // The request is not prefixed with the access token
+            return Ok(None);
+        };
+        let Ok(command) = core::str::from_utf8(command) else {
+            // The message is not an ASCII command
+            return Ok(None);
+        };
+        let command = command.trim_end_matches(['\r', '\n']);
+        let Some(command) = command.strip_prefix("AT") else {
+            // The message is not an AT command
+            return Ok(None);
+        };
+
+        // Execute the command and fall back to an error response
+        let mut writer = ResponseWriter { buf: response, len: 0 };
+        if !self.execute(driver, command, &mut writer)? {
+            writer.len = 0;
+            writer.write("ERROR")?;
+        }
+        Ok(Some(writer.len))
+    }
+
+    /// Applies the staged config change, if any, and returns whether a change has been applied
+    ///
+    /// # Important
+    /// This should be called after the response to the last request has been transmitted. Any ongoing operation is
+    /// aborted, and should be restarted afterwards.
+    pub fn apply<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<bool, ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let Some(config) = self.pending.take() else {
+            // There is no staged change
+            return Ok(false);
+        };
+        driver.standby()?;
+        driver.set_config(&config)?;
+        Ok(true)
+    }
+
+    /// Executes the given command without the `AT` prefix and writes the response, or returns `false` if the command is
+    /// unknown or invalid
+    fn execute<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        command: &str,
+        writer: &mut ResponseWriter,
+    ) -> Result<bool, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match command.split_once('=') {
+            Some((name, argument)) => self.set(driver, name, argument, writer),
+            None => Self::query(driver, command, writer),
+        }
+    }
+
+    /// Executes the given query or action command and writes the response
+    fn query<Device, Variant, Switch>(
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        command: &str,
+        writer: &mut ResponseWriter,
+    ) -> Result<bool, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match command {
+            "" => writer.write("OK")?,
+            "+FREQ?" => {
+                let frequency = driver.config()?.frequency();
+                writer.write_fmt(format_args!("+FREQ:{}", frequency.as_u32()))?;
+            }
+            "+SF?" => {
+                let spreading_factor = driver.config()?.spreading_factor();
+                writer.write_fmt(format_args!("+SF:{}", spreading_factor as u8))?;
+            }
+            "+BW?" => {
+                let bandwidth = driver.config()?.bandwidth();
+                let Some((hz, _)) = BANDWIDTHS.iter().find(|(_, known)| *known == bandwidth) else {
+                    // This should never happen as the table is exhaustive
+                    return Ok(false);
+                };
+                writer.write_fmt(format_args!("+BW:{hz}"))?;
+            }
+            "+PWR?" => {
+                let power = driver.tx_power()?;
+                writer.write_fmt(format_args!("+PWR:{power}"))?;
+            }
+            "+VER?" => {
+                let revision = driver.silicon_revision()?;
+                writer.write_fmt(format_args!("+VER:{revision:#04x}"))?;
+            }
+            #[cfg(feature = "stats")]
+            "+STATS?" => {
+                let stats = driver.stats();
+                writer.write_fmt(format_args!(
+                    "+STATS:{},{},{},{},{}",
+                    stats.tx_packets(),
+                    stats.rx_packets(),
+                    stats.crc_errors(),
+                    stats.rx_timeouts(),
+                    stats.io_errors()
+                ))?;
+            }
+            "+SELFTEST" => {
+                let verdict = if driver.self_test()?.passed() { "PASS" } else { "FAIL" };
+                writer.write_fmt(format_args!("+SELFTEST:{verdict}"))?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Executes the given set command and writes the response
+    fn set<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        name: &str,
+        argument: &str,
+        writer: &mut ResponseWriter,
+    ) -> Result<bool, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let value = argument.parse::<u32>();
+        match (name, value) {
+            ("+FREQ", Ok(value)) => {
+                let config = self.staged(driver)?.with_frequency(Frequency::hz(value));
+                if !self.stage(config, driver.capabilities()) {
+                    // The frequency is not supported or not allowed
+                    return Ok(false);
+                }
+            }
+            ("+SF", Ok(value)) => {
+                let Some(spreading_factor) = SPREADING_FACTORS.iter().find(|sf| **sf as u32 == value) else {
+                    // The spreading factor is not supported
+                    return Ok(false);
+                };
+                let config = self.staged(driver)?.with_spreading_factor(*spreading_factor);
+                if !self.stage(config, driver.capabilities()) {
+                    // The spreading factor is not supported by the chip
+                    return Ok(false);
+                }
+            }
+            ("+BW", Ok(value)) => {
+                let Some((_, bandwidth)) = BANDWIDTHS.iter().find(|(hz, _)| *hz == value) else {
+                    // The bandwidth is not supported
+                    return Ok(false);
+                };
+                let config = self.staged(driver)?.with_bandwidth(*bandwidth);
+                if !self.stage(config, driver.capabilities()) {
+                    // The bandwidth is not supported by the chip at the staged frequency
+                    return Ok(false);
+                }
+            }
+            ("+PWR", _) => {
+                let Ok(power) = argument.parse::<i8>() else {
+                    // The power is invalid or out of range
+                    return Ok(false);
+                };
+                match driver.set_tx_power(power) {
+                    Ok(()) => (),
+                    Err(ConfigError::InvalidArgumentError(_)) => return Ok(false),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            _ => return Ok(false),
+        }
+        writer.write("OK")?;
+        Ok(true)
+    }
+
+    /// Stages the given config, or returns `false` if it would be rejected by [`Sx127xDriver::set_config`]
+    ///
+    /// # Validation
+    /// The config is validated via [`Config::validate`], against the chip capabilities and against the region lockout
+    /// (see [`region::ALLOWED_REGIONS`]) before it is staged, so `OK` is only sent for changes that can be applied.
+    fn stage(&mut self, config: Config, capabilities: Capabilities) -> bool {
+        let Ok(config) = config.validate() else {
+            // The config is invalid
+            return false;
+        };
+        let Ok(config) = capabilities.validate(config) else {
+            // The config is not supported by the chip
+            return false;
+        };
+        if !region::ALLOWED_REGIONS.is_empty() && region::allowed_region(config.frequency()).is_none() {
+            // The frequency is outside of the allowed regions
+            return false;
+        }
+        self.pending = Some(config);
+        true
+    }
+    /// The staged config, or the active config of the driver if no change is staged
+    fn staged<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<Config, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match self.pending {
+            Some(config) => Ok(config),
+            None => Ok(driver.config()?),
+        }
+    }
+}
+impl Default for RemoteControl<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A formatter that writes a response into a fixed buffer
+struct ResponseWriter<'a> {
+    /// The response buffer
+    buf: &'a mut [u8],
+    /// The amount of bytes written
+    len: usize,
+}
+impl ResponseWriter<'_> {
+    /// Writes the given response
+    fn write(&mut self, response: &str) -> Result<(), BufferTooSmallError> {
+        self.write_str(response).map_err(|_| err!(BufferTooSmallError, "Response buffer is too small"))
+    }
+    /// Writes the given formatted response
+    fn write_fmt(&mut self, args: core::fmt::Arguments) -> Result<(), BufferTooSmallError> {
+        Write::write_fmt(self, args).map_err(|_| err!(BufferTooSmallError, "Response buffer is too small"))
+    }
+}
+impl Write for ResponseWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len.checked_add(s.len()).ok_or(core::fmt::Error)?;
+        let slot = self.buf.get_mut(self.len..end).ok_or(core::fmt::Error)?;
+        slot.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
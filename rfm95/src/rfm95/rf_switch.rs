@@ -0,0 +1,96 @@
+//! External RF switch and front-end module control
+
+use crate::err;
+use crate::error::IoError;
+use core::fmt::{Debug, Formatter};
+use embedded_hal::digital::OutputPin;
+
+/// The state of an external RF switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfSwitchState {
+    /// The modem is idle (sleep or standby); the external front-end can be powered down
+    Off,
+    /// The modem is transmitting; the antenna must be connected to the TX path
+    Tx,
+    /// The modem is receiving (including CAD); the antenna must be connected to the RX path
+    Rx,
+}
+
+/// An external RF switch or front-end module that is toggled by the driver around mode changes
+///
+/// # Switching Order
+/// The driver switches to [`RfSwitchState::Tx`] or [`RfSwitchState::Rx`] _before_ the modem enters the respective
+/// mode, and to [`RfSwitchState::Off`] _after_ the modem has left it, so the antenna path is always set up while the
+/// modem is active.
+pub trait RfSwitch {
+    /// Switches the external front-end to the given state
+    fn set_state(&mut self, state: RfSwitchState) -> Result<(), IoError>;
+}
+
+/// A placeholder for boards without an external RF switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoRfSwitch;
+impl RfSwitch for NoRfSwitch {
+    fn set_state(&mut self, _state: RfSwitchState) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+/// An RF switch with an active-high TX-enable and RX-enable pin, as used by most E22/E32-style modules
+///
+/// # Pin States
+/// In [`RfSwitchState::Off`], both pins are low. When switching paths, the active pin is always deasserted before the
+/// other pin is asserted, so both paths are never enabled at the same time.
+pub struct PinRfSwitch<TxEnable, RxEnable> {
+    /// The TX-enable pin
+    tx_enable: TxEnable,
+    /// The RX-enable pin
+    rx_enable: RxEnable,
+}
+impl<TxEnable, RxEnable> PinRfSwitch<TxEnable, RxEnable>
+where
+    TxEnable: OutputPin,
+    RxEnable: OutputPin,
+{
+    /// Creates a new RF switch from the given TX-enable and RX-enable pins
+    pub const fn new(tx_enable: TxEnable, rx_enable: RxEnable) -> Self {
+        Self { tx_enable, rx_enable }
+    }
+
+    /// Releases the TX-enable and RX-enable pins
+    pub fn release(self) -> (TxEnable, RxEnable) {
+        (self.tx_enable, self.rx_enable)
+    }
+}
+impl<TxEnable, RxEnable> RfSwitch for PinRfSwitch<TxEnable, RxEnable>
+where
+    TxEnable: OutputPin,
+    RxEnable: OutputPin,
+{
+    fn set_state(&mut self, state: RfSwitchState) -> Result<(), IoError> {
+        // Deassert the inactive pins first, then assert the active pin
+        let (tx_enable, rx_enable) = match state {
+            RfSwitchState::Off => (false, false),
+            RfSwitchState::Tx => (true, false),
+            RfSwitchState::Rx => (false, true),
+        };
+        if !tx_enable {
+            self.tx_enable.set_low().map_err(|_| err!(IoError, "Failed to deassert TX-enable pin"))?;
+        }
+        if !rx_enable {
+            self.rx_enable.set_low().map_err(|_| err!(IoError, "Failed to deassert RX-enable pin"))?;
+        }
+        if tx_enable {
+            self.tx_enable.set_high().map_err(|_| err!(IoError, "Failed to assert TX-enable pin"))?;
+        }
+        if rx_enable {
+            self.rx_enable.set_high().map_err(|_| err!(IoError, "Failed to assert RX-enable pin"))?;
+        }
+        Ok(())
+    }
+}
+impl<TxEnable, RxEnable> Debug for PinRfSwitch<TxEnable, RxEnable> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        f.debug_struct("PinRfSwitch").finish_non_exhaustive()
+    }
+}
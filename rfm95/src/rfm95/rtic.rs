@@ -0,0 +1,105 @@
+//! Async helpers for RTIC 2 on top of `rtic-monotonics`
+
+use crate::clock::{Clock, MonotonicClock};
+use crate::error::{IoError, RxCompleteError, TxStartError};
+use crate::rfm95::chip::Chip;
+use crate::rfm95::csma_tx::{CsmaOutcome, CsmaTransmitter};
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use embedded_hal::spi::SpiDevice;
+use rtic_time::Monotonic;
+
+/// Waits until the given timestamp in microseconds of the [`MonotonicClock`] of `M`
+pub async fn delay_until<M, const NOM: u32, const DENOM: u32>(deadline: u64)
+where
+    M: Monotonic<Instant = fugit::Instant<u64, NOM, DENOM>>,
+{
+    let deadline = fugit::Duration::<u64, NOM, DENOM>::micros(deadline);
+    M::delay_until(fugit::Instant::<u64, NOM, DENOM>::from_ticks(deadline.ticks())).await;
+}
+
+/// Waits until a single or continuous RX operation has completed, and copies the message into `buf`
+///
+/// # Timeout
+/// The driver is polled every `poll_interval`. If `timeout` is given, the wait is aborted after it has elapsed and
+/// [`RxOutcome::Timeout`] is returned; this is useful for continuous RX, or for timeouts beyond the hardware maximum of
+/// single RX (see [`Sx127xDriver::rx_timeout_max`]). An aborted RX operation is not stopped.
+pub async fn wait_rx<M, Device, Variant, Switch>(
+    driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    buf: &mut [u8],
+    poll_interval: M::Duration,
+    timeout: Option<M::Duration>,
+) -> Result<RxOutcome, RxCompleteError>
+where
+    M: Monotonic,
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
+{
+    let receive = async {
+        loop {
+            match driver.complete_rx(buf)? {
+                RxOutcome::Pending => M::delay(poll_interval).await,
+                outcome => return Ok(outcome),
+            }
+        }
+    };
+
+    // Apply the timeout if any
+    let Some(timeout) = timeout else {
+        // Wait without timeout
+        return receive.await;
+    };
+    M::timeout_after(timeout, receive).await.unwrap_or(Ok(RxOutcome::Timeout))
+}
+
+/// Waits until a TX operation has completed, and returns the amount of bytes sent
+///
+/// # Polling
+/// The driver is polled every `poll_interval`.
+pub async fn wait_tx<M, Device, Variant, Switch>(
+    driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    poll_interval: M::Duration,
+) -> Result<usize, IoError>
+where
+    M: Monotonic,
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
+{
+    loop {
+        match driver.complete_tx()? {
+            Some(len) => return Ok(len),
+            None => M::delay(poll_interval).await,
+        }
+    }
+}
+
+/// Drives a CSMA/CA transmitter until the queued frame has been started or dropped
+///
+/// # Scheduling
+/// While backing off, the task sleeps until the backoff has elapsed; while sensing the channel, the driver is polled
+/// every `poll_interval`. Returns [`CsmaOutcome::Idle`] if no frame is queued.
+pub async fn drive_csma<M, Device, Variant, Switch, const MTU: usize, const NOM: u32, const DENOM: u32>(
+    transmitter: &mut CsmaTransmitter<MTU>,
+    driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    poll_interval: M::Duration,
+) -> Result<CsmaOutcome, TxStartError>
+where
+    M: Monotonic<Instant = fugit::Instant<u64, NOM, DENOM>>,
+    Device: SpiDevice,
+    Variant: Chip,
+    Switch: RfSwitch,
+{
+    let clock = MonotonicClock::<M>::new();
+    loop {
+        match transmitter.poll(driver, clock.now())? {
+            CsmaOutcome::Pending => match transmitter.backoff_until() {
+                Some(deadline) => delay_until::<M, NOM, DENOM>(deadline).await,
+                None => M::delay(poll_interval).await,
+            },
+            outcome => return Ok(outcome),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Hardware-side RX counters
+
+/// The valid header and packet counters of the modem
+///
+/// # Diagnostics
+/// The modem counts the valid headers and packets it has detected since the last transition into RX mode. Comparing
+/// these counters with the amount of packets the application actually consumed reveals polling gaps, and a high header
+/// count with a low packet count hints at CRC errors or interference. Both counters wrap around at `u16::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxCounters {
+    /// The amount of valid headers received since the last transition into RX mode
+    valid_headers: u16,
+    /// The amount of valid packets received since the last transition into RX mode
+    valid_packets: u16,
+}
+impl RxCounters {
+    /// Creates new RX counters
+    pub const fn new(valid_headers: u16, valid_packets: u16) -> Self {
+        Self { valid_headers, valid_packets }
+    }
+
+    /// The amount of valid headers received since the last transition into RX mode
+    pub const fn valid_headers(&self) -> u16 {
+        self.valid_headers
+    }
+    /// The amount of valid packets received since the last transition into RX mode
+    pub const fn valid_packets(&self) -> u16 {
+        self.valid_packets
+    }
+}
@@ -0,0 +1,117 @@
+//! A watchdog for stuck RX operations
+
+use crate::clock::Clock;
+use crate::error::IoError;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::event::Event;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A software watchdog that recovers the modem if an RX operation neither completes nor times out
+///
+/// # Stuck RX
+/// Occasionally, SX127x modems get stuck in RX mode without ever raising `RxDone` or `RxTimeout`, e.g. after a
+/// corrupted header. The watchdog is armed together with a single RX operation, and if the operation has not finished
+/// long after its timeout, the modem is returned to standby and [`Event::Stalled`] is reported, so the application can
+/// restart the operation.
+///
+/// # Grace Period
+/// The RX timeout only limits the time until a preamble is detected; a packet that is being received when the timeout
+/// elapses is still completed. The grace period should thus cover the airtime of the longest expected packet (see
+/// [`crate::lora::airtime::airtime`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxWatchdog {
+    /// The grace period after the RX timeout
+    grace: Duration,
+    /// The deadline in clock microseconds, or `None` if the watchdog is not armed
+    deadline: Option<u64>,
+}
+impl RxWatchdog {
+    /// Creates a new, disarmed watchdog with the given grace period after the RX timeout
+    pub const fn new(grace: Duration) -> Self {
+        Self { grace, deadline: None }
+    }
+
+    /// The grace period after the RX timeout
+    pub const fn grace(&self) -> Duration {
+        self.grace
+    }
+    /// The deadline in clock microseconds, or `None` if the watchdog is not armed
+    pub const fn deadline(&self) -> Option<u64> {
+        self.deadline
+    }
+
+    /// Arms the watchdog for an RX operation with the given timeout that has just been started
+    pub fn arm<C>(&mut self, timeout: Duration, clock: &C)
+    where
+        C: Clock,
+    {
+        let limit = timeout.saturating_add(self.grace);
+        let limit_micros = u64::try_from(limit.as_micros()).unwrap_or(u64::MAX);
+        self.deadline = Some(clock.now().saturating_add(limit_micros));
+    }
+    /// Disarms the watchdog, e.g. once the RX operation has completed
+    pub fn disarm(&mut self) {
+        self.deadline = None;
+    }
+
+    /// Checks whether the watchdog has expired, and if so, returns the modem to standby and returns
+    /// [`Event::Stalled`]
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking and only accesses the modem if the watchdog has expired. The watchdog is disarmed
+    /// once it has expired.
+    pub fn check<Device, Variant, Switch, C>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        clock: &C,
+    ) -> Result<Option<Event>, IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+        C: Clock,
+    {
+        // Check the deadline
+        let Some(deadline) = self.deadline else {
+            // The watchdog is not armed
+            return Ok(None);
+        };
+        if clock.now() < deadline {
+            // The watchdog has not expired yet
+            return Ok(None);
+        }
+
+        // Recover the modem
+        self.deadline = None;
+        driver.standby()?;
+        Ok(Some(Event::Stalled))
+    }
+    /// Handles a pending interrupt via [`Sx127xDriver::handle_interrupt`], or checks the watchdog if no interrupt is
+    /// pending
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. The watchdog is disarmed if the RX operation has completed, timed out or failed.
+    pub fn poll<Device, Variant, Switch, C>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        clock: &C,
+    ) -> Result<Option<Event>, IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+        C: Clock,
+    {
+        match driver.handle_interrupt()? {
+            Some(event @ (Event::RxDone(_) | Event::RxTimeout | Event::CrcError)) => {
+                self.disarm();
+                Ok(Some(event))
+            }
+            Some(event) => Ok(Some(event)),
+            None => self.check(driver, clock),
+        }
+    }
+}
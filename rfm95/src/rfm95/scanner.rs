@@ -0,0 +1,113 @@
+//! Multi-channel scanning receiver
+
+use crate::err;
+use crate::error::{ConfigError, InvalidArgumentError, RxStartError};
+use crate::lora::channel::{Channel, ChannelPlan};
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A scanning receiver that cycles channel activity detection (CAD) across the enabled channels of a channel plan, and
+/// locks onto the first channel that shows a preamble
+///
+/// # Usage
+/// Start the scan via [`Self::start`], then call [`Self::poll`] until it returns the locked channel. At this point, a
+/// single RX is already running on the locked channel, which can be completed via [`Sx127xDriver::complete_rx`] and
+/// friends as usual. To resume scanning afterwards, call [`Self::start`] again.
+///
+/// # Preamble Length
+/// A full scan cycle takes roughly two symbols per enabled channel. To reliably catch a packet, the preamble of the
+/// senders must be longer than a full scan cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scanner {
+    /// The channel plan to scan
+    plan: ChannelPlan,
+    /// The RX timeout once a preamble has been detected
+    rx_timeout: Duration,
+    /// The channel that is currently scanned
+    channel: Option<Channel>,
+}
+impl Scanner {
+    /// Creates a new scanner for the given channel plan and the RX timeout to use once a preamble has been detected
+    pub const fn new(plan: ChannelPlan, rx_timeout: Duration) -> Self {
+        Self { plan, rx_timeout, channel: None }
+    }
+
+    /// The channel plan to scan
+    pub const fn plan(&self) -> &ChannelPlan {
+        &self.plan
+    }
+    /// The RX timeout once a preamble has been detected
+    pub const fn rx_timeout(&self) -> Duration {
+        self.rx_timeout
+    }
+    /// The channel that is currently scanned or locked
+    pub const fn channel(&self) -> Option<Channel> {
+        self.channel
+    }
+
+    /// Tunes to the next enabled channel and starts the scan
+    ///
+    /// # Non-Blocking
+    /// This function schedules the first CAD operation and returns immediately. To advance the scan, use
+    /// [`Self::poll`].
+    pub fn start<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Select the next channel
+        let Some(channel) = self.plan.next_channel() else {
+            // There is no enabled channel to scan
+            return Err(err!(InvalidArgumentError, "No enabled channel to scan"))?;
+        };
+
+        // Tune to the channel and start the CAD
+        driver.set_frequency(channel)?;
+        driver.start_cad()?;
+        self.channel = Some(channel);
+        Ok(())
+    }
+
+    /// Advances the scan and returns the locked channel once a preamble has been detected
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If the current CAD operation is not done yet or no preamble has been detected on
+    /// the current channel, it returns `Ok(None)`; in the latter case, the scan advances to the next enabled channel.
+    /// If a preamble has been detected, a single RX is started on the current channel and the channel is returned.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<Option<Channel>, RxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Check the state of the current CAD operation
+        let Some(channel) = self.channel else {
+            // The scan has not been started yet
+            return Err(err!(InvalidArgumentError, "The scan has not been started"))?;
+        };
+        match driver.complete_cad()? {
+            // Lock onto the channel and receive the packet
+            Some(true) => {
+                driver.start_rx(self.rx_timeout)?;
+                Ok(Some(channel))
+            }
+            // Advance to the next channel
+            Some(false) => {
+                self.start(driver)?;
+                Ok(None)
+            }
+            // The CAD operation is still running
+            None => Ok(None),
+        }
+    }
+}
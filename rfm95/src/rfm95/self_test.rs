@@ -0,0 +1,46 @@
+//! Power-on self test reports
+
+/// The report of a power-on self test
+///
+/// # Scope
+/// The self test checks the SPI connection and the digital part of the modem only; it does not test the RF path. See
+/// [`crate::rfm95::Sx127xDriver::self_test`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// The silicon revision
+    silicon_revision: u8,
+    /// Whether the scratch register round-trip succeeded
+    scratch: bool,
+    /// The amount of FIFO bytes that did not read back correctly
+    fifo_errors: u16,
+    /// Whether all operation mode transitions succeeded
+    opmode: bool,
+}
+impl SelfTestReport {
+    /// Creates a new self test report
+    pub const fn new(silicon_revision: u8, scratch: bool, fifo_errors: u16, opmode: bool) -> Self {
+        Self { silicon_revision, scratch, fifo_errors, opmode }
+    }
+
+    /// The silicon revision
+    pub const fn silicon_revision(&self) -> u8 {
+        self.silicon_revision
+    }
+    /// Whether the scratch register round-trip succeeded
+    pub const fn scratch(&self) -> bool {
+        self.scratch
+    }
+    /// The amount of FIFO bytes that did not read back correctly
+    pub const fn fifo_errors(&self) -> u16 {
+        self.fifo_errors
+    }
+    /// Whether all operation mode transitions succeeded
+    pub const fn opmode(&self) -> bool {
+        self.opmode
+    }
+
+    /// Whether all checks have passed
+    pub const fn passed(&self) -> bool {
+        self.scratch && self.fifo_errors == 0 && self.opmode
+    }
+}
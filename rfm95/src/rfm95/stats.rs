@@ -0,0 +1,71 @@
+//! Driver statistics counters
+
+/// Statistics counters of the driver, e.g. to report the link health of deployed devices
+///
+/// # Counting
+/// The TX and RX counters are updated when the driver observes the completion of an operation (e.g. via
+/// [`crate::rfm95::Sx127xDriver::complete_tx`] or [`crate::rfm95::Sx127xDriver::complete_rx`]). I/O errors are counted
+/// per failed SPI transaction. All counters saturate at `u32::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DriverStats {
+    /// The amount of packets transmitted
+    tx_packets: u32,
+    /// The amount of valid packets received
+    rx_packets: u32,
+    /// The amount of packets received with a CRC error
+    crc_errors: u32,
+    /// The amount of RX operations that timed out
+    rx_timeouts: u32,
+    /// The amount of failed SPI transactions
+    io_errors: u32,
+}
+impl DriverStats {
+    /// Creates new statistics with all counters set to zero
+    pub const fn new() -> Self {
+        Self { tx_packets: 0, rx_packets: 0, crc_errors: 0, rx_timeouts: 0, io_errors: 0 }
+    }
+
+    /// The amount of packets transmitted
+    pub const fn tx_packets(&self) -> u32 {
+        self.tx_packets
+    }
+    /// The amount of valid packets received
+    pub const fn rx_packets(&self) -> u32 {
+        self.rx_packets
+    }
+    /// The amount of packets received with a CRC error
+    pub const fn crc_errors(&self) -> u32 {
+        self.crc_errors
+    }
+    /// The amount of RX operations that timed out
+    pub const fn rx_timeouts(&self) -> u32 {
+        self.rx_timeouts
+    }
+    /// The amount of failed SPI transactions
+    pub const fn io_errors(&self) -> u32 {
+        self.io_errors
+    }
+
+    /// Counts a transmitted packet
+    pub(crate) fn count_tx_packet(&mut self) {
+        self.tx_packets = self.tx_packets.saturating_add(1);
+    }
+    /// Counts a valid received packet
+    pub(crate) fn count_rx_packet(&mut self) {
+        self.rx_packets = self.rx_packets.saturating_add(1);
+    }
+    /// Counts a packet received with a CRC error
+    pub(crate) fn count_crc_error(&mut self) {
+        self.crc_errors = self.crc_errors.saturating_add(1);
+    }
+    /// Counts a timed out RX operation
+    pub(crate) fn count_rx_timeout(&mut self) {
+        self.rx_timeouts = self.rx_timeouts.saturating_add(1);
+    }
+    /// Sets the amount of failed SPI transactions
+    #[cfg(feature = "stats")]
+    pub(crate) const fn with_io_errors(mut self, io_errors: u32) -> Self {
+        self.io_errors = io_errors;
+        self
+    }
+}
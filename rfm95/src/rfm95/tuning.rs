@@ -0,0 +1,182 @@
+//! Keyed carrier for antenna tuning
+
+use crate::error::{IoError, TxStartError};
+use crate::lora::ledger::DutyCycle;
+use crate::lora::types::Frequency;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::keying::KeyedTx;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// An antenna tuning aid that keys an unmodulated carrier on and off with a fixed pattern, so an installer with a power
+/// meter, a VNA or a second node can adjust the antenna matching
+///
+/// # Safety Limits
+/// Each keyed period is limited to the maximum dwell time of the driver (see [`Sx127xDriver::set_max_dwell`]) if
+/// configured, and the unkeyed period is extended as necessary to respect the duty cycle (10% by default, see
+/// [`Self::with_duty_cycle`]). The tuning aid stops on its own after the session limit (5 minutes by default, see
+/// [`Self::with_session_limit`]).
+///
+/// # Signal
+/// While keyed, the modem transmits an unmodulated carrier (see [`Sx127xDriver::start_carrier`]), so the readings do
+/// not depend on the modulation bandwidth. The modem stays in carrier mode while unkeyed.
+///
+/// # Usage
+/// Start the tuning aid via [`Self::start`], then call [`Self::poll`] regularly until it returns `false`, or stop it
+/// via [`Self::stop`]. The LoRa config and the previous frequency and TX power are restored once the tuning aid stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TuningAid {
+    /// The keyed transmission
+    keyed: KeyedTx,
+    /// The keyed period in microseconds
+    on: u64,
+    /// The unkeyed period in microseconds
+    off: u64,
+    /// The duty cycle limit
+    duty_cycle: DutyCycle,
+    /// The session limit in microseconds
+    session_limit: u64,
+    /// The clock time in microseconds at which the session ends
+    session_end: u64,
+}
+impl TuningAid {
+    /// Creates a new tuning aid for the given frequency and TX power in dBm, keyed for 500 ms every 5 s
+    pub const fn new(frequency: Frequency, tx_power: i8) -> Self {
+        Self {
+            keyed: KeyedTx::new(frequency, tx_power),
+            on: 500_000,
+            off: 4_500_000,
+            duty_cycle: DutyCycle::per_mille(100),
+            session_limit: 300_000_000,
+            session_end: 0,
+        }
+    }
+    /// Sets the keyed and unkeyed periods
+    pub fn with_pattern(mut self, on: Duration, off: Duration) -> Self {
+        self.on = u64::try_from(on.as_micros()).unwrap_or(u64::MAX);
+        self.off = u64::try_from(off.as_micros()).unwrap_or(u64::MAX);
+        self
+    }
+    /// Sets the duty cycle limit
+    pub const fn with_duty_cycle(mut self, duty_cycle: DutyCycle) -> Self {
+        self.duty_cycle = duty_cycle;
+        self
+    }
+    /// Sets the time after which the tuning aid stops on its own
+    pub fn with_session_limit(mut self, session_limit: Duration) -> Self {
+        self.session_limit = u64::try_from(session_limit.as_micros()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// The frequency to transmit on
+    pub const fn frequency(&self) -> Frequency {
+        self.keyed.frequency()
+    }
+    /// The TX power in dBm
+    pub const fn tx_power(&self) -> i8 {
+        self.keyed.tx_power()
+    }
+    /// The duty cycle limit
+    pub const fn duty_cycle(&self) -> DutyCycle {
+        self.duty_cycle
+    }
+    /// Whether the tuning aid is running
+    pub const fn is_running(&self) -> bool {
+        self.keyed.is_running()
+    }
+    /// Whether the transmitter is keyed
+    pub const fn is_keyed(&self) -> bool {
+        self.keyed.is_keyed()
+    }
+
+    /// Tunes to the frequency and TX power, and keys the transmitter
+    pub fn start<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Tune and start the session
+        self.keyed.tune(driver)?;
+        self.session_end = now.saturating_add(self.session_limit);
+        self.key(driver, now)
+    }
+    /// Leaves the carrier mode, restores the previous frequency and TX power, and stops the tuning aid
+    pub fn stop<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.keyed.stop(driver)
+    }
+
+    /// Keys and unkeys the transmitter according to the pattern, and returns whether the tuning aid is still running
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. Once the session limit has passed, the tuning aid is stopped.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<bool, TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Enforce the session limit
+        if self.is_running() && now >= self.session_end {
+            self.stop(driver)?;
+        }
+
+        // Toggle the transmitter once the current period has passed
+        match (self.keyed.is_due(now), self.keyed.is_keyed()) {
+            (true, true) => self.unkey(driver, now)?,
+            (true, false) => self.key(driver, now)?,
+            (false, _) => (),
+        }
+        Ok(self.is_running())
+    }
+
+    /// Keys the transmitter for the keyed period, limited to the maximum dwell time
+    fn key<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let max_dwell = driver.max_dwell().map(|max_dwell| u64::try_from(max_dwell.as_micros()).unwrap_or(u64::MAX));
+        let on = self.on.min(max_dwell.unwrap_or(u64::MAX));
+        self.keyed.key(driver, now, on)
+    }
+    /// Unkeys the transmitter for the unkeyed period, extended to respect the duty cycle
+    fn unkey<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // The keyed period makes up at most the duty cycle of the whole period
+        let period = self.duty_cycle.period(self.on);
+        let off = self.off.max(period.saturating_sub(self.on));
+        self.keyed.unkey(driver, now, off)
+    }
+}
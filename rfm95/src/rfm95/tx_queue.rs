@@ -0,0 +1,227 @@
+//! Prioritized TX queue with airtime budgeting
+
+use crate::clock::Clock;
+use crate::err;
+use crate::error::{InvalidArgumentError, TxStartError};
+use crate::lora::airtime;
+use crate::lora::ledger::DutyCycle;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// A queued frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuedFrame<const MTU: usize> {
+    /// The frame data
+    data: [u8; MTU],
+    /// The frame length
+    len: usize,
+    /// The frame priority; higher values are sent first
+    priority: u8,
+    /// The deadline in clock microseconds after which the frame is dropped
+    deadline: Option<u64>,
+    /// The enqueue sequence number to keep frames of the same priority in order
+    sequence: u32,
+}
+
+/// A fixed-capacity TX queue that drains prioritized frames through [`Sx127xDriver::start_tx`] while respecting a
+/// duty-cycle and dwell-time budget
+///
+/// # Usage
+/// Queue frames via [`Self::push`], then call [`Self::poll`] regularly. Frames with a higher priority are sent first
+/// (e.g. alarms ahead of routine telemetry); frames with the same priority are sent in order. Frames that are still
+/// queued after their deadline are dropped.
+///
+/// # Airtime Budget
+/// After each transmission, the queue waits until the transmission makes up at most the configured duty cycle of the
+/// elapsed time (e.g. 1% of the airtime per transmission implies an off-time of 99 times the airtime). Frames whose
+/// airtime exceeds the maximum dwell time are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxQueue<const CAPACITY: usize, const MTU: usize> {
+    /// The queued frames
+    slots: [Option<QueuedFrame<MTU>>; CAPACITY],
+    /// The next enqueue sequence number
+    sequence: u32,
+    /// The duty cycle limit
+    duty_cycle: DutyCycle,
+    /// The maximum dwell time per transmission
+    max_dwell: Option<Duration>,
+    /// Whether a TX operation is in progress
+    busy: bool,
+    /// The clock time in microseconds at which the next transmission is allowed
+    ready_at: u64,
+    /// The amount of frames that have been dropped because their deadline has passed
+    dropped: u32,
+}
+impl<const CAPACITY: usize, const MTU: usize> TxQueue<CAPACITY, MTU> {
+    /// Creates a new empty queue without duty-cycle or dwell-time limits
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { None }; CAPACITY],
+            sequence: 0,
+            duty_cycle: DutyCycle::UNLIMITED,
+            max_dwell: None,
+            busy: false,
+            ready_at: 0,
+            dropped: 0,
+        }
+    }
+    /// Sets the duty cycle limit
+    pub const fn with_duty_cycle(mut self, duty_cycle: DutyCycle) -> Self {
+        self.duty_cycle = duty_cycle;
+        self
+    }
+    /// Sets the maximum dwell time per transmission (e.g. 400 ms for US915)
+    pub const fn with_max_dwell(mut self, max_dwell: Duration) -> Self {
+        self.max_dwell = Some(max_dwell);
+        self
+    }
+
+    /// The duty cycle limit
+    pub const fn duty_cycle(&self) -> DutyCycle {
+        self.duty_cycle
+    }
+    /// The maximum dwell time per transmission
+    pub const fn max_dwell(&self) -> Option<Duration> {
+        self.max_dwell
+    }
+    /// The maximum amount of queued frames
+    pub const fn capacity(&self) -> usize {
+        CAPACITY
+    }
+    /// The amount of queued frames
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+    /// Whether the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+    /// The amount of frames that have been dropped because their deadline has passed
+    pub const fn dropped(&self) -> u32 {
+        self.dropped
+    }
+    /// The clock time in microseconds at which the next transmission is allowed
+    pub const fn ready_at(&self) -> u64 {
+        self.ready_at
+    }
+
+    /// Queues a frame with the given priority and an optional deadline in clock microseconds
+    ///
+    /// # Priority
+    /// Frames with a higher priority are sent first; frames with the same priority are sent in order.
+    pub fn push(&mut self, data: &[u8], priority: u8, deadline: Option<u64>) -> Result<(), InvalidArgumentError> {
+        // Copy the frame
+        let mut frame_data = [0; MTU];
+        let Some(frame_slice) = frame_data.get_mut(..data.len()) else {
+            // The frame is too long
+            return Err(err!(InvalidArgumentError, "Frame exceeds the queue MTU"));
+        };
+        frame_slice.copy_from_slice(data);
+
+        // Store the frame in a free slot
+        let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) else {
+            // There is no free slot
+            return Err(err!(InvalidArgumentError, "TX queue is full"));
+        };
+        let frame = QueuedFrame { data: frame_data, len: data.len(), priority, deadline, sequence: self.sequence };
+        *slot = Some(frame);
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(())
+    }
+    /// Removes all queued frames
+    pub fn clear(&mut self) {
+        self.slots = [const { None }; CAPACITY];
+    }
+
+    /// Completes the current transmission and starts the next frame if the airtime budget allows it, and returns the
+    /// length of the started frame
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. If a transmission is still in progress, the budget does not allow another
+    /// transmission yet, or the queue is empty, it returns `Ok(None)`.
+    ///
+    /// # Errors
+    /// If a frame exceeds the maximum dwell time, it is dropped and an error is returned. If the transmission cannot be
+    /// started, the frame remains queued.
+    pub fn poll<Device, Variant, Switch, C>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        clock: &C,
+    ) -> Result<Option<usize>, TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+        C: Clock,
+    {
+        // Complete the current transmission
+        if self.busy {
+            let Some(_) = driver.complete_tx()? else {
+                // The TX operation has not been completed yet
+                return Ok(None);
+            };
+            self.busy = false;
+        }
+
+        // Respect the airtime budget and drop expired frames
+        let now = clock.now();
+        if now < self.ready_at {
+            return Ok(None);
+        }
+        self.drop_expired(now);
+
+        // Select the next frame and compute its airtime
+        let Some((index, frame)) = self.next_frame() else {
+            // The queue is empty
+            return Ok(None);
+        };
+        let airtime = airtime::airtime(frame.len, driver.config()?);
+        if self.max_dwell.is_some_and(|max_dwell| airtime > max_dwell) {
+            // Drop the frame as it can never be sent
+            self.remove(index);
+            return Err(err!(InvalidArgumentError, "Frame exceeds the maximum dwell time"))?;
+        }
+
+        // Start the transmission and compute the off-time
+        let data = frame.data.get(..frame.len).unwrap_or_default();
+        driver.start_tx(data)?;
+        let airtime_micros = u64::try_from(airtime.as_micros()).unwrap_or(u64::MAX);
+        self.remove(index);
+        self.busy = true;
+        self.ready_at = now.saturating_add(self.duty_cycle.period(airtime_micros));
+        Ok(Some(frame.len))
+    }
+
+    /// Drops all frames whose deadline has passed
+    fn drop_expired(&mut self, now: u64) {
+        for slot in self.slots.iter_mut() {
+            // Drop the frame if it is expired
+            if slot.is_some_and(|frame| frame.deadline.is_some_and(|deadline| deadline < now)) {
+                *slot = None;
+                self.dropped = self.dropped.saturating_add(1);
+            }
+        }
+    }
+    /// Gets the slot index and the frame with the highest priority, or the oldest of those with the same priority
+    fn next_frame(&self) -> Option<(usize, QueuedFrame<MTU>)> {
+        // Order frames by priority and sequence number, relative to the next sequence number to account for wrap-around
+        let age = |frame: &QueuedFrame<MTU>| self.sequence.wrapping_sub(frame.sequence);
+        let frames = self.slots.iter().enumerate();
+        let frames = frames.filter_map(|(index, slot)| slot.map(|frame| (index, frame)));
+        frames.max_by_key(|(_, frame)| (frame.priority, age(frame)))
+    }
+    /// Removes the frame at the given slot index
+    fn remove(&mut self, index: usize) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            *slot = None;
+        }
+    }
+}
+impl<const CAPACITY: usize, const MTU: usize> Default for TxQueue<CAPACITY, MTU> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
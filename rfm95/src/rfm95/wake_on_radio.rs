@@ -0,0 +1,293 @@
+//! Duty-cycled wake-on-radio receive via preamble sampling
+
+use crate::error::{ConfigError, IoError, ProtocolError, TxStartError};
+use crate::lora::airtime;
+use crate::lora::types::PreambleLength;
+use crate::rfm95::chip::Chip;
+use crate::rfm95::driver::Sx127xDriver;
+use crate::rfm95::long_rx::LongRx;
+use crate::rfm95::outcome::RxOutcome;
+use crate::rfm95::rf_switch::RfSwitch;
+use core::time::Duration;
+use embedded_hal::spi::SpiDevice;
+
+/// The state of a wake-on-radio receiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WakeState {
+    /// The receiver is stopped
+    Stopped,
+    /// The modem sleeps until the given timestamp
+    Sleeping(u64),
+    /// A CAD operation samples the channel
+    Sampling,
+    /// The receive window for the remainder of the preamble and the packet is open
+    Receiving(LongRx),
+}
+
+/// A duty-cycled receiver that samples the channel for preambles (wake-on-radio)
+///
+/// # Preamble Sampling
+/// The receiver sleeps most of the time and periodically wakes up for a short CAD operation; it only stays awake to
+/// receive if a preamble is detected. To reliably hit a sample, the transmitter sends a long preamble that covers the
+/// entire sampling interval (see [`Self::start_tx`]), so both sides must use the same interval and modem config.
+///
+/// # Usage
+/// Start the receiver via [`Self::start`], then call [`Self::poll`] regularly, at least once per interval. Once a
+/// packet has been received (or failed the CRC), the receiver stops, so the application can reply; call [`Self::start`]
+/// again to resume. Preambles that do not lead to a packet (e.g. of other networks) are counted as false wake-ups.
+///
+/// # Important
+/// The receiver sets the preamble length of the modem to the long preamble while it is running, as the modem expects
+/// the configured preamble length; [`Self::stop`] restores the previous preamble length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WakeOnRadio {
+    /// The sampling interval in microseconds
+    interval: u64,
+    /// The state of the receiver
+    state: WakeState,
+    /// The preamble length to restore, if the long preamble is set
+    restore_preamble: Option<PreambleLength>,
+    /// The amount of detected preambles that did not lead to a packet
+    false_wakeups: u32,
+}
+impl WakeOnRadio {
+    /// The amount of preamble symbols on top of the sampling interval, to cover a CAD operation and the wake-up time
+    const PREAMBLE_MARGIN_SYMBOLS: u16 = 4;
+
+    /// Creates a new wake-on-radio receiver with the given sampling interval
+    pub fn new(interval: Duration) -> Self {
+        let interval = u64::try_from(interval.as_micros()).unwrap_or(u64::MAX);
+        Self { interval, state: WakeState::Stopped, restore_preamble: None, false_wakeups: 0 }
+    }
+
+    /// The sampling interval
+    pub const fn interval(&self) -> Duration {
+        Duration::from_micros(self.interval)
+    }
+    /// The amount of detected preambles that did not lead to a packet
+    pub const fn false_wakeups(&self) -> u32 {
+        self.false_wakeups
+    }
+    /// Whether the receiver is running
+    pub const fn is_running(&self) -> bool {
+        !matches!(self.state, WakeState::Stopped)
+    }
+
+    /// The long preamble length that covers the sampling interval for the current config
+    pub fn preamble_len<Device, Variant, Switch>(
+        &self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<PreambleLength, IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        // Compute the amount of symbols per interval, rounded up
+        let symbol_airtime = airtime::symbol_airtime(driver.spreading_factor()?, driver.bandwidth()?);
+        let symbol_micros = u64::try_from(symbol_airtime.as_micros()).unwrap_or(u64::MAX);
+        let symbols = self.interval.div_ceil(symbol_micros.max(1));
+
+        // Add the margin
+        let symbols = u16::try_from(symbols).unwrap_or(u16::MAX).saturating_add(Self::PREAMBLE_MARGIN_SYMBOLS);
+        Ok(PreambleLength::new(symbols))
+    }
+
+    /// Starts the receiver and puts the modem to sleep until the first sample
+    pub fn start<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.set_long_preamble(driver)?;
+        self.sleep(driver, now)?;
+        Ok(())
+    }
+    /// Stops the receiver, restores the previous preamble length and puts the modem into standby
+    pub fn stop<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.state = WakeState::Stopped;
+        driver.standby()?;
+        self.restore_preamble(driver)
+    }
+
+    /// Advances the receiver, copies a received message into `buf` and returns the outcome
+    ///
+    /// # Non-Blocking
+    /// This function is non-blocking. It returns `Ok(RxOutcome::Pending)` while the receiver is sleeping, sampling or
+    /// receiving, and [`RxOutcome::Received`] or [`RxOutcome::CrcError`] once a packet has been received; the receiver
+    /// is stopped afterwards. False wake-ups are not reported as timeouts, but counted.
+    pub fn poll<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        buf: &mut [u8],
+        now: u64,
+    ) -> Result<RxOutcome, ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match self.state {
+            WakeState::Stopped => Ok(RxOutcome::Pending),
+            WakeState::Sleeping(until) if now < until => Ok(RxOutcome::Pending),
+            WakeState::Sleeping(_) => {
+                // Sample the channel
+                driver.start_cad()?;
+                self.state = WakeState::Sampling;
+                Ok(RxOutcome::Pending)
+            }
+            WakeState::Sampling => {
+                let Some(detected) = driver.complete_cad()? else {
+                    // The CAD operation has not been completed yet
+                    return Ok(RxOutcome::Pending);
+                };
+                match detected {
+                    // Stay awake for the remainder of the preamble and the packet
+                    true => self.receive(driver, now)?,
+                    false => self.sleep(driver, now)?,
+                }
+                Ok(RxOutcome::Pending)
+            }
+            WakeState::Receiving(window) => match driver.complete_rx(buf)? {
+                RxOutcome::Pending => Ok(RxOutcome::Pending),
+                // Chain the next RX operation if the long preamble exceeds the hardware timeout
+                RxOutcome::Timeout if window.chain(driver, now)? => Ok(RxOutcome::Pending),
+                RxOutcome::Timeout => {
+                    // The detected preamble did not lead to a packet
+                    self.false_wakeups = self.false_wakeups.saturating_add(1);
+                    self.sleep(driver, now)?;
+                    Ok(RxOutcome::Pending)
+                }
+                outcome => {
+                    // Stop the receiver so the application can reply
+                    self.state = WakeState::Stopped;
+                    self.restore_preamble(driver)?;
+                    Ok(outcome)
+                }
+            },
+        }
+    }
+
+    /// Schedules a single TX operation with a long preamble that wakes up a wake-on-radio receiver with the same
+    /// interval
+    ///
+    /// # Non-Blocking
+    /// This function schedules the TX operation and returns immediately. To check if the TX operation is done and to
+    /// restore the previous preamble length, use [`Self::complete_tx`].
+    pub fn start_tx<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        data: &[u8],
+    ) -> Result<(), TxStartError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        self.set_long_preamble(driver)?;
+        driver.start_tx(data)
+    }
+    /// Checks if a TX operation started via [`Self::start_tx`] has completed, restores the previous preamble length and
+    /// returns the amount of bytes sent
+    ///
+    /// # Non-Blocking
+    /// See [`Sx127xDriver::complete_tx`].
+    pub fn complete_tx<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<Option<usize>, ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let Some(written) = driver.complete_tx()? else {
+            // The TX operation has not been completed yet
+            return Ok(None);
+        };
+        self.restore_preamble(driver)?;
+        Ok(Some(written))
+    }
+
+    /// Puts the modem to sleep until the next sample
+    fn sleep<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), IoError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        driver.sleep()?;
+        self.state = WakeState::Sleeping(now.saturating_add(self.interval));
+        Ok(())
+    }
+    /// Opens a receive window that covers the remainder of the long preamble
+    ///
+    /// # Hardware Limit
+    /// The long preamble usually exceeds the hardware timeout of 1023 symbols, so the window chains single RX
+    /// operations (see [`LongRx`]).
+    fn receive<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+        now: u64,
+    ) -> Result<(), ProtocolError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        let symbol_airtime = airtime::symbol_airtime(driver.spreading_factor()?, driver.bandwidth()?);
+        let preamble_len = self.preamble_len(driver)?.as_u16();
+        let mut window = LongRx::new(symbol_airtime.saturating_mul(u32::from(preamble_len)));
+        window.start_at(driver, now)?;
+        self.state = WakeState::Receiving(window);
+        Ok(())
+    }
+    /// Sets the long preamble and remembers the previous preamble length
+    fn set_long_preamble<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        if self.restore_preamble.is_none() {
+            self.restore_preamble = Some(driver.preamble_len()?);
+        }
+        let preamble_len = self.preamble_len(driver)?;
+        driver.set_preamble_len(preamble_len)
+    }
+    /// Restores the previous preamble length, if the long preamble is set
+    fn restore_preamble<Device, Variant, Switch>(
+        &mut self,
+        driver: &mut Sx127xDriver<Device, Variant, Switch>,
+    ) -> Result<(), ConfigError>
+    where
+        Device: SpiDevice,
+        Variant: Chip,
+        Switch: RfSwitch,
+    {
+        match self.restore_preamble.take() {
+            Some(preamble_len) => driver.set_preamble_len(preamble_len),
+            None => Ok(()),
+        }
+    }
+}